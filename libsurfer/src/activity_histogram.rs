@@ -0,0 +1,254 @@
+//! Histogram of the number of transitions over the time range of the waveform.
+use std::fmt::Write;
+
+use camino::Utf8PathBuf;
+use egui::{Context, Sense, Window};
+use emath::{Pos2, Rect, Vec2};
+use num::{BigUint, ToPrimitive, Zero};
+use serde::Deserialize;
+
+use crate::SystemState;
+use crate::displayed_item::{DisplayedItem, DisplayedItemRef};
+use crate::message::Message;
+use crate::wave_container::{VariableRef, VariableRefExt};
+use crate::wave_data::WaveData;
+
+/// Which variables to count the transitions of
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub enum HistogramScope {
+    Item(DisplayedItemRef),
+    /// All displayed variables combined
+    AllDisplayed,
+}
+
+pub struct ActivityHistogram {
+    pub title: String,
+    /// Last timestamp of the waveform, the buckets cover `0..=end`
+    pub end: BigUint,
+    pub counts: Vec<u64>,
+}
+
+impl ActivityHistogram {
+    /// Time range `start..end` covered by bucket `idx`
+    #[must_use]
+    pub fn bucket_range(&self, idx: usize) -> (BigUint, BigUint) {
+        let width = &self.end + 1u32;
+        let buckets = self.counts.len();
+        (&width * idx / buckets, &width * (idx + 1) / buckets)
+    }
+
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = "start,end,transitions\n".to_string();
+        for (idx, count) in self.counts.iter().enumerate() {
+            let (start, end) = self.bucket_range(idx);
+            let _ = writeln!(csv, "{start},{end},{count}");
+        }
+        csv
+    }
+}
+
+/// Count the `transitions` that fall into each of `buckets` equally wide buckets covering
+/// `0..=end`.
+#[must_use]
+pub fn bin_transitions(
+    transitions: impl IntoIterator<Item = BigUint>,
+    end: &BigUint,
+    buckets: usize,
+) -> Vec<u64> {
+    let mut counts = vec![0; buckets];
+    if buckets == 0 {
+        return counts;
+    }
+    let width = end + 1u32;
+    for time in transitions {
+        let idx = (time * buckets / &width)
+            .to_usize()
+            .unwrap_or(usize::MAX)
+            .min(buckets - 1);
+        counts[idx] += 1;
+    }
+    counts
+}
+
+impl WaveData {
+    /// Times of all value changes of `variable`, not including the initial value
+    #[must_use]
+    pub fn transition_times(&self, variable: &VariableRef) -> Vec<BigUint> {
+        let mut times = vec![];
+        let Some(waves) = self.inner.as_waves() else {
+            return times;
+        };
+        let mut time = BigUint::zero();
+        while let Ok(Some(result)) = waves.query_variable(variable, &time) {
+            let Some(next) = result.next else {
+                break;
+            };
+            times.push(next.clone());
+            time = next;
+        }
+        times
+    }
+
+    #[must_use]
+    pub fn activity_histogram(
+        &self,
+        scope: HistogramScope,
+        buckets: usize,
+    ) -> Option<ActivityHistogram> {
+        let end = self.num_timestamps()?.to_biguint()?;
+        let variable_ref = |item_ref: &DisplayedItemRef| match self.displayed_items.get(item_ref) {
+            Some(DisplayedItem::Variable(variable)) => Some(&variable.variable_ref),
+            _ => None,
+        };
+        let (title, variables) = match scope {
+            HistogramScope::Item(item_ref) => {
+                let variable = variable_ref(&item_ref)?;
+                (variable.full_path_string(), vec![variable])
+            }
+            HistogramScope::AllDisplayed => (
+                "All displayed variables".to_string(),
+                self.items_tree
+                    .iter()
+                    .filter_map(|node| variable_ref(&node.item_ref))
+                    .collect(),
+            ),
+        };
+        let counts = bin_transitions(
+            variables
+                .into_iter()
+                .flat_map(|variable| self.transition_times(variable)),
+            &end,
+            buckets,
+        );
+        Some(ActivityHistogram { title, end, counts })
+    }
+}
+
+impl SystemState {
+    pub fn draw_activity_histogram_window(
+        &self,
+        histogram: &ActivityHistogram,
+        ctx: &Context,
+        msgs: &mut Vec<Message>,
+    ) {
+        let mut open = true;
+        Window::new("Activity")
+            .collapsible(true)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(&histogram.title);
+                let (response, painter) = ui.allocate_painter(
+                    Vec2::new(ui.available_width().max(300.), 120.),
+                    Sense::hover(),
+                );
+                let rect = response.rect;
+                let max = histogram.counts.iter().copied().max().unwrap_or(0).max(1) as f32;
+                let bar_width = rect.width() / histogram.counts.len().max(1) as f32;
+                let color = self.user.config.theme.variable_default;
+                for (idx, count) in histogram.counts.iter().enumerate() {
+                    let left = rect.left() + idx as f32 * bar_width;
+                    let top = rect.bottom() - rect.height() * (*count as f32 / max);
+                    painter.rect_filled(
+                        Rect::from_min_max(
+                            Pos2::new(left, top),
+                            Pos2::new(left + (bar_width - 1.).max(1.), rect.bottom()),
+                        ),
+                        0.,
+                        color,
+                    );
+                }
+                if let Some(pos) = response.hover_pos() {
+                    let idx = (((pos.x - rect.left()) / bar_width) as usize)
+                        .min(histogram.counts.len().saturating_sub(1));
+                    if let Some(count) = histogram.counts.get(idx) {
+                        let (start, end) = histogram.bucket_range(idx);
+                        response.on_hover_text(format!("{start} to {end}: {count}"));
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Save as CSV...").clicked() {
+                        msgs.push(Message::SaveActivityHistogram(None));
+                    }
+                    if ui.button("Close").clicked() {
+                        msgs.push(Message::CloseActivityHistogram);
+                    }
+                });
+            });
+        if !open {
+            msgs.push(Message::CloseActivityHistogram);
+        }
+    }
+
+    pub fn save_activity_histogram(&mut self, path: Option<Utf8PathBuf>) {
+        let Some(histogram) = &self.activity_histogram else {
+            return;
+        };
+        let encoded = histogram.to_csv();
+
+        self.save_bytes_to(
+            path,
+            "Save activity histogram",
+            ("CSV files (*.csv)".to_string(), vec!["csv".to_string()]),
+            encoded.into_bytes(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+
+    #[test]
+    fn transitions_are_binned_over_the_time_range() {
+        let times = [0u32, 10, 24, 25, 49, 50, 99, 100].map(BigUint::from);
+        let counts = bin_transitions(times.clone(), &BigUint::from(99u32), 4);
+        // The last transition is clamped into the last bucket
+        assert_eq!(counts, vec![3, 2, 1, 2]);
+
+        let histogram = ActivityHistogram {
+            title: String::new(),
+            end: BigUint::from(99u32),
+            counts,
+        };
+        assert_eq!(
+            histogram.to_csv(),
+            "start,end,transitions\n0,25,3\n25,50,2\n50,75,1\n75,100,2\n"
+        );
+
+        assert!(bin_transitions(times, &BigUint::from(99u32), 0).is_empty());
+    }
+
+    #[test]
+    fn activity_histogram_bucket_counts() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk", "tb.dut.counter"]);
+
+        state.update(Message::ComputeActivityHistogram {
+            scope: HistogramScope::Item(DisplayedItemRef(1)),
+            buckets: 4,
+        });
+        let histogram = state.activity_histogram.as_ref().unwrap();
+        assert_eq!(histogram.title, "tb.clk");
+        assert_eq!(histogram.counts, vec![20, 20, 20, 20]);
+        assert!(
+            histogram
+                .to_csv()
+                .starts_with("start,end,transitions\n0,200,20\n")
+        );
+
+        state.update(Message::ComputeActivityHistogram {
+            scope: HistogramScope::AllDisplayed,
+            buckets: 4,
+        });
+        // The counter starts counting at 110, once reset is released
+        assert_eq!(
+            state.activity_histogram.as_ref().unwrap().counts,
+            vec![25, 30, 30, 30]
+        );
+
+        state.update(Message::CloseActivityHistogram);
+        assert!(state.activity_histogram.is_none());
+    }
+}
@@ -2,7 +2,8 @@
 
 use crate::analog_signal_cache::{AnalogSignalCache, CacheQueryResult, is_nan_highimp};
 use crate::displayed_item::{
-    AnalogSettings, DisplayedFieldRef, DisplayedItemRef, DisplayedVariable,
+    AnalogSettings, AnalogStyle, AnalogUndefMode, DisplayedFieldRef, DisplayedItemRef,
+    DisplayedVariable,
 };
 use crate::drawing_canvas::{AnalogDrawingCommands, DrawingCommands, VariableDrawCommands};
 use crate::message::Message;
@@ -40,6 +41,7 @@ pub(crate) fn variable_analog_draw_commands(
     translators: &TranslatorList,
     view_width: f32,
     viewport_idx: usize,
+    default_style: AnalogStyle,
 ) -> Option<VariableDrawCommands> {
     let render_mode = displayed_variable.analog.as_ref()?;
 
@@ -53,7 +55,11 @@ pub(crate) fn variable_analog_draw_commands(
         .signal_id(&displayed_variable.variable_ref)
         .ok()?;
     let translator_name = translator.name();
-    let cache_key = (signal_id, translator_name.clone());
+    let cache_key = (
+        signal_id,
+        translator_name.clone(),
+        displayed_variable.fixed_point_radix,
+    );
 
     // Check if cache exists and is valid (correct generation and matching key)
     let cache = match &render_mode.cache {
@@ -102,6 +108,8 @@ pub(crate) fn variable_analog_draw_commands(
         &num_timestamps,
         view_width,
         render_mode.settings,
+        render_mode.style.unwrap_or(default_style),
+        render_mode.undef_mode,
     )
     .build();
 
@@ -133,6 +141,7 @@ pub fn draw_analog(
         min_valid_pixel,
         max_valid_pixel,
         analog_settings,
+        style,
     } = analog_commands
     else {
         draw_building_indicator(offset, height_scaling_factor, ctx);
@@ -148,7 +157,7 @@ pub fn draw_analog(
     );
 
     let render_ctx = RenderContext::new(
-        color,
+        analog_stroke(style, color, ctx.theme.linewidth),
         min_val,
         max_val,
         *min_valid_pixel,
@@ -170,9 +179,47 @@ pub fn draw_analog(
         }
     }
 
+    if style.point_markers {
+        for (px, value) in point_marker_positions(values, *min_valid_pixel, *max_valid_pixel) {
+            render_ctx.draw_point_marker(render_ctx.to_screen(px, value, ctx), ctx);
+        }
+    }
+
     draw_amplitude_labels(&render_ctx, ctx);
 }
 
+/// Stroke of an analog trace with the given style. Traces without a line width of their own use
+/// `default_width`, the line width of the theme.
+#[must_use]
+pub fn analog_stroke(style: &AnalogStyle, color: Color32, default_width: f32) -> Stroke {
+    Stroke::new(style.line_width.unwrap_or(default_width), color)
+}
+
+/// Pixel positions and values of the point markers, i.e., the start of each flat segment with a
+/// defined value within the time range of the signal.
+#[must_use]
+pub fn point_marker_positions(
+    values: &[AnalogDrawingCommand],
+    min_valid_pixel: f32,
+    max_valid_pixel: f32,
+) -> Vec<(f32, f64)> {
+    values
+        .iter()
+        .filter_map(|command| match command {
+            AnalogDrawingCommand::Flat {
+                start_px,
+                start_val,
+                ..
+            } if start_val.is_finite()
+                && (min_valid_pixel..=max_valid_pixel).contains(start_px) =>
+            {
+                Some((*start_px, *start_val))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
 /// Draw a building indicator with animated dots while analog cache is being built.
 fn draw_building_indicator(offset: f32, height_scaling_factor: f32, ctx: &mut DrawingContext) {
     // Animate dots: cycle through ".", "..", "..." every 333ms
@@ -195,7 +242,7 @@ fn draw_building_indicator(offset: f32, height_scaling_factor: f32, ctx: &mut Dr
     );
 }
 
-fn select_value_range(
+pub(crate) fn select_value_range(
     viewport_min: f64,
     viewport_max: f64,
     global_min: f64,
@@ -230,6 +277,10 @@ struct CommandBuilder<'a> {
     max_valid_pixel: f32,
     output: CommandOutput,
     analog_settings: AnalogSettings,
+    style: AnalogStyle,
+    undef_mode: AnalogUndefMode,
+    /// Last finite value seen, substituted for X/Z values in [`AnalogUndefMode::HoldLast`]
+    last_defined: Option<f64>,
 }
 
 /// Accumulates commands and tracks value bounds.
@@ -315,6 +366,8 @@ impl<'a> CommandBuilder<'a> {
         num_timestamps: &'a BigInt,
         view_width: f32,
         analog_settings: AnalogSettings,
+        style: AnalogStyle,
+        undef_mode: AnalogUndefMode,
     ) -> Self {
         let min_valid_pixel =
             viewport.pixel_from_time(&BigInt::from(0), view_width, num_timestamps);
@@ -329,6 +382,9 @@ impl<'a> CommandBuilder<'a> {
             max_valid_pixel,
             output: CommandOutput::new(),
             analog_settings,
+            style,
+            undef_mode,
+            last_defined: None,
         }
     }
 
@@ -359,6 +415,42 @@ impl<'a> CommandBuilder<'a> {
         self.cache.query_at_time(time)
     }
 
+    /// Maps an X/Z (non-finite) value to the value drawn for it according to the undef mode.
+    /// Values must be resolved in time order for [`AnalogUndefMode::HoldLast`] to work.
+    fn resolve(&mut self, value: f64) -> f64 {
+        if value.is_finite() {
+            self.last_defined = Some(value);
+            return value;
+        }
+        match self.undef_mode {
+            AnalogUndefMode::Gap => value,
+            AnalogUndefMode::HoldLast => self.last_defined.unwrap_or(value),
+            AnalogUndefMode::Zero => 0.,
+        }
+    }
+
+    /// Resolves the extents of a pixel containing X/Z values. The cache only reports that the
+    /// range has a non-finite value, so the finite maximum is approximated by the entry and
+    /// exit values of the pixel.
+    fn resolve_range(&self, min: f64, max: f64, entry_val: f64, exit_val: f64) -> (f64, f64) {
+        if max.is_finite() {
+            return (min, max);
+        }
+        let fill = match self.undef_mode {
+            AnalogUndefMode::Gap => return (min, max),
+            AnalogUndefMode::HoldLast => self.last_defined,
+            AnalogUndefMode::Zero => Some(0.),
+        };
+        let (lo, hi) = [min, entry_val, exit_val]
+            .into_iter()
+            .chain(fill)
+            .filter(|v| v.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        if lo <= hi { (lo, hi) } else { (min, max) }
+    }
+
     /// Captures the most recent sample occurring before the visible viewport.
     /// This method ensures rendering continuity when a signal value extends from before
     /// the viewport into the visible area.
@@ -368,6 +460,7 @@ impl<'a> CommandBuilder<'a> {
         if let Some((time, value)) = query.current {
             let px = self.pixel_at_time(time);
             if px < 0.0 {
+                let value = self.resolve(value);
                 self.output.update_bounds(value);
                 self.output.pending_flat = Some((px, value));
                 return Some(px);
@@ -420,6 +513,7 @@ impl<'a> CommandBuilder<'a> {
         next_query_time: &mut Option<u64>,
     ) -> u32 {
         if let Some((_, value)) = query.current {
+            let value = self.resolve(value);
             self.output.update_bounds(value);
             self.output.emit_flat(px as f32, value);
         }
@@ -442,9 +536,6 @@ impl<'a> CommandBuilder<'a> {
 
     fn process_range(&mut self, px: u32, t0: u64, t1: u64) {
         if let Some((min, max)) = self.cache.query_time_range(t0, t1.saturating_sub(1)) {
-            self.output.update_bounds(min);
-            self.output.update_bounds(max);
-
             // Query the value at the first transition within the pixel (entry value)
             // This is used as end_val for the preceding Flat in interpolated mode
             let t0_query = self.query(t0);
@@ -466,6 +557,12 @@ impl<'a> CommandBuilder<'a> {
             let exit_query = self.query(t1.saturating_sub(1));
             let exit_val = exit_query.current.map_or(max, |(_, v)| v);
 
+            let (min, max) = self.resolve_range(min, max, entry_val, exit_val);
+            let entry_val = self.resolve(entry_val);
+            let exit_val = self.resolve(exit_val);
+            self.output.update_bounds(min);
+            self.output.update_bounds(max);
+
             self.output
                 .emit_range(px as f32, min, max, entry_val, exit_val);
         }
@@ -487,6 +584,7 @@ impl<'a> CommandBuilder<'a> {
         let after_query = self.query(next_time);
 
         if let Some((_, value)) = after_query.current {
+            let value = self.resolve(value);
             self.output.update_bounds(value);
 
             if let Some((start, start_val)) = self.output.pending_flat.take() {
@@ -528,6 +626,7 @@ impl<'a> CommandBuilder<'a> {
             min_valid_pixel: self.min_valid_pixel,
             max_valid_pixel: self.max_valid_pixel,
             analog_settings: self.analog_settings,
+            style: self.style,
         }
     }
 }
@@ -614,7 +713,7 @@ pub struct RenderContext {
 impl RenderContext {
     #[allow(clippy::too_many_arguments)]
     fn new(
-        color: Color32,
+        stroke: Stroke,
         min_val: f64,
         max_val: f64,
         min_valid_pixel: f32,
@@ -624,7 +723,7 @@ impl RenderContext {
         ctx: &DrawingContext,
     ) -> Self {
         Self {
-            stroke: Stroke::new(ctx.theme.linewidth, color),
+            stroke,
             min_val,
             max_val,
             min_valid_pixel,
@@ -672,6 +771,11 @@ impl RenderContext {
             .add(PathShape::line(vec![from, to], self.stroke));
     }
 
+    pub fn draw_point_marker(&self, pos: Pos2, ctx: &mut DrawingContext) {
+        ctx.painter
+            .circle_filled(pos, self.stroke.width + 1.0, self.stroke.color);
+    }
+
     pub fn draw_undefined(&self, start_x: f32, end_x: f32, value: f64, ctx: &mut DrawingContext) {
         let color = if value == f64::INFINITY {
             ctx.theme.accent_error.background
@@ -932,3 +1036,106 @@ fn draw_amplitude_labels(render_ctx: &RenderContext, ctx: &mut DrawingContext) {
             .text(min_pos, Align2::LEFT_BOTTOM, min_text, font, text_color);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analog_signal_cache::NAN_UNDEF;
+
+    #[test]
+    fn analog_style_is_applied_to_draw_commands() {
+        let cache = AnalogSignalCache::from_samples(vec![(0, 1.0), (500, 2.0)], 1000, 64).unwrap();
+        let viewport = Viewport::default();
+        let num_timestamps = BigInt::from(1000);
+        let style = AnalogStyle {
+            line_width: Some(3.0),
+            point_markers: true,
+        };
+
+        let commands = CommandBuilder::new(
+            &cache,
+            &viewport,
+            &num_timestamps,
+            100.0,
+            AnalogSettings::step_viewport(),
+            style,
+            AnalogUndefMode::Gap,
+        )
+        .build();
+
+        let AnalogDrawingCommands::Ready {
+            values,
+            min_valid_pixel,
+            max_valid_pixel,
+            style: command_style,
+            ..
+        } = commands
+        else {
+            panic!("Expected ready draw commands");
+        };
+        assert_eq!(command_style, style);
+        assert_eq!(
+            point_marker_positions(&values, min_valid_pixel, max_valid_pixel),
+            vec![(0.0, 1.0), (50.0, 2.0)]
+        );
+
+        let stroke = analog_stroke(&command_style, Color32::RED, 2.0);
+        assert_eq!(stroke.width, 3.0);
+        assert_eq!(stroke.color, Color32::RED);
+        assert_eq!(
+            analog_stroke(&AnalogStyle::default(), Color32::RED, 2.0).width,
+            2.0
+        );
+    }
+
+    fn flat_start_values(undef_mode: AnalogUndefMode) -> Vec<(f32, f64)> {
+        let cache =
+            AnalogSignalCache::from_samples(vec![(0, 1.0), (300, NAN_UNDEF), (600, 2.0)], 1000, 64)
+                .unwrap();
+        let viewport = Viewport::default();
+        let num_timestamps = BigInt::from(1000);
+
+        let AnalogDrawingCommands::Ready { values, .. } = CommandBuilder::new(
+            &cache,
+            &viewport,
+            &num_timestamps,
+            100.0,
+            AnalogSettings::step_viewport(),
+            AnalogStyle::default(),
+            undef_mode,
+        )
+        .build() else {
+            panic!("Expected ready draw commands");
+        };
+        values
+            .iter()
+            .filter_map(|command| match command {
+                AnalogDrawingCommand::Flat {
+                    start_px,
+                    start_val,
+                    ..
+                } => Some((*start_px, *start_val)),
+                AnalogDrawingCommand::Range { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn undef_mode_controls_undefined_values() {
+        let gap = flat_start_values(AnalogUndefMode::Gap);
+        assert_eq!(gap.len(), 3);
+        assert_eq!(gap[0], (0.0, 1.0));
+        assert_eq!(gap[1].0, 30.0);
+        assert!(gap[1].1.is_nan());
+        assert_eq!(gap[2], (60.0, 2.0));
+
+        assert_eq!(
+            flat_start_values(AnalogUndefMode::HoldLast),
+            vec![(0.0, 1.0), (60.0, 2.0)]
+        );
+        assert_eq!(
+            flat_start_values(AnalogUndefMode::Zero),
+            vec![(0.0, 1.0), (30.0, 0.0), (60.0, 2.0)]
+        );
+    }
+}
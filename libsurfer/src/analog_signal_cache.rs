@@ -285,6 +285,16 @@ impl AnalogSignalCache {
             signal_data.push((time_u64, numeric));
         }
 
+        Self::from_samples(signal_data, num_timestamps, block_size)
+    }
+
+    /// Builds the cache from already translated `(time, value)` samples. The global extents
+    /// are computed here, once, and reused by every draw until the cache is replaced.
+    pub(crate) fn from_samples(
+        signal_data: Vec<(u64, f64)>,
+        num_timestamps: u64,
+        block_size: usize,
+    ) -> Option<Self> {
         if signal_data.is_empty() {
             return None;
         }
@@ -354,6 +364,7 @@ impl AnalogCacheEntry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wave_container::SignalId;
 
     #[test]
     fn test_single_sample() {
@@ -840,6 +851,26 @@ mod tests {
         assert_eq!(result.next, None);
     }
 
+    #[test]
+    fn test_entry_keeps_first_extents() {
+        let entry = AnalogCacheEntry::new((SignalId::None, "Unsigned".to_string(), None), 0);
+        assert!(!entry.is_ready());
+
+        let signal = (0..1000u32).map(|i| (u64::from(i), f64::from(i % 100) - 10.0));
+        let cache = AnalogSignalCache::from_samples(signal.collect(), 1000, 64).unwrap();
+        assert_eq!((cache.global_min, cache.global_max), (-10.0, 89.0));
+        entry.set(cache);
+        assert!(entry.is_ready());
+
+        // A second build for the same entry must not replace the stored extents
+        let other = AnalogSignalCache::from_samples(vec![(0, 500.0)], 1000, 64).unwrap();
+        entry.set(other);
+        for _ in 0..2 {
+            let cache = entry.get().unwrap();
+            assert_eq!((cache.global_min, cache.global_max), (-10.0, 89.0));
+        }
+    }
+
     #[test]
     fn test_query_at_time_at_boundaries() {
         let signal = vec![(0, 1.0), (100, 2.0)];
@@ -0,0 +1,226 @@
+//! Expected values of variables, with the time ranges where they are violated drawn on the
+//! canvas.
+use num::{BigUint, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use surfer_translation_types::VariableValue;
+use tracing::warn;
+
+use crate::SystemState;
+use crate::displayed_item::{DisplayedFieldRef, DisplayedItem, DisplayedVariable};
+use crate::wave_data::WaveData;
+
+/// Condition that the value of a variable is expected to fulfill at all times.
+///
+/// Values are compared numerically when both sides are numbers, so `Eq("3")` matches a
+/// value of `0011`. Values with X or Z bits are compared as strings, ignoring case.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Assertion {
+    /// The value is always equal to the given value
+    Eq(String),
+    /// The value is always within `min..=max`. Values with X or Z bits are violations.
+    Range(BigUint, BigUint),
+    /// The value is never equal to the given value
+    Never(String),
+}
+
+fn value_matches(value: &VariableValue, expected: &str) -> bool {
+    match (value.parse_biguint(), expected.parse::<BigUint>()) {
+        (Ok(value), Ok(expected)) => *value == expected,
+        _ => value.to_string().eq_ignore_ascii_case(expected),
+    }
+}
+
+impl Assertion {
+    #[must_use]
+    pub fn holds(&self, value: &VariableValue) -> bool {
+        match self {
+            Assertion::Eq(expected) => value_matches(value, expected),
+            Assertion::Range(min, max) => value
+                .parse_biguint()
+                .is_ok_and(|value| (min..=max).contains(&value.as_ref())),
+            Assertion::Never(forbidden) => !value_matches(value, forbidden),
+        }
+    }
+}
+
+/// Time ranges `start..end` where `assertion` does not hold, given the value `changes` of a
+/// variable in time order. A violation that lasts until the last change extends to `end`.
+#[must_use]
+pub fn violation_regions(
+    changes: impl IntoIterator<Item = (u64, VariableValue)>,
+    end: u64,
+    assertion: &Assertion,
+) -> Vec<(u64, u64)> {
+    let mut regions = vec![];
+    let mut violation_start = None;
+    for (time, value) in changes {
+        match (violation_start, assertion.holds(&value)) {
+            (None, false) => violation_start = Some(time),
+            (Some(start), true) => {
+                // Values that only exist for zero time are not violations
+                if start < time {
+                    regions.push((start, time));
+                }
+                violation_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = violation_start {
+        regions.push((start, end.max(start)));
+    }
+    regions
+}
+
+impl WaveData {
+    /// Violations of the assertion of `variable` between `start` and `end`, clipped to that
+    /// range. Only the changes in the range are visited. Empty if there is no assertion or the
+    /// variable is not loaded yet.
+    #[must_use]
+    pub fn assertion_violations(
+        &self,
+        variable: &DisplayedVariable,
+        start: &BigUint,
+        end: &BigUint,
+    ) -> Vec<(u64, u64)> {
+        let Some(assertion) = &variable.assertion else {
+            return vec![];
+        };
+        let Some(wave_container) = self.inner.as_waves() else {
+            return vec![];
+        };
+        let query = |time: &BigUint| {
+            wave_container
+                .query_variable(&variable.variable_ref, time)
+                .ok()
+                .flatten()
+        };
+        // The value at `start` followed by the changes up to `end`
+        let changes = std::iter::successors(query(start), |result| {
+            query(result.next.as_ref().filter(|next| *next <= end)?)
+        })
+        .filter_map(|result| {
+            let (time, value) = result.current?;
+            Some((time.max(start.clone()).to_u64()?, value))
+        });
+        violation_regions(changes, end.to_u64().unwrap_or_default(), assertion)
+    }
+}
+
+impl SystemState {
+    pub fn set_variable_assertion(
+        &mut self,
+        displayed_field_ref: &DisplayedFieldRef,
+        assertion: Option<Assertion>,
+    ) {
+        if !displayed_field_ref.field.is_empty() {
+            warn!("Assertions on fields of variables are not supported");
+            return;
+        }
+        let Some(waves) = self.user.waves.as_mut() else {
+            return;
+        };
+        if let Some(DisplayedItem::Variable(variable)) =
+            waves.displayed_items.get_mut(&displayed_field_ref.item)
+        {
+            variable.assertion = assertion;
+            self.invalidate_draw_commands();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displayed_item::DisplayedItemRef;
+    use crate::message::Message;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+
+    fn changes(values: &[(u64, &str)]) -> Vec<(u64, VariableValue)> {
+        values
+            .iter()
+            .map(|(time, value)| match value.parse::<BigUint>() {
+                Ok(value) => (*time, VariableValue::BigUint(value)),
+                Err(_) => (*time, VariableValue::String((*value).to_string())),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn violations_cover_time_where_assertion_fails() {
+        let reset = changes(&[(0, "1"), (100, "0"), (150, "1"), (160, "0")]);
+        assert_eq!(
+            violation_regions(reset.clone(), 200, &Assertion::Eq("0".to_string())),
+            vec![(0, 100), (150, 160)]
+        );
+        assert_eq!(
+            violation_regions(reset, 200, &Assertion::Never("0".to_string())),
+            vec![(100, 150), (160, 200)]
+        );
+
+        let counter = changes(&[(0, "x"), (10, "2"), (20, "5"), (20, "6"), (30, "3")]);
+        assert_eq!(
+            violation_regions(
+                counter.clone(),
+                40,
+                &Assertion::Range(BigUint::from(2u32), BigUint::from(4u32))
+            ),
+            vec![(0, 10), (20, 30)]
+        );
+        // The glitch to 5 at time 20 is not a violation on its own
+        assert_eq!(
+            violation_regions(counter.clone(), 40, &Assertion::Never("5".to_string())),
+            vec![]
+        );
+        assert_eq!(
+            violation_regions(counter, 40, &Assertion::Never("X".to_string())),
+            vec![(0, 10)]
+        );
+    }
+
+    #[test]
+    fn variable_assertion_violations_follow_signal() {
+        let mut state = load_counter_vcd_with_variables(&["tb.reset"]);
+
+        let end = state
+            .user
+            .waves
+            .as_ref()
+            .unwrap()
+            .num_timestamps()
+            .unwrap()
+            .to_u64()
+            .unwrap();
+        let violations_between = |state: &SystemState, start: u64, end: u64| {
+            let waves = state.user.waves.as_ref().unwrap();
+            match waves.displayed_items.get(&DisplayedItemRef(1)) {
+                Some(DisplayedItem::Variable(variable)) => {
+                    waves.assertion_violations(variable, &BigUint::from(start), &BigUint::from(end))
+                }
+                _ => panic!("Expected a variable"),
+            }
+        };
+        let violations = |state: &SystemState| violations_between(state, 0, end);
+
+        state.update(Message::SetVariableAssertion(
+            DisplayedItemRef(1).into(),
+            Assertion::Eq("0".to_string()),
+        ));
+        // Reset is released at 100
+        assert_eq!(violations(&state), vec![(0, 100)]);
+        // Violations are clipped to the queried range
+        assert_eq!(violations_between(&state, 50, 300), vec![(50, 100)]);
+        assert_eq!(violations_between(&state, 20, 60), vec![(20, 60)]);
+        assert_eq!(violations_between(&state, 150, 300), vec![]);
+
+        state.update(Message::SetVariableAssertion(
+            DisplayedItemRef(1).into(),
+            Assertion::Never("0".to_string()),
+        ));
+        assert_eq!(violations(&state), vec![(100, end)]);
+        assert_eq!(violations_between(&state, 150, 300), vec![(150, 300)]);
+
+        state.update(Message::RemoveVariableAssertion(DisplayedItemRef(1).into()));
+        assert_eq!(violations(&state), vec![]);
+    }
+}
@@ -10,7 +10,7 @@ use crate::{
     command_parser::get_parser,
     fzcmd::parse_command,
     message::Message,
-    wave_source::{LoadProgress, LoadProgressStatus},
+    wave_source::{LoadProgress, LoadProgressStatus, fetch_url},
 };
 
 impl SystemState {
@@ -103,7 +103,7 @@ impl SystemState {
                     .collect::<Vec<_>>()
             })
             .filter_map(|(no, command)| {
-                if command.starts_with("run_command_file ") {
+                if command.starts_with("run_command_file ") || command.starts_with("source ") {
                     // Load commands from other file in place, otherwise they will be
                     // loaded when the corresponding message is processed, leading to
                     // a different position in the processing than expected.
@@ -140,10 +140,9 @@ impl SystemState {
     pub fn load_commands_from_url(&mut self, url: String) {
         let sender = self.channels.msg_sender.clone();
         let url_ = url.clone();
+        let token = self.url_token(&url);
         perform_async_work(async move {
-            let maybe_response = reqwest::get(&url)
-                .map(|e| e.with_context(|| format!("Failed fetch download {url}")))
-                .await;
+            let maybe_response = fetch_url(&url, token.as_deref()).await;
             let response: reqwest::Response = match maybe_response {
                 Ok(r) => r,
                 Err(e) => {
@@ -186,3 +185,34 @@ pub fn read_command_bytes(bytes: Vec<u8>) -> Vec<String> {
         .map(|file_content| file_content.lines().map(str::to_string).collect())
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{load_counter_vcd, wait_for_waves_fully_loaded};
+
+    #[test]
+    fn source_runs_command_file_mid_session() {
+        let mut state = load_counter_vcd();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("commands.sucl");
+        std::fs::write(
+            &path,
+            "variable_add tb.clk\nnot_a_command\nvariable_add tb.dut.counter\n",
+        )
+        .unwrap();
+        let msg = parse_command(&format!("source {}", path.display()), get_parser(&state)).unwrap();
+        state.update(msg);
+        wait_for_waves_fully_loaded(&mut state, 10);
+
+        // The invalid command is skipped without stopping the rest of the file
+        let waves = state.user.waves.as_ref().unwrap();
+        let names = waves
+            .items_tree
+            .iter()
+            .map(|node| waves.displayed_items[&node.item_ref].name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["clk", "counter"]);
+    }
+}
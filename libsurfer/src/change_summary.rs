@@ -0,0 +1,246 @@
+//! Window listing how often each displayed variable changes between two markers.
+use egui::{Context, Window};
+use egui_extras::{Column, TableBuilder};
+use num::{BigUint, One, Zero};
+use serde::Deserialize;
+
+use crate::SystemState;
+use crate::displayed_item::DisplayedItem;
+use crate::message::Message;
+use crate::wave_container::VariableRef;
+use crate::wave_data::WaveData;
+
+/// Column the change summary is sorted by
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub enum ChangeSummarySort {
+    /// Most changes first
+    Changes,
+    Name,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ChangeCount {
+    pub name: String,
+    pub changes: usize,
+}
+
+pub struct ChangeSummary {
+    pub markers: (u8, u8),
+    /// Start and end of the summarized range, both inclusive
+    pub start: BigUint,
+    pub end: BigUint,
+    /// Variables with at least one change in the range
+    pub rows: Vec<ChangeCount>,
+    pub sort: ChangeSummarySort,
+}
+
+impl ChangeSummary {
+    pub fn sort_by(&mut self, sort: ChangeSummarySort) {
+        self.sort = sort;
+        match sort {
+            ChangeSummarySort::Changes => self.rows.sort_by(|a, b| b.changes.cmp(&a.changes)),
+            ChangeSummarySort::Name => self.rows.sort_by(|a, b| a.name.cmp(&b.name)),
+        }
+    }
+}
+
+impl WaveData {
+    /// Number of value changes of `variable` in `start..=end`, not counting the initial value
+    #[must_use]
+    pub fn count_changes_between(
+        &self,
+        variable: &VariableRef,
+        start: &BigUint,
+        end: &BigUint,
+    ) -> usize {
+        let Some(waves) = self.inner.as_waves() else {
+            return 0;
+        };
+        // Querying just before `start` makes a change at `start` the first `next`
+        let mut time = if start.is_zero() {
+            BigUint::zero()
+        } else {
+            start - BigUint::one()
+        };
+        let mut changes = 0;
+        while let Ok(Some(result)) = waves.query_variable(variable, &time) {
+            let Some(next) = result.next else {
+                break;
+            };
+            if &next > end {
+                break;
+            }
+            changes += 1;
+            time = next;
+        }
+        changes
+    }
+
+    /// Change counts of the displayed variables between the two markers, most active first,
+    /// or `None` if one of the markers is not set.
+    #[must_use]
+    pub fn change_summary(&self, marker_a: u8, marker_b: u8) -> Option<ChangeSummary> {
+        let a = self.markers.get(&marker_a)?.to_biguint()?;
+        let b = self.markers.get(&marker_b)?.to_biguint()?;
+        let (start, end) = if a <= b { (a, b) } else { (b, a) };
+        let rows = self
+            .items_tree
+            .iter()
+            .filter_map(|node| match self.displayed_items.get(&node.item_ref)? {
+                item @ DisplayedItem::Variable(variable) => {
+                    let changes = self.count_changes_between(&variable.variable_ref, &start, &end);
+                    (changes > 0).then(|| ChangeCount {
+                        name: item.name(),
+                        changes,
+                    })
+                }
+                _ => None,
+            })
+            .collect();
+        let mut summary = ChangeSummary {
+            markers: (marker_a, marker_b),
+            start,
+            end,
+            rows,
+            sort: ChangeSummarySort::Changes,
+        };
+        summary.sort_by(ChangeSummarySort::Changes);
+        Some(summary)
+    }
+}
+
+impl SystemState {
+    pub fn draw_change_summary_window(
+        &self,
+        summary: &ChangeSummary,
+        ctx: &Context,
+        msgs: &mut Vec<Message>,
+    ) {
+        let mut open = true;
+        Window::new("Changes between markers")
+            .collapsible(true)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Markers #{} and #{}: {} to {}",
+                    summary.markers.0, summary.markers.1, summary.start, summary.end
+                ));
+                if summary.rows.is_empty() {
+                    ui.label("No displayed variable changes in this range");
+                }
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .columns(Column::auto().resizable(true), 2)
+                    .auto_shrink(emath::Vec2b::new(false, true))
+                    .header(row_height, |mut header| {
+                        for (label, sort) in [
+                            ("Variable", ChangeSummarySort::Name),
+                            ("Changes", ChangeSummarySort::Changes),
+                        ] {
+                            header.col(|ui| {
+                                if ui
+                                    .selectable_label(summary.sort == sort, label)
+                                    .on_hover_text("Sort by this column")
+                                    .clicked()
+                                {
+                                    msgs.push(Message::SortChangeSummary(sort));
+                                }
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        for row in &summary.rows {
+                            body.row(row_height, |mut table_row| {
+                                table_row.col(|ui| {
+                                    ui.label(&row.name);
+                                });
+                                table_row.col(|ui| {
+                                    ui.label(row.changes.to_string());
+                                });
+                            });
+                        }
+                    });
+                ui.add_space(15.);
+                ui.vertical_centered(|ui| {
+                    if ui.button("Close").clicked() {
+                        msgs.push(Message::CloseChangeSummary);
+                    }
+                });
+            });
+        if !open {
+            msgs.push(Message::CloseChangeSummary);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigInt;
+
+    use super::*;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+    use crate::wave_container::VariableRefExt;
+
+    #[test]
+    fn change_summary_counts_changes_between_markers() {
+        let mut state = load_counter_vcd_with_variables(&[
+            "tb.overflow",
+            "tb.dut.counter",
+            "tb.reset",
+            "tb.clk",
+        ]);
+        state.update(Message::SetMarker {
+            id: 1,
+            time: BigInt::from(150),
+        });
+        state.update(Message::SetMarker {
+            id: 2,
+            time: BigInt::from(100),
+        });
+
+        // The markers may be given in any order, changes at both ends are included
+        state.update(Message::SummarizeChangesBetween(1, 2));
+        let summary = state.change_summary.as_ref().unwrap();
+        assert_eq!(
+            (&summary.start, &summary.end),
+            (&BigUint::from(100u32), &BigUint::from(150u32))
+        );
+        let counts = summary
+            .rows
+            .iter()
+            .map(|row| (row.name.as_str(), row.changes))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![("clk", 6), ("counter", 3), ("reset", 1)]);
+
+        state.update(Message::SortChangeSummary(ChangeSummarySort::Name));
+        let names = state
+            .change_summary
+            .as_ref()
+            .unwrap()
+            .rows
+            .iter()
+            .map(|row| row.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["clk", "counter", "reset"]);
+        assert_eq!(
+            state.change_summary.as_ref().unwrap().sort,
+            ChangeSummarySort::Name
+        );
+
+        let waves = state.user.waves.as_ref().unwrap();
+        let counter = VariableRef::from_hierarchy_string("tb.dut.counter");
+        assert_eq!(
+            waves.count_changes_between(&counter, &BigUint::from(111u32), &BigUint::from(129u32)),
+            0
+        );
+        assert_eq!(
+            waves.count_changes_between(&counter, &BigUint::ZERO, &BigUint::from(110u32)),
+            1
+        );
+
+        state.update(Message::SummarizeChangesBetween(1, 9));
+        assert!(state.change_summary.is_none());
+    }
+}
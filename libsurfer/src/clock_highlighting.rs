@@ -84,3 +84,42 @@ pub fn clock_highlight_type_menu(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displayed_item::{DisplayedFieldRef, DisplayedItemRef};
+    use crate::message::MessageTarget;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+    use crate::view::DrawConfig;
+    use crate::{CachedDrawData, SystemState};
+
+    #[test]
+    fn disabled_clock_highlight_skips_clock_edges() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk"]);
+        state.update(Message::VariableFormatChange(
+            MessageTarget::Explicit(DisplayedFieldRef::from(DisplayedItemRef(1))),
+            String::from("Clock"),
+        ));
+
+        let clock_edges = |state: &SystemState| {
+            let cfg = DrawConfig::new(100., 1000., 16., 14.);
+            state.generate_draw_commands(&cfg, &mut vec![], 0);
+            match &state.draw_data.borrow()[0] {
+                Some(CachedDrawData::WaveDrawData(data)) => data.clock_edges.len(),
+                _ => panic!("Expected wave draw data"),
+            }
+        };
+        assert!(state.clock_highlight_enabled());
+        assert!(clock_edges(&state) > 0);
+
+        let highlight_type = state.clock_highlight_type();
+        state.update(Message::SetClockHighlightEnabled(false));
+        assert!(state.draw_data.borrow()[0].is_none());
+        assert_eq!(clock_edges(&state), 0);
+        assert_eq!(state.clock_highlight_type(), highlight_type);
+
+        state.update(Message::SetClockHighlightEnabled(true));
+        assert!(clock_edges(&state) > 0);
+    }
+}
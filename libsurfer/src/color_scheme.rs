@@ -0,0 +1,531 @@
+//! Rule based coloring of displayed items, and schemes of saved per-variable overrides.
+use std::collections::{BTreeMap, HashSet};
+
+use derive_more::Display;
+use enum_iterator::Sequence;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use surfer_translation_types::{Translator, VariableType};
+use tracing::warn;
+
+use crate::displayed_item::{DisplayedItem, FieldFormat};
+use crate::translation::TranslatorList;
+use crate::wave_container::{VariableRef, VariableRefExt};
+use crate::wave_data::WaveData;
+
+/// Selects the displayed items that a color rule applies to.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum Matcher {
+    /// Regular expression searched for in the variable name, without scope, or in the
+    /// displayed name of items that are not variables
+    Name(String),
+    /// Type of the variable as given by the waveform
+    Type(VariableType),
+    /// Every variable, but no other items
+    AnyVariable,
+}
+
+/// A color rule as written in the configuration file.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct ColorRule {
+    pub matcher: Matcher,
+    pub color: String,
+}
+
+impl From<ColorRule> for (Matcher, String) {
+    fn from(rule: ColorRule) -> Self {
+        (rule.matcher, rule.color)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Sequence)]
+pub enum BuiltinColorScheme {
+    #[display("Clocks, resets and data")]
+    ClocksResetsData,
+    #[display("Variable type")]
+    VariableType,
+}
+
+impl BuiltinColorScheme {
+    #[must_use]
+    pub fn rules(self) -> Vec<(Matcher, String)> {
+        let name =
+            |pattern: &str, color: &str| (Matcher::Name(pattern.to_string()), color.to_string());
+        let typ = |t: VariableType, color: &str| (Matcher::Type(t), color.to_string());
+        match self {
+            BuiltinColorScheme::ClocksResetsData => vec![
+                name("(?i)(clk|clock)", "Blue"),
+                name("(?i)(rst|reset)", "Red"),
+                (Matcher::AnyVariable, "Green".to_string()),
+            ],
+            BuiltinColorScheme::VariableType => vec![
+                typ(VariableType::VCDParameter, "Gray"),
+                typ(VariableType::RealParameter, "Gray"),
+                typ(VariableType::VCDReal, "Orange"),
+                typ(VariableType::VCDRealTime, "Orange"),
+                typ(VariableType::RealTime, "Orange"),
+                typ(VariableType::ShortReal, "Orange"),
+                typ(VariableType::VCDString, "Pink"),
+                typ(VariableType::Enum, "Violet"),
+                typ(VariableType::VCDEvent, "Yellow"),
+            ],
+        }
+    }
+}
+
+/// Color, format and height chosen for a variable, as stored in a scheme file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ItemOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub field_formats: Vec<FieldFormat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height_scaling_factor: Option<f32>,
+}
+
+/// Overrides of variables keyed by their full path, to be reused in another session of the
+/// same design.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ItemScheme {
+    pub items: BTreeMap<String, ItemOverrides>,
+}
+
+enum CompiledMatcher {
+    Name(Regex),
+    Type(VariableType),
+    AnyVariable,
+}
+
+impl CompiledMatcher {
+    fn new(matcher: &Matcher) -> Result<Self, regex::Error> {
+        match matcher {
+            Matcher::Name(pattern) => Regex::new(pattern).map(CompiledMatcher::Name),
+            Matcher::Type(t) => Ok(CompiledMatcher::Type(*t)),
+            Matcher::AnyVariable => Ok(CompiledMatcher::AnyVariable),
+        }
+    }
+
+    fn matches(&self, subject: &MatchSubject) -> bool {
+        match self {
+            CompiledMatcher::Name(regex) => regex.is_match(&subject.name),
+            CompiledMatcher::Type(t) => subject.typ == Some(*t),
+            CompiledMatcher::AnyVariable => subject.is_variable,
+        }
+    }
+}
+
+/// The parts of a displayed item that matchers are applied to
+struct MatchSubject {
+    name: String,
+    typ: Option<VariableType>,
+    is_variable: bool,
+}
+
+fn match_subject(
+    item: &DisplayedItem,
+    variable_type: impl Fn(&VariableRef) -> Option<VariableType>,
+) -> MatchSubject {
+    match item {
+        DisplayedItem::Variable(variable) => MatchSubject {
+            name: variable.variable_ref.name.clone(),
+            typ: variable_type(&variable.variable_ref),
+            is_variable: true,
+        },
+        other => MatchSubject {
+            name: other.name(),
+            typ: None,
+            is_variable: false,
+        },
+    }
+}
+
+/// Sets the color of all `items` matched by one of the `rules`, where the first matching rule
+/// wins. Items that no rule matches keep their color.
+pub fn apply_color_rules<'a>(
+    items: impl Iterator<Item = &'a mut DisplayedItem>,
+    rules: &[(Matcher, String)],
+    variable_type: impl Fn(&VariableRef) -> Option<VariableType>,
+) {
+    let compiled = rules
+        .iter()
+        .filter_map(|(matcher, color)| match CompiledMatcher::new(matcher) {
+            Ok(compiled) => Some((compiled, color)),
+            Err(e) => {
+                warn!("Ignoring color rule with invalid regex: {e}");
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for item in items {
+        let subject = match_subject(item, &variable_type);
+        let color = compiled
+            .iter()
+            .find_map(|(matcher, color)| matcher.matches(&subject).then_some(*color));
+        if let Some(color) = color {
+            item.set_color(&Some(color.clone()));
+        }
+    }
+}
+
+impl WaveData {
+    pub fn auto_color_items(&mut self, rules: &[(Matcher, String)]) {
+        let wave_container = self.inner.as_waves();
+        apply_color_rules(self.displayed_items.values_mut(), rules, |variable| {
+            wave_container
+                .and_then(|waves| waves.variable_meta(variable).ok())
+                .and_then(|meta| meta.variable_type)
+        });
+    }
+
+    /// The overrides of all displayed variables that have any
+    #[must_use]
+    pub fn item_scheme(&self) -> ItemScheme {
+        let items = self
+            .displayed_items
+            .values()
+            .filter_map(|item| {
+                let DisplayedItem::Variable(variable) = item else {
+                    return None;
+                };
+                let overrides = ItemOverrides {
+                    color: variable.color.clone(),
+                    background_color: variable.background_color.clone(),
+                    format: variable.format.clone(),
+                    field_formats: variable.field_formats.clone(),
+                    height_scaling_factor: variable.height_scaling_factor,
+                };
+                (overrides != ItemOverrides::default())
+                    .then(|| (variable.variable_ref.full_path_string(), overrides))
+            })
+            .collect();
+        ItemScheme { items }
+    }
+
+    /// Apply the overrides in `scheme` to the displayed variables with the same full path.
+    /// Settings without an override are kept. Returns the paths in the scheme that no
+    /// displayed variable matches.
+    pub fn apply_item_scheme(
+        &mut self,
+        scheme: &ItemScheme,
+        translators: &TranslatorList,
+    ) -> Vec<String> {
+        let wave_container = self.inner.as_waves();
+        let mut matched = HashSet::new();
+        for item in self.displayed_items.values_mut() {
+            let DisplayedItem::Variable(variable) = item else {
+                continue;
+            };
+            let Some((path, overrides)) = scheme
+                .items
+                .get_key_value(&variable.variable_ref.full_path_string())
+            else {
+                continue;
+            };
+            matched.insert(path);
+
+            if overrides.color.is_some() {
+                variable.color.clone_from(&overrides.color);
+            }
+            if overrides.background_color.is_some() {
+                variable
+                    .background_color
+                    .clone_from(&overrides.background_color);
+            }
+            if overrides.height_scaling_factor.is_some() {
+                variable.height_scaling_factor = overrides.height_scaling_factor;
+            }
+            if !overrides.field_formats.is_empty() {
+                variable.field_formats.clone_from(&overrides.field_formats);
+            }
+            if let Some(format) = &overrides.format {
+                if !translators
+                    .all_translator_names()
+                    .contains(&format.as_str())
+                {
+                    warn!("No translator {format}, keeping the format of {path}");
+                    continue;
+                }
+                let info = wave_container
+                    .and_then(|waves| waves.variable_meta(&variable.variable_ref).ok())
+                    .and_then(|meta| translators.get_translator(format).variable_info(&meta).ok());
+                if let Some(info) = info {
+                    variable.format = Some(format.clone());
+                    variable.info = info;
+                }
+            }
+        }
+        scheme
+            .items
+            .keys()
+            .filter(|path| !matched.contains(path))
+            .cloned()
+            .collect()
+    }
+
+    /// Select exactly the visible items that `matcher` matches
+    pub fn select_matching(&mut self, matcher: &Matcher) {
+        let compiled = match CompiledMatcher::new(matcher) {
+            Ok(compiled) => compiled,
+            Err(e) => {
+                warn!("Not selecting items, invalid regex: {e}");
+                return;
+            }
+        };
+        let wave_container = self.inner.as_waves();
+        let variable_type = |variable: &VariableRef| {
+            wave_container
+                .and_then(|waves| waves.variable_meta(variable).ok())
+                .and_then(|meta| meta.variable_type)
+        };
+        for node in self.items_tree.iter_visible_mut() {
+            node.selected = self
+                .displayed_items
+                .get(&node.item_ref)
+                .is_some_and(|item| compiled.matches(&match_subject(item, &variable_type)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use surfer_translation_types::VariableInfo;
+
+    use super::*;
+    use crate::SystemState;
+    use crate::displayed_item::{DisplayedDivider, DisplayedVariable, LeadingZeroMode};
+    use crate::displayed_item::{DisplayedFieldRef, DisplayedItemRef};
+    use crate::displayed_item_tree::VisibleItemIndex;
+    use crate::message::{Message, MessageTarget};
+    use crate::state_file_io::decode_item_scheme;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+    use crate::variable_name_type::VariableNameType;
+
+    fn variable(path: &str) -> DisplayedItem {
+        let variable_ref = VariableRef::from_hierarchy_string(path);
+        DisplayedItem::Variable(DisplayedVariable {
+            display_name: variable_ref.name.clone(),
+            variable_ref,
+            info: VariableInfo::Bool,
+            color: None,
+            background_color: None,
+            display_name_type: VariableNameType::Local,
+            manual_name: None,
+            format: None,
+            field_formats: vec![],
+            height_scaling_factor: None,
+            analog: None,
+            assertion: None,
+            unit: None,
+            value_text_align: None,
+            show_interval_labels: false,
+            leading_zeros: LeadingZeroMode::Default,
+            numeric_format: None,
+            fixed_point_radix: None,
+            captured_value: None,
+        })
+    }
+
+    #[test]
+    fn color_scheme_applies_first_matching_rule() {
+        let mut items = vec![
+            variable("tb.dut.clk"),
+            variable("tb.dut.rst_n"),
+            variable("tb.dut.data"),
+            variable("tb.dut.count"),
+            variable("tb.dut.WIDTH"),
+            DisplayedItem::Divider(DisplayedDivider {
+                color: Some("Pink".to_string()),
+                background_color: None,
+                name: Some("Outputs".to_string()),
+            }),
+        ];
+        let rules = vec![
+            (
+                Matcher::Name("(?i)(clk|clock)".to_string()),
+                "Blue".to_string(),
+            ),
+            (Matcher::Name("^rst".to_string()), "Red".to_string()),
+            (
+                Matcher::Type(VariableType::VCDParameter),
+                "Gray".to_string(),
+            ),
+            (Matcher::Name("^data$".to_string()), "Green".to_string()),
+            (Matcher::Name("(".to_string()), "Yellow".to_string()),
+        ];
+        let variable_type = |variable: &VariableRef| {
+            (variable.name == "WIDTH").then_some(VariableType::VCDParameter)
+        };
+
+        apply_color_rules(items.iter_mut(), &rules, variable_type);
+
+        let colors = items.iter().map(DisplayedItem::color).collect::<Vec<_>>();
+        assert_eq!(
+            colors,
+            vec![
+                Some("Blue"),
+                Some("Red"),
+                Some("Green"),
+                None,
+                Some("Gray"),
+                Some("Pink")
+            ]
+        );
+    }
+
+    #[test]
+    fn clocks_resets_data_only_colors_variables() {
+        let divider = |name: &str| {
+            DisplayedItem::Divider(DisplayedDivider {
+                color: None,
+                background_color: None,
+                name: Some(name.to_string()),
+            })
+        };
+        let mut items = vec![
+            variable("tb.dut.clk"),
+            variable("tb.dut.count"),
+            divider("Outputs"),
+        ];
+
+        apply_color_rules(
+            items.iter_mut(),
+            &BuiltinColorScheme::ClocksResetsData.rules(),
+            |_| None,
+        );
+
+        let colors = items.iter().map(DisplayedItem::color).collect::<Vec<_>>();
+        assert_eq!(colors, vec![Some("Blue"), Some("Green"), None]);
+    }
+
+    #[test]
+    fn color_rules_parse_from_toml() {
+        let rules: BTreeMap<String, Vec<ColorRule>> = toml::from_str(
+            r#"
+            "Handshakes" = [
+                { matcher = { Name = "(?i)valid" }, color = "Yellow" },
+                { matcher = { Type = "VCDParameter" }, color = "Gray" },
+            ]
+            "#,
+        )
+        .unwrap();
+        let rules = rules["Handshakes"]
+            .iter()
+            .cloned()
+            .map(<(Matcher, String)>::from)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            vec![
+                (Matcher::Name("(?i)valid".to_string()), "Yellow".to_string()),
+                (
+                    Matcher::Type(VariableType::VCDParameter),
+                    "Gray".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn builtin_schemes_have_valid_rules() {
+        for scheme in enum_iterator::all::<BuiltinColorScheme>() {
+            for (matcher, _) in scheme.rules() {
+                if let Matcher::Name(pattern) = matcher {
+                    assert!(Regex::new(&pattern).is_ok(), "{scheme}: {pattern}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn select_items_matching_name_and_type() {
+        let mut state = load_counter_vcd_with_variables(&[
+            "tb.clk",
+            "tb.overflow",
+            "tb.reset",
+            "tb.dut.counter",
+        ]);
+        state.update(Message::AddDivider(Some("reset".to_string()), None));
+        let selected_names = |state: &SystemState| {
+            let waves = state.user.waves.as_ref().unwrap();
+            waves
+                .items_tree
+                .iter_visible_selected()
+                .map(|node| match &waves.displayed_items[&node.item_ref] {
+                    DisplayedItem::Variable(variable) => variable.variable_ref.name.clone(),
+                    other => other.name(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        state.update(Message::SelectItemsMatching(Matcher::Name(
+            "^(clk|reset)$".to_string(),
+        )));
+        assert_eq!(selected_names(&state), vec!["clk", "reset", "reset"]);
+
+        state.update(Message::SelectItemsMatching(Matcher::Type(
+            VariableType::VCDWire,
+        )));
+        assert_eq!(selected_names(&state), vec!["overflow"]);
+
+        // An invalid pattern keeps the selection
+        state.update(Message::SelectItemsMatching(Matcher::Name("(".to_string())));
+        assert_eq!(selected_names(&state), vec!["overflow"]);
+    }
+
+    #[test]
+    fn item_scheme_round_trips_through_file() {
+        let variables = ["tb.clk", "tb.dut.counter"];
+        let mut state = load_counter_vcd_with_variables(&variables);
+        state.update(Message::ItemColorChange(
+            MessageTarget::Explicit(VisibleItemIndex(0)),
+            Some("Blue".to_string()),
+        ));
+        state.update(Message::ItemHeightScalingFactorChange(
+            MessageTarget::Explicit(VisibleItemIndex(0)),
+            2.0,
+        ));
+        state.update(Message::VariableFormatChange(
+            MessageTarget::Explicit(DisplayedFieldRef::from(DisplayedItemRef(2))),
+            "Binary".to_string(),
+        ));
+        let saved = state.user.waves.as_ref().unwrap().item_scheme();
+        assert_eq!(
+            saved.items["tb.clk"],
+            ItemOverrides {
+                color: Some("Blue".to_string()),
+                height_scaling_factor: Some(2.0),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            saved.items["tb.dut.counter"],
+            ItemOverrides {
+                format: Some("Binary".to_string()),
+                ..Default::default()
+            }
+        );
+
+        let encoded = state.encode_item_scheme().unwrap();
+        let mut scheme = decode_item_scheme(encoded.as_bytes()).unwrap();
+        assert_eq!(scheme, saved);
+        scheme.items.insert(
+            "tb.dut.missing".to_string(),
+            ItemOverrides {
+                color: Some("Red".to_string()),
+                ..Default::default()
+            },
+        );
+
+        let mut other = load_counter_vcd_with_variables(&variables);
+        let waves = other.user.waves.as_mut().unwrap();
+        let misses = waves.apply_item_scheme(&scheme, &other.translators);
+        assert_eq!(misses, vec!["tb.dut.missing".to_string()]);
+        assert_eq!(waves.item_scheme(), saved);
+    }
+}
@@ -1,13 +1,18 @@
 //! Command prompt handling.
+use camino::Utf8PathBuf;
 use regex::Regex;
 use std::sync::LazyLock;
 use std::{fs, str::FromStr};
 
+use crate::activity_histogram::HistogramScope;
+use crate::color_scheme::Matcher;
 use crate::config::ArrowKeyBindings;
 use crate::displayed_item_tree::{Node, VisibleItemIndex};
 use crate::fzcmd::{Command, ParamGreed};
 use crate::hierarchy::HierarchyStyle;
+use crate::hierarchy_export::HierarchyFormat;
 use crate::message::MessageTarget;
+use crate::time::Radix;
 use crate::transaction_container::StreamScopeRef;
 use crate::wave_container::{ScopeRef, ScopeRefExt, VariableRef, VariableRefExt};
 use crate::wave_data::ScopeType;
@@ -15,7 +20,7 @@ use crate::wave_source::LoadOptions;
 use crate::{
     SystemState,
     clock_highlighting::ClockHighlightType,
-    displayed_item::DisplayedItem,
+    displayed_item::{DisplayedFieldRef, DisplayedItem},
     message::Message,
     util::{alpha_idx_to_uint_idx, uint_idx_to_alpha_idx},
     variable_name_type::VariableNameType,
@@ -52,6 +57,15 @@ fn separate_at_space(query: &str) -> (String, String, String, String) {
     )
 }
 
+/// Parse an optional viewport index, defaulting to the first viewport if it is omitted
+fn parse_viewport_idx(word: &str, num_viewports: usize) -> Option<usize> {
+    let word = word.trim();
+    if word.is_empty() {
+        return Some(0);
+    }
+    word.parse().ok().filter(|idx| *idx < num_viewports)
+}
+
 pub fn get_parser(state: &SystemState) -> Command<Message> {
     fn single_word(
         suggestions: Vec<String>,
@@ -203,6 +217,28 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
         Vec::new()
     };
 
+    let event_names = state
+        .user
+        .waves
+        .as_ref()
+        .map(|waves| {
+            waves
+                .events
+                .iter()
+                .map(|event| event.name.clone())
+                .collect_vec()
+        })
+        .unwrap_or_default();
+    let focused_variable_at_cursor = state.user.waves.as_ref().and_then(|waves| {
+        let node = waves.items_tree.get_visible(waves.focused_item?)?;
+        match waves.displayed_items.get(&node.item_ref)? {
+            DisplayedItem::Variable(variable) => {
+                Some((variable.variable_ref.clone(), waves.cursor.clone()?))
+            }
+            _ => None,
+        }
+    });
+
     fn parse_marker(query: &str, markers: &[(Option<String>, u8)]) -> Option<u8> {
         if let Some(id_str) = query.strip_prefix("#") {
             let id = id_str.parse::<u8>().ok()?;
@@ -240,21 +276,30 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
     let mut commands = if state.user.waves.is_some() {
         vec![
             "load_file",
+            "merge_file",
             "load_url",
             #[cfg(not(target_arch = "wasm32"))]
             "load_state",
             "run_command_file",
+            "source",
             "run_command_file_from_url",
             "switch_file",
             "variable_add",
             "generator_add",
             "item_focus",
+            "swap_items",
             "item_set_color",
             "item_set_background_color",
             "item_set_format",
+            "translator_config_set",
             "item_unset_color",
             "item_unset_background_color",
             "item_unfocus",
+            "item_remove_selected",
+            "select_matching",
+            "select_type",
+            "item_reveal_in_hierarchy",
+            "item_toggle_hidden",
             "item_rename",
             "zoom_fit",
             "scope_add",
@@ -270,11 +315,14 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
             "config_reload",
             "theme_select",
             "reload",
+            "reload_preserving_view",
+            "refresh_signals",
             "remove_unavailable",
             "show_controls",
             "show_mouse_gestures",
             "show_quick_start",
             "show_logs",
+            "list_translators",
             #[cfg(feature = "performance_plot")]
             "show_performance",
             "scroll_to_start",
@@ -287,6 +335,11 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
             "toggle_side_panel",
             "toggle_fullscreen",
             "toggle_tick_lines",
+            "toggle_clock_highlight",
+            "toggle_scope_type_grouping",
+            "item_height_reset_all",
+            "item_height_set_all",
+            "item_expand_all_to_depth",
             "variable_add_from_scope",
             "generator_add_from_stream",
             "variable_set_name_type",
@@ -294,6 +347,8 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
             "preference_set_clock_highlight",
             "preference_set_hierarchy_style",
             "preference_set_arrow_key_bindings",
+            "preference_set_time_label_radix",
+            "preference_set_placement_grid",
             "goto_cursor",
             "goto_marker",
             "dump_tree",
@@ -303,26 +358,67 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
             "group_unfold_recursive",
             "group_fold_all",
             "group_unfold_all",
+            "group_fold_all_except_focused",
             "save_state",
             "save_state_as",
+            "config_export",
+            "scheme_save",
+            "scheme_apply",
             "timeline_add",
             "cursor_set",
+            "trigger_set",
+            "trigger_clear",
             "marker_set",
             "marker_remove",
+            "marker_shift_all",
+            "marker_align_to_cursor",
+            "marker_export",
+            "marker_import",
+            "repro_bundle_export",
+            "mark",
+            "marker_compare",
+            "marker_summarize_changes",
             "show_marker_window",
+            "show_wave_metadata",
+            "event_add",
+            "event_goto",
+            "show_events",
+            "hierarchy_export",
+            "svg_export",
             "viewport_add",
             "viewport_remove",
+            "viewport_sync",
+            "viewport_follow_cursor",
+            "toggle_viewport_link",
             "transition_next",
             "transition_previous",
+            "value_change_next",
+            "value_change_previous",
+            "goto_first_edge",
+            "goto_last_edge",
+            "goto_first_nonzero",
+            "goto_first_nonzero_or_unknown",
+            "info",
+            "watch_add",
+            "watch_remove",
+            "activity_histogram",
+            "activity_histogram_all",
             "transaction_next",
             "transaction_prev",
+            "transaction_export_json",
+            "goto_transaction",
             "copy_value",
+            "copy_value_table",
+            "copy_all_values",
+            "capture_value",
+            "clear_captured_value",
             "pause_simulation",
             "unpause_simulation",
             "undo",
             "redo",
             #[cfg(not(target_arch = "wasm32"))]
             wcp_start_or_stop,
+            "copy_connection_info",
             #[cfg(not(target_arch = "wasm32"))]
             "exit",
         ]
@@ -333,6 +429,7 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
             #[cfg(not(target_arch = "wasm32"))]
             "load_state",
             "run_command_file",
+            "source",
             "run_command_file_from_url",
             "config_reload",
             "theme_select",
@@ -342,14 +439,18 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
             "preference_set_clock_highlight",
             "preference_set_hierarchy_style",
             "preference_set_arrow_key_bindings",
+            "preference_set_time_label_radix",
+            "preference_set_placement_grid",
             "show_controls",
             "show_mouse_gestures",
             "show_quick_start",
             "show_logs",
+            "list_translators",
             #[cfg(feature = "performance_plot")]
             "show_performance",
             #[cfg(not(target_arch = "wasm32"))]
             wcp_start_or_stop,
+            "copy_connection_info",
             #[cfg(not(target_arch = "wasm32"))]
             "exit",
         ]
@@ -361,9 +462,83 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
 
     let mut theme_names = state.user.config.theme.theme_names.clone();
     let state_file = state.user.state_file.clone();
+    let visible_item_refs = state
+        .user
+        .waves
+        .as_ref()
+        .map(|waves| {
+            waves
+                .items_tree
+                .iter_visible()
+                .map(|node| node.item_ref)
+                .collect_vec()
+        })
+        .unwrap_or_default();
+    let num_viewports = state
+        .user
+        .waves
+        .as_ref()
+        .map_or(1, |waves| waves.viewports.len());
+    let viewport_suggestions = (0..num_viewports).map(|idx| idx.to_string()).collect_vec();
+    let viewports_following = state
+        .user
+        .waves
+        .as_ref()
+        .map(|waves| {
+            waves
+                .viewports
+                .iter()
+                .map(|viewport| viewport.follow_cursor)
+                .collect_vec()
+        })
+        .unwrap_or_default();
+    let displayed_variable_types = state
+        .user
+        .waves
+        .as_ref()
+        .map(|waves| {
+            waves
+                .displayed_items
+                .values()
+                .filter_map(|item| match item {
+                    DisplayedItem::Variable(variable) => {
+                        waves
+                            .inner
+                            .as_waves()?
+                            .variable_meta(&variable.variable_ref)
+                            .ok()?
+                            .variable_type
+                    }
+                    _ => None,
+                })
+                .sorted_by_key(ToString::to_string)
+                .dedup()
+                .collect_vec()
+        })
+        .unwrap_or_default();
+    let watched_variables = state
+        .watch_list
+        .iter()
+        .map(VariableRef::full_path_string)
+        .collect_vec();
+    let focused_field_ref = state.user.waves.as_ref().and_then(|waves| {
+        let node = waves.items_tree.get_visible(waves.focused_item?)?;
+        Some(DisplayedFieldRef {
+            item: node.item_ref,
+            field: vec![],
+        })
+    });
+    let focused_item = state
+        .user
+        .waves
+        .as_ref()
+        .and_then(|waves| waves.focused_item);
     let show_hierarchy = state.show_hierarchy();
     let show_menu = state.show_menu();
     let show_tick_lines = state.show_ticks();
+    let clock_highlight_enabled = state.clock_highlight_enabled();
+    let viewports_linked = state.viewports_linked();
+    let group_scopes_by_type = state.group_scopes_by_type();
     theme_names.insert(0, "default".to_string());
     Command::NonTerminal(
         ParamGreed::Word,
@@ -371,6 +546,8 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
         Box::new(move |query, _| {
             let variables_in_active_scope = variables_in_active_scope.clone();
             let markers = markers.clone();
+            let event_names = event_names.clone();
+            let focused_variable_at_cursor = focused_variable_at_cursor.clone();
             let scopes = scopes.clone();
             let active_scope = active_scope.clone();
             let is_transaction_container = is_transaction_container;
@@ -393,6 +570,15 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         )))
                     }),
                 ),
+                "merge_file" => single_word_delayed_suggestions(
+                    Box::new(all_wave_files),
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::LoadAdditionalFile(
+                            word.into(),
+                            LoadOptions::KeepAvailable,
+                        )))
+                    }),
+                ),
                 "load_url" => Some(Command::NonTerminal(
                     ParamGreed::Rest,
                     vec![],
@@ -403,7 +589,7 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         )))
                     }),
                 )),
-                "run_command_file" => single_word_delayed_suggestions(
+                "run_command_file" | "source" => single_word_delayed_suggestions(
                     Box::new(all_command_files),
                     Box::new(|word| Some(Command::Terminal(Message::LoadCommandFile(word.into())))),
                 ),
@@ -425,12 +611,22 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         ))))
                     }),
                 ),
-                "scroll_to_start" | "goto_start" => {
-                    Some(Command::Terminal(Message::GoToStart { viewport_idx: 0 }))
-                }
-                "scroll_to_end" | "goto_end" => {
-                    Some(Command::Terminal(Message::GoToEnd { viewport_idx: 0 }))
-                }
+                "scroll_to_start" | "goto_start" => optional_single_word(
+                    viewport_suggestions.clone(),
+                    Box::new(move |word| {
+                        Some(Command::Terminal(Message::GoToStart {
+                            viewport_idx: parse_viewport_idx(word, num_viewports)?,
+                        }))
+                    }),
+                ),
+                "scroll_to_end" | "goto_end" => optional_single_word(
+                    viewport_suggestions.clone(),
+                    Box::new(move |word| {
+                        Some(Command::Terminal(Message::GoToEnd {
+                            viewport_idx: parse_viewport_idx(word, num_viewports)?,
+                        }))
+                    }),
+                ),
                 "zoom_in" => Some(Command::Terminal(Message::CanvasZoom {
                     mouse_ptr: None,
                     delta: 0.5,
@@ -441,7 +637,14 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                     delta: 2.0,
                     viewport_idx: 0,
                 })),
-                "zoom_fit" => Some(Command::Terminal(Message::ZoomToFit { viewport_idx: 0 })),
+                "zoom_fit" => optional_single_word(
+                    viewport_suggestions.clone(),
+                    Box::new(move |word| {
+                        Some(Command::Terminal(Message::ZoomToFit {
+                            viewport_idx: parse_viewport_idx(word, num_viewports)?,
+                        }))
+                    }),
+                ),
                 "toggle_menu" => Some(Command::Terminal(Message::SetMenuVisible(!show_menu))),
                 "toggle_side_panel" => Some(Command::Terminal(Message::SetSidePanelVisible(
                     !show_hierarchy,
@@ -450,6 +653,30 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                 "toggle_tick_lines" => {
                     Some(Command::Terminal(Message::SetTickLines(!show_tick_lines)))
                 }
+                "item_height_reset_all" => Some(Command::Terminal(Message::ResetAllItemHeights)),
+                "item_height_set_all" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        word.parse::<f32>()
+                            .ok()
+                            .filter(|scale| *scale > 0.)
+                            .map(|scale| Command::Terminal(Message::SetAllItemHeights(scale)))
+                    }),
+                ),
+                "item_expand_all_to_depth" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        word.parse()
+                            .ok()
+                            .map(|levels| Command::Terminal(Message::ExpandAllToDepth(levels)))
+                    }),
+                ),
+                "toggle_clock_highlight" => Some(Command::Terminal(
+                    Message::SetClockHighlightEnabled(!clock_highlight_enabled),
+                )),
+                "toggle_scope_type_grouping" => Some(Command::Terminal(
+                    Message::SetGroupScopesByType(!group_scopes_by_type),
+                )),
                 // scope commands
                 "scope_add" | "module_add" | "stream_add" | "scope_add_recursive" => {
                     let recursive = query == "scope_add_recursive";
@@ -524,6 +751,8 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                 "reload" => Some(Command::Terminal(Message::ReloadWaveform(
                     keep_during_reload,
                 ))),
+                "reload_preserving_view" => Some(Command::Terminal(Message::ReloadPreservingView)),
+                "refresh_signals" => Some(Command::Terminal(Message::RefreshDisplayedSignals)),
                 "remove_unavailable" => Some(Command::Terminal(Message::RemovePlaceholders)),
                 "surver_select_file" => single_word(
                     surver_file_names.clone(),
@@ -566,6 +795,43 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         )
                     }
                 }
+                "watch_add" => single_word(
+                    variables.clone(),
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::AddToWatch(
+                            VariableRef::from_hierarchy_string(word),
+                        )))
+                    }),
+                ),
+                "watch_remove" => single_word(
+                    watched_variables.clone(),
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::RemoveFromWatch(
+                            VariableRef::from_hierarchy_string(word),
+                        )))
+                    }),
+                ),
+                "activity_histogram" => {
+                    let focused_item_ref = focused_field_ref.as_ref().map(|field| field.item);
+                    single_word(
+                        vec![],
+                        Box::new(move |buckets| {
+                            Some(Command::Terminal(Message::ComputeActivityHistogram {
+                                scope: HistogramScope::Item(focused_item_ref?),
+                                buckets: buckets.parse().ok()?,
+                            }))
+                        }),
+                    )
+                }
+                "activity_histogram_all" => single_word(
+                    vec![],
+                    Box::new(|buckets| {
+                        Some(Command::Terminal(Message::ComputeActivityHistogram {
+                            scope: HistogramScope::AllDisplayed,
+                            buckets: buckets.parse().ok()?,
+                        }))
+                    }),
+                ),
                 "variable_add_from_scope" | "generator_add_from_stream" => single_word(
                     variables_in_active_scope
                         .into_iter()
@@ -616,6 +882,32 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         )))
                     }),
                 ),
+                "translator_config_set" => Some(Command::NonTerminal(
+                    ParamGreed::ToComma,
+                    format_names.clone(),
+                    Box::new(|translator, _| {
+                        let translator = translator.trim().to_string();
+                        Some(Command::NonTerminal(
+                            ParamGreed::Custom(&separate_at_space),
+                            vec![],
+                            Box::new(move |key, _| {
+                                let translator = translator.clone();
+                                let key = key.to_string();
+                                Some(Command::NonTerminal(
+                                    ParamGreed::Rest,
+                                    vec![],
+                                    Box::new(move |value, _| {
+                                        Some(Command::Terminal(Message::SetTranslatorConfig(
+                                            translator.clone(),
+                                            key.clone(),
+                                            value.to_string(),
+                                        )))
+                                    }),
+                                ))
+                            }),
+                        ))
+                    }),
+                )),
                 "item_unset_background_color" => Some(Command::Terminal(
                     Message::ItemBackgroundColorChange(MessageTarget::CurrentSelection, None),
                 )),
@@ -629,6 +921,34 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         )))
                     }),
                 )),
+                "swap_items" => {
+                    let displayed_items = displayed_items.clone();
+                    Some(Command::NonTerminal(
+                        ParamGreed::Custom(&separate_at_space),
+                        displayed_items.clone(),
+                        Box::new(move |first, _| {
+                            // split off the idx which is always followed by an underscore
+                            let alpha_idx: String =
+                                first.chars().take_while(|c| *c != '_').collect();
+                            let first = alpha_idx_to_uint_idx(&alpha_idx)?;
+                            Some(Command::NonTerminal(
+                                ParamGreed::OptionalWord,
+                                displayed_items.clone(),
+                                // without a second item, swap with the focused item
+                                Box::new(move |second, _| {
+                                    let second = if second.trim().is_empty() {
+                                        focused_item?
+                                    } else {
+                                        let alpha_idx: String =
+                                            second.chars().take_while(|c| *c != '_').collect();
+                                        alpha_idx_to_uint_idx(&alpha_idx)?
+                                    };
+                                    Some(Command::Terminal(Message::SwapItems(first, second)))
+                                }),
+                            ))
+                        }),
+                    ))
+                }
                 "variable_set_name_type" => single_word(
                     vec![
                         "Local".to_string(),
@@ -663,6 +983,58 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                             .map(|idx| Command::Terminal(Message::FocusItem(idx)))
                     }),
                 ),
+                "goto_first_edge" | "goto_last_edge" => focused_field_ref.clone().map(|variable| {
+                    Command::Terminal(Message::CursorToSignalEdge {
+                        first: query == "goto_first_edge",
+                        variable,
+                    })
+                }),
+                "value_change_next" | "value_change_previous" => {
+                    focused_field_ref.clone().map(|variable| {
+                        Command::Terminal(Message::MoveCursorToValueChange {
+                            variable,
+                            next: query == "value_change_next",
+                        })
+                    })
+                }
+                "goto_first_nonzero" | "goto_first_nonzero_or_unknown" => {
+                    let visible_item_refs = visible_item_refs.clone();
+                    let unknown_is_nonzero = query == "goto_first_nonzero_or_unknown";
+                    single_word(
+                        displayed_items.clone(),
+                        Box::new(move |word| {
+                            // split off the idx which is always followed by an underscore
+                            let alpha_idx: String =
+                                word.chars().take_while(|c| *c != '_').collect();
+                            let VisibleItemIndex(vidx) = alpha_idx_to_uint_idx(&alpha_idx)?;
+                            let item = *visible_item_refs.get(vidx)?;
+                            Some(Command::Terminal(Message::GoToFirstNonZero {
+                                variable: item.into(),
+                                unknown_is_nonzero,
+                            }))
+                        }),
+                    )
+                }
+                "info" => {
+                    let visible_item_refs = visible_item_refs.clone();
+                    let focused_field_ref = focused_field_ref.clone();
+                    optional_single_word(
+                        displayed_items.clone(),
+                        Box::new(move |word| {
+                            if word.is_empty() {
+                                return focused_field_ref.clone().map(|field| {
+                                    Command::Terminal(Message::PrintVariableInfo(field))
+                                });
+                            }
+                            // split off the idx which is always followed by an underscore
+                            let alpha_idx: String =
+                                word.chars().take_while(|c| *c != '_').collect();
+                            let VisibleItemIndex(vidx) = alpha_idx_to_uint_idx(&alpha_idx)?;
+                            let item = *visible_item_refs.get(vidx)?;
+                            Some(Command::Terminal(Message::PrintVariableInfo(item.into())))
+                        }),
+                    )
+                }
                 "transition_next" => single_word(
                     displayed_items.clone(),
                     Box::new(|word| {
@@ -697,6 +1069,23 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                 "transaction_prev" => {
                     Some(Command::Terminal(Message::MoveTransaction { next: false }))
                 }
+                "goto_transaction" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        word.parse()
+                            .ok()
+                            .map(|id| Command::Terminal(Message::GoToTransactionById(id)))
+                    }),
+                ),
+                "transaction_export_json" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::ExportTransactionsJson {
+                            path: Some(Utf8PathBuf::from(word)),
+                            selection: vec![],
+                        }))
+                    }),
+                ),
                 "copy_value" => single_word(
                     displayed_items.clone(),
                     Box::new(|word| {
@@ -709,6 +1098,37 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         })
                     }),
                 ),
+                "copy_all_values" => Some(Command::Terminal(Message::CopyAllValuesAtCursor)),
+                "capture_value" | "clear_captured_value" => {
+                    let capture = query == "capture_value";
+                    single_word(
+                        displayed_items.clone(),
+                        Box::new(move |word| {
+                            // split off the idx which is always followed by an underscore
+                            let alpha_idx: String =
+                                word.chars().take_while(|c| *c != '_').collect();
+                            alpha_idx_to_uint_idx(&alpha_idx).map(|idx| {
+                                Command::Terminal(if capture {
+                                    Message::CaptureItemValue(Some(idx))
+                                } else {
+                                    Message::ClearCapturedValue(Some(idx))
+                                })
+                            })
+                        }),
+                    )
+                }
+                "copy_value_table" => optional_single_word(
+                    vec!["header".to_string()],
+                    Box::new(|word| match word {
+                        "" => Some(Command::Terminal(Message::CopySelectedValuesTable {
+                            header: false,
+                        })),
+                        "header" => Some(Command::Terminal(Message::CopySelectedValuesTable {
+                            header: true,
+                        })),
+                        _ => None,
+                    }),
+                ),
                 "preference_set_clock_highlight" => single_word(
                     ["Line", "Cycle", "None"]
                         .iter()
@@ -740,7 +1160,51 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         )))
                     }),
                 ),
+                "preference_set_time_label_radix" => single_word(
+                    enum_iterator::all::<Radix>()
+                        .map(|o| o.to_string())
+                        .collect_vec(),
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::SetTimeLabelRadix(
+                            Radix::from_str(word).unwrap_or(Radix::Decimal),
+                        )))
+                    }),
+                ),
+                "preference_set_placement_grid" => single_word(
+                    vec![],
+                    Box::new(|grid_str| match grid_str.parse() {
+                        Ok(grid) => Some(Command::Terminal(Message::SetPlacementGrid(grid))),
+                        _ => None,
+                    }),
+                ),
                 "item_unfocus" => Some(Command::Terminal(Message::UnfocusItem)),
+                "item_remove_selected" => Some(Command::Terminal(Message::RemoveVisibleItems(
+                    MessageTarget::CurrentSelection,
+                ))),
+                "select_matching" => single_word(
+                    vec![],
+                    Box::new(|pattern| {
+                        Some(Command::Terminal(Message::SelectItemsMatching(
+                            Matcher::Name(pattern.to_string()),
+                        )))
+                    }),
+                ),
+                "select_type" => {
+                    let types = displayed_variable_types.clone();
+                    single_word(
+                        types.iter().map(ToString::to_string).collect(),
+                        Box::new(move |word| {
+                            let typ = types.iter().find(|typ| typ.to_string() == word)?;
+                            Some(Command::Terminal(Message::SelectItemsMatching(
+                                Matcher::Type(*typ),
+                            )))
+                        }),
+                    )
+                }
+                "item_reveal_in_hierarchy" => {
+                    Some(Command::Terminal(Message::RevealInHierarchy(None)))
+                }
+                "item_toggle_hidden" => Some(Command::Terminal(Message::ToggleItemHidden(None))),
                 "divider_add" => optional_single_word(
                     vec![],
                     Box::new(|word| {
@@ -780,6 +1244,9 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                 }
                 "group_fold_all" => Some(Command::Terminal(Message::GroupFoldAll)),
                 "group_unfold_all" => Some(Command::Terminal(Message::GroupUnfoldAll)),
+                "group_fold_all_except_focused" => {
+                    Some(Command::Terminal(Message::CollapseAllExceptFocused))
+                }
                 "show_controls" => Some(Command::Terminal(Message::SetKeyHelpVisible(true))),
                 "show_mouse_gestures" => {
                     Some(Command::Terminal(Message::SetGestureHelpVisible(true)))
@@ -809,6 +1276,14 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         _ => None,
                     }),
                 ),
+                "trigger_set" => single_word(
+                    vec![],
+                    Box::new(|time_str| {
+                        let time = time_str.parse().ok()?;
+                        Some(Command::Terminal(Message::SetTriggerTime(Some(time))))
+                    }),
+                ),
+                "trigger_clear" => Some(Command::Terminal(Message::SetTriggerTime(None))),
                 "marker_set" => Some(Command::NonTerminal(
                     ParamGreed::Custom(&separate_at_space),
                     // FIXME use once fzcmd does not enforce suggestion match, as of now we couldn't add a marker (except the first)
@@ -830,6 +1305,7 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                                     None => Some(Command::Terminal(Message::AddMarker {
                                         time,
                                         name: Some(name.clone()),
+                                        idx: None,
                                         move_focus: true,
                                     })),
                                 }
@@ -837,6 +1313,15 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         ))
                     }),
                 )),
+                "mark" => Some(Command::NonTerminal(
+                    ParamGreed::Rest,
+                    vec![],
+                    Box::new(|prefix, _| {
+                        Some(Command::Terminal(Message::AddMarkerAtCursorAutoNamed(
+                            prefix.to_owned(),
+                        )))
+                    }),
+                )),
                 "marker_remove" => Some(Command::NonTerminal(
                     ParamGreed::Rest,
                     marker_suggestions(&markers),
@@ -845,10 +1330,120 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         Some(Command::Terminal(Message::RemoveMarker(marker_id)))
                     }),
                 )),
+                "marker_shift_all" => single_word(
+                    vec![],
+                    Box::new(|delta_str| match delta_str.parse() {
+                        Ok(delta) => Some(Command::Terminal(Message::ShiftAllMarkers(delta))),
+                        _ => None,
+                    }),
+                ),
+                "marker_align_to_cursor" => Some(Command::NonTerminal(
+                    ParamGreed::Rest,
+                    marker_suggestions(&markers),
+                    Box::new(move |name, _| {
+                        let marker_id = parse_marker(name, &markers)?;
+                        Some(Command::Terminal(Message::AlignMarkerToCursor(marker_id)))
+                    }),
+                )),
+                "marker_compare" => Some(Command::NonTerminal(
+                    ParamGreed::Rest,
+                    marker_suggestions(&markers),
+                    Box::new(move |name, _| {
+                        let marker_id = parse_marker(name, &markers)?;
+                        Some(Command::Terminal(Message::ShowCursorMarkerCompare(
+                            marker_id,
+                        )))
+                    }),
+                )),
+                "marker_summarize_changes" => Some(Command::NonTerminal(
+                    ParamGreed::Custom(&separate_at_space),
+                    marker_suggestions(&markers),
+                    Box::new(move |first, _| {
+                        let first = parse_marker(first, &markers)?;
+                        let markers = markers.clone();
+                        Some(Command::NonTerminal(
+                            ParamGreed::Word,
+                            marker_suggestions(&markers),
+                            Box::new(move |second, _| {
+                                let second = parse_marker(second, &markers)?;
+                                Some(Command::Terminal(Message::SummarizeChangesBetween(
+                                    first, second,
+                                )))
+                            }),
+                        ))
+                    }),
+                )),
                 "show_marker_window" => {
                     Some(Command::Terminal(Message::SetCursorWindowVisible(true)))
                 }
+                "show_wave_metadata" => {
+                    Some(Command::Terminal(Message::SetWaveMetadataVisible(true)))
+                }
+                "event_add" => Some(Command::NonTerminal(
+                    ParamGreed::Rest,
+                    vec![],
+                    Box::new(move |name, _| {
+                        let (variable, time) = focused_variable_at_cursor.clone()?;
+                        Some(Command::Terminal(Message::AddEvent {
+                            name: name.to_owned(),
+                            variable,
+                            time,
+                        }))
+                    }),
+                )),
+                "event_goto" => Some(Command::NonTerminal(
+                    ParamGreed::Rest,
+                    event_names.clone(),
+                    Box::new(move |name, _| {
+                        let idx = event_names.iter().position(|event| event == name)?;
+                        Some(Command::Terminal(Message::GoToEvent(idx)))
+                    }),
+                )),
+                "show_events" => Some(Command::Terminal(Message::SetEventsWindowVisible(true))),
+                "hierarchy_export" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        let path = Utf8PathBuf::from(word);
+                        Some(Command::Terminal(Message::ExportHierarchy {
+                            format: HierarchyFormat::from_path(&path),
+                            path: Some(path),
+                        }))
+                    }),
+                ),
+                "marker_export" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::ExportMarkers(
+                            Utf8PathBuf::from(word),
+                        )))
+                    }),
+                ),
+                "marker_import" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::ImportMarkers(
+                            Utf8PathBuf::from(word),
+                        )))
+                    }),
+                ),
+                "repro_bundle_export" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::ExportReproBundle(
+                            Utf8PathBuf::from(word),
+                        )))
+                    }),
+                ),
+                "svg_export" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::ExportSvg(Some(
+                            Utf8PathBuf::from(word),
+                        ))))
+                    }),
+                ),
                 "show_logs" => Some(Command::Terminal(Message::SetLogsVisible(true))),
+                "list_translators" => Some(Command::Terminal(Message::ListTranslatorsForFocused)),
                 "save_state" => Some(Command::Terminal(Message::SaveStateFile(
                     state_file.clone(),
                 ))),
@@ -860,6 +1455,30 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                         ))))
                     }),
                 ),
+                "config_export" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::ExportEffectiveConfig(Some(
+                            Utf8PathBuf::from(word),
+                        ))))
+                    }),
+                ),
+                "scheme_save" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::SaveScheme(Some(
+                            std::path::Path::new(word).into(),
+                        ))))
+                    }),
+                ),
+                "scheme_apply" => single_word(
+                    vec![],
+                    Box::new(|word| {
+                        Some(Command::Terminal(Message::ApplyScheme(Some(
+                            std::path::Path::new(word).into(),
+                        ))))
+                    }),
+                ),
                 "load_state" => single_word(
                     vec![],
                     Box::new(|word| {
@@ -870,6 +1489,27 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                 ),
                 "viewport_add" => Some(Command::Terminal(Message::AddViewport)),
                 "viewport_remove" => Some(Command::Terminal(Message::RemoveViewport)),
+                "viewport_sync" => optional_single_word(
+                    viewport_suggestions.clone(),
+                    Box::new(move |word| {
+                        Some(Command::Terminal(Message::SyncViewports {
+                            source: parse_viewport_idx(word, num_viewports)?,
+                        }))
+                    }),
+                ),
+                "viewport_follow_cursor" => optional_single_word(
+                    viewport_suggestions.clone(),
+                    Box::new(move |word| {
+                        let viewport_idx = parse_viewport_idx(word, num_viewports)?;
+                        Some(Command::Terminal(Message::SetViewportFollowCursor {
+                            viewport_idx,
+                            follow: !viewports_following.get(viewport_idx)?,
+                        }))
+                    }),
+                ),
+                "toggle_viewport_link" => Some(Command::Terminal(Message::SetViewportsLinked(
+                    !viewports_linked,
+                ))),
                 "pause_simulation" => Some(Command::Terminal(Message::PauseSimulation)),
                 "unpause_simulation" => Some(Command::Terminal(Message::UnpauseSimulation)),
                 "undo" => Some(Command::Terminal(Message::Undo(1))),
@@ -879,9 +1519,62 @@ pub fn get_parser(state: &SystemState) -> Command<Message> {
                     initiate: false,
                 })),
                 "wcp_server_stop" => Some(Command::Terminal(Message::StopWcpServer)),
+                "copy_connection_info" => {
+                    Some(Command::Terminal(Message::CopyServerConnectionInfo))
+                }
                 "exit" => Some(Command::Terminal(Message::Exit)),
                 _ => None,
             }
         }),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fzcmd::parse_command;
+    use crate::tests::fixtures::load_counter_vcd;
+
+    #[test]
+    fn viewport_commands_parse_optional_index() {
+        let mut state = load_counter_vcd();
+        state.update(Message::AddViewport);
+
+        let parse = |command: &str| parse_command(command, get_parser(&state));
+        assert!(matches!(
+            parse("zoom_fit"),
+            Ok(Message::ZoomToFit { viewport_idx: 0 })
+        ));
+        assert!(matches!(
+            parse("zoom_fit 1"),
+            Ok(Message::ZoomToFit { viewport_idx: 1 })
+        ));
+        assert!(matches!(
+            parse("goto_start"),
+            Ok(Message::GoToStart { viewport_idx: 0 })
+        ));
+        assert!(matches!(
+            parse("scroll_to_start 1"),
+            Ok(Message::GoToStart { viewport_idx: 1 })
+        ));
+        assert!(matches!(
+            parse("goto_end 1"),
+            Ok(Message::GoToEnd { viewport_idx: 1 })
+        ));
+        assert!(parse("zoom_fit 2").is_err());
+        assert!(parse("goto_end x").is_err());
+    }
+
+    #[test]
+    fn translator_config_command_allows_spaces_in_name_and_value() {
+        let state = load_counter_vcd();
+
+        let parse = |command: &str| parse_command(command, get_parser(&state));
+        assert!(matches!(
+            parse("translator_config_set Signed fixed point, mode fast and exact"),
+            Ok(Message::SetTranslatorConfig(name, key, value))
+                if name == "Signed fixed point" && key == "mode" && value == "fast and exact"
+        ));
+        assert!(parse("translator_config_set Signed fixed point, mode").is_err());
+    }
+}
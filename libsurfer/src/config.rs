@@ -6,22 +6,25 @@ use derive_more::{Display, FromStr};
 #[cfg(not(target_arch = "wasm32"))]
 use directories::ProjectDirs;
 use ecolor::Color32;
+use egui::panel::Side;
 use enum_iterator::Sequence;
 use epaint::{PathStroke, Stroke};
 use eyre::Report;
 use eyre::{Context, Result};
 use serde::de;
-use serde::{Deserialize, Deserializer, Serialize};
-use std::collections::HashMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap};
 #[cfg(not(target_arch = "wasm32"))]
 use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
 use surver::SurverConfig;
 
+use crate::color_scheme::ColorRule;
+use crate::displayed_item::{AnalogStyle, NumericFormatOptions};
 use crate::hierarchy::{HierarchyStyle, ParameterDisplayLocation};
 use crate::keyboard_shortcuts::{SurferShortcuts, deserialize_shortcuts};
 use crate::mousegestures::GestureZones;
-use crate::time::TimeFormat;
+use crate::time::{Radix, TimeFormat};
 use crate::wave_container::VariableMeta;
 use crate::{clock_highlighting::ClockHighlightType, variable_name_type::VariableNameType};
 use surfer_translation_types::VariableEncoding;
@@ -72,6 +75,106 @@ pub enum ArrowKeyBindings {
     Scroll,
 }
 
+/// Select what happens to the viewports when a waveform is reloaded
+#[derive(Clone, Copy, Debug, Deserialize, Display, FromStr, PartialEq, Eq, Sequence, Serialize)]
+pub enum ReloadViewport {
+    /// Keep the current zoom level and position, clipped to the new time range
+    Keep,
+
+    /// Zoom to fit the full new time range
+    ZoomToFit,
+
+    /// Keep the current time range if it fits in the new time range, otherwise zoom to fit
+    KeepIfValid,
+}
+
+/// Select what happens when adding a variable that is already displayed
+#[derive(Clone, Copy, Debug, Deserialize, Display, FromStr, PartialEq, Eq, Sequence, Serialize)]
+pub enum OnDuplicateAdd {
+    /// Add another copy of the variable
+    Allow,
+
+    /// Focus the already displayed variable instead of adding it
+    Focus,
+
+    /// Do not add the variable
+    Skip,
+}
+
+/// Select the level of dragged items that are dropped where they can go either into a group
+/// or next to it, e.g. right after the last item of a group
+#[derive(Clone, Copy, Debug, Deserialize, Display, FromStr, PartialEq, Eq, Sequence, Serialize)]
+pub enum GroupDropLevel {
+    /// Use the level under the mouse pointer
+    Pointer,
+
+    /// Drop into the innermost group
+    Inside,
+
+    /// Drop next to the group
+    Sibling,
+}
+
+/// Select what double-clicking a variable in the hierarchy does in addition to adding it
+#[derive(Clone, Copy, Debug, Deserialize, Display, FromStr, PartialEq, Eq, Sequence, Serialize)]
+pub enum HierarchyDoubleClick {
+    /// Add the variable once more, the same as a second click
+    Add,
+
+    /// Focus the added variable
+    AddAndFocus,
+
+    /// Focus the added variable and move the cursor to its first transition
+    AddAndGoToActivity,
+}
+
+/// Placement of the value text within the regions of multi-bit variables
+#[derive(Clone, Copy, Debug, Deserialize, Display, FromStr, PartialEq, Eq, Sequence, Serialize)]
+pub enum ValueTextAlign {
+    /// Start at the left edge, values that do not fit lose their last characters
+    Left,
+
+    /// End at the right edge, values that do not fit lose their first characters
+    Right,
+}
+
+/// Side of the waveform view on which the item name and value columns are placed
+#[derive(Clone, Copy, Debug, Deserialize, Display, FromStr, PartialEq, Eq, Sequence, Serialize)]
+pub enum ItemColumnSide {
+    Left,
+    Right,
+}
+
+impl ItemColumnSide {
+    #[must_use]
+    pub fn panel_side(self) -> Side {
+        match self {
+            ItemColumnSide::Left => Side::Left,
+            ItemColumnSide::Right => Side::Right,
+        }
+    }
+}
+
+/// Style of the fold arrows in front of the names of grouped items
+#[derive(Clone, Copy, Debug, Deserialize, Display, FromStr, PartialEq, Eq, Sequence, Serialize)]
+pub enum GroupConnectorStyle {
+    /// Arrows as large as the text
+    Arrow,
+    /// Smaller arrows, leaving more of the name column for the names
+    Compact,
+}
+
+impl GroupConnectorStyle {
+    /// Width of the fold arrows, which is also kept free in front of items without children
+    #[must_use]
+    pub fn icon_width(self, text_size: f32) -> f32 {
+        match self {
+            GroupConnectorStyle::Arrow => text_size,
+            GroupConnectorStyle::Compact => text_size * 0.6,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Display, FromStr, PartialEq, Eq, Sequence, Serialize)]
 pub enum TransitionValue {
     /// Transition value is the previous value
@@ -112,10 +215,13 @@ impl AutoLoad {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct SurferConfig {
     pub layout: SurferLayout,
-    #[serde(deserialize_with = "deserialize_theme")]
+    #[serde(
+        deserialize_with = "deserialize_theme",
+        serialize_with = "serialize_theme"
+    )]
     pub theme: SurferTheme,
     /// Mouse gesture configurations. Color and linewidth are configured in the theme using [`SurferTheme::gesture`].
     pub gesture: SurferGesture,
@@ -132,6 +238,11 @@ pub struct SurferConfig {
     autoreload_files: AutoLoad,
     /// Load state file
     autoload_sibling_state_files: AutoLoad,
+    /// Name of the state file loaded along with a waveform, relative to the directory of the
+    /// waveform. `{name}` is replaced by the file name of the waveform and `{stem}` by the file
+    /// name without its last extension. If empty, any state file in the directory is used.
+    #[serde(default)]
+    pub sibling_state_file_pattern: String,
     /// WCP Configuration
     pub wcp: WcpConfig,
     /// HTTP Server Configuration
@@ -146,6 +257,9 @@ pub struct SurferConfig {
     /// Keyboard shortcuts
     #[serde(deserialize_with = "deserialize_shortcuts")]
     pub shortcuts: SurferShortcuts,
+    /// Named rule sets for coloring items, in addition to [`crate::color_scheme::BuiltinColorScheme`]
+    #[serde(default)]
+    pub color_schemes: BTreeMap<String, Vec<ColorRule>>,
 }
 
 impl SurferConfig {
@@ -170,7 +284,7 @@ impl SurferConfig {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct SurferLayout {
     /// Flag to show/hide the hierarchy view
     show_hierarchy: bool,
@@ -234,6 +348,47 @@ pub struct SurferLayout {
     /// Value to display when cursor is on a transition
     #[serde(default = "default_next")]
     transition_value: TransitionValue,
+    /// Number base of the time labels of the ticks
+    #[serde(default = "default_time_label_radix")]
+    time_label_radix: Radix,
+    /// Hatch the parts of the waveform view before time zero and after the last timestamp
+    #[serde(default)]
+    show_no_data_hatch: bool,
+    /// Mark pixels where a variable changes more than once, so that busy regions remain
+    /// visible when zoomed out
+    #[serde(default)]
+    show_glitch_density: bool,
+    /// Cross-hatch values of multi-bit variables where only some of the bits are undefined or
+    /// high-impedance
+    #[serde(default)]
+    show_partial_unknowns: bool,
+    /// Show the average time spent on a frame in the statusbar
+    #[serde(default)]
+    show_fps: bool,
+    /// Show the cursor time relative to the trigger in the statusbar, if a trigger is set
+    #[serde(default = "default_true")]
+    show_trigger_relative_time: bool,
+    /// Draw a small trend line of the visible values next to the names of numeric variables
+    #[serde(default)]
+    show_sparklines: bool,
+    /// Style of analog traces that do not set their own
+    #[serde(default)]
+    analog_style: AnalogStyle,
+    /// Placement of the values of multi-bit variables that do not set their own
+    #[serde(default = "default_value_text_align")]
+    value_text_align: ValueTextAlign,
+    /// Side of the waveform view with the item name and value columns
+    #[serde(default = "default_item_column_side")]
+    item_column_side: ItemColumnSide,
+    /// Formatting of decimal and floating point values of variables that do not set their own
+    #[serde(default)]
+    numeric_format: NumericFormatOptions,
+    /// Indentation in points per level of nested groups in the name column
+    #[serde(default = "default_group_indent_px")]
+    group_indent_px: f32,
+    /// Style of the fold arrows of groups in the name column
+    #[serde(default = "default_group_connector_style")]
+    group_connector_style: GroupConnectorStyle,
 }
 
 fn default_true() -> bool {
@@ -244,6 +399,26 @@ fn default_next() -> TransitionValue {
     TransitionValue::Next
 }
 
+fn default_value_text_align() -> ValueTextAlign {
+    ValueTextAlign::Left
+}
+
+fn default_item_column_side() -> ItemColumnSide {
+    ItemColumnSide::Left
+}
+
+fn default_time_label_radix() -> Radix {
+    Radix::Decimal
+}
+
+fn default_group_indent_px() -> f32 {
+    10.0
+}
+
+fn default_group_connector_style() -> GroupConnectorStyle {
+    GroupConnectorStyle::Arrow
+}
+
 impl SurferLayout {
     #[must_use]
     pub fn show_hierarchy(&self) -> bool {
@@ -333,9 +508,61 @@ impl SurferLayout {
     pub fn transition_value(&self) -> TransitionValue {
         self.transition_value
     }
+    #[must_use]
+    pub fn time_label_radix(&self) -> Radix {
+        self.time_label_radix
+    }
+    #[must_use]
+    pub fn show_no_data_hatch(&self) -> bool {
+        self.show_no_data_hatch
+    }
+    #[must_use]
+    pub fn show_glitch_density(&self) -> bool {
+        self.show_glitch_density
+    }
+    #[must_use]
+    pub fn show_partial_unknowns(&self) -> bool {
+        self.show_partial_unknowns
+    }
+    #[must_use]
+    pub fn show_fps(&self) -> bool {
+        self.show_fps
+    }
+    #[must_use]
+    pub fn show_trigger_relative_time(&self) -> bool {
+        self.show_trigger_relative_time
+    }
+    #[must_use]
+    pub fn show_sparklines(&self) -> bool {
+        self.show_sparklines
+    }
+    #[must_use]
+    pub fn analog_style(&self) -> AnalogStyle {
+        self.analog_style
+    }
+    #[must_use]
+    pub fn value_text_align(&self) -> ValueTextAlign {
+        self.value_text_align
+    }
+    #[must_use]
+    pub fn item_column_side(&self) -> ItemColumnSide {
+        self.item_column_side
+    }
+    #[must_use]
+    pub fn numeric_format(&self) -> NumericFormatOptions {
+        self.numeric_format
+    }
+    #[must_use]
+    pub fn group_indent_px(&self) -> f32 {
+        self.group_indent_px
+    }
+    #[must_use]
+    pub fn group_connector_style(&self) -> GroupConnectorStyle {
+        self.group_connector_style
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct SurferBehavior {
     /// Keep or remove variables if unavailable during reload
     pub keep_during_reload: bool,
@@ -344,6 +571,57 @@ pub struct SurferBehavior {
     /// Whether dragging with primary mouse button will measure time or move cursor
     /// (press shift for the other)
     primary_button_drag_behavior: PrimaryMouseDrag,
+    /// What to do with the viewports after a reload updated the time range
+    #[serde(default = "default_reload_viewport")]
+    reload_viewport: ReloadViewport,
+    /// Draw a faint vertical line with the time at the mouse position in the waveform view
+    #[serde(default)]
+    hover_crosshair: bool,
+    /// Center the view on the cursor after moving it to a transition, instead of only
+    /// scrolling when it leaves the view
+    #[serde(default)]
+    center_on_transition: bool,
+    /// What to do when adding a variable that is already displayed
+    #[serde(default = "default_on_duplicate_add")]
+    on_duplicate_add: OnDuplicateAdd,
+    /// Level of dragged items dropped at the boundary of a group. Holding alt while dragging
+    /// drops them inside the group, holding shift drops them next to it.
+    #[serde(default = "default_group_drop_level")]
+    group_drop_level: GroupDropLevel,
+    /// What to do when a variable in the hierarchy is double-clicked
+    #[serde(default = "default_hierarchy_double_click")]
+    hierarchy_double_click: HierarchyDoubleClick,
+    /// Spacing, in the current time unit, of the grid new markers and events are rounded
+    /// to. 0 turns the grid off.
+    #[serde(default)]
+    placement_grid: u64,
+    /// Exponent applied to the zoom factor of the scroll wheel and pinch gestures, larger values
+    /// zoom faster
+    #[serde(default = "default_zoom_sensitivity")]
+    zoom_sensitivity: f32,
+    /// Swap the zoom direction of the scroll wheel and pinch gestures
+    #[serde(default)]
+    invert_zoom_scroll: bool,
+}
+
+fn default_zoom_sensitivity() -> f32 {
+    1.0
+}
+
+fn default_reload_viewport() -> ReloadViewport {
+    ReloadViewport::Keep
+}
+
+fn default_on_duplicate_add() -> OnDuplicateAdd {
+    OnDuplicateAdd::Allow
+}
+
+fn default_group_drop_level() -> GroupDropLevel {
+    GroupDropLevel::Pointer
+}
+
+fn default_hierarchy_double_click() -> HierarchyDoubleClick {
+    HierarchyDoubleClick::Add
 }
 
 impl SurferBehavior {
@@ -356,9 +634,54 @@ impl SurferBehavior {
     pub fn arrow_key_bindings(&self) -> ArrowKeyBindings {
         self.arrow_key_bindings
     }
+
+    #[must_use]
+    pub fn reload_viewport(&self) -> ReloadViewport {
+        self.reload_viewport
+    }
+
+    #[must_use]
+    pub fn hover_crosshair(&self) -> bool {
+        self.hover_crosshair
+    }
+
+    #[must_use]
+    pub fn center_on_transition(&self) -> bool {
+        self.center_on_transition
+    }
+
+    #[must_use]
+    pub fn on_duplicate_add(&self) -> OnDuplicateAdd {
+        self.on_duplicate_add
+    }
+
+    #[must_use]
+    pub fn group_drop_level(&self) -> GroupDropLevel {
+        self.group_drop_level
+    }
+
+    #[must_use]
+    pub fn hierarchy_double_click(&self) -> HierarchyDoubleClick {
+        self.hierarchy_double_click
+    }
+
+    #[must_use]
+    pub fn placement_grid(&self) -> u64 {
+        self.placement_grid
+    }
+
+    #[must_use]
+    pub fn zoom_sensitivity(&self) -> f32 {
+        self.zoom_sensitivity
+    }
+
+    #[must_use]
+    pub fn invert_zoom_scroll(&self) -> bool {
+        self.invert_zoom_scroll
+    }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 /// Mouse gesture configurations. Color and linewidth are configured in the theme using [`SurferTheme::gesture`].
 pub struct SurferGesture {
     /// Size of the overlay help
@@ -518,6 +841,11 @@ pub struct SurferTheme {
     /// alternate background and so on, set to zero to disable
     pub alt_frequency: usize,
 
+    /// Shade every other top-level item, with groups shaded as a unit, with a faint tint of
+    /// the canvas background. Replaces the `alt_frequency` shading when set.
+    #[serde(default)]
+    pub zebra_rows: bool,
+
     /// Viewport separator line
     pub viewport_separator: SurferLineStyle,
 
@@ -530,6 +858,10 @@ pub struct SurferTheme {
     /// Tick information
     pub ticks: SurferTicks,
 
+    /// Name the theme was selected by, empty for the default theme
+    #[serde(default)]
+    pub name: String,
+
     /// List of theme names
     #[serde(default = "Vec::new")]
     pub theme_names: Vec<String>,
@@ -917,7 +1249,9 @@ impl SurferTheme {
 
         let (theme, _) = Self::generate_defaults(theme_name.as_ref());
 
-        let theme = theme.set_override("theme_names", all_theme_names())?;
+        let theme = theme
+            .set_override("theme_names", all_theme_names())?
+            .set_override("name", theme_name.unwrap_or_default())?;
 
         theme
             .build()?
@@ -990,7 +1324,9 @@ impl SurferTheme {
             }
         }
 
-        let theme = theme.set_override("theme_names", theme_names)?;
+        let theme = theme
+            .set_override("theme_names", theme_names)?
+            .set_override("name", theme_name.unwrap_or_default())?;
 
         theme
             .build()?
@@ -1017,7 +1353,7 @@ pub struct ThemeColorTriple {
     pub alt_background: Color32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct WcpConfig {
     /// Controls if a server is started after Surfer is launched
     pub autostart: bool,
@@ -1158,6 +1494,14 @@ where
     SurferTheme::new(Some(buf)).map_err(de::Error::custom)
 }
 
+/// Themes are referred to by name in the configuration
+fn serialize_theme<S>(theme: &SurferTheme, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    theme.name.serialize(serializer)
+}
+
 /// Searches for `.surfer` directories upward from the current location until it reaches root.
 /// Returns an empty vector in case the search fails in any way. If any `.surfer` directories
 /// are found, they will be returned in a `Vec<PathBuf>` in a pre-order of most top-level to most
@@ -1,7 +1,8 @@
 use crate::message::Message;
 use ecolor::Color32;
-use egui::{Layout, RichText};
+use egui::{Key, Layout, RichText};
 use emath::Align;
+use num::BigInt;
 
 #[derive(Debug, Default, Copy, Clone)]
 pub struct ReloadWaveformDialog {
@@ -14,6 +15,30 @@ pub struct OpenSiblingStateFileDialog {
     do_not_show_again: bool,
 }
 
+/// Model of the dialog adding a named marker, opened by [`Message::ShowAddMarkerDialog`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddMarkerDialog {
+    /// Time of the new marker, as entered by the user.
+    pub time: String,
+    /// Name of the new marker, the default marker name is used if empty.
+    pub name: String,
+    pub idx: u8,
+    pub color: Option<String>,
+}
+
+impl AddMarkerDialog {
+    /// Dialog adding marker `idx` at the cursor, or at `viewport_center` if the cursor is not set.
+    #[must_use]
+    pub fn new(cursor: Option<&BigInt>, viewport_center: &BigInt, idx: u8) -> Self {
+        Self {
+            time: cursor.unwrap_or(viewport_center).to_string(),
+            name: String::new(),
+            idx,
+            color: None,
+        }
+    }
+}
+
 /// Draw a dialog that asks the user if it wants to load a state file situated in the same directory as the waveform file.
 pub(crate) fn draw_open_sibling_state_file_dialog(
     ctx: &egui::Context,
@@ -116,3 +141,157 @@ pub(crate) fn draw_reload_waveform_dialog(
             });
         });
 }
+
+/// Draw a dialog for adding a marker with a name, index and color chosen by the user.
+/// `free_indices` are the marker indices that can be picked.
+pub(crate) fn draw_add_marker_dialog(
+    ctx: &egui::Context,
+    dialog: &AddMarkerDialog,
+    free_indices: &[u8],
+    color_names: &[String],
+    msgs: &mut Vec<Message>,
+) {
+    let mut new_dialog = dialog.clone();
+    egui::Window::new("Add marker")
+        .auto_sized()
+        .collapsible(false)
+        .fixed_pos(ctx.available_rect().center())
+        .show(ctx, |ui| {
+            egui::Grid::new("add_marker_dialog")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Name");
+                    let name_edit = ui.text_edit_singleline(&mut new_dialog.name);
+                    if ctx.memory(|mem| mem.focused().is_none()) {
+                        name_edit.request_focus();
+                    }
+                    ui.end_row();
+
+                    ui.label("Time");
+                    ui.text_edit_singleline(&mut new_dialog.time);
+                    ui.end_row();
+
+                    ui.label("Id");
+                    egui::ComboBox::from_id_salt("add_marker_dialog_idx")
+                        .selected_text(new_dialog.idx.to_string())
+                        .show_ui(ui, |ui| {
+                            for idx in free_indices {
+                                ui.selectable_value(&mut new_dialog.idx, *idx, idx.to_string());
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Color");
+                    egui::ComboBox::from_id_salt("add_marker_dialog_color")
+                        .selected_text(new_dialog.color.as_deref().unwrap_or("Default"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut new_dialog.color, None, "Default");
+                            for color_name in color_names {
+                                ui.selectable_value(
+                                    &mut new_dialog.color,
+                                    Some(color_name.clone()),
+                                    color_name,
+                                );
+                            }
+                        });
+                    ui.end_row();
+                });
+            ui.add_space(14.0);
+            let time_valid = new_dialog.time.trim().parse::<BigInt>().is_ok();
+            ui.with_layout(Layout::right_to_left(Align::TOP), |ui| {
+                let add_button = ui.add_enabled(time_valid, egui::Button::new("Add"));
+                let cancel_button = ui.button("Cancel");
+
+                if new_dialog != *dialog {
+                    msgs.push(Message::UpdateAddMarkerDialog(new_dialog.clone()));
+                }
+                if add_button.clicked() || (time_valid && ctx.input(|i| i.key_pressed(Key::Enter)))
+                {
+                    msgs.push(Message::CloseAddMarkerDialog { add_marker: true });
+                } else if cancel_button.clicked() || ctx.input(|i| i.key_pressed(Key::Escape)) {
+                    msgs.push(Message::CloseAddMarkerDialog { add_marker: false });
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displayed_item::DisplayedItem;
+    use crate::tests::fixtures::load_counter_vcd;
+
+    #[test]
+    fn add_marker_dialog_starts_at_cursor() {
+        let dialog = AddMarkerDialog::new(Some(&BigInt::from(120)), &BigInt::from(400), 2);
+        assert_eq!(
+            dialog,
+            AddMarkerDialog {
+                time: "120".to_string(),
+                name: String::new(),
+                idx: 2,
+                color: None,
+            }
+        );
+    }
+
+    #[test]
+    fn add_marker_dialog_starts_at_viewport_center_without_cursor() {
+        let dialog = AddMarkerDialog::new(None, &BigInt::from(400), 0);
+        assert_eq!(dialog.time, "400");
+        assert_eq!(dialog.name, "");
+        assert_eq!(dialog.idx, 0);
+    }
+
+    #[test]
+    fn add_marker_dialog_adds_named_marker() {
+        let mut state = load_counter_vcd();
+
+        // Without a cursor, the dialog starts at the center of the view
+        state.update(Message::ZoomToRange {
+            start: BigInt::from(100),
+            end: BigInt::from(300),
+            viewport_idx: 0,
+        });
+        state.update(Message::ShowAddMarkerDialog);
+        assert_eq!(
+            state.user.show_add_marker_dialog,
+            Some(AddMarkerDialog::new(None, &BigInt::from(200), 0))
+        );
+
+        state.update(Message::CursorSet(BigInt::from(120)));
+        state.update(Message::ShowAddMarkerDialog);
+        let dialog = state.user.show_add_marker_dialog.clone().unwrap();
+        assert_eq!(dialog.time, "120");
+        assert_eq!(dialog.name, "");
+
+        state.update(Message::UpdateAddMarkerDialog(AddMarkerDialog {
+            name: "edge".to_string(),
+            idx: 3,
+            color: Some("Blue".to_string()),
+            ..dialog
+        }));
+        state.update(Message::CloseAddMarkerDialog { add_marker: true });
+        assert!(state.user.show_add_marker_dialog.is_none());
+
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(waves.markers.get(&3), Some(&BigInt::from(120)));
+        let marker = waves
+            .displayed_items
+            .values()
+            .find_map(|item| match item {
+                DisplayedItem::Marker(marker) => Some(marker),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(marker.idx, 3);
+        assert_eq!(marker.name.as_deref(), Some("edge"));
+        assert_eq!(marker.color.as_deref(), Some("Blue"));
+
+        // Cancelling closes the dialog without adding a marker
+        state.update(Message::ShowAddMarkerDialog);
+        state.update(Message::CloseAddMarkerDialog { add_marker: false });
+        assert!(state.user.show_add_marker_dialog.is_none());
+        assert_eq!(state.user.waves.as_ref().unwrap().markers.len(), 1);
+    }
+}
@@ -1,17 +1,23 @@
 //! The items that are drawn in the main wave form view: waves, dividers, etc.
+use derive_more::Display;
 use ecolor::Color32;
 use egui::{FontSelection, RichText, Style, WidgetText};
 use emath::Align;
+use enum_iterator::Sequence;
 use epaint::text::LayoutJob;
+use num::{BigUint, One};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use crate::analog_signal_cache::AnalogCacheEntry;
-use surfer_translation_types::VariableInfo;
+use crate::assertion::Assertion;
+use surfer_translation_types::{
+    NumberNotation, TranslatedValue, ValueKind, VariableIndex, VariableInfo,
+};
 
-use crate::config::SurferConfig;
+use crate::config::{SurferConfig, ValueTextAlign};
 use crate::transaction_container::TransactionStreamRef;
-use crate::wave_container::{FieldRef, VariableRef, VariableRefExt, WaveContainer};
+use crate::wave_container::{FieldRef, VariableMeta, VariableRef, VariableRefExt, WaveContainer};
 use crate::{
     marker::DEFAULT_MARKER_NAME, time::DEFAULT_TIMELINE_NAME, variable_name_type::VariableNameType,
 };
@@ -65,7 +71,7 @@ pub enum DisplayedItem {
     Group(DisplayedGroup),
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct FieldFormat {
     pub field: Vec<String>,
     pub format: String,
@@ -85,6 +91,170 @@ pub enum AnalogYAxisScale {
     Global,
 }
 
+/// How undefined (X) and high-impedance (Z) values are drawn in an analog trace.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default, Display, Sequence)]
+pub enum AnalogUndefMode {
+    /// Leave a gap in the trace
+    #[default]
+    Gap,
+    /// Keep drawing the last defined value
+    #[display("Hold last")]
+    HoldLast,
+    /// Draw the value as zero
+    Zero,
+}
+
+/// How leading zeros of unsigned integer values of a variable, such as hexadecimal, octal,
+/// binary and unsigned decimal values, are shown.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Display, Sequence)]
+pub enum LeadingZeroMode {
+    /// Keep the value as formatted by the translator
+    #[default]
+    Default,
+    /// Remove leading zeros, keeping at least one digit
+    Suppress,
+    /// Pad the value with zeros to the number of digits of the largest value of the variable
+    Pad,
+}
+
+impl LeadingZeroMode {
+    /// Apply the mode to `value` written in `notation` for a variable of `num_bits` bits.
+    /// Values of translators which do not write unsigned integers and values containing other
+    /// characters than the digits of the radix are returned unchanged.
+    #[must_use]
+    pub fn apply(
+        self,
+        value: TranslatedValue,
+        notation: Option<NumberNotation>,
+        num_bits: Option<u32>,
+    ) -> TranslatedValue {
+        let Some(NumberNotation::Unsigned { radix }) = notation else {
+            return value;
+        };
+        if value.kind != ValueKind::Normal
+            || value.value.is_empty()
+            || !value.value.chars().all(|c| c.is_digit(radix))
+        {
+            return value;
+        }
+        let formatted = match self {
+            LeadingZeroMode::Default => return value,
+            LeadingZeroMode::Suppress => {
+                let trimmed = value.value.trim_start_matches('0');
+                if trimmed.is_empty() {
+                    "0".to_string()
+                } else {
+                    trimmed.to_string()
+                }
+            }
+            LeadingZeroMode::Pad => {
+                let Some(num_bits) = num_bits else {
+                    return value;
+                };
+                let largest = (BigUint::one() << num_bits) - 1u32;
+                let width = largest.to_str_radix(radix).len();
+                format!("{:0>width$}", value.value)
+            }
+        };
+        TranslatedValue {
+            value: formatted,
+            kind: value.kind,
+        }
+    }
+}
+
+/// Formatting of the values of the decimal and floating point translators.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NumericFormatOptions {
+    /// Separator inserted between groups of three digits of `Unsigned` and `Signed` values
+    #[serde(default)]
+    pub thousands_separator: Option<char>,
+    /// Number of decimal places of floating point and posit values, the shortest exact
+    /// representation if not set
+    #[serde(default)]
+    pub decimal_places: Option<usize>,
+}
+
+impl NumericFormatOptions {
+    /// Apply the options to `value` written in `notation`. Values of translators which do not
+    /// write decimal numbers and values which are not plain numbers are returned unchanged.
+    #[must_use]
+    pub fn apply(
+        self,
+        value: TranslatedValue,
+        notation: Option<NumberNotation>,
+    ) -> TranslatedValue {
+        if value.kind != ValueKind::Normal {
+            return value;
+        }
+        let formatted = match notation {
+            Some(NumberNotation::Unsigned { radix: 10 } | NumberNotation::Signed) => self
+                .thousands_separator
+                .and_then(|separator| group_thousands(&value.value, separator)),
+            Some(NumberNotation::Decimal) => self
+                .decimal_places
+                .and_then(|places| with_decimal_places(&value.value, places)),
+            _ => None,
+        };
+        match formatted {
+            Some(formatted) => TranslatedValue {
+                value: formatted,
+                kind: value.kind,
+            },
+            None => value,
+        }
+    }
+}
+
+/// Insert `separator` between groups of three digits of the decimal integer `value`, or
+/// `None` if `value` is not a decimal integer.
+fn group_thousands(value: &str, separator: char) -> Option<String> {
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", value),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let mut grouped = sign.to_string();
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    Some(grouped)
+}
+
+/// Format the finite number `value` with `places` decimal places, or `None` if `value` is
+/// not a finite number.
+fn with_decimal_places(value: &str, places: usize) -> Option<String> {
+    let number = value
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())?;
+    Some(format!("{number:.places$}"))
+}
+
+/// Split of the bits of a fixed point variable into integer and fraction bits, e.g. Q3.5.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FixedPointRadix {
+    pub int_bits: u32,
+    pub frac_bits: u32,
+}
+
+impl FixedPointRadix {
+    /// Index with the binary point between bit `frac_bits` and `frac_bits - 1`, as the fixed
+    /// point translators read it
+    #[must_use]
+    pub fn index(self) -> VariableIndex {
+        VariableIndex {
+            msb: i64::from(self.int_bits) - 1,
+            lsb: -i64::from(self.frac_bits),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub struct AnalogSettings {
     pub render_style: AnalogRenderStyle,
@@ -125,11 +295,28 @@ impl AnalogSettings {
     }
 }
 
+/// Appearance of an analog trace.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub struct AnalogStyle {
+    /// Line width of the trace, the line width of the theme if not set
+    #[serde(default)]
+    pub line_width: Option<f32>,
+    /// Draw a marker at each sample
+    #[serde(default)]
+    pub point_markers: bool,
+}
+
 /// Per-variable analog state (settings + cache). Presence means enabled, None means disabled.
 /// NOTE: Clone is NOT derived - see manual impl below for undo/redo compatibility.
 #[derive(Serialize, Deserialize)]
 pub struct AnalogVarState {
     pub settings: AnalogSettings,
+    /// Style of this trace, the configured default style if not set
+    #[serde(default)]
+    pub style: Option<AnalogStyle>,
+    /// How X and Z values participate in the trace
+    #[serde(default)]
+    pub undef_mode: AnalogUndefMode,
     #[serde(skip)]
     pub cache: Option<Arc<AnalogCacheEntry>>,
 }
@@ -146,6 +333,8 @@ impl Clone for AnalogVarState {
     fn clone(&self) -> Self {
         Self {
             settings: self.settings,
+            style: self.style,
+            undef_mode: self.undef_mode,
             cache: None, // Intentionally not cloned - rebuilt on demand
         }
     }
@@ -154,6 +343,8 @@ impl Clone for AnalogVarState {
 impl PartialEq for AnalogVarState {
     fn eq(&self, other: &Self) -> bool {
         self.settings == other.settings
+            && self.style == other.style
+            && self.undef_mode == other.undef_mode
     }
 }
 
@@ -162,6 +353,8 @@ impl AnalogVarState {
     pub fn new(settings: AnalogSettings) -> Self {
         Self {
             settings,
+            style: None,
+            undef_mode: AnalogUndefMode::default(),
             cache: None,
         }
     }
@@ -201,6 +394,30 @@ pub struct DisplayedVariable {
     pub field_formats: Vec<FieldFormat>,
     pub height_scaling_factor: Option<f32>,
     pub analog: Option<AnalogVarState>,
+    #[serde(default)]
+    pub assertion: Option<Assertion>,
+    /// Appended to numeric values, e.g. `V` or `MHz`
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// Placement of the value text, `None` uses the configured default
+    #[serde(default)]
+    pub value_text_align: Option<ValueTextAlign>,
+    /// Label each transition with the time since the previous transition
+    #[serde(default)]
+    pub show_interval_labels: bool,
+    /// Leading zeros of hexadecimal, octal and binary values
+    #[serde(default)]
+    pub leading_zeros: LeadingZeroMode,
+    /// Formatting of decimal and floating point values, the global options if not set
+    #[serde(default)]
+    pub numeric_format: Option<NumericFormatOptions>,
+    /// Binary point used by the fixed point translators instead of the index of the variable
+    #[serde(default)]
+    pub fixed_point_radix: Option<FixedPointRadix>,
+    /// Value captured with [`crate::message::Message::CaptureItemValue`], shown next to the
+    /// name until cleared
+    #[serde(skip)]
+    pub captured_value: Option<String>,
 }
 
 impl DisplayedVariable {
@@ -216,6 +433,55 @@ impl DisplayedVariable {
         }
     }
 
+    /// `meta` with the binary point set by `fixed_point_radix`, if any, to be used when
+    /// translating the values of the variable
+    #[must_use]
+    pub fn translation_meta(&self, mut meta: VariableMeta) -> VariableMeta {
+        if let Some(radix) = self.fixed_point_radix {
+            meta.index = Some(radix.index());
+        }
+        meta
+    }
+
+    /// Text shown next to the name of the variable while a value is captured
+    #[must_use]
+    pub fn captured_value_annotation(&self) -> Option<String> {
+        self.captured_value
+            .as_ref()
+            .map(|value| format!("(was: {value})"))
+    }
+
+    /// Whether the variable has numeric values that can be drawn as an analog trace.
+    #[must_use]
+    pub fn is_numeric(&self) -> bool {
+        matches!(self.info, VariableInfo::Bits | VariableInfo::Real)
+    }
+
+    /// Append the unit of the variable to a numeric value. Values that are not numbers, such
+    /// as X and Z states, and values of non-numeric variables are returned unchanged.
+    #[must_use]
+    pub fn with_unit(&self, value: TranslatedValue) -> TranslatedValue {
+        match &self.unit {
+            Some(unit) if self.is_numeric() && value.kind == ValueKind::Normal => TranslatedValue {
+                value: format!("{} {unit}", value.value),
+                kind: value.kind,
+            },
+            _ => value,
+        }
+    }
+
+    /// Switch between digital and analog rendering, with analog starting as a step trace.
+    /// Variables that are not numeric are left unchanged.
+    pub fn toggle_analog(&mut self) {
+        if !self.is_numeric() {
+            return;
+        }
+        self.analog = match self.analog {
+            Some(_) => None,
+            None => Some(AnalogVarState::step_viewport()),
+        };
+    }
+
     /// Updates the variable after a new waveform has been loaded.
     #[must_use]
     pub fn update(
@@ -251,6 +517,13 @@ impl DisplayedVariable {
             field_formats: self.field_formats,
             height_scaling_factor: self.height_scaling_factor,
             analog: self.analog,
+            assertion: self.assertion,
+            unit: self.unit,
+            value_text_align: self.value_text_align,
+            show_interval_labels: self.show_interval_labels,
+            leading_zeros: self.leading_zeros,
+            numeric_format: self.numeric_format,
+            fixed_point_radix: self.fixed_point_radix,
         }
     }
 }
@@ -315,6 +588,26 @@ pub struct DisplayedPlaceholder {
     pub field_formats: Vec<FieldFormat>,
     pub height_scaling_factor: Option<f32>,
     pub analog: Option<AnalogVarState>,
+    #[serde(default)]
+    pub assertion: Option<Assertion>,
+    /// Appended to numeric values, e.g. `V` or `MHz`
+    #[serde(default)]
+    pub unit: Option<String>,
+    /// Placement of the value text, `None` uses the configured default
+    #[serde(default)]
+    pub value_text_align: Option<ValueTextAlign>,
+    /// Label each transition with the time since the previous transition
+    #[serde(default)]
+    pub show_interval_labels: bool,
+    /// Leading zeros of hexadecimal, octal and binary values
+    #[serde(default)]
+    pub leading_zeros: LeadingZeroMode,
+    /// Formatting of decimal and floating point values, the global options if not set
+    #[serde(default)]
+    pub numeric_format: Option<NumericFormatOptions>,
+    /// Binary point used by the fixed point translators instead of the index of the variable
+    #[serde(default)]
+    pub fixed_point_radix: Option<FixedPointRadix>,
 }
 
 impl DisplayedPlaceholder {
@@ -336,6 +629,14 @@ impl DisplayedPlaceholder {
             field_formats: self.field_formats,
             height_scaling_factor: self.height_scaling_factor,
             analog: self.analog,
+            assertion: self.assertion,
+            unit: self.unit,
+            value_text_align: self.value_text_align,
+            show_interval_labels: self.show_interval_labels,
+            leading_zeros: self.leading_zeros,
+            numeric_format: self.numeric_format,
+            fixed_point_radix: self.fixed_point_radix,
+            captured_value: None,
         }
     }
 
@@ -606,4 +907,364 @@ impl DisplayedItem {
             _ => {}
         }
     }
+
+    /// Go back to the height given by the configuration
+    pub fn reset_height_scaling_factor(&mut self) {
+        match self {
+            DisplayedItem::Variable(variable) => variable.height_scaling_factor = None,
+            DisplayedItem::Placeholder(placeholder) => placeholder.height_scaling_factor = None,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigInt;
+    use num::BigUint;
+    use surfer_translation_types::{Translator, ValueRepr, VariableEncoding, VariableValue};
+
+    use super::*;
+    use crate::SystemState;
+    use crate::displayed_item_tree::VisibleItemIndex;
+    use crate::message::{Message, MessageTarget};
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+    use crate::translation::numeric_translators::SignedFixedPointTranslator;
+
+    fn variable(info: VariableInfo) -> DisplayedVariable {
+        let variable_ref = VariableRef::from_hierarchy_string("tb.dut.count");
+        DisplayedVariable {
+            display_name: variable_ref.name.clone(),
+            variable_ref,
+            info,
+            color: None,
+            background_color: None,
+            display_name_type: VariableNameType::Local,
+            manual_name: None,
+            format: None,
+            field_formats: vec![],
+            height_scaling_factor: None,
+            analog: None,
+            assertion: None,
+            unit: None,
+            value_text_align: None,
+            show_interval_labels: false,
+            leading_zeros: LeadingZeroMode::Default,
+            numeric_format: None,
+            fixed_point_radix: None,
+            captured_value: None,
+        }
+    }
+
+    #[test]
+    fn toggle_analog_switches_numeric_variables() {
+        let mut var = variable(VariableInfo::Bits);
+        var.toggle_analog();
+        assert_eq!(
+            var.analog.as_ref().map(|a| a.settings),
+            Some(AnalogSettings::step_viewport())
+        );
+
+        let encoded = ron::to_string(&var).unwrap();
+        let decoded: DisplayedVariable = ron::from_str(&encoded).unwrap();
+        assert_eq!(decoded.analog, var.analog);
+
+        var.toggle_analog();
+        assert!(var.analog.is_none());
+    }
+
+    #[test]
+    fn toggle_analog_ignores_non_numeric_variables() {
+        for info in [
+            VariableInfo::Bool,
+            VariableInfo::String,
+            VariableInfo::Event,
+        ] {
+            let mut var = variable(info);
+            var.toggle_analog();
+            assert!(var.analog.is_none());
+        }
+    }
+
+    #[test]
+    fn fixed_point_radix_overrides_the_binary_point() {
+        let mut var = variable(VariableInfo::Bits);
+        let meta = VariableMeta {
+            var: var.variable_ref.clone(),
+            num_bits: Some(8),
+            variable_type: None,
+            variable_type_name: None,
+            index: Some(VariableIndex { msb: 7, lsb: 0 }),
+            direction: None,
+            enum_map: Default::default(),
+            encoding: VariableEncoding::BitVector,
+        };
+        assert_eq!(var.translation_meta(meta.clone()).index, meta.index);
+
+        var.fixed_point_radix = Some(FixedPointRadix {
+            int_bits: 3,
+            frac_bits: 5,
+        });
+        let fixed_meta = var.translation_meta(meta);
+        assert_eq!(fixed_meta.index, Some(VariableIndex { msb: 2, lsb: -5 }));
+        let translated = SignedFixedPointTranslator
+            .translate(
+                &fixed_meta,
+                &VariableValue::BigUint(BigUint::from(0b1111_0000u32)),
+            )
+            .unwrap();
+        assert!(matches!(translated.val, ValueRepr::String(ref value) if value == "-0.5"));
+
+        let encoded = ron::to_string(&var).unwrap();
+        let decoded: DisplayedVariable = ron::from_str(&encoded).unwrap();
+        assert_eq!(decoded.fixed_point_radix, var.fixed_point_radix);
+    }
+
+    #[test]
+    fn captured_value_is_annotated_but_not_saved() {
+        let mut var = variable(VariableInfo::Bits);
+        assert_eq!(var.captured_value_annotation(), None);
+
+        var.captured_value = Some("0x2a".to_string());
+        assert_eq!(
+            var.captured_value_annotation().as_deref(),
+            Some("(was: 0x2a)")
+        );
+
+        let encoded = ron::to_string(&var).unwrap();
+        let decoded: DisplayedVariable = ron::from_str(&encoded).unwrap();
+        assert_eq!(decoded.captured_value, None);
+    }
+
+    #[test]
+    fn leading_zero_mode_suppresses_or_pads_values() {
+        let value = |value: &str, kind| TranslatedValue {
+            value: value.to_string(),
+            kind,
+        };
+        let radix = |radix| Some(NumberNotation::Unsigned { radix });
+        for (mode, input, notation, num_bits, expected) in [
+            (LeadingZeroMode::Default, "0f", radix(16), Some(8), "0f"),
+            (LeadingZeroMode::Default, "7", radix(10), Some(8), "7"),
+            (LeadingZeroMode::Suppress, "0f", radix(16), Some(8), "f"),
+            (LeadingZeroMode::Suppress, "00", radix(8), Some(6), "0"),
+            (LeadingZeroMode::Suppress, "005", radix(8), Some(9), "5"),
+            (LeadingZeroMode::Suppress, "00101", radix(2), Some(5), "101"),
+            (LeadingZeroMode::Suppress, "007", radix(10), Some(8), "7"),
+            (LeadingZeroMode::Pad, "0f", radix(16), Some(8), "0f"),
+            (LeadingZeroMode::Pad, "f", radix(16), Some(12), "00f"),
+            (LeadingZeroMode::Pad, "5", radix(8), Some(7), "005"),
+            (LeadingZeroMode::Pad, "101", radix(2), Some(5), "00101"),
+            // The largest 8 bit value, 255, has three decimal digits
+            (LeadingZeroMode::Pad, "7", radix(10), Some(8), "007"),
+            // Pad does nothing without a known width
+            (LeadingZeroMode::Pad, "f", radix(16), None, "f"),
+            // Values with other characters than digits are left alone
+            (LeadingZeroMode::Suppress, "0x1", radix(16), Some(8), "0x1"),
+            (LeadingZeroMode::Pad, "1x", radix(16), Some(12), "1x"),
+            (LeadingZeroMode::Suppress, "0012", radix(2), Some(4), "0012"),
+            (LeadingZeroMode::Pad, "", radix(2), Some(4), ""),
+            // Other translators are left alone
+            (LeadingZeroMode::Suppress, "007", None, Some(8), "007"),
+            (LeadingZeroMode::Pad, "7", None, Some(8), "7"),
+        ] {
+            assert_eq!(
+                mode.apply(value(input, ValueKind::Normal), notation, num_bits)
+                    .value,
+                expected,
+                "{mode} {input} {notation:?}"
+            );
+        }
+        assert_eq!(
+            LeadingZeroMode::Suppress
+                .apply(value("00", ValueKind::Undef), radix(16), Some(8))
+                .value,
+            "00"
+        );
+
+        let mut var = variable(VariableInfo::Bits);
+        var.leading_zeros = LeadingZeroMode::Suppress;
+        let encoded = ron::to_string(&var).unwrap();
+        let decoded: DisplayedVariable = ron::from_str(&encoded).unwrap();
+        assert_eq!(decoded.leading_zeros, LeadingZeroMode::Suppress);
+    }
+
+    #[test]
+    fn numeric_format_groups_decimals() {
+        let options = NumericFormatOptions {
+            thousands_separator: Some(','),
+            decimal_places: None,
+        };
+        let unsigned = Some(NumberNotation::Unsigned { radix: 10 });
+        let signed = Some(NumberNotation::Signed);
+        let format = |input: &str, notation| {
+            let value = TranslatedValue {
+                value: input.to_string(),
+                kind: ValueKind::Normal,
+            };
+            options.apply(value, notation).value
+        };
+        assert_eq!(format("0", unsigned), "0");
+        assert_eq!(format("999", unsigned), "999");
+        assert_eq!(format("1000", unsigned), "1,000");
+        assert_eq!(format("4294967295", unsigned), "4,294,967,295");
+        assert_eq!(format("-123456", signed), "-123,456");
+        assert_eq!(format("-12", signed), "-12");
+        // Only decimal values are grouped, and only plain integers
+        assert_eq!(
+            format("123456", Some(NumberNotation::Unsigned { radix: 16 })),
+            "123456"
+        );
+        assert_eq!(format("12x456", unsigned), "12x456");
+        assert_eq!(
+            NumericFormatOptions::default()
+                .apply(
+                    TranslatedValue {
+                        value: "1000".to_string(),
+                        kind: ValueKind::Normal,
+                    },
+                    unsigned
+                )
+                .value,
+            "1000"
+        );
+    }
+
+    #[test]
+    fn numeric_format_sets_float_decimal_places() {
+        let decimal = Some(NumberNotation::Decimal);
+        let format = |places, input: &str, notation| {
+            let options = NumericFormatOptions {
+                thousands_separator: Some(','),
+                decimal_places: Some(places),
+            };
+            let value = TranslatedValue {
+                value: input.to_string(),
+                kind: ValueKind::Normal,
+            };
+            options.apply(value, notation).value
+        };
+        assert_eq!(format(2, "3.14159", decimal), "3.14");
+        assert_eq!(format(3, "1.5", decimal), "1.500");
+        assert_eq!(format(0, "2.5e2", decimal), "250");
+        assert_eq!(format(1, "-0.26", decimal), "-0.3");
+        assert_eq!(format(2, "inf", decimal), "inf");
+        assert_eq!(format(2, "NaN", decimal), "NaN");
+        assert_eq!(format(2, "1.25", None), "1.25");
+
+        let mut var = variable(VariableInfo::Bits);
+        var.numeric_format = Some(NumericFormatOptions {
+            thousands_separator: Some('_'),
+            decimal_places: Some(4),
+        });
+        let encoded = ron::to_string(&var).unwrap();
+        let decoded: DisplayedVariable = ron::from_str(&encoded).unwrap();
+        assert_eq!(decoded.numeric_format, var.numeric_format);
+    }
+
+    #[test]
+    fn unit_is_appended_to_numeric_values() {
+        let value = |value: &str, kind| TranslatedValue {
+            value: value.to_string(),
+            kind,
+        };
+        let mut var = variable(VariableInfo::Real);
+        var.unit = Some("V".to_string());
+        assert_eq!(
+            var.with_unit(value("3.3", ValueKind::Normal)).value,
+            "3.3 V"
+        );
+        assert_eq!(var.with_unit(value("x", ValueKind::Undef)).value, "x");
+        assert_eq!(var.with_unit(value("z", ValueKind::HighImp)).value, "z");
+
+        let encoded = ron::to_string(&var).unwrap();
+        let decoded: DisplayedVariable = ron::from_str(&encoded).unwrap();
+        assert_eq!(decoded.unit.as_deref(), Some("V"));
+
+        let mut var = variable(VariableInfo::String);
+        var.unit = Some("V".to_string());
+        assert_eq!(
+            var.with_unit(value("idle", ValueKind::Normal)).value,
+            "idle"
+        );
+    }
+
+    #[test]
+    fn reset_all_item_heights_is_undoable() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk", "tb.reset", "tb.dut.counter"]);
+        state.update(Message::ItemHeightScalingFactorChange(
+            MessageTarget::Explicit(VisibleItemIndex(0)),
+            2.0,
+        ));
+        state.update(Message::ItemHeightScalingFactorChange(
+            MessageTarget::Explicit(VisibleItemIndex(2)),
+            3.0,
+        ));
+
+        let heights = |state: &SystemState| {
+            let waves = state.user.waves.as_ref().unwrap();
+            waves
+                .items_tree
+                .iter()
+                .map(|node| waves.displayed_items[&node.item_ref].height_scaling_factor())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(heights(&state), vec![2.0, 1.0, 3.0]);
+
+        state.update(Message::ResetAllItemHeights);
+        assert_eq!(heights(&state), vec![1.0, 1.0, 1.0]);
+        assert!(
+            state
+                .user
+                .waves
+                .as_ref()
+                .unwrap()
+                .displayed_items
+                .values()
+                .all(|item| match item {
+                    DisplayedItem::Variable(variable) => variable.height_scaling_factor.is_none(),
+                    _ => true,
+                })
+        );
+        state.update(Message::Undo(1));
+        assert_eq!(heights(&state), vec![2.0, 1.0, 3.0]);
+
+        state.update(Message::SetAllItemHeights(1.5));
+        assert_eq!(heights(&state), vec![1.5, 1.5, 1.5]);
+        state.update(Message::Undo(1));
+        assert_eq!(heights(&state), vec![2.0, 1.0, 3.0]);
+    }
+
+    #[test]
+    fn captured_value_is_kept_while_the_cursor_moves() {
+        let mut state = load_counter_vcd_with_variables(&["tb.dut.counter"]);
+        let captured = |state: &SystemState| {
+            state
+                .user
+                .waves
+                .as_ref()
+                .unwrap()
+                .displayed_items
+                .values()
+                .find_map(|item| match item {
+                    DisplayedItem::Variable(variable) => Some(variable.captured_value_annotation()),
+                    _ => None,
+                })
+                .flatten()
+        };
+
+        // Nothing to capture without a cursor
+        state.update(Message::CaptureItemValue(Some(VisibleItemIndex(0))));
+        assert_eq!(captured(&state), None);
+
+        state.update(Message::CursorSet(BigInt::from(130)));
+        state.update(Message::CaptureItemValue(Some(VisibleItemIndex(0))));
+        assert_eq!(captured(&state).as_deref(), Some("(was: 2)"));
+
+        state.update(Message::CursorSet(BigInt::from(500)));
+        assert_eq!(captured(&state).as_deref(), Some("(was: 2)"));
+
+        state.update(Message::ClearCapturedValue(Some(VisibleItemIndex(0))));
+        assert_eq!(captured(&state), None);
+    }
 }
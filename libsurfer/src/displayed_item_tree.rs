@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::ops::Range;
 
 use crate::MoveDir;
+use crate::config::GroupDropLevel;
 use crate::displayed_item::DisplayedItemRef;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -13,6 +14,9 @@ pub struct Node {
     /// Whether a subtree of this node (if it exists) is shown
     pub unfolded: bool,
     pub selected: bool,
+    /// Whether the item is left out of the waveform view while keeping its place in the list
+    #[serde(default)]
+    pub hidden: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -194,6 +198,26 @@ impl DisplayedItemTree {
         self.iter_visible().filter(|i| i.selected)
     }
 
+    /// Iterate through visible items that are not hidden, i.e., the ones to draw waveforms for
+    pub fn iter_visible_drawn(&self) -> impl Iterator<Item = &Node> + use<'_> {
+        self.iter_visible().filter(|i| !i.hidden)
+    }
+
+    /// Whether each visible item gets the alternate row shading. Top-level items alternate,
+    /// and the items inside a group share the shading of the group.
+    #[must_use]
+    pub fn zebra_shading(&self) -> Vec<bool> {
+        let mut shaded = true;
+        self.iter_visible()
+            .map(|node| {
+                if node.level == 0 {
+                    shaded = !shaded;
+                }
+                shaded
+            })
+            .collect()
+    }
+
     /// Iterate through items, skipping invisible items, return index of n-th visible item
     #[must_use]
     pub fn get_visible(&self, index: VisibleItemIndex) -> Option<&Node> {
@@ -234,6 +258,7 @@ impl DisplayedItemTree {
                 level: position.level,
                 unfolded: true,
                 selected: false,
+                hidden: false,
             },
         );
 
@@ -484,6 +509,53 @@ impl DisplayedItemTree {
         Ok(())
     }
 
+    /// Exchange the positions of two visible items, including their subtrees
+    ///
+    /// Each item takes over the level of the other one. The tree is left unchanged on error,
+    /// which includes trying to swap an item with a node inside its own subtree.
+    pub fn swap_items(
+        &mut self,
+        a: VisibleItemIndex,
+        b: VisibleItemIndex,
+    ) -> Result<(), MoveError> {
+        let (Some(ItemIndex(a)), Some(ItemIndex(b))) = (self.to_displayed(a), self.to_displayed(b))
+        else {
+            return Err(MoveError::InvalidIndex);
+        };
+        let (first, second) = if a <= b { (a, b) } else { (b, a) };
+        if first == second {
+            return Ok(());
+        }
+        if self.subtree_contains(ItemIndex(first), ItemIndex(second)) {
+            return Err(MoveError::CircularMove);
+        }
+
+        let first_level = self.items[first].level;
+        let second_level = self.items[second].level;
+        let second_len = self.subtree_end(second) - second;
+
+        let backup = self.items.clone();
+        // Move the second item in front of the first, which shifts the first item and everything
+        // up to the old location of the second item by the size of the second subtree
+        self.move_items(
+            vec![ItemIndex(second)],
+            TargetPosition {
+                before: ItemIndex(first),
+                level: first_level,
+            },
+        )
+        .and_then(|()| {
+            self.move_items(
+                vec![ItemIndex(first + second_len)],
+                TargetPosition {
+                    before: ItemIndex(second + second_len),
+                    level: second_level,
+                },
+            )
+        })
+        .inspect_err(|_| self.items = backup)
+    }
+
     /// Return the range of valid levels for inserting above `item`, given the visible nodes
     ///
     /// `f` will be called with what will become the in-order predecessor node
@@ -517,6 +589,23 @@ impl DisplayedItemTree {
         }
     }
 
+    /// Pick the level to drop dragged items at from the valid `levels`, see
+    /// [`Self::valid_levels_visible`]
+    ///
+    /// With [`GroupDropLevel::Pointer`] the first level for which `under_pointer` returns true
+    /// is used, falling back to the deepest level.
+    pub fn drop_level(
+        levels: Range<u8>,
+        drop_level: GroupDropLevel,
+        under_pointer: impl FnMut(&u8) -> bool,
+    ) -> Option<u8> {
+        match drop_level {
+            GroupDropLevel::Pointer => levels.find_or_last(under_pointer),
+            GroupDropLevel::Inside => levels.max(),
+            GroupDropLevel::Sibling => levels.min(),
+        }
+    }
+
     pub fn xfold(&mut self, ItemIndex(item): ItemIndex, unfolded: bool) {
         self.items[item].unfolded = unfolded;
         if !unfolded {
@@ -536,6 +625,22 @@ impl DisplayedItemTree {
         }
     }
 
+    /// Fold all items except `item` and its ancestors, so that `item` stays visible
+    pub fn xfold_all_except_ancestors(&mut self, ItemIndex(item): ItemIndex) {
+        self.xfold_all(false);
+        self.items[item].unfolded = true;
+        let mut level = self.items[item].level;
+        for x in self.items[..item].iter_mut().rev() {
+            if level == 0 {
+                break;
+            }
+            if x.level < level {
+                x.unfolded = true;
+                level = x.level;
+            }
+        }
+    }
+
     pub fn xfold_recursive(&mut self, ItemIndex(item): ItemIndex, unfolded: bool) {
         let end = self.subtree_end(item);
         self.items[item].unfolded = unfolded;
@@ -553,6 +658,12 @@ impl DisplayedItemTree {
         }
     }
 
+    pub fn xhide(&mut self, vidx: VisibleItemIndex, hidden: bool) {
+        if let Some(idx) = self.to_displayed(vidx) {
+            self.items[idx.0].hidden = hidden;
+        }
+    }
+
     /// Select/Deselect all visible items
     pub fn xselect_all_visible(&mut self, selected: bool) {
         for x in &mut self.iter_visible_mut() {
@@ -657,6 +768,9 @@ fn shift_subtree_to_level(nodes: &mut [Node], target_level: u8) -> Result<(), Mo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::SystemState;
+    use crate::message::Message;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
     use itertools::Itertools;
 
     fn build_tree(nodes: &[(usize, u8, bool, bool)]) -> DisplayedItemTree {
@@ -667,6 +781,7 @@ mod tests {
                 level,
                 unfolded,
                 selected,
+                hidden: false,
             });
         }
         tree
@@ -701,6 +816,48 @@ mod tests {
         ])
     }
 
+    #[test]
+    fn test_xfold_all_except_ancestors() {
+        // 1: group, 10: group, 100: item, 2: group, 20: item, 3: group, 30: group, 300: item
+        let mut tree = build_tree(&[
+            (1, 0, true, false),
+            (10, 1, true, false),
+            (100, 2, true, false),
+            (2, 0, true, false),
+            (20, 1, true, false),
+            (3, 0, true, false),
+            (30, 1, true, false),
+            (300, 2, true, false),
+        ]);
+        tree.xfold_all_except_ancestors(ItemIndex(2));
+        assert_eq!(
+            tree.iter()
+                .map(|x| (x.item_ref.0, x.unfolded))
+                .collect_vec(),
+            vec![
+                (1, true),
+                (10, true),
+                (100, true),
+                (2, false),
+                (20, false),
+                (3, false),
+                (30, false),
+                (300, false),
+            ]
+        );
+        assert_eq!(
+            tree.iter_visible().map(|x| x.item_ref.0).collect_vec(),
+            vec![1, 10, 100, 2, 3]
+        );
+
+        // A focused group stays unfolded together with its ancestors
+        tree.xfold_all_except_ancestors(ItemIndex(6));
+        assert_eq!(
+            tree.iter_visible().map(|x| x.item_ref.0).collect_vec(),
+            vec![1, 2, 3, 30, 300]
+        );
+    }
+
     #[test]
     fn test_iter_visible() {
         let tree = test_tree();
@@ -710,6 +867,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_zebra_shading() {
+        let tree = test_tree();
+        // visible items: 0, 1, 2 (folded), 3, 30, 31, 4, 5
+        assert_eq!(
+            tree.zebra_shading(),
+            vec![false, true, false, true, true, true, false, true]
+        );
+
+        let mut tree = test_tree();
+        tree.xfold(ItemIndex(2), true);
+        // visible items: 0, 1, 2, 20, 200, 3, 30, 31, 4, 5
+        assert_eq!(
+            tree.zebra_shading(),
+            vec![
+                false, true, false, false, false, true, true, true, false, true
+            ]
+        );
+        assert!(DisplayedItemTree::new().zebra_shading().is_empty());
+    }
+
     #[test]
     fn test_iter_visible_extra() {
         let tree = test_tree();
@@ -1464,4 +1642,239 @@ mod tests {
             0..4
         );
     }
+
+    #[test]
+    fn test_drop_level_at_group_boundary() {
+        let tree = test_tree();
+        let is_group = |node: &Node| node.item_ref == DisplayedItemRef(3);
+        let drop_level = |vidx, drop_level, pointer_level: Option<u8>| {
+            DisplayedItemTree::drop_level(
+                tree.valid_levels_visible(VisibleItemIndex(vidx), is_group),
+                drop_level,
+                |&level| Some(level) == pointer_level,
+            )
+        };
+
+        // After the last item of group 3, before 4
+        assert_eq!(drop_level(6, GroupDropLevel::Pointer, Some(0)), Some(0));
+        assert_eq!(drop_level(6, GroupDropLevel::Pointer, None), Some(1));
+        assert_eq!(drop_level(6, GroupDropLevel::Inside, Some(0)), Some(1));
+        assert_eq!(drop_level(6, GroupDropLevel::Sibling, Some(1)), Some(0));
+
+        // Directly after the group header there is only a single valid level
+        for mode in [
+            GroupDropLevel::Pointer,
+            GroupDropLevel::Inside,
+            GroupDropLevel::Sibling,
+        ] {
+            assert_eq!(drop_level(4, mode, Some(0)), Some(1));
+        }
+    }
+
+    #[test]
+    fn test_hidden_item_is_kept_but_not_drawn() {
+        let mut tree = test_tree();
+        tree.xhide(VisibleItemIndex(1), true);
+
+        assert_eq!(
+            tree.iter_visible().map(|x| x.item_ref.0).collect_vec(),
+            vec![0, 1, 2, 3, 30, 31, 4, 5]
+        );
+        assert_eq!(
+            tree.iter_visible_drawn()
+                .map(|x| x.item_ref.0)
+                .collect_vec(),
+            vec![0, 2, 3, 30, 31, 4, 5]
+        );
+
+        tree.xhide(VisibleItemIndex(1), false);
+        assert_eq!(tree.iter_visible_drawn().count(), 8);
+    }
+
+    #[test]
+    fn test_hidden_item_round_trips_through_state() {
+        let mut tree = test_tree();
+        tree.xhide(VisibleItemIndex(4), true);
+
+        let encoded = ron::to_string(&tree).expect("tree must serialize");
+        let decoded: DisplayedItemTree = ron::from_str(&encoded).expect("tree must deserialize");
+        assert_eq!(decoded.items, tree.items);
+        assert!(decoded.items[6].hidden);
+
+        // state files written before items could be hidden still load
+        let legacy: Node =
+            ron::from_str("(item_ref: (7), level: 0, unfolded: true, selected: false)")
+                .expect("legacy node must deserialize");
+        assert!(!legacy.hidden);
+    }
+
+    #[test]
+    fn test_swap_items() {
+        let mut tree = test_tree();
+        tree.swap_items(VisibleItemIndex(6), VisibleItemIndex(1))
+            .expect("swap_items must succeed");
+        assert_eq!(
+            tree.items.iter().map(|x| x.item_ref.0).collect_vec(),
+            vec![0, 4, 2, 20, 200, 3, 30, 31, 1, 5]
+        );
+    }
+
+    #[test]
+    fn test_swap_items_moves_subtree_and_levels() {
+        let mut tree = test_tree();
+        tree.swap_items(VisibleItemIndex(2), VisibleItemIndex(4))
+            .expect("swap_items must succeed");
+        assert_eq!(
+            tree.items
+                .iter()
+                .map(|x| (x.item_ref.0, x.level))
+                .collect_vec(),
+            vec![
+                (0, 0),
+                (1, 0),
+                (30, 0),
+                (3, 0),
+                (2, 1),
+                (20, 2),
+                (200, 3),
+                (31, 1),
+                (4, 0),
+                (5, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_swap_items_invalid() {
+        let mut tree = test_tree();
+        assert_eq!(
+            tree.swap_items(VisibleItemIndex(3), VisibleItemIndex(4)),
+            Err(MoveError::CircularMove)
+        );
+        assert_eq!(
+            tree.swap_items(VisibleItemIndex(0), VisibleItemIndex(8)),
+            Err(MoveError::InvalidIndex)
+        );
+        assert_eq!(tree.items, test_tree().items);
+    }
+
+    #[test]
+    fn swap_items_and_undo() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk", "tb.reset", "tb.dut.counter"]);
+        let item_order = |state: &SystemState| {
+            state
+                .user
+                .waves
+                .as_ref()
+                .unwrap()
+                .items_tree
+                .iter()
+                .map(|node| node.item_ref.0)
+                .collect::<Vec<_>>()
+        };
+        let undo_stack_len = state.undo_stack.len();
+
+        state.update(Message::SwapItems(VisibleItemIndex(0), VisibleItemIndex(2)));
+        assert_eq!(item_order(&state), vec![3, 2, 1]);
+        assert_eq!(state.undo_stack.len(), undo_stack_len + 1);
+
+        // Invalid indices leave the order and the undo stack alone
+        state.update(Message::SwapItems(
+            VisibleItemIndex(0),
+            VisibleItemIndex(10),
+        ));
+        assert_eq!(item_order(&state), vec![3, 2, 1]);
+        assert_eq!(state.undo_stack.len(), undo_stack_len + 1);
+
+        state.update(Message::Undo(1));
+        assert_eq!(item_order(&state), vec![1, 2, 3]);
+    }
+
+    /// Loads counter.vcd with a group containing `clk`, followed by `reset`, `overflow` and `counter`
+    fn state_with_group_for_drag() -> SystemState {
+        let mut state = load_counter_vcd_with_variables(&[
+            "tb.clk",
+            "tb.reset",
+            "tb.overflow",
+            "tb.dut.counter",
+        ]);
+        state.update(Message::GroupNew {
+            name: Some("group".to_string()),
+            before: Some(ItemIndex(0)),
+            items: Some(vec![DisplayedItemRef(1)]),
+        });
+        state.update(Message::ItemSelectionClear);
+        state
+    }
+
+    /// Name and level of all items, and the name of the focused item
+    fn tree_and_focus(state: &SystemState) -> (Vec<(String, u8)>, Option<String>) {
+        let waves = state.user.waves.as_ref().unwrap();
+        let name = |item_ref| waves.displayed_items[item_ref].name();
+        let tree = waves
+            .items_tree
+            .iter()
+            .map(|node| (name(&node.item_ref), node.level))
+            .collect();
+        let focused = waves
+            .focused_item
+            .and_then(|vidx| waves.items_tree.get_visible(vidx))
+            .map(|node| name(&node.item_ref));
+        (tree, focused)
+    }
+
+    #[test]
+    fn dragging_focused_item_into_group_keeps_focus() {
+        let mut state = state_with_group_for_drag();
+        // The messages sent when starting to drag an item that is not selected
+        state.update(Message::FocusItem(VisibleItemIndex(4)));
+        state.update(Message::ItemSelectionClear);
+        state.update(Message::SetItemSelected(VisibleItemIndex(4), true));
+        state.update(Message::VariableDragStarted(VisibleItemIndex(4)));
+        state.update(Message::VariableDragTargetChanged(TargetPosition {
+            before: ItemIndex(2),
+            level: 1,
+        }));
+        state.update(Message::VariableDragFinished);
+
+        let (tree, focused) = tree_and_focus(&state);
+        assert_eq!(
+            tree,
+            vec![
+                ("group".to_string(), 0),
+                ("clk".to_string(), 1),
+                ("counter".to_string(), 1),
+                ("reset".to_string(), 0),
+                ("overflow".to_string(), 0),
+            ]
+        );
+        assert_eq!(focused.as_deref(), Some("counter"));
+    }
+
+    #[test]
+    fn dragging_selected_items_keeps_other_focused_item_in_place() {
+        let mut state = state_with_group_for_drag();
+        state.update(Message::FocusItem(VisibleItemIndex(2)));
+        state.update(Message::SetItemSelected(VisibleItemIndex(3), true));
+        state.update(Message::SetItemSelected(VisibleItemIndex(4), true));
+        state.update(Message::VariableDragStarted(VisibleItemIndex(3)));
+        state.update(Message::VariableDragTargetChanged(TargetPosition {
+            before: ItemIndex(1),
+            level: 1,
+        }));
+        state.update(Message::VariableDragFinished);
+
+        let (tree, focused) = tree_and_focus(&state);
+        assert_eq!(
+            tree,
+            vec![
+                ("group".to_string(), 0),
+                ("overflow".to_string(), 1),
+                ("counter".to_string(), 1),
+                ("clk".to_string(), 1),
+                ("reset".to_string(), 0),
+            ]
+        );
+        assert_eq!(focused.as_deref(), Some("reset"));
+    }
 }
@@ -13,23 +13,27 @@ use std::collections::HashMap;
 use std::f32::consts::PI;
 use surfer_translation_types::{
     SubFieldFlatTranslationResult, TranslatedValue, ValueKind, VariableInfo, VariableValue,
+    is_partially_unknown,
 };
 use tracing::{error, warn};
 
 use crate::CachedDrawData::TransactionDrawData;
 use crate::analog_renderer::{AnalogDrawingCommand, variable_analog_draw_commands};
 use crate::clock_highlighting::draw_clock_edge_marks;
-use crate::config::SurferTheme;
+use crate::config::{SurferTheme, ValueTextAlign};
 use crate::data_container::DataContainer;
 use crate::displayed_item::{
-    AnalogSettings, DisplayedFieldRef, DisplayedItemRef, DisplayedVariable,
+    AnalogSettings, AnalogStyle, DisplayedFieldRef, DisplayedItemRef, DisplayedVariable,
+    NumericFormatOptions,
 };
+use crate::time::TimeFormatter;
 use crate::tooltips::handle_transaction_tooltip;
 use crate::transaction_container::{TransactionRef, TransactionStreamRef};
 use crate::translation::{TranslationResultExt, TranslatorList, ValueKindExt, VariableInfoExt};
-use crate::view::{DrawConfig, DrawingContext, ItemDrawingInfo};
+use crate::view::{DrawConfig, DrawingContext, ItemDrawingInfo, ruler_time_at};
+use crate::viewport::{Viewport, scroll_zoom_delta};
 use crate::wave_container::{QueryResult, VariableRefExt};
-use crate::wave_data::WaveData;
+use crate::wave_data::{WaveData, item_height_scaling_factor};
 use crate::{
     CachedDrawData, CachedTransactionDrawData, CachedWaveDrawData, Message, SystemState,
     displayed_item::DisplayedItem,
@@ -65,6 +69,10 @@ pub struct DrawnRegion {
     /// draw draw a vertical line and prevent apparent aliasing
     force_anti_alias: bool,
     dinotrace_style: DinotraceDrawingStyle,
+    /// True if the variable changed more than once within the pixel ending at this region
+    glitch: bool,
+    /// True if some, but not all, bits of the value are undefined or high-impedance
+    partial_unknown: bool,
 }
 
 pub enum DrawingCommands {
@@ -90,6 +98,8 @@ pub enum AnalogDrawingCommands {
         /// Pixel position of last timestamp (end of signal data).
         max_valid_pixel: f32,
         analog_settings: AnalogSettings,
+        /// Style of the trace, with the configured default applied
+        style: AnalogStyle,
     },
 }
 #[derive(Clone, PartialEq, Debug)]
@@ -115,6 +125,9 @@ impl From<&VariableInfo> for DigitalDrawingType {
 pub struct DigitalDrawingCommands {
     pub drawing_type: DigitalDrawingType,
     pub values: Vec<(f32, DrawnRegion)>,
+    /// Pixel and time of each value change, only collected for variables showing interval
+    /// labels
+    pub transitions: Vec<(f32, BigUint)>,
 }
 
 impl DigitalDrawingCommands {
@@ -123,6 +136,7 @@ impl DigitalDrawingCommands {
         DigitalDrawingCommands {
             drawing_type: DigitalDrawingType::from(info),
             values: vec![],
+            transitions: vec![],
         }
     }
 
@@ -156,6 +170,11 @@ fn variable_draw_commands(
     view_width: f32,
     viewport_idx: usize,
     use_dinotrace_style: bool,
+    analog_style: AnalogStyle,
+    numeric_format: NumericFormatOptions,
+    show_glitch_density: bool,
+    show_partial_unknowns: bool,
+    collect_clock_edges: bool,
 ) -> Option<VariableDrawCommands> {
     let wave_container = waves.inner.as_waves()?;
 
@@ -170,7 +189,7 @@ fn variable_draw_commands(
         .variable_meta(&displayed_variable.variable_ref)
         .context("failed to get variable meta")
     {
-        Ok(meta) => meta,
+        Ok(meta) => displayed_variable.translation_meta(meta),
         Err(e) => {
             warn!("{e:#?}");
             return None;
@@ -195,6 +214,7 @@ fn variable_draw_commands(
             translators,
             view_width,
             viewport_idx,
+            analog_style,
         )
     } else {
         variable_digital_draw_commands(
@@ -210,6 +230,10 @@ fn variable_draw_commands(
             view_width,
             viewport_idx,
             use_dinotrace_style,
+            numeric_format,
+            show_glitch_density,
+            show_partial_unknowns,
+            collect_clock_edges,
         )
     }
 }
@@ -229,6 +253,10 @@ fn variable_digital_draw_commands(
     view_width: f32,
     viewport_idx: usize,
     use_dinotrace_style: bool,
+    numeric_format: NumericFormatOptions,
+    show_glitch_density: bool,
+    show_partial_unknowns: bool,
+    collect_clock_edges: bool,
 ) -> Option<VariableDrawCommands> {
     let mut clock_edges = vec![];
     let mut local_msgs = vec![];
@@ -238,6 +266,7 @@ fn variable_digital_draw_commands(
     let mut local_commands: HashMap<Vec<String>, DigitalDrawingCommands> = HashMap::new();
 
     let mut prev_values = HashMap::new();
+    let mut prev_partial_unknown = false;
 
     // In order to insert a final draw command at the end of a trace,
     // we need to know if this is the last timestamp to draw
@@ -246,6 +275,8 @@ fn variable_digital_draw_commands(
     // list, since we skip one pixel to have a previous value
     let start_pixel = timestamps.get(1).map(|t| t.0).unwrap_or_default();
 
+    let number_notation = translator.number_notation();
+
     // Iterate over all the time stamps to draw on
     let mut next_change = timestamps.first().map(|t| t.0).unwrap_or_default();
     for ((_, prev_time), (pixel, time)) in timestamps.iter().zip(timestamps.iter().skip(1)) {
@@ -318,7 +349,36 @@ fn variable_digital_draw_commands(
             DinotraceDrawingStyle::Normal
         };
 
+        let glitch = show_glitch_density
+            && &change_time > prev_time
+            && pixel_transition_count(prev_time, time, |t| {
+                wave_container
+                    .query_variable(&displayed_variable.variable_ref, t)
+                    .ok()
+                    .flatten()
+                    .and_then(|result| result.next)
+            }) > 1;
+        let partial_unknown = show_partial_unknowns
+            && matches!(&val, VariableValue::String(bits) if is_partially_unknown(bits));
+
         for SubFieldFlatTranslationResult { names, value } in fields {
+            let value = if names.is_empty() {
+                value.map(|value| {
+                    let value = displayed_variable.leading_zeros.apply(
+                        value,
+                        number_notation,
+                        meta.num_bits,
+                    );
+                    displayed_variable.with_unit(
+                        displayed_variable
+                            .numeric_format
+                            .unwrap_or(numeric_format)
+                            .apply(value, number_notation),
+                    )
+                })
+            } else {
+                value
+            };
             let entry = local_commands.entry(names.clone()).or_insert_with(|| {
                 DigitalDrawingCommands::new_from_variable_info(info.get_subinfo(&names))
             });
@@ -334,15 +394,23 @@ fn variable_digital_draw_commands(
                 && names.is_empty()
                 && wave_container.wants_anti_aliasing();
             let new_value = prev != Some(&value);
+            let glitch = glitch && names.is_empty();
+            let partial_unknown = partial_unknown && names.is_empty();
+            // Partially unknown values may translate to the same text as fully unknown ones
+            let partial_unknown_changed =
+                names.is_empty() && partial_unknown != prev_partial_unknown;
 
             // This is not the value we drew last time
-            if new_value || is_last_timestep || anti_alias {
+            if new_value || is_last_timestep || anti_alias || glitch || partial_unknown_changed {
+                if names.is_empty() {
+                    prev_partial_unknown = partial_unknown;
+                }
                 prev_values
                     .entry(names.clone())
                     .or_insert(value.clone())
                     .clone_from(&value);
 
-                if entry.drawing_type == DigitalDrawingType::Clock {
+                if collect_clock_edges && entry.drawing_type == DigitalDrawingType::Clock {
                     match value.as_ref().map(|result| result.value.as_str()) {
                         Some("1") => {
                             if !is_last_timestep && !is_first_timestep {
@@ -354,12 +422,27 @@ fn variable_digital_draw_commands(
                     }
                 }
 
+                if displayed_variable.show_interval_labels
+                    && names.is_empty()
+                    && new_value
+                    && entry.transitions.last().map(|(_, t)| t) != Some(&change_time)
+                {
+                    let change_pixel = waves.viewports[viewport_idx].pixel_from_time(
+                        &change_time.to_bigint().unwrap(),
+                        view_width,
+                        &num_timestamps,
+                    );
+                    entry.transitions.push((change_pixel, change_time.clone()));
+                }
+
                 entry.push((
                     *pixel,
                     DrawnRegion {
                         inner: value,
-                        force_anti_alias: anti_alias && !new_value,
+                        force_anti_alias: (anti_alias || glitch) && !new_value,
                         dinotrace_style,
+                        glitch,
+                        partial_unknown,
                     },
                 ));
             }
@@ -385,6 +468,28 @@ impl SystemState {
         }
     }
 
+    /// Redraw after viewport `viewport_idx` was moved, moving the other viewports along with
+    /// it if the viewports are linked
+    pub fn viewport_changed(&mut self, viewport_idx: usize) {
+        if self.viewports_linked()
+            && let Some(waves) = &mut self.user.waves
+        {
+            waves.sync_viewports(viewport_idx);
+        }
+        self.invalidate_draw_commands();
+    }
+
+    /// Whether the tick labels in the draw data of `viewport_idx` are relative to another time
+    /// than the ruler is now, i.e., the cursor moved while the ruler is relative to it
+    fn ticks_reference_moved(&self, waves: &WaveData, viewport_idx: usize) -> bool {
+        match &self.draw_data.borrow()[viewport_idx] {
+            Some(CachedDrawData::WaveDrawData(draw_data)) => {
+                draw_data.ticks_reference.as_ref() != self.relative_time_reference(waves)
+            }
+            _ => false,
+        }
+    }
+
     pub fn generate_draw_commands(
         &self,
         cfg: &DrawConfig,
@@ -440,10 +545,15 @@ impl SystemState {
         timestamps.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
 
         let use_dinotrace_style = self.use_dinotrace_style();
+        let analog_style = self.user.config.layout.analog_style();
+        let numeric_format = self.numeric_format();
+        let show_glitch_density = self.show_glitch_density();
+        let show_partial_unknowns = self.show_partial_unknowns();
+        let collect_clock_edges = self.clock_highlight_enabled();
         let translators = &self.translators;
         let commands = waves
             .items_tree
-            .iter_visible()
+            .iter_visible_drawn()
             .map(|node| (node.item_ref, waves.displayed_items.get(&node.item_ref)))
             .filter_map(|(id, item)| match item {
                 Some(DisplayedItem::Variable(variable_ref)) => Some((id, variable_ref)),
@@ -464,6 +574,11 @@ impl SystemState {
                     cfg.canvas_width,
                     viewport_idx,
                     use_dinotrace_style,
+                    analog_style,
+                    numeric_format,
+                    show_glitch_density,
+                    show_partial_unknowns,
+                    collect_clock_edges,
                 )
             })
             .collect::<Vec<_>>();
@@ -488,12 +603,64 @@ impl SystemState {
             clock_edges.append(&mut new_clock_edges);
         }
 
+        let viewport = waves.viewports[viewport_idx];
+        let view_start = viewport
+            .left_edge_time(&num_timestamps)
+            .to_biguint()
+            .unwrap_or_default();
+        let view_end = viewport
+            .right_edge_time(&num_timestamps)
+            .min(num_timestamps.clone())
+            .to_biguint()
+            .unwrap_or_default();
+        let assertion_violations = waves
+            .items_tree
+            .iter_visible_drawn()
+            .filter_map(|node| match waves.displayed_items.get(&node.item_ref) {
+                Some(DisplayedItem::Variable(variable)) if variable.assertion.is_some() => {
+                    let pixel = |time: u64| {
+                        viewport.pixel_from_time(
+                            &BigInt::from(time),
+                            cfg.canvas_width,
+                            &num_timestamps,
+                        )
+                    };
+                    let regions = waves
+                        .assertion_violations(variable, &view_start, &view_end)
+                        .into_iter()
+                        .map(|(start, end)| (pixel(start), pixel(end)))
+                        .collect();
+                    Some((node.item_ref, regions))
+                }
+                _ => None,
+            })
+            .collect();
+
+        // Sparklines follow the first viewport, so they are sampled along with its draw commands
+        let sparklines = if viewport_idx == 0 && self.show_sparklines() {
+            waves
+                .items_tree
+                .iter_visible()
+                .filter_map(|node| match waves.displayed_items.get(&node.item_ref) {
+                    Some(DisplayedItem::Variable(variable)) => {
+                        Some((node.item_ref, waves.sparkline_samples(variable)))
+                    }
+                    _ => None,
+                })
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
         let ticks = self.get_ticks_for_viewport_idx(waves, viewport_idx, cfg);
 
         Some(CachedDrawData::WaveDrawData(CachedWaveDrawData {
             draw_commands,
             clock_edges,
             ticks,
+            ticks_reference: self.relative_time_reference(waves).cloned(),
+            assertion_violations,
+            sparklines,
         }))
     }
 
@@ -517,7 +684,7 @@ impl SystemState {
 
         let displayed_streams = waves
             .items_tree
-            .iter_visible()
+            .iter_visible_drawn()
             .map(|node| node.item_ref)
             .collect::<Vec<_>>()
             .par_iter()
@@ -731,6 +898,7 @@ impl SystemState {
         // the draw commands have been invalidated, recompute
         if self.draw_data.borrow()[viewport_idx].is_none()
             || Some(response.rect) != *self.last_canvas_rect.borrow()
+            || self.ticks_reference_moved(waves, viewport_idx)
         {
             self.generate_draw_commands(&cfg, msgs, viewport_idx);
             *self.last_canvas_rect.borrow_mut() = Some(response.rect);
@@ -763,9 +931,14 @@ impl SystemState {
                     &num_timestamps,
                 ));
 
+                let behavior = &self.user.config.behavior;
                 msgs.push(Message::CanvasZoom {
                     mouse_ptr,
-                    delta: ui.input(egui::InputState::zoom_delta),
+                    delta: scroll_zoom_delta(
+                        ui.input(egui::InputState::zoom_delta),
+                        behavior.zoom_sensitivity(),
+                        behavior.invert_zoom_scroll(),
+                    ),
                     viewport_idx,
                 });
             }
@@ -847,6 +1020,10 @@ impl SystemState {
             self.draw_background(drawing_info, y_zero, &ctx, gap, background_color);
         }
 
+        if self.show_no_data_hatch() {
+            self.draw_no_data_hatch(waves, &mut ctx, viewport_idx);
+        }
+
         #[cfg(feature = "performance_plot")]
         self.timing.borrow_mut().start("Wave drawing");
 
@@ -868,6 +1045,12 @@ impl SystemState {
             &self.user.config.theme,
         );
 
+        waves.draw_trigger(
+            &self.user.config.theme,
+            &mut ctx,
+            &waves.viewports[viewport_idx],
+        );
+
         waves.draw_cursor(
             &self.user.config.theme,
             &mut ctx,
@@ -898,6 +1081,13 @@ impl SystemState {
             self.draw_default_timeline(waves, &ctx, viewport_idx);
         }
 
+        if self.user.config.behavior.hover_crosshair()
+            && let Some(hover_pos) = response.hover_pos()
+        {
+            let x = to_screen.inverse().transform_pos(hover_pos).x;
+            self.draw_hover_crosshair(waves, &mut ctx, x, viewport_idx);
+        }
+
         self.draw_mouse_gesture_widget(
             egui_ctx,
             waves,
@@ -953,6 +1143,7 @@ impl SystemState {
             );
         }
         let zero_y = (ctx.to_screen)(0., 0.).y;
+        let maximized_item = waves.maximized_item();
         for (item_count, drawing_info) in waves
             .drawing_infos
             .iter()
@@ -974,11 +1165,30 @@ impl SystemState {
 
             match drawing_info {
                 ItemDrawingInfo::Variable(variable_info) => {
-                    if let Some(commands) = draw_commands.get(&variable_info.displayed_field_ref) {
-                        let height_scaling_factor = displayed_item.map_or(
-                            1.0,
-                            super::displayed_item::DisplayedItem::height_scaling_factor,
+                    if variable_info.displayed_field_ref.field.is_empty()
+                        && let Some(regions) = draw_data
+                            .assertion_violations
+                            .get(&variable_info.displayed_field_ref.item)
+                    {
+                        self.draw_assertion_violations(
+                            regions,
+                            y_offset,
+                            drawing_info.bottom() - drawing_info.top(),
+                            ctx,
                         );
+                    }
+                    if let Some(commands) = draw_commands.get(&variable_info.displayed_field_ref) {
+                        let height_scaling_factor = item_height_scaling_factor(
+                            drawing_info.vidx(),
+                            displayed_item.map_or(
+                                1.0,
+                                super::displayed_item::DisplayedItem::height_scaling_factor,
+                            ),
+                            maximized_item,
+                            waves.total_height,
+                            self.user.config.layout.waveforms_line_height,
+                        )
+                        .unwrap_or(1.0);
 
                         let color = color.unwrap_or_else(|| {
                             if let Some(DisplayedItem::Variable(variable)) = displayed_item {
@@ -1026,6 +1236,8 @@ impl SystemState {
                                         }
                                     }
                                     DigitalDrawingType::Event => {
+                                        // Every transition of an event is drawn, so there are
+                                        // no glitches to mark
                                         for event in &digital_commands.values {
                                             self.draw_event(
                                                 event,
@@ -1049,6 +1261,15 @@ impl SystemState {
                                             .config
                                             .theme
                                             .get_best_text_color(background_color);
+                                        let text_align = match displayed_item {
+                                            Some(DisplayedItem::Variable(variable)) => {
+                                                variable.value_text_align
+                                            }
+                                            _ => None,
+                                        }
+                                        .unwrap_or_else(|| {
+                                            self.user.config.layout.value_text_align()
+                                        });
 
                                         for (old, new) in digital_commands
                                             .values
@@ -1062,10 +1283,39 @@ impl SystemState {
                                                 height_scaling_factor,
                                                 ctx,
                                                 text_color,
+                                                text_align,
                                             );
                                         }
                                     }
                                 }
+                                for (start, end) in partial_unknown_spans(&digital_commands.values)
+                                {
+                                    self.draw_partial_unknown_overlay(
+                                        start,
+                                        end,
+                                        y_offset,
+                                        height_scaling_factor,
+                                        ctx,
+                                    );
+                                }
+                                for (x, region) in &digital_commands.values {
+                                    if region.glitch {
+                                        self.draw_glitch_marker(
+                                            *x,
+                                            y_offset,
+                                            height_scaling_factor,
+                                            ctx,
+                                        );
+                                    }
+                                }
+                                if !digital_commands.transitions.is_empty() {
+                                    self.draw_interval_labels(
+                                        waves,
+                                        &digital_commands.transitions,
+                                        y_offset,
+                                        ctx,
+                                    );
+                                }
                             }
                             DrawingCommands::Analog(analog_commands) => {
                                 crate::analog_renderer::draw_analog(
@@ -1178,6 +1428,8 @@ impl SystemState {
                                     .0
                                     .as_ref()
                                     .is_some_and(|t| t == tx_ref);
+                                let is_transaction_selected =
+                                    waves.selected_transactions.contains(tx_ref);
 
                                 if inc_relation_tx_ids.contains(tx_ref) {
                                     inc_relation_starts.push(start);
@@ -1200,6 +1452,11 @@ impl SystemState {
                                     );
 
                                     if response.clicked() {
+                                        if ui.input(|i| i.modifiers.command) {
+                                            msgs.push(Message::ToggleTransactionSelected(
+                                                tx_ref.clone(),
+                                            ));
+                                        }
                                         msgs.push(Message::FocusTransaction(
                                             Some(tx_ref.clone()),
                                             None,
@@ -1217,8 +1474,11 @@ impl SystemState {
                                         tx_color
                                     };
 
-                                    let stroke =
-                                        Stroke::new(1.5, tx_fill_color.gamma_multiply(1.2));
+                                    let stroke = if is_transaction_selected {
+                                        Stroke::new(3.0, self.user.config.theme.foreground)
+                                    } else {
+                                        Stroke::new(1.5, tx_fill_color.gamma_multiply(1.2))
+                                    };
                                     ctx.painter.rect(
                                         transaction_rect,
                                         CornerRadius::same(5),
@@ -1282,6 +1542,7 @@ impl SystemState {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn draw_region(
         &self,
         ((old_x, prev_region), (new_x, _)): (&(f32, DrawnRegion), &(f32, DrawnRegion)),
@@ -1290,6 +1551,7 @@ impl SystemState {
         height_scaling_factor: f32,
         ctx: &mut DrawingContext,
         text_color: Color32,
+        text_align: ValueTextAlign,
     ) {
         if let Some(prev_result) = &prev_region.inner {
             let color = prev_result.kind.color(user_color, ctx.theme);
@@ -1358,21 +1620,14 @@ impl SystemState {
             let fits_text = num_chars >= 1;
 
             if fits_text {
-                let content = if prev_result.value.len() > num_chars {
-                    prev_result
-                        .value
-                        .chars()
-                        .take(num_chars - 1)
-                        .chain(['…'])
-                        .collect::<String>()
-                } else {
-                    prev_result.value.to_string()
+                let (pos, anchor) = match text_align {
+                    ValueTextAlign::Left => (*old_x + transition_width, Align2::LEFT_CENTER),
+                    ValueTextAlign::Right => (*new_x - transition_width, Align2::RIGHT_CENTER),
                 };
-
                 ctx.painter.text(
-                    trace_coords(*old_x + transition_width, 0.5),
-                    Align2::LEFT_CENTER,
-                    content,
+                    trace_coords(pos, 0.5),
+                    anchor,
+                    fit_value_text(&prev_result.value, num_chars, text_align),
                     FontId::monospace(text_size),
                     text_color,
                 );
@@ -1494,6 +1749,113 @@ impl SystemState {
         }
     }
 
+    /// Marks a pixel where the variable changed more than once with a tick that extends
+    /// slightly beyond the trace.
+    fn draw_glitch_marker(
+        &self,
+        x: f32,
+        offset: f32,
+        height_scaling_factor: f32,
+        ctx: &mut DrawingContext,
+    ) {
+        let trace_coords =
+            |x, y| (ctx.to_screen)(x, y * ctx.cfg.line_height * height_scaling_factor + offset);
+        let stroke = Stroke {
+            color: self.user.config.theme.accent_warn.background,
+            width: self.user.config.theme.linewidth,
+        };
+        ctx.painter.line_segment(
+            [trace_coords(x - 0.5, -0.1), trace_coords(x - 0.5, 1.1)],
+            stroke,
+        );
+    }
+
+    /// Cross-hatches the trace between `start` and `end`, where the value is partially unknown.
+    fn draw_partial_unknown_overlay(
+        &self,
+        start: f32,
+        end: f32,
+        offset: f32,
+        height_scaling_factor: f32,
+        ctx: &mut DrawingContext,
+    ) {
+        const SPACING: f32 = 6.;
+        let height = ctx.cfg.line_height * height_scaling_factor;
+        let trace_coords = |x, y| (ctx.to_screen)(x, y + offset);
+        let rect = Rect::from_min_max(trace_coords(start, 0.), trace_coords(end, height));
+        let painter = ctx.painter.with_clip_rect(rect);
+        let stroke = Stroke::new(
+            1.,
+            self.user.config.theme.variable_undef.gamma_multiply(0.6),
+        );
+        // Align the lines to a fixed grid so that the pattern does not move with the region
+        let mut x = ((start - height) / SPACING).floor() * SPACING;
+        while x < end {
+            painter.line_segment(
+                [trace_coords(x, height), trace_coords(x + height, 0.)],
+                stroke,
+            );
+            painter.line_segment(
+                [trace_coords(x, 0.), trace_coords(x + height, height)],
+                stroke,
+            );
+            x += SPACING;
+        }
+    }
+
+    /// Draws the time since the previous transition above the waveform, between the transitions.
+    fn draw_interval_labels(
+        &self,
+        waves: &WaveData,
+        transitions: &[(f32, BigUint)],
+        offset: f32,
+        ctx: &mut DrawingContext,
+    ) {
+        let text_size = ctx.cfg.text_size * 0.8;
+        let time_formatter = TimeFormatter::new(
+            &waves.inner.metadata().timescale,
+            &self.user.wanted_timeunit,
+            &self.get_time_format(),
+        );
+        let color = self.user.config.theme.foreground.gamma_multiply(0.7);
+        for (x, label) in interval_labels(transitions, text_size * (20. / 31.), &time_formatter) {
+            ctx.painter.text(
+                (ctx.to_screen)(x, offset),
+                Align2::CENTER_BOTTOM,
+                label,
+                FontId::monospace(text_size),
+                color,
+            );
+        }
+    }
+
+    /// Shades the pixel ranges where the assertion of a variable does not hold.
+    fn draw_assertion_violations(
+        &self,
+        regions: &[(f32, f32)],
+        offset: f32,
+        height: f32,
+        ctx: &mut DrawingContext,
+    ) {
+        let color = self
+            .user
+            .config
+            .theme
+            .accent_error
+            .background
+            .gamma_multiply(0.4);
+        for (start, end) in regions {
+            ctx.painter.rect_filled(
+                Rect::from_min_max(
+                    (ctx.to_screen)(*start, offset),
+                    (ctx.to_screen)(end.max(start + 1.), offset + height),
+                ),
+                CornerRadius::ZERO,
+                color,
+            );
+        }
+    }
+
     /// Draws a curvy arrow from `start` to `end`.
     fn draw_arrow(&self, start: Pos2, end: Pos2, ctx: &DrawingContext, stroke: &PathStroke) {
         let x_diff = (end.x - start.x).max(100.);
@@ -1576,6 +1938,20 @@ impl SystemState {
                     y: offset,
                 };
 
+            if self.show_default_timeline()
+                && let Some(time) = ruler_time_at(
+                    top_left.to_pos2(),
+                    ui.text_style_height(&egui::TextStyle::Body),
+                    &waves.viewports[viewport_idx],
+                    frame_size.x,
+                    &waves.safe_num_timestamps(),
+                )
+            {
+                self.draw_line(&time, ctx, viewport_idx, waves);
+                self.ruler_context_menu(ui, waves, &time, viewport_idx, msgs);
+                return;
+            }
+
             let snap_pos =
                 self.snap_to_edge(Some(top_left.to_pos2()), waves, frame_size.x, viewport_idx);
 
@@ -1596,6 +1972,7 @@ impl SystemState {
                             msgs.push(Message::AddMarker {
                                 time,
                                 name: None,
+                                idx: None,
                                 move_focus: true,
                             });
                         });
@@ -1653,6 +2030,62 @@ impl SystemState {
         Some(timestamp)
     }
 
+    fn draw_hover_crosshair(
+        &self,
+        waves: &WaveData,
+        ctx: &mut DrawingContext,
+        x: f32,
+        viewport_idx: usize,
+    ) {
+        let theme = &self.user.config.theme;
+        let color = theme.foreground.gamma_multiply(0.3);
+        draw_vertical_line(x, ctx, Stroke::new(1., color));
+
+        let time_formatter = TimeFormatter::new(
+            &waves.inner.metadata().timescale,
+            &self.user.wanted_timeunit,
+            &self.get_time_format(),
+        );
+        let label = crosshair_time_label(
+            &waves.viewports[viewport_idx],
+            x,
+            ctx.cfg.canvas_width,
+            &waves.safe_num_timestamps(),
+            &time_formatter,
+        );
+        ctx.painter.text(
+            (ctx.to_screen)(x + 2., 0.),
+            Align2::LEFT_TOP,
+            label,
+            FontId::proportional(ctx.cfg.text_size),
+            theme.foreground,
+        );
+    }
+
+    fn draw_no_data_hatch(&self, waves: &WaveData, ctx: &mut DrawingContext, viewport_idx: usize) {
+        const SPACING: f32 = 8.;
+        let color = self.user.config.theme.foreground.gamma_multiply(0.1);
+        let height = ctx.cfg.canvas_height;
+        for (start, end) in no_data_regions(
+            &waves.viewports[viewport_idx],
+            ctx.cfg.canvas_width,
+            &waves.safe_num_timestamps(),
+        ) {
+            let rect = Rect::from_min_max((ctx.to_screen)(start, 0.), (ctx.to_screen)(end, height));
+            let painter = ctx.painter.with_clip_rect(rect);
+            painter.rect_filled(rect, CornerRadius::ZERO, color.gamma_multiply(0.5));
+            // Align the lines to a fixed grid so that the pattern does not move with the region
+            let mut x = ((start - height) / SPACING).floor() * SPACING;
+            while x < end {
+                painter.line_segment(
+                    [(ctx.to_screen)(x, height), (ctx.to_screen)(x + height, 0.)],
+                    Stroke::new(1., color),
+                );
+                x += SPACING;
+            }
+        }
+    }
+
     /// Draw a vertical line at the given time position. Used for context menu.
     pub fn draw_line(
         &self,
@@ -1671,6 +2104,120 @@ impl SystemState {
     }
 }
 
+/// Time label of the hover crosshair at the canvas x-position `x`
+#[must_use]
+pub fn crosshair_time_label(
+    viewport: &Viewport,
+    x: f32,
+    frame_width: f32,
+    num_timestamps: &BigInt,
+    time_formatter: &TimeFormatter,
+) -> String {
+    time_formatter.format(&viewport.as_time_bigint(x, frame_width, num_timestamps))
+}
+
+/// Pixel ranges where consecutive regions of `values` are partially unknown, merged into one
+/// range per run. Each region extends to the pixel of the next one.
+#[must_use]
+pub fn partial_unknown_spans(values: &[(f32, DrawnRegion)]) -> Vec<(f32, f32)> {
+    let mut spans: Vec<(f32, f32)> = vec![];
+    for ((x, region), (next_x, _)) in values.iter().tuple_windows() {
+        if !region.partial_unknown {
+            continue;
+        }
+        match spans.last_mut() {
+            Some((_, end)) if end == x => *end = *next_x,
+            _ => spans.push((*x, *next_x)),
+        }
+    }
+    spans
+}
+
+/// Labels with the time since the previous transition, centered between each pair of
+/// consecutive `transitions`. Labels that do not fit between their transitions are left out.
+#[must_use]
+pub fn interval_labels(
+    transitions: &[(f32, BigUint)],
+    char_width: f32,
+    time_formatter: &TimeFormatter,
+) -> Vec<(f32, String)> {
+    transitions
+        .iter()
+        .tuple_windows()
+        .filter_map(|((prev_x, prev_time), (x, time))| {
+            let interval = time.to_bigint()? - prev_time.to_bigint()?;
+            let label = time_formatter.format(&interval);
+            let label_width = (label.chars().count() + 1) as f32 * char_width;
+            (x - prev_x >= label_width).then(|| ((prev_x + x) / 2., label))
+        })
+        .collect()
+}
+
+/// Value text fitting in `num_chars` characters. Values that are too long are cut at the end,
+/// or at the start when right-aligned, and the cut is marked with an ellipsis.
+#[must_use]
+pub fn fit_value_text(value: &str, num_chars: usize, align: ValueTextAlign) -> String {
+    let len = value.chars().count();
+    if len <= num_chars {
+        return value.to_string();
+    }
+    let kept = num_chars.saturating_sub(1);
+    match align {
+        ValueTextAlign::Left => value.chars().take(kept).chain(['…']).collect(),
+        ValueTextAlign::Right => ['…']
+            .into_iter()
+            .chain(value.chars().skip(len - kept))
+            .collect(),
+    }
+}
+
+/// Canvas x-ranges that are before time zero or after the last timestamp
+#[must_use]
+pub fn no_data_regions(
+    viewport: &Viewport,
+    frame_width: f32,
+    num_timestamps: &BigInt,
+) -> Vec<(f32, f32)> {
+    let start = viewport.pixel_from_time(&BigInt::zero(), frame_width, num_timestamps);
+    let end = viewport.pixel_from_time(num_timestamps, frame_width, num_timestamps);
+    let mut regions = vec![];
+    if start > 0. {
+        regions.push((0., start.min(frame_width)));
+    }
+    if end < frame_width {
+        regions.push((end.max(0.), frame_width));
+    }
+    regions
+}
+
+/// Whether `time` is outside of the loaded time range `[0, num_timestamps)`
+#[must_use]
+pub fn is_outside_of_data(time: &BigInt, num_timestamps: &BigInt) -> bool {
+    time < &BigInt::zero() || time >= num_timestamps
+}
+
+/// Number of value changes in the time range `(prev_time, time]` covered by one pixel, found
+/// by following `next_change`, which returns the first change after the given time. Counting
+/// stops at two since the drawing code only needs to know if there is more than one.
+pub fn pixel_transition_count(
+    prev_time: &BigUint,
+    time: &BigUint,
+    mut next_change: impl FnMut(&BigUint) -> Option<BigUint>,
+) -> usize {
+    let mut count = 0;
+    let mut current = prev_time.clone();
+    while count < 2 {
+        match next_change(&current) {
+            Some(next) if next > current && next <= *time => {
+                count += 1;
+                current = next;
+            }
+            _ => break,
+        }
+    }
+    count
+}
+
 /// Draw a vertical line at the given x position with the specified stroke
 pub fn draw_vertical_line(x: f32, ctx: &mut DrawingContext, stroke: impl Into<Stroke>) {
     ctx.painter.line_segment(
@@ -1684,7 +2231,7 @@ pub fn draw_vertical_line(x: f32, ctx: &mut DrawingContext, stroke: impl Into<St
 
 impl WaveData {}
 
-trait VariableExt {
+pub(crate) trait VariableExt {
     fn bool_drawing_spec(
         &self,
         user_color: Color32,
@@ -1731,3 +2278,181 @@ impl VariableExt for String {
         (height, color, background)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::load_counter_vcd;
+    use crate::time::{TimeFormat, TimeScale, TimeUnit};
+    use crate::viewport::Relative;
+
+    #[test]
+    fn partial_unknown_spans_cover_runs_of_partial_values() {
+        let values = ["0101", "01x1", "zz10", "xxxx", "1z11", "1111", "0x00"]
+            .into_iter()
+            .enumerate()
+            .map(|(x, bits)| {
+                (
+                    x as f32 * 10.,
+                    DrawnRegion {
+                        inner: None,
+                        force_anti_alias: false,
+                        dinotrace_style: DinotraceDrawingStyle::Normal,
+                        glitch: false,
+                        partial_unknown: is_partially_unknown(bits),
+                    },
+                )
+            })
+            .collect::<Vec<_>>();
+        // The fully unknown value ends the first run, and the last region has no end
+        assert_eq!(partial_unknown_spans(&values), vec![(10., 30.), (40., 50.)]);
+    }
+
+    #[test]
+    fn crosshair_label_follows_x_position() {
+        let viewport = Viewport::default();
+        let timescale = TimeScale {
+            unit: TimeUnit::NanoSeconds,
+            multiplier: Some(1),
+        };
+        let time_formatter =
+            TimeFormatter::new(&timescale, &TimeUnit::NanoSeconds, &TimeFormat::default());
+        let num_timestamps = BigInt::from(1000);
+        let label = |x| crosshair_time_label(&viewport, x, 500., &num_timestamps, &time_formatter);
+
+        assert_eq!(label(0.), "0 ns");
+        assert_eq!(label(125.), "250 ns");
+        assert_eq!(label(500.), "1000 ns");
+    }
+
+    #[test]
+    fn interval_labels_between_spaced_transitions() {
+        let timescale = TimeScale {
+            unit: TimeUnit::NanoSeconds,
+            multiplier: Some(1),
+        };
+        let time_formatter =
+            TimeFormatter::new(&timescale, &TimeUnit::NanoSeconds, &TimeFormat::default());
+        let transitions = [
+            (0., BigUint::from(0u32)),
+            (100., BigUint::from(100u32)),
+            (150., BigUint::from(150u32)),
+            (152., BigUint::from(152u32)),
+            (400., BigUint::from(400u32)),
+        ];
+
+        assert_eq!(
+            interval_labels(&transitions, 6., &time_formatter),
+            vec![
+                (50., "100 ns".to_string()),
+                (125., "50 ns".to_string()),
+                (276., "248 ns".to_string()),
+            ]
+        );
+        // Wider characters leave out the labels of the shorter intervals
+        assert_eq!(
+            interval_labels(&transitions, 10., &time_formatter),
+            vec![(50., "100 ns".to_string()), (276., "248 ns".to_string())]
+        );
+        assert!(interval_labels(&transitions[..1], 6., &time_formatter).is_empty());
+    }
+
+    #[test]
+    fn fit_value_text_keeps_aligned_end() {
+        assert_eq!(
+            fit_value_text("deadbeef", 8, ValueTextAlign::Right),
+            "deadbeef"
+        );
+        assert_eq!(fit_value_text("deadbeef", 5, ValueTextAlign::Left), "dead…");
+        assert_eq!(
+            fit_value_text("deadbeef", 5, ValueTextAlign::Right),
+            "…beef"
+        );
+        assert_eq!(fit_value_text("deadbeef", 1, ValueTextAlign::Right), "…");
+    }
+
+    #[test]
+    fn no_data_regions_outside_of_data() {
+        let num_timestamps = BigInt::from(1000);
+        let mut viewport = Viewport::default();
+        viewport.curr_left = Relative(-0.25);
+        viewport.curr_right = Relative(1.25);
+        assert_eq!(
+            no_data_regions(&viewport, 600., &num_timestamps),
+            vec![(0., 100.), (500., 600.)]
+        );
+
+        assert_eq!(
+            no_data_regions(&Viewport::default(), 600., &num_timestamps),
+            vec![]
+        );
+
+        viewport.curr_left = Relative(1.5);
+        viewport.curr_right = Relative(2.0);
+        assert_eq!(
+            no_data_regions(&viewport, 600., &num_timestamps),
+            vec![(0., 600.)]
+        );
+    }
+
+    #[test]
+    fn times_outside_of_data_are_detected_at_the_boundaries() {
+        let num_timestamps = BigInt::from(1000);
+        let outside = |time: i32| is_outside_of_data(&BigInt::from(time), &num_timestamps);
+        assert!(outside(-1));
+        assert!(!outside(0));
+        assert!(!outside(999));
+        assert!(outside(1000));
+        assert!(outside(1001));
+    }
+
+    #[test]
+    fn pixel_transition_count_from_change_list() {
+        let changes = [10u32, 20, 21, 22, 40].map(BigUint::from);
+        let next_change = |t: &BigUint| changes.iter().find(|change| *change > t).cloned();
+        let count = |prev: u32, time: u32| {
+            pixel_transition_count(&BigUint::from(prev), &BigUint::from(time), next_change)
+        };
+
+        // Quiet pixel
+        assert_eq!(count(0, 9), 0);
+        assert_eq!(count(23, 39), 0);
+        // Single transition, also at the end of the pixel
+        assert_eq!(count(5, 15), 1);
+        assert_eq!(count(30, 40), 1);
+        // A change at the start of the pixel belongs to the previous pixel
+        assert_eq!(count(10, 19), 0);
+        // Multiple transitions saturate at two
+        assert_eq!(count(15, 21), 2);
+        assert_eq!(count(15, 30), 2);
+        // No further changes
+        assert_eq!(count(40, 100), 0);
+    }
+
+    #[test]
+    fn relative_ticks_are_regenerated_when_the_cursor_moves() {
+        let mut state = load_counter_vcd();
+        state.update(Message::CursorSet(BigInt::from(100)));
+        state.update(Message::ToggleRelativeTimeRuler);
+
+        let cfg = DrawConfig::new(100., 1000., 16., 14.);
+        state.generate_draw_commands(&cfg, &mut vec![], 0);
+        let waves = state.user.waves.as_ref().unwrap();
+        assert!(!state.ticks_reference_moved(waves, 0));
+
+        state.update(Message::CursorSet(BigInt::from(200)));
+        let waves = state.user.waves.as_ref().unwrap();
+        assert!(state.ticks_reference_moved(waves, 0));
+        state.generate_draw_commands(&cfg, &mut vec![], 0);
+        match &state.draw_data.borrow()[0] {
+            Some(CachedDrawData::WaveDrawData(draw_data)) => {
+                assert_eq!(draw_data.ticks_reference, Some(BigInt::from(200)));
+                assert_eq!(
+                    draw_data.ticks,
+                    state.get_ticks_for_viewport_idx(state.user.waves.as_ref().unwrap(), 0, &cfg)
+                );
+            }
+            _ => panic!("Expected wave draw data"),
+        }
+    }
+}
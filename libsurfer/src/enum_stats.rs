@@ -0,0 +1,345 @@
+//! Window listing how long and how often a variable holds each of its values.
+use std::collections::HashMap;
+
+use egui::{Context, Window};
+use egui_extras::{Column, TableBuilder};
+use num::{BigInt, BigUint, ToPrimitive, Zero};
+use serde::Deserialize;
+use surfer_translation_types::{TranslatedValue, ValueKind, VariableValue};
+
+use crate::SystemState;
+use crate::displayed_item::{DisplayedFieldRef, DisplayedItem};
+use crate::message::Message;
+use crate::time::TimeFormatter;
+use crate::translation::TranslationResultExt;
+use crate::wave_data::WaveData;
+
+/// Bucket collecting all values that are undefined or high impedance
+pub const UNKNOWN_BUCKET: &str = "X/Z";
+
+/// Time range the value statistics are computed over
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub enum EnumStatsRange {
+    /// From time zero to the end of the waveform
+    All,
+    /// The range shown in the first viewport
+    Visible,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct EnumValueStats {
+    pub value: String,
+    /// Number of times the value was entered, or held at the start of the range
+    pub count: usize,
+    pub duration: BigUint,
+}
+
+pub struct EnumStats {
+    pub title: String,
+    /// Start and end of the range, the end is exclusive
+    pub start: BigUint,
+    pub end: BigUint,
+    /// Longest held values first
+    pub rows: Vec<EnumValueStats>,
+}
+
+/// Per-value statistics of `segments` in `start..end`. Each segment is the time a value
+/// started, in increasing order, and lasts until the next one. Segments starting before
+/// `start` are clamped to it and consecutive equal values are counted once.
+#[must_use]
+pub fn accumulate_enum_stats(
+    segments: impl IntoIterator<Item = (BigUint, String)>,
+    start: &BigUint,
+    end: &BigUint,
+) -> Vec<EnumValueStats> {
+    let mut runs: Vec<(BigUint, String)> = vec![];
+    for (time, value) in segments {
+        if &time >= end {
+            break;
+        }
+        let time = time.max(start.clone());
+        if runs.last().is_some_and(|(last, _)| *last == time) {
+            runs.pop();
+        }
+        if runs.last().is_some_and(|(_, last)| *last == value) {
+            continue;
+        }
+        runs.push((time, value));
+    }
+
+    let mut stats: HashMap<String, (usize, BigUint)> = HashMap::new();
+    for (idx, (time, value)) in runs.iter().enumerate() {
+        let run_end = runs.get(idx + 1).map_or(end, |(next, _)| next);
+        let entry = stats
+            .entry(value.clone())
+            .or_insert_with(|| (0, BigUint::zero()));
+        entry.0 += 1;
+        entry.1 += run_end - time;
+    }
+    let mut rows: Vec<_> = stats
+        .into_iter()
+        .map(|(value, (count, duration))| EnumValueStats {
+            value,
+            count,
+            duration,
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.duration
+            .cmp(&a.duration)
+            .then_with(|| a.value.cmp(&b.value))
+    });
+    rows
+}
+
+impl WaveData {
+    /// Time range of `range`, clamped to the waveform
+    #[must_use]
+    pub fn enum_stats_range(&self, range: EnumStatsRange) -> Option<(BigUint, BigUint)> {
+        match range {
+            EnumStatsRange::All => Some((BigUint::zero(), self.num_timestamps()?.to_biguint()?)),
+            EnumStatsRange::Visible => self.visible_time_range(),
+        }
+    }
+}
+
+impl SystemState {
+    /// Duration and number of occurrences of each translated value of `field_ref` in `range`
+    #[must_use]
+    pub fn enum_stats(
+        &self,
+        field_ref: &DisplayedFieldRef,
+        range: EnumStatsRange,
+    ) -> Option<EnumStats> {
+        let waves = self.user.waves.as_ref()?;
+        let item = waves.displayed_items.get(&field_ref.item)?;
+        let DisplayedItem::Variable(displayed_variable) = item else {
+            return None;
+        };
+        let wave_container = waves.inner.as_waves()?;
+        let variable = &displayed_variable.variable_ref;
+        let meta =
+            displayed_variable.translation_meta(wave_container.variable_meta(variable).ok()?);
+        let translator = waves.variable_translator_with_meta(
+            &field_ref.without_field(),
+            &self.translators,
+            &meta,
+        );
+        let (start, end) = waves.enum_stats_range(range)?;
+
+        let translate = |value: VariableValue| {
+            let Ok(translated) = translator.translate(&meta, &value) else {
+                return "-".to_string();
+            };
+            translated
+                .format_flat(
+                    &displayed_variable.format,
+                    &displayed_variable.field_formats,
+                    &self.translators,
+                )
+                .into_iter()
+                .find(|subfield| subfield.names == field_ref.field)
+                .and_then(|subfield| subfield.value)
+                .map_or_else(
+                    || "-".to_string(),
+                    |TranslatedValue { value, kind }| match kind {
+                        ValueKind::Undef | ValueKind::HighImp => UNKNOWN_BUCKET.to_string(),
+                        _ => value,
+                    },
+                )
+        };
+
+        let mut segments = vec![];
+        let mut time = start.clone();
+        while let Ok(Some(result)) = wave_container.query_variable(variable, &time) {
+            if let Some((change_time, value)) = result.current {
+                segments.push((change_time, translate(value)));
+            }
+            match result.next {
+                Some(next) if next > time && next < end => time = next,
+                _ => break,
+            }
+        }
+
+        let title = if field_ref.field.is_empty() {
+            item.name()
+        } else {
+            format!("{}.{}", item.name(), field_ref.field.join("."))
+        };
+        Some(EnumStats {
+            title,
+            rows: accumulate_enum_stats(segments, &start, &end),
+            start,
+            end,
+        })
+    }
+
+    pub fn draw_enum_stats_window(
+        &self,
+        stats: &EnumStats,
+        waves: &WaveData,
+        ctx: &Context,
+        msgs: &mut Vec<Message>,
+    ) {
+        let mut open = true;
+        let time_formatter = TimeFormatter::new(
+            &waves.inner.metadata().timescale,
+            &self.user.wanted_timeunit,
+            &self.get_time_format(),
+        );
+        let format_time = |time: &BigUint| time_formatter.format(&BigInt::from(time.clone()));
+        let total = (&stats.end - &stats.start).to_f64().unwrap_or_default();
+        Window::new("Value statistics")
+            .collapsible(true)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{}: {} to {}",
+                    stats.title,
+                    format_time(&stats.start),
+                    format_time(&stats.end)
+                ));
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .columns(Column::auto().resizable(true), 4)
+                    .auto_shrink(emath::Vec2b::new(false, true))
+                    .header(row_height, |mut header| {
+                        for label in ["Value", "Count", "Duration", "Share"] {
+                            header.col(|ui| {
+                                ui.strong(label);
+                            });
+                        }
+                    })
+                    .body(|mut body| {
+                        for row in &stats.rows {
+                            body.row(row_height, |mut table_row| {
+                                table_row.col(|ui| {
+                                    ui.label(&row.value);
+                                });
+                                table_row.col(|ui| {
+                                    ui.label(row.count.to_string());
+                                });
+                                table_row.col(|ui| {
+                                    ui.label(format_time(&row.duration));
+                                });
+                                table_row.col(|ui| {
+                                    if total > 0. {
+                                        let share = row.duration.to_f64().unwrap_or_default();
+                                        ui.label(format!("{:.1} %", 100. * share / total));
+                                    } else {
+                                        ui.label("-");
+                                    }
+                                });
+                            });
+                        }
+                    });
+                ui.add_space(15.);
+                ui.vertical_centered(|ui| {
+                    if ui.button("Close").clicked() {
+                        msgs.push(Message::CloseEnumStats);
+                    }
+                });
+            });
+        if !open {
+            msgs.push(Message::CloseEnumStats);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::displayed_item::DisplayedItemRef;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+
+    fn segments(values: &[(u32, &str)]) -> Vec<(BigUint, String)> {
+        values
+            .iter()
+            .map(|(time, value)| (BigUint::from(*time), (*value).to_string()))
+            .collect()
+    }
+
+    fn row(value: &str, count: usize, duration: u32) -> EnumValueStats {
+        EnumValueStats {
+            value: value.to_string(),
+            count,
+            duration: BigUint::from(duration),
+        }
+    }
+
+    #[test]
+    fn durations_and_counts_are_accumulated_per_value() {
+        let values = segments(&[
+            (0, UNKNOWN_BUCKET),
+            (5, "IDLE"),
+            (20, "BUSY"),
+            (50, "BUSY"),
+            (60, "IDLE"),
+            (70, "DONE"),
+        ]);
+        assert_eq!(
+            accumulate_enum_stats(values, &BigUint::ZERO, &BigUint::from(100u32)),
+            vec![
+                row("BUSY", 1, 40),
+                row("DONE", 1, 30),
+                row("IDLE", 2, 25),
+                row(UNKNOWN_BUCKET, 1, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn segments_are_clamped_to_the_range() {
+        let values = segments(&[(0, "A"), (10, "B"), (30, "A"), (40, "C")]);
+        assert_eq!(
+            accumulate_enum_stats(values, &BigUint::from(15u32), &BigUint::from(35u32)),
+            vec![row("B", 1, 15), row("A", 1, 5)]
+        );
+    }
+
+    #[test]
+    fn enum_stats_accumulate_durations_per_value() {
+        let mut state = load_counter_vcd_with_variables(&["tb.reset", "tb._tmp"]);
+        let item_refs = state
+            .user
+            .waves
+            .as_ref()
+            .unwrap()
+            .items_tree
+            .iter()
+            .map(|node| node.item_ref)
+            .collect::<Vec<_>>();
+        let stats_of = |state: &mut SystemState, item: DisplayedItemRef| {
+            state.update(Message::ComputeEnumStats(
+                DisplayedFieldRef::from(item),
+                EnumStatsRange::All,
+            ));
+            state
+                .enum_stats
+                .as_ref()
+                .unwrap()
+                .rows
+                .iter()
+                .map(|row| (row.value.clone(), row.count, row.duration.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            stats_of(&mut state, item_refs[0]),
+            vec![
+                ("0".to_string(), 1, BigUint::from(700u32)),
+                ("1".to_string(), 1, BigUint::from(100u32)),
+            ]
+        );
+        // Undefined values are collected in their own bucket
+        assert_eq!(
+            stats_of(&mut state, item_refs[1]),
+            vec![(UNKNOWN_BUCKET.to_string(), 1, BigUint::from(800u32))]
+        );
+
+        state.update(Message::CloseEnumStats);
+        assert!(state.enum_stats.is_none());
+    }
+}
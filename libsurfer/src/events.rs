@@ -0,0 +1,140 @@
+//! Named events, each remembering a variable and a time to navigate to.
+use egui::{Context, Grid, Window};
+use egui_remixicon::icons;
+use num::BigInt;
+use serde::{Deserialize, Serialize};
+
+use crate::SystemState;
+use crate::displayed_item::DisplayedItem;
+use crate::displayed_item_tree::VisibleItemIndex;
+use crate::message::Message;
+use crate::time::TimeFormatter;
+use crate::wave_container::{VariableRef, VariableRefExt};
+use crate::wave_data::WaveData;
+
+/// A named time of interest in a variable.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WaveEvent {
+    pub name: String,
+    pub variable: VariableRef,
+    pub time: BigInt,
+}
+
+impl WaveData {
+    /// Messages focusing the first displayed instance of the variable of event `idx`, and
+    /// moving the cursor and the first viewport to the time of the event. The focus is left
+    /// unchanged if the variable is not displayed.
+    #[must_use]
+    pub fn go_to_event_messages(&self, idx: usize) -> Vec<Message> {
+        let Some(event) = self.events.get(idx) else {
+            return vec![];
+        };
+        let mut messages = vec![];
+        if let Some(vidx) = self.items_tree.iter_visible().position(|node| {
+            matches!(
+                self.displayed_items.get(&node.item_ref),
+                Some(DisplayedItem::Variable(variable)) if variable.variable_ref == event.variable
+            )
+        }) {
+            messages.push(Message::FocusItem(VisibleItemIndex(vidx)));
+        }
+        messages.push(Message::CursorSet(event.time.clone()));
+        messages.push(Message::GoToTime(Some(event.time.clone()), 0));
+        messages
+    }
+}
+
+impl SystemState {
+    pub fn draw_events_window(&self, waves: &WaveData, ctx: &Context, msgs: &mut Vec<Message>) {
+        let mut open = true;
+        let time_formatter = TimeFormatter::new(
+            &waves.inner.metadata().timescale,
+            &self.user.wanted_timeunit,
+            &self.get_time_format(),
+        );
+        Window::new("Events")
+            .collapsible(true)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                if waves.events.is_empty() {
+                    ui.label("No events. Add one from the context menu of a variable.");
+                }
+                Grid::new("events").striped(true).show(ui, |ui| {
+                    for (idx, event) in waves.events.iter().enumerate() {
+                        if ui.link(&event.name).clicked() {
+                            msgs.push(Message::GoToEvent(idx));
+                        }
+                        ui.label(event.variable.full_path_string());
+                        ui.label(time_formatter.format(&event.time));
+                        if ui
+                            .button(icons::DELETE_BIN_2_FILL)
+                            .on_hover_text("Remove event")
+                            .clicked()
+                        {
+                            msgs.push(Message::RemoveEvent(idx));
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(15.);
+                ui.vertical_centered(|ui| {
+                    if ui.button("Close").clicked() {
+                        msgs.push(Message::SetEventsWindowVisible(false));
+                    }
+                });
+            });
+        if !open {
+            msgs.push(Message::SetEventsWindowVisible(false));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+
+    #[test]
+    fn events_navigate_to_variable_and_time() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk", "tb.dut.counter"]);
+
+        state.update(Message::AddEvent {
+            name: "overflow".to_string(),
+            variable: VariableRef::from_hierarchy_string("tb.dut.counter"),
+            time: BigInt::from(130),
+        });
+        state.update(Message::AddEvent {
+            name: "hidden".to_string(),
+            variable: VariableRef::from_hierarchy_string("tb.reset"),
+            time: BigInt::from(100),
+        });
+
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(waves.events.len(), 2);
+        assert!(matches!(
+            waves.go_to_event_messages(0).as_slice(),
+            [
+                Message::FocusItem(VisibleItemIndex(1)),
+                Message::CursorSet(cursor),
+                Message::GoToTime(Some(time), 0),
+            ] if *cursor == BigInt::from(130) && *time == BigInt::from(130)
+        ));
+        // Events of variables which are not displayed leave the focus alone
+        assert!(matches!(
+            waves.go_to_event_messages(1).as_slice(),
+            [Message::CursorSet(_), Message::GoToTime(Some(_), 0)]
+        ));
+        assert!(waves.go_to_event_messages(2).is_empty());
+
+        state.update(Message::GoToEvent(0));
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(waves.focused_item, Some(VisibleItemIndex(1)));
+        assert_eq!(waves.cursor, Some(BigInt::from(130)));
+
+        state.update(Message::RemoveEvent(0));
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(waves.events.len(), 1);
+        assert_eq!(waves.events[0].name, "hidden");
+    }
+}
@@ -1,11 +1,11 @@
 use std::future::Future;
-#[cfg(not(target_arch = "wasm32"))]
 use std::path::PathBuf;
 
 #[cfg(not(target_arch = "wasm32"))]
 use camino::Utf8PathBuf;
 use rfd::{AsyncFileDialog, FileHandle};
 use serde::Deserialize;
+use tracing::error;
 
 use crate::SystemState;
 use crate::async_util::perform_async_work;
@@ -94,6 +94,37 @@ impl SystemState {
         });
     }
 
+    /// Write `bytes` to `path`, or to a file picked in a save dialog with `title` and `filter`
+    /// if there is no path. On wasm, the file is always picked in a dialog, which downloads it.
+    pub fn save_bytes_to(
+        &mut self,
+        path: Option<impl Into<PathBuf>>,
+        title: &'static str,
+        filter: (String, Vec<String>),
+        bytes: Vec<u8>,
+    ) {
+        let messages = async move |destination: FileHandle| {
+            destination
+                .write(&bytes)
+                .await
+                .map_err(|e| error!("{title}: failed to write to {destination:#?} {e:#?}"))
+                .ok();
+            Vec::<Message>::new()
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = path {
+            let path: PathBuf = path.into();
+            let sender = self.channels.msg_sender.clone();
+            perform_async_work(async move {
+                checked_send_many(&sender, messages(path.into()).await);
+            });
+            return;
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = path;
+        self.file_dialog_save(title, filter, messages);
+    }
+
     pub fn open_file_dialog(&mut self, mode: OpenMode) {
         let load_options = (mode, self.user.config.behavior.keep_during_reload).into();
 
@@ -281,7 +281,7 @@ fn fuzzy_match(alternatives: &[String], query: &str) -> Vec<(String, Vec<bool>)>
         .collect()
 }
 
-fn fuzzy_score(line: &str, query: &str) -> (i64, Vec<bool>) {
+pub(crate) fn fuzzy_score(line: &str, query: &str) -> (i64, Vec<bool>) {
     static MATCHER: LazyLock<SkimMatcherV2> = LazyLock::new(SkimMatcherV2::default);
     let (score, indices) = MATCHER.fuzzy_indices(line, query).unwrap_or_default();
 
@@ -215,11 +215,14 @@ fn key_listing(ui: &mut Ui, shortcuts: &SurferShortcuts) {
     let undo = shortcuts.format_shortcut(ShortcutAction::Undo);
     let redo = shortcuts.format_shortcut(ShortcutAction::Redo);
     let add_marker = shortcuts.format_shortcut(ShortcutAction::MarkerAdd);
+    let add_named_marker = shortcuts.format_shortcut(ShortcutAction::MarkerAddNamed);
     let scroll_up = shortcuts.format_shortcut(ShortcutAction::ScrollUp);
     let scroll_down = shortcuts.format_shortcut(ShortcutAction::ScrollDown);
     let delete_selected = shortcuts.format_shortcut(ShortcutAction::DeleteSelected);
     let toggle_menu = shortcuts.format_shortcut(ShortcutAction::ToggleMenu);
     let divider_add = shortcuts.format_shortcut(ShortcutAction::DividerAdd);
+    let relative_time_ruler = shortcuts.format_shortcut(ShortcutAction::ToggleRelativeTimeRuler);
+    let center_focused_item = shortcuts.format_shortcut(ShortcutAction::CenterFocusedItem);
     #[cfg(not(target_arch = "wasm32"))]
     let ui_zoom_in = shortcuts.format_shortcut(ShortcutAction::UiZoomIn);
     #[cfg(not(target_arch = "wasm32"))]
@@ -248,6 +251,7 @@ fn key_listing(ui: &mut Ui, shortcuts: &SurferShortcuts) {
         ("", "Ctrl+j/⬇", "Move focused item down"),
         ("", "Alt+k/⬆", "Move focus up"),
         ("", "Alt+j/⬇", "Move focus down"),
+        ("", &center_focused_item, "Center focused item vertically"),
         ("", &selected_item_toggle, "Add focused item to selection"),
         ("", "Ctrl+Alt+k/⬆", "Extend selection up"),
         ("", "Ctrl+Alt+j/⬇", "Extend selection down"),
@@ -255,9 +259,15 @@ fn key_listing(ui: &mut Ui, shortcuts: &SurferShortcuts) {
         ("", &redo, "Redo last change"),
         ("", &focus_item, "Fast focus a variable"),
         ("", &add_marker, "Add marker at current cursor"),
+        ("", &add_named_marker, "Add named marker"),
         ("", "Ctrl+0-9", "Add numbered marker"),
         ("", "0-9", "Center view at numbered marker"),
         ("", &divider_add, "Add divider"),
+        (
+            "",
+            &relative_time_ruler,
+            "Show ruler times relative to the cursor",
+        ),
         (icons::REWIND_START_FILL, &goto_start, "Go to start"),
         (icons::FORWARD_END_FILL, &goto_end, "Go to end"),
         (icons::REFRESH_LINE, &reload_waveform, "Reload waveform"),
@@ -275,14 +285,16 @@ fn key_listing(ui: &mut Ui, shortcuts: &SurferShortcuts) {
         ),
         (
             "",
-            "Ctrl+➡/l",
+            "Shift+➡/l",
             "Go to next non-zero transition of focused variable",
         ),
         (
             "",
-            "Ctrl+⬅/h",
+            "Shift+⬅/h",
             "Go to previous non-zero transition of focused variable",
         ),
+        ("", "Alt+➡", "Move cursor right by one pixel of time"),
+        ("", "Alt+⬅", "Move cursor left by one pixel of time"),
         (
             icons::DELETE_BIN_2_FILL,
             &delete_selected,
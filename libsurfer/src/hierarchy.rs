@@ -1,6 +1,8 @@
 //! Functions for drawing the left hand panel showing scopes and variables.
 use crate::SystemState;
+use crate::config::HierarchyDoubleClick;
 use crate::data_container::{DataContainer, VariableType as VarType};
+use crate::displayed_item::{DisplayedItem, DisplayedItemRef};
 use crate::displayed_item_tree::VisibleItemIndex;
 use crate::message::Message;
 use crate::tooltips::{scope_tooltip_text, variable_tooltip_text};
@@ -25,12 +27,16 @@ use eyre::Context;
 use itertools::Itertools;
 use num::BigUint;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::ops::Range;
 use tracing::warn;
 #[derive(Clone, Copy, Debug, Deserialize, Display, FromStr, PartialEq, Eq, Serialize, Sequence)]
 pub enum HierarchyStyle {
+    /// Scopes and variables in two separate lists
     Separate,
+    /// Scopes and variables in a joint tree
     Tree,
+    /// All variables in one flat list, shown with their full paths
     Variables,
 }
 
@@ -49,6 +55,78 @@ pub enum ScopeExpandType {
     CollapseAll,
 }
 
+/// Location of a displayed variable in the hierarchy panel
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RevealTarget {
+    /// Scope to make active and expand down to
+    pub scope: ScopeRef,
+    /// Variable to highlight in the variable list
+    pub variable: VariableRef,
+}
+
+impl RevealTarget {
+    /// Variables without an enclosing scope have nothing to reveal and give `None`.
+    #[must_use]
+    pub fn from_variable(variable: &VariableRef) -> Option<Self> {
+        if variable.path.strs.is_empty() {
+            return None;
+        }
+        Some(Self {
+            scope: variable.path.clone(),
+            variable: variable.clone(),
+        })
+    }
+}
+
+/// Messages for double-clicking `variable` in the hierarchy. The first click has already added
+/// the variable, `displayed` is where the last displayed copy of it ended up, if any.
+#[must_use]
+pub fn hierarchy_double_click_messages(
+    action: HierarchyDoubleClick,
+    variable: &VariableRef,
+    displayed: Option<(VisibleItemIndex, DisplayedItemRef)>,
+) -> Vec<Message> {
+    match (action, displayed) {
+        (HierarchyDoubleClick::Add, _) | (_, None) => {
+            vec![Message::AddVariables(vec![variable.clone()])]
+        }
+        (HierarchyDoubleClick::AddAndFocus, Some((vidx, _))) => vec![Message::FocusItem(vidx)],
+        (HierarchyDoubleClick::AddAndGoToActivity, Some((vidx, item_ref))) => vec![
+            Message::FocusItem(vidx),
+            Message::CursorToSignalEdge {
+                first: true,
+                variable: item_ref.into(),
+            },
+        ],
+    }
+}
+
+/// Split `scopes` into groups of scopes sharing the module type given by `type_of`, and the
+/// remaining scopes whose type is unknown or not shared with any other scope. Groups are sorted
+/// by type name, the scopes keep their relative order.
+pub fn group_scopes_by_type<T>(
+    scopes: Vec<T>,
+    type_of: impl Fn(&T) -> Option<String>,
+) -> (Vec<(String, Vec<T>)>, Vec<T>) {
+    let types = scopes.iter().map(&type_of).collect_vec();
+    let counts = types.iter().flatten().cloned().counts();
+    let mut groups: HashMap<String, Vec<T>> = HashMap::new();
+    let mut ungrouped = vec![];
+    for (scope, type_name) in scopes.into_iter().zip(types) {
+        match type_name {
+            Some(type_name) if counts[&type_name] > 1 => {
+                groups.entry(type_name).or_default().push(scope);
+            }
+            _ => ungrouped.push(scope),
+        }
+    }
+    let groups = groups
+        .into_iter()
+        .sorted_by(|(a, _), (b, _)| numeric_sort::cmp(a, b))
+        .collect();
+    (groups, ungrouped)
+}
+
 impl SystemState {
     /// Scopes and variables in two separate lists
     pub fn separate(&mut self, ui: &mut Ui, msgs: &mut Vec<Message>) {
@@ -81,8 +159,8 @@ impl SystemState {
                 });
                 ui.add_space(3.0);
 
-                ScrollArea::both()
-                    .id_salt("scopes")
+                let output = self
+                    .scope_scroll_area("scopes")
                     .auto_shrink([false; 2])
                     .show(ui, |ui| {
                         ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
@@ -90,6 +168,7 @@ impl SystemState {
                             self.draw_all_scopes(msgs, waves, false, ui);
                         }
                     });
+                self.push_hierarchy_scroll_offset(output.state.offset.y, msgs);
             });
         CentralPanel::default()
             .frame(Frame::new().inner_margin(Margin::same(5)))
@@ -265,13 +344,14 @@ impl SystemState {
                     });
                     ui.add_space(3.0);
 
-                    ScrollArea::both().id_salt("hierarchy").show(ui, |ui| {
+                    let output = self.scope_scroll_area("hierarchy").show(ui, |ui| {
                         ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
                         if let Some(waves) = &self.user.waves {
                             ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
                             self.draw_all_scopes(msgs, waves, true, ui);
                         }
                     });
+                    self.push_hierarchy_scroll_offset(output.state.offset.y, msgs);
                 });
             },
         );
@@ -502,6 +582,15 @@ impl SystemState {
             return;
         }
 
+        // Also hide scopes where the IO filter leaves no variables to show
+        let variable_filter = &self.user.variable_filter;
+        if !self.show_empty_scopes()
+            && variable_filter.filters_directions()
+            && !variable_filter.scope_has_included_direction(scope, wave_container)
+        {
+            return;
+        }
+
         if child_scopes.is_empty() && (!draw_variables || no_variables_in_scope) {
             // Indent our label by both icon width and icon spacing to
             // match the other headers that actually have an icon.
@@ -511,15 +600,24 @@ impl SystemState {
             });
         } else {
             let should_open_header = self.should_open_header_and_scroll_to(scope);
+            let expanded = self.user.expanded_scopes.contains(&scope.to_string());
+            // The generation gives the headers new ids when a state is loaded, so that the
+            // stored expansion is used instead of what egui remembers
             let mut collapsing_header =
                 egui::collapsing_header::CollapsingState::load_with_default_open(
                     ui.ctx(),
-                    egui::Id::new(scope),
-                    false,
+                    egui::Id::new((scope, self.hierarchy_generation)),
+                    expanded,
                 );
             if let Some((header_state, _)) = should_open_header {
                 collapsing_header.set_open(header_state);
             }
+            if collapsing_header.is_open() != expanded {
+                msgs.push(Message::SetScopeExpanded(
+                    scope.clone(),
+                    collapsing_header.is_open(),
+                ));
+            }
             collapsing_header
                 .show_header(ui, |ui| {
                     ui.with_layout(
@@ -576,7 +674,7 @@ impl SystemState {
             return;
         };
 
-        wave_container
+        let mut child_scopes = wave_container
             .child_scopes(root_scope)
             .context("Failed to get child scopes")
             .map_err(|e| warn!("{e:#?}"))
@@ -584,15 +682,33 @@ impl SystemState {
             .into_iter()
             .flatten()
             .sorted_by(|a, b| numeric_sort::cmp(&a.name(), &b.name()))
-            .for_each(|child_scope| {
-                self.draw_selectable_child_or_orphan_scope(
-                    msgs,
-                    wave,
-                    &child_scope,
-                    draw_variables,
-                    ui,
-                );
+            .collect_vec();
+
+        if self.group_scopes_by_type() {
+            let (groups, ungrouped) = group_scopes_by_type(child_scopes, |scope| {
+                wave_container.get_scope_component(scope)
             });
+            for (type_name, scopes) in groups {
+                egui::CollapsingHeader::new(format!("{type_name} ({})", scopes.len()))
+                    .id_salt((root_scope, &type_name, self.hierarchy_generation))
+                    .show(ui, |ui| {
+                        for scope in &scopes {
+                            self.draw_selectable_child_or_orphan_scope(
+                                msgs,
+                                wave,
+                                scope,
+                                draw_variables,
+                                ui,
+                            );
+                        }
+                    });
+            }
+            child_scopes = ungrouped;
+        }
+
+        for child_scope in &child_scopes {
+            self.draw_selectable_child_or_orphan_scope(msgs, wave, child_scope, draw_variables, ui);
+        }
     }
 
     fn filter_and_draw_variable_list(
@@ -773,9 +889,13 @@ impl SystemState {
                         label.append(&value, 0.0, text_format);
                     }
 
-                    let mut response = ui.add(egui::Button::selectable(false, label));
+                    let revealed = self.revealed_variable.as_ref() == Some(variable);
+                    let mut response = ui.add(egui::Button::selectable(revealed, label));
 
                     let _ = response.interact(egui::Sense::click_and_drag());
+                    if revealed && self.scroll_to_revealed_variable.replace(false) {
+                        response.scroll_to_me(Some(Align::Center));
+                    }
 
                     if self.show_tooltip() {
                         // Reuse the already-obtained `meta` and pass a clone of the variable
@@ -802,14 +922,66 @@ impl SystemState {
                             msgs.push(Message::AddDraggedVariables(vec![variable.clone()]));
                         }
                     });
-                    response
-                        .clicked()
-                        .then(|| msgs.push(Message::AddVariables(vec![variable.clone()])));
+                    if response.double_clicked() {
+                        msgs.push(Message::Batch(hierarchy_double_click_messages(
+                            self.user.config.behavior.hierarchy_double_click(),
+                            &variable,
+                            self.last_displayed_variable(&variable),
+                        )));
+                    } else if response.clicked() {
+                        msgs.push(Message::AddVariables(vec![variable.clone()]));
+                    }
                 },
             );
         }
     }
 
+    /// Scroll area of the scopes, scrolled to the stored position if a state was just loaded
+    fn scope_scroll_area(&self, id_salt: &str) -> ScrollArea {
+        let scroll_area = ScrollArea::both().id_salt((id_salt, self.hierarchy_generation));
+        if self.restore_hierarchy_scroll.replace(false) {
+            scroll_area.vertical_scroll_offset(self.user.hierarchy_scroll_offset)
+        } else {
+            scroll_area
+        }
+    }
+
+    fn push_hierarchy_scroll_offset(&self, offset: f32, msgs: &mut Vec<Message>) {
+        if (offset - self.user.hierarchy_scroll_offset).abs() > 1. {
+            msgs.push(Message::SetHierarchyScrollOffset(offset));
+        }
+    }
+
+    /// Position and reference of the last displayed copy of `variable`
+    fn last_displayed_variable(
+        &self,
+        variable: &VariableRef,
+    ) -> Option<(VisibleItemIndex, DisplayedItemRef)> {
+        let waves = self.user.waves.as_ref()?;
+        waves
+            .items_tree
+            .iter_visible()
+            .enumerate()
+            .filter(|(_, node)| {
+                matches!(
+                    waves.displayed_items.get(&node.item_ref),
+                    Some(DisplayedItem::Variable(displayed)) if displayed.variable_ref == *variable
+                )
+            })
+            .map(|(vidx, node)| (VisibleItemIndex(vidx), node.item_ref))
+            .last()
+    }
+
+    /// Forget expanded scopes that do not exist in the loaded waveform
+    pub(crate) fn retain_existing_expanded_scopes(&mut self) {
+        let Some(wave_container) = self.user.waves.as_ref().and_then(|w| w.inner.as_waves()) else {
+            return;
+        };
+        self.user
+            .expanded_scopes
+            .retain(|scope| wave_container.scope_exists(&ScopeRef::from_hierarchy_string(scope)));
+    }
+
     fn should_open_header_and_scroll_to(&self, scope: &ScopeRef) -> Option<(bool, bool)> {
         let mut scope_ref_cell = self.scope_ref_to_expand.borrow_mut();
         if let Some(state) = scope_ref_cell.as_mut() {
@@ -830,3 +1002,122 @@ impl SystemState {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reveal_target_is_parent_scope_of_variable() {
+        let variable = VariableRef::from_hierarchy_string("tb.dut.alu.result");
+        let target = RevealTarget::from_variable(&variable).unwrap();
+        assert_eq!(target.scope, ScopeRef::from_hierarchy_string("tb.dut.alu"));
+        assert_eq!(target.variable, variable);
+    }
+
+    #[test]
+    fn reveal_target_scope_opens_ancestors() {
+        let state = SystemState::new_default_config().unwrap();
+        let variable = VariableRef::from_hierarchy_string("tb.dut.alu.result");
+        let target = RevealTarget::from_variable(&variable).unwrap();
+        *state.scope_ref_to_expand.borrow_mut() =
+            Some(ScopeExpandType::ExpandSpecific(target.scope));
+
+        let other = ScopeRef::from_hierarchy_string("other");
+        assert_eq!(state.should_open_header_and_scroll_to(&other), None);
+        let tb = ScopeRef::from_hierarchy_string("tb");
+        assert_eq!(
+            state.should_open_header_and_scroll_to(&tb),
+            Some((true, true))
+        );
+        let dut = ScopeRef::from_hierarchy_string("tb.dut");
+        assert_eq!(
+            state.should_open_header_and_scroll_to(&dut),
+            Some((true, true))
+        );
+        assert_eq!(*state.scope_ref_to_expand.borrow(), None);
+    }
+
+    #[test]
+    fn group_scopes_by_type_groups_shared_types() {
+        let types = HashMap::from([
+            ("fifo_0", "fifo"),
+            ("alu", "alu"),
+            ("fifo_1", "fifo"),
+            ("gen_block", ""),
+            ("fifo_10", "fifo"),
+            ("mem_a", "sram"),
+            ("mem_b", "sram"),
+        ]);
+        let scopes = vec![
+            "alu",
+            "fifo_0",
+            "fifo_1",
+            "fifo_10",
+            "gen_block",
+            "mem_a",
+            "mem_b",
+            "no_type",
+        ];
+        let (groups, ungrouped) = group_scopes_by_type(scopes, |scope| {
+            types
+                .get(scope)
+                .filter(|type_name| !type_name.is_empty())
+                .map(ToString::to_string)
+        });
+        assert_eq!(
+            groups,
+            vec![
+                ("fifo".to_string(), vec!["fifo_0", "fifo_1", "fifo_10"]),
+                ("sram".to_string(), vec!["mem_a", "mem_b"]),
+            ]
+        );
+        // Unique and unknown types are kept in the normal nesting
+        assert_eq!(ungrouped, vec!["alu", "gen_block", "no_type"]);
+
+        let (groups, ungrouped) = group_scopes_by_type(vec!["a", "b"], |_| None);
+        assert!(groups.is_empty());
+        assert_eq!(ungrouped, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn reveal_target_of_top_level_variable_is_none() {
+        let variable = VariableRef::from_hierarchy_string("clk");
+        assert_eq!(RevealTarget::from_variable(&variable), None);
+    }
+
+    #[test]
+    fn hierarchy_double_click_messages_follow_config() {
+        let variable = VariableRef::from_hierarchy_string("tb.clk");
+        let displayed = Some((VisibleItemIndex(2), DisplayedItemRef(5)));
+        let is_add = |message: &Message| match message {
+            Message::AddVariables(variables) => *variables == [variable.clone()],
+            _ => false,
+        };
+
+        let messages = |action| hierarchy_double_click_messages(action, &variable, displayed);
+        assert!(matches!(
+            messages(HierarchyDoubleClick::Add).as_slice(),
+            [add] if is_add(add)
+        ));
+        assert!(matches!(
+            messages(HierarchyDoubleClick::AddAndFocus).as_slice(),
+            [Message::FocusItem(VisibleItemIndex(2))]
+        ));
+        assert!(matches!(
+            messages(HierarchyDoubleClick::AddAndGoToActivity).as_slice(),
+            [
+                Message::FocusItem(VisibleItemIndex(2)),
+                Message::CursorToSignalEdge { first: true, variable: field },
+            ] if *field == DisplayedItemRef(5).into()
+        ));
+
+        // Nothing to focus if the first click did not add the variable
+        for action in enum_iterator::all::<HierarchyDoubleClick>() {
+            assert!(matches!(
+                hierarchy_double_click_messages(action, &variable, None).as_slice(),
+                [add] if is_add(add)
+            ));
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Export of the scope and variable hierarchy of the loaded waveform.
+use std::fmt::Write;
+
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use crate::SystemState;
+use crate::wave_container::{ScopeRef, ScopeRefExt, WaveContainer};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+pub enum HierarchyFormat {
+    Json,
+    /// Indented tree with one scope or variable per line
+    Text,
+}
+
+impl HierarchyFormat {
+    /// Picks the format from the file extension, defaulting to text
+    #[must_use]
+    pub fn from_path(path: &Utf8PathBuf) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => HierarchyFormat::Json,
+            _ => HierarchyFormat::Text,
+        }
+    }
+
+    fn file_filter(self) -> (String, Vec<String>) {
+        match self {
+            HierarchyFormat::Json => ("JSON files (*.json)".to_string(), vec!["json".to_string()]),
+            HierarchyFormat::Text => ("Text files (*.txt)".to_string(), vec!["txt".to_string()]),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct HierarchyScope {
+    pub name: String,
+    pub scopes: Vec<HierarchyScope>,
+    pub variables: Vec<HierarchyVariable>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct HierarchyVariable {
+    pub name: String,
+    pub width: Option<u32>,
+    #[serde(rename = "type")]
+    pub variable_type: Option<String>,
+}
+
+impl WaveContainer {
+    /// All scopes of the waveform with their sub-scopes and variables
+    #[must_use]
+    pub fn hierarchy_tree(&self) -> Vec<HierarchyScope> {
+        self.root_scopes()
+            .iter()
+            .map(|scope| self.hierarchy_scope(scope))
+            .collect()
+    }
+
+    fn hierarchy_scope(&self, scope: &ScopeRef) -> HierarchyScope {
+        let scopes = self
+            .child_scopes(scope)
+            .unwrap_or_default()
+            .iter()
+            .map(|child| self.hierarchy_scope(child))
+            .collect();
+        let variables = self
+            .variables_in_scope(scope)
+            .iter()
+            .map(|variable| {
+                let meta = self.variable_meta(variable).ok();
+                HierarchyVariable {
+                    name: variable.index.map_or_else(
+                        || variable.name.clone(),
+                        |index| format!("{}[{index}]", variable.name),
+                    ),
+                    width: meta.as_ref().and_then(|meta| meta.num_bits),
+                    variable_type: meta
+                        .and_then(|meta| meta.variable_type)
+                        .map(|typ| typ.to_string()),
+                }
+            })
+            .collect();
+        HierarchyScope {
+            name: scope.name(),
+            scopes,
+            variables,
+        }
+    }
+}
+
+/// Renders the hierarchy with two spaces of indentation per level
+#[must_use]
+pub fn hierarchy_to_text(scopes: &[HierarchyScope]) -> String {
+    fn write_scope(out: &mut String, scope: &HierarchyScope, depth: usize) {
+        let indent = "  ".repeat(depth);
+        let _ = writeln!(out, "{indent}{}", scope.name);
+        for child in &scope.scopes {
+            write_scope(out, child, depth + 1);
+        }
+        for variable in &scope.variables {
+            let _ = write!(out, "{indent}  {}", variable.name);
+            if let Some(typ) = &variable.variable_type {
+                let _ = write!(out, " {typ}");
+            }
+            if let Some(width) = variable.width {
+                let _ = write!(out, " [{width}]");
+            }
+            out.push('\n');
+        }
+    }
+
+    let mut out = String::new();
+    for scope in scopes {
+        write_scope(&mut out, scope, 0);
+    }
+    out
+}
+
+impl SystemState {
+    #[must_use]
+    pub fn encode_hierarchy(&self, format: HierarchyFormat) -> Option<String> {
+        let tree = self.user.waves.as_ref()?.inner.hierarchy_tree();
+        match format {
+            HierarchyFormat::Json => serde_json::to_string_pretty(&tree)
+                .map_err(|e| error!("Failed to encode hierarchy. {e:#?}"))
+                .ok(),
+            HierarchyFormat::Text => Some(hierarchy_to_text(&tree)),
+        }
+    }
+
+    pub fn export_hierarchy(&mut self, path: Option<Utf8PathBuf>, format: HierarchyFormat) {
+        let Some(encoded) = self.encode_hierarchy(format) else {
+            return;
+        };
+
+        self.save_bytes_to(
+            path,
+            "Export hierarchy",
+            format.file_filter(),
+            encoded.into_bytes(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::load_counter_vcd;
+
+    #[test]
+    fn hierarchy_text_is_indented_per_level() {
+        let tree = vec![HierarchyScope {
+            name: "tb".to_string(),
+            scopes: vec![HierarchyScope {
+                name: "dut".to_string(),
+                scopes: vec![],
+                variables: vec![HierarchyVariable {
+                    name: "counter".to_string(),
+                    width: Some(4),
+                    variable_type: Some("reg".to_string()),
+                }],
+            }],
+            variables: vec![HierarchyVariable {
+                name: "clk".to_string(),
+                width: None,
+                variable_type: None,
+            }],
+        }];
+        assert_eq!(
+            hierarchy_to_text(&tree),
+            "tb\n  dut\n    counter reg [4]\n  clk\n"
+        );
+    }
+
+    #[test]
+    fn hierarchy_format_follows_extension() {
+        assert_eq!(
+            HierarchyFormat::from_path(&Utf8PathBuf::from("out.JSON")),
+            HierarchyFormat::Json
+        );
+        assert_eq!(
+            HierarchyFormat::from_path(&Utf8PathBuf::from("out.txt")),
+            HierarchyFormat::Text
+        );
+    }
+
+    #[test]
+    fn hierarchy_export_contains_scopes_and_variables() {
+        let state = load_counter_vcd();
+
+        let json: serde_json::Value =
+            serde_json::from_str(&state.encode_hierarchy(HierarchyFormat::Json).unwrap()).unwrap();
+        let tb = &json[0];
+        assert_eq!(tb["name"], "tb");
+        let names = |scope: &serde_json::Value| {
+            scope["variables"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|variable| variable["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(names(tb).contains(&"_tmp".to_string()));
+        let dut = &tb["scopes"][0];
+        assert_eq!(dut["name"], "dut");
+        assert!(names(dut).contains(&"counter".to_string()));
+        let counter = dut["variables"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|variable| variable["name"] == "counter")
+            .unwrap();
+        assert_eq!(counter["width"], 4);
+        assert_eq!(counter["type"], "reg");
+
+        let text = state.encode_hierarchy(HierarchyFormat::Text).unwrap();
+        assert!(text.starts_with("tb\n  dut\n"));
+        assert!(text.contains("\n    counter reg [4]\n"));
+    }
+}
@@ -34,10 +34,16 @@ pub enum ShortcutAction {
     ScrollDown,
     DeleteSelected,
     MarkerAdd,
+    MarkerAddNamed,
     ToggleMenu,
     ShowCommandPrompt,
+    ShowQuickOpen,
     RenameItem,
     DividerAdd,
+    ItemMaximize,
+    ToggleAnalog,
+    ToggleRelativeTimeRuler,
+    CenterFocusedItem,
 }
 
 // Cached dispatch table entry: (action, modifier_priority)
@@ -98,13 +104,25 @@ pub struct SurferShortcuts {
     #[serde(with = "keyboard_shortcuts_serde")]
     pub marker_add: Vec<KeyboardShortcut>,
     #[serde(with = "keyboard_shortcuts_serde")]
+    pub marker_add_named: Vec<KeyboardShortcut>,
+    #[serde(with = "keyboard_shortcuts_serde")]
     pub toggle_menu: Vec<KeyboardShortcut>,
     #[serde(with = "keyboard_shortcuts_serde")]
     pub show_command_prompt: Vec<KeyboardShortcut>,
     #[serde(with = "keyboard_shortcuts_serde")]
+    pub show_quick_open: Vec<KeyboardShortcut>,
+    #[serde(with = "keyboard_shortcuts_serde")]
     pub rename_item: Vec<KeyboardShortcut>,
     #[serde(with = "keyboard_shortcuts_serde")]
     pub divider_add: Vec<KeyboardShortcut>,
+    #[serde(with = "keyboard_shortcuts_serde")]
+    pub item_maximize: Vec<KeyboardShortcut>,
+    #[serde(with = "keyboard_shortcuts_serde")]
+    pub toggle_analog: Vec<KeyboardShortcut>,
+    #[serde(with = "keyboard_shortcuts_serde")]
+    pub toggle_relative_time_ruler: Vec<KeyboardShortcut>,
+    #[serde(with = "keyboard_shortcuts_serde")]
+    pub center_focused_item: Vec<KeyboardShortcut>,
 
     #[serde(skip)]
     cached_dispatch_table: Vec<DispatchEntry>,
@@ -234,6 +252,10 @@ impl SurferShortcuts {
                 action: ShortcutAction::MarkerAdd,
                 priority: modifier_priority(&self.marker_add),
             },
+            DispatchEntry {
+                action: ShortcutAction::MarkerAddNamed,
+                priority: modifier_priority(&self.marker_add_named),
+            },
             DispatchEntry {
                 action: ShortcutAction::ToggleMenu,
                 priority: modifier_priority(&self.toggle_menu),
@@ -242,6 +264,10 @@ impl SurferShortcuts {
                 action: ShortcutAction::ShowCommandPrompt,
                 priority: modifier_priority(&self.show_command_prompt),
             },
+            DispatchEntry {
+                action: ShortcutAction::ShowQuickOpen,
+                priority: modifier_priority(&self.show_quick_open),
+            },
             DispatchEntry {
                 action: ShortcutAction::RenameItem,
                 priority: modifier_priority(&self.rename_item),
@@ -250,6 +276,22 @@ impl SurferShortcuts {
                 action: ShortcutAction::DividerAdd,
                 priority: modifier_priority(&self.divider_add),
             },
+            DispatchEntry {
+                action: ShortcutAction::ItemMaximize,
+                priority: modifier_priority(&self.item_maximize),
+            },
+            DispatchEntry {
+                action: ShortcutAction::ToggleAnalog,
+                priority: modifier_priority(&self.toggle_analog),
+            },
+            DispatchEntry {
+                action: ShortcutAction::ToggleRelativeTimeRuler,
+                priority: modifier_priority(&self.toggle_relative_time_ruler),
+            },
+            DispatchEntry {
+                action: ShortcutAction::CenterFocusedItem,
+                priority: modifier_priority(&self.center_focused_item),
+            },
         ]);
 
         // Sort by modifier priority (lower number = higher priority)
@@ -283,10 +325,16 @@ impl SurferShortcuts {
             ShortcutAction::ScrollDown => &self.scroll_down,
             ShortcutAction::DeleteSelected => &self.delete_selected,
             ShortcutAction::MarkerAdd => &self.marker_add,
+            ShortcutAction::MarkerAddNamed => &self.marker_add_named,
             ShortcutAction::ToggleMenu => &self.toggle_menu,
             ShortcutAction::ShowCommandPrompt => &self.show_command_prompt,
+            ShortcutAction::ShowQuickOpen => &self.show_quick_open,
             ShortcutAction::RenameItem => &self.rename_item,
             ShortcutAction::DividerAdd => &self.divider_add,
+            ShortcutAction::ItemMaximize => &self.item_maximize,
+            ShortcutAction::ToggleAnalog => &self.toggle_analog,
+            ShortcutAction::ToggleRelativeTimeRuler => &self.toggle_relative_time_ruler,
+            ShortcutAction::CenterFocusedItem => &self.center_focused_item,
         }
     }
 
@@ -423,17 +471,28 @@ impl SurferShortcuts {
                         msgs.push(Message::AddMarker {
                             time: cursor.clone(),
                             name: None,
+                            idx: None,
                             move_focus: state.user.config.layout.move_focus_on_inserted_marker(),
                         });
                     }
                 }
             }
+            ShortcutAction::MarkerAddNamed => {
+                if let Some(waves) = &state.user.waves
+                    && waves.can_add_marker()
+                {
+                    msgs.push(Message::ShowAddMarkerDialog);
+                }
+            }
             ShortcutAction::ToggleMenu => {
                 msgs.push(Message::SetMenuVisible(!state.show_menu()));
             }
             ShortcutAction::ShowCommandPrompt => {
                 msgs.push(Message::ShowCommandPrompt(String::new(), None));
             }
+            ShortcutAction::ShowQuickOpen => {
+                msgs.push(Message::ShowQuickOpen);
+            }
             ShortcutAction::RenameItem => {
                 if let Some(waves) = &state.user.waves
                     && waves.focused_item.is_some()
@@ -444,6 +503,16 @@ impl SurferShortcuts {
             ShortcutAction::DividerAdd => {
                 msgs.push(Message::AddDivider(None, None));
             }
+            ShortcutAction::ItemMaximize => {
+                msgs.push(Message::ToggleFocusedItemMaximized);
+            }
+            ShortcutAction::ToggleAnalog => {
+                msgs.push(Message::ToggleAnalogDigital(None));
+            }
+            ShortcutAction::ToggleRelativeTimeRuler => {
+                msgs.push(Message::ToggleRelativeTimeRuler);
+            }
+            ShortcutAction::CenterFocusedItem => msgs.push(Message::CenterFocusedItem),
         }
     }
 
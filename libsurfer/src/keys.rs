@@ -1,13 +1,18 @@
 //! Keyboard handling.
 use egui::{Context, Event, Key, Modifiers};
 use emath::Vec2;
+use num::BigInt;
 
 use crate::config::ArrowKeyBindings;
-use crate::message::MessageTarget;
+use crate::message::{CursorStep, MessageTarget};
 use crate::{MoveDir, SystemState, message::Message, wave_data::PER_SCROLL_EVENT};
 
 impl SystemState {
     pub fn handle_pressed_keys(&self, ctx: &Context, msgs: &mut Vec<Message>) {
+        // The quick open palette handles its own keys
+        if self.quick_open.visible {
+            return;
+        }
         if !(self.command_prompt.visible | self.user.variable_name_filter_focused) {
             self.user.config.shortcuts.process(ctx, msgs, self);
         }
@@ -107,10 +112,18 @@ impl SystemState {
                     (Key::F11, true, false, _) => msgs.push(Message::ToggleFullscreen),
                     (Key::ArrowRight, true, false, false) => {
                         msgs.push(match self.user.config.behavior.arrow_key_bindings {
-                            ArrowKeyBindings::Edge => Message::MoveCursorToTransition {
-                                next: true,
-                                variable: None,
-                                skip_zero: modifiers.shift,
+                            ArrowKeyBindings::Edge if modifiers.alt => Message::MoveCursorBy {
+                                step: CursorStep::Time(self.fine_cursor_step()),
+                            },
+                            ArrowKeyBindings::Edge if modifiers.shift => {
+                                Message::MoveCursorToTransition {
+                                    next: true,
+                                    variable: None,
+                                    skip_zero: true,
+                                }
+                            }
+                            ArrowKeyBindings::Edge => Message::MoveCursorBy {
+                                step: CursorStep::Transition { next: true },
                             },
                             ArrowKeyBindings::Scroll => Message::CanvasScroll {
                                 delta: Vec2 {
@@ -123,10 +136,18 @@ impl SystemState {
                     }
                     (Key::ArrowLeft, true, false, false) => {
                         msgs.push(match self.user.config.behavior.arrow_key_bindings {
-                            ArrowKeyBindings::Edge => Message::MoveCursorToTransition {
-                                next: false,
-                                variable: None,
-                                skip_zero: modifiers.shift,
+                            ArrowKeyBindings::Edge if modifiers.alt => Message::MoveCursorBy {
+                                step: CursorStep::Time(-self.fine_cursor_step()),
+                            },
+                            ArrowKeyBindings::Edge if modifiers.shift => {
+                                Message::MoveCursorToTransition {
+                                    next: false,
+                                    variable: None,
+                                    skip_zero: true,
+                                }
+                            }
+                            ArrowKeyBindings::Edge => Message::MoveCursorBy {
+                                step: CursorStep::Transition { next: false },
                             },
                             ArrowKeyBindings::Scroll => Message::CanvasScroll {
                                 delta: Vec2 {
@@ -177,6 +198,20 @@ impl SystemState {
         });
     }
 
+    /// Time covered by one pixel of the first viewport, but at least one time unit
+    fn fine_cursor_step(&self) -> BigInt {
+        let one = BigInt::from(1);
+        let (Some(waves), Some(canvas)) = (&self.user.waves, *self.last_canvas_rect.borrow())
+        else {
+            return one;
+        };
+        let num_timestamps = waves.safe_num_timestamps();
+        let viewport = &waves.viewports[0];
+        let step = viewport.as_time_bigint(1., canvas.width(), &num_timestamps)
+            - viewport.as_time_bigint(0., canvas.width(), &num_timestamps);
+        step.max(one)
+    }
+
     pub fn get_count(&self) -> usize {
         self.user
             .count
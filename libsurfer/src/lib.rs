@@ -1,13 +1,17 @@
 #![deny(unused_crate_dependencies)]
 
+pub mod activity_histogram;
 pub mod analog_renderer;
 pub mod analog_signal_cache;
+pub mod assertion;
 pub mod async_util;
 pub mod batch_commands;
 #[cfg(feature = "performance_plot")]
 pub mod benchmark;
+pub mod change_summary;
 mod channels;
 pub mod clock_highlighting;
+pub mod color_scheme;
 pub mod command_parser;
 pub mod command_prompt;
 pub mod config;
@@ -18,26 +22,36 @@ pub mod dialog;
 pub mod displayed_item;
 pub mod displayed_item_tree;
 pub mod drawing_canvas;
+pub mod enum_stats;
+pub mod events;
 pub mod file_dialog;
 pub mod file_watcher;
 pub mod fzcmd;
 pub mod graphics;
 pub mod help;
 pub mod hierarchy;
+pub mod hierarchy_export;
 pub mod keyboard_shortcuts;
 pub mod keys;
 pub mod logs;
 pub mod marker;
+pub mod marker_compare;
+pub mod marker_export;
+pub mod merged_container;
 pub mod menus;
 pub mod message;
 pub mod mousegestures;
 pub mod overview;
+pub mod quick_open;
 pub mod remote;
+pub mod repro_bundle;
 pub mod server_file_window;
+pub mod sparkline;
 pub mod state;
 pub mod state_file_io;
 pub mod state_util;
 pub mod statusbar;
+pub mod svg_export;
 pub mod system_state;
 #[cfg(test)]
 pub mod tests;
@@ -45,6 +59,7 @@ pub mod time;
 pub mod toolbar;
 pub mod tooltips;
 pub mod transaction_container;
+pub mod transaction_export;
 pub mod transactions;
 pub mod translation;
 pub mod util;
@@ -59,8 +74,10 @@ pub mod viewport;
 pub mod wasm_api;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm_panic;
+pub mod watch_list;
 pub mod wave_container;
 pub mod wave_data;
+pub mod wave_metadata;
 pub mod wave_source;
 pub mod wcp;
 pub mod wellen;
@@ -94,7 +111,7 @@ use itertools::Itertools;
 use message::MessageTarget;
 use num::BigInt;
 use serde::Deserialize;
-use surfer_translation_types::Translator;
+use surfer_translation_types::{PluginConfig, Translator, VariableInfo};
 use surfer_wcp::{WcpCSMessage, WcpEvent, WcpSCMessage};
 pub use system_state::SystemState;
 #[cfg(target_arch = "wasm32")]
@@ -106,20 +123,25 @@ use wave_container::ScopeRef;
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "wasm_plugins"))]
 use crate::async_util::perform_work;
-use crate::config::{SurferConfig, SurferTheme};
+use crate::config::{OnDuplicateAdd, SurferConfig, SurferTheme};
 use crate::dialog::{OpenSiblingStateFileDialog, ReloadWaveformDialog};
 use crate::displayed_item::{
     AnalogVarState, DisplayedFieldRef, DisplayedItem, DisplayedItemRef, FieldFormat,
+    FixedPointRadix,
 };
 use crate::displayed_item_tree::VisibleItemIndex;
 use crate::drawing_canvas::TxDrawingCommands;
-use crate::message::Message;
+use crate::events::WaveEvent;
+use crate::hierarchy::{RevealTarget, ScopeExpandType};
+use crate::message::{CursorStep, Message};
+use crate::quick_open::QuickOpen;
+use crate::sparkline::SparklineSamples;
 use crate::transaction_container::{TransactionRef, TransactionStreamRef};
 use crate::translation::{AnyTranslator, all_translators};
 use crate::variable_filter::{VariableIOFilterType, VariableNameFilterType};
 use crate::viewport::Viewport;
 use crate::wave_container::{ScopeRefExt, VariableRefExt, WaveContainer};
-use crate::wave_data::WaveData;
+use crate::wave_data::{ScopeType, WaveData};
 use crate::wave_source::{LoadOptions, WaveFormat, WaveSource};
 use crate::wellen::{HeaderResult, convert_format};
 
@@ -148,6 +170,8 @@ pub struct StartupParams {
     pub waves: Option<WaveSource>,
     pub wcp_initiate: Option<u16>,
     pub startup_commands: Vec<String>,
+    /// Time range to show in the first viewport once the waveform has loaded
+    pub view_range: Option<(BigInt, BigInt)>,
 }
 
 fn setup_custom_font(ctx: &egui::Context) {
@@ -222,6 +246,12 @@ struct CachedWaveDrawData {
     pub draw_commands: HashMap<DisplayedFieldRef, drawing_canvas::DrawingCommands>,
     pub clock_edges: Vec<f32>,
     pub ticks: Vec<(String, f32)>,
+    /// Time the tick labels are relative to, the draw data is regenerated when it moves
+    pub ticks_reference: Option<BigInt>,
+    /// Pixel ranges where the assertion of a variable is violated
+    pub assertion_violations: HashMap<DisplayedItemRef, Vec<(f32, f32)>>,
+    /// Sampled values of the variables with a sparkline, only kept for the first viewport
+    pub sparklines: HashMap<DisplayedItemRef, SparklineSamples>,
 }
 
 struct CachedTransactionDrawData {
@@ -290,10 +320,41 @@ impl SystemState {
             Message::SetActiveScope(scope) => {
                 let waves = self.user.waves.as_mut()?;
                 waves.set_active_scope(scope)?;
+                self.revealed_variable = None;
             }
             Message::ExpandScope(scope_ref) => {
                 *self.scope_ref_to_expand.borrow_mut() = Some(scope_ref);
             }
+            Message::SetScopeExpanded(scope, expanded) => {
+                if expanded {
+                    self.user.expanded_scopes.insert(scope.to_string());
+                } else {
+                    self.user.expanded_scopes.remove(&scope.to_string());
+                }
+            }
+            Message::SetHierarchyScrollOffset(offset) => {
+                self.user.hierarchy_scroll_offset = offset;
+            }
+            Message::RevealInHierarchy(vidx) => {
+                let waves = self.user.waves.as_mut()?;
+                let vidx = vidx.or(waves.focused_item)?;
+                let Some(DisplayedItem::Variable(variable)) = waves
+                    .items_tree
+                    .get_visible(vidx)
+                    .and_then(|node| waves.displayed_items.get(&node.item_ref))
+                else {
+                    return None;
+                };
+                let target = RevealTarget::from_variable(&variable.variable_ref)?;
+                waves.set_active_scope(Some(ScopeType::WaveScope(target.scope.clone())))?;
+                *self.scope_ref_to_expand.borrow_mut() =
+                    Some(ScopeExpandType::ExpandSpecific(target.scope));
+                self.revealed_variable = Some(target.variable);
+                *self.scroll_to_revealed_variable.borrow_mut() = true;
+                if !self.show_hierarchy() {
+                    self.user.show_hierarchy = Some(true);
+                }
+            }
             Message::AddVariables(vars) => {
                 if !vars.is_empty() {
                     let undo_msg = if vars.len() == 1 {
@@ -303,9 +364,15 @@ impl SystemState {
                     };
                     self.save_current_canvas(undo_msg);
                     if let Some(waves) = self.user.waves.as_mut() {
-                        if let (Some(cmd), _) =
-                            waves.add_variables(&self.translators, vars, None, true, false, None)
-                        {
+                        if let (Some(cmd), _) = waves.add_variables(
+                            &self.translators,
+                            vars,
+                            None,
+                            true,
+                            false,
+                            None,
+                            self.user.config.behavior.on_duplicate_add(),
+                        ) {
                             self.load_variables(cmd);
                         }
                         self.invalidate_draw_commands();
@@ -331,9 +398,15 @@ impl SystemState {
                 let waves = self.user.waves.as_mut()?;
 
                 // TODO add parameter to add_variables, insert to (self.drag_target_idx, self.drag_source_idx)
-                if let (Some(cmd), _) =
-                    waves.add_variables(&self.translators, vars, None, true, false, None)
-                {
+                if let (Some(cmd), _) = waves.add_variables(
+                    &self.translators,
+                    vars,
+                    None,
+                    true,
+                    false,
+                    None,
+                    self.user.config.behavior.on_duplicate_add(),
+                ) {
                     self.load_variables(cmd);
                 }
 
@@ -413,6 +486,10 @@ impl SystemState {
                 let waves = self.user.waves.as_mut()?;
                 waves.items_tree.xselect_all_visible(true);
             }
+            Message::SelectItemsMatching(matcher) => {
+                let waves = self.user.waves.as_mut()?;
+                waves.select_matching(&matcher);
+            }
             Message::SetItemSelected(vidx, selected) => {
                 let waves = self.user.waves.as_mut()?;
                 waves.items_tree.xselect(vidx, selected);
@@ -432,6 +509,10 @@ impl SystemState {
                 let waves = self.user.waves.as_mut()?;
                 waves.focused_item = None;
             }
+            Message::ToggleFocusedItemMaximized => {
+                let waves = self.user.waves.as_mut()?;
+                waves.focused_item_maximized = !waves.focused_item_maximized;
+            }
             Message::MoveFocus(direction, count, select) => {
                 let waves = self.user.waves.as_mut()?;
                 let visible_item_cnt = waves.items_tree.iter_visible().count();
@@ -473,10 +554,30 @@ impl SystemState {
                     self.invalidate_draw_commands();
                 }
             }
+            Message::ToggleTransactionSelected(tx_ref) => {
+                let waves = self.user.waves.as_mut()?;
+                if let Some(idx) = waves
+                    .selected_transactions
+                    .iter()
+                    .position(|selected| *selected == tx_ref)
+                {
+                    waves.selected_transactions.remove(idx);
+                } else {
+                    waves.selected_transactions.push(tx_ref);
+                }
+            }
+            Message::ClearTransactionSelection => {
+                let waves = self.user.waves.as_mut()?;
+                waves.selected_transactions.clear();
+            }
             Message::ScrollToItem(position) => {
                 let waves = self.user.waves.as_mut()?;
                 waves.scroll_to_item(position);
             }
+            Message::CenterFocusedItem => {
+                let waves = self.user.waves.as_mut()?;
+                waves.center_focused_item();
+            }
             Message::SetScrollOffset(offset) => {
                 let waves = self.user.waves.as_mut()?;
                 waves.scroll_offset = offset;
@@ -485,6 +586,12 @@ impl SystemState {
             Message::SetCursorWindowVisible(visibility) => {
                 self.user.show_cursor_window = visibility;
             }
+            Message::SetEventsWindowVisible(visibility) => {
+                self.user.show_events_window = visibility;
+            }
+            Message::SetWaveMetadataVisible(visibility) => {
+                self.user.show_wave_metadata = visibility;
+            }
             Message::VerticalScroll(direction, count) => {
                 let waves = self.user.waves.as_mut()?;
                 let current_item = waves.get_top_item();
@@ -623,7 +730,7 @@ impl SystemState {
                 let waves = self.user.waves.as_mut()?;
                 waves.viewports[viewport_idx]
                     .handle_canvas_scroll(f64::from(delta.y) + f64::from(delta.x));
-                self.invalidate_draw_commands();
+                self.viewport_changed(viewport_idx);
             }
             Message::CanvasZoom {
                 delta,
@@ -637,7 +744,7 @@ impl SystemState {
                         f64::from(delta),
                         &num_timestamps,
                     );
-                    self.invalidate_draw_commands();
+                    self.viewport_changed(viewport_idx);
                 } else {
                     warn!(
                         "Canvas zoom: No timestamps count, even though waveforms should be loaded"
@@ -647,17 +754,17 @@ impl SystemState {
             Message::ZoomToFit { viewport_idx } => {
                 let waves = self.user.waves.as_mut()?;
                 waves.viewports[viewport_idx].zoom_to_fit();
-                self.invalidate_draw_commands();
+                self.viewport_changed(viewport_idx);
             }
             Message::GoToEnd { viewport_idx } => {
                 let waves = self.user.waves.as_mut()?;
                 waves.viewports[viewport_idx].go_to_end();
-                self.invalidate_draw_commands();
+                self.viewport_changed(viewport_idx);
             }
             Message::GoToStart { viewport_idx } => {
                 let waves = self.user.waves.as_mut()?;
                 waves.viewports[viewport_idx].go_to_start();
-                self.invalidate_draw_commands();
+                self.viewport_changed(viewport_idx);
             }
             Message::GoToTime(time, viewport_idx) => {
                 let waves = self.user.waves.as_mut()?;
@@ -665,7 +772,7 @@ impl SystemState {
                 if let Some(num_timestamps) = waves.num_timestamps() {
                     let time = time?;
                     waves.viewports[viewport_idx].go_to_time(&time.clone(), &num_timestamps);
-                    self.invalidate_draw_commands();
+                    self.viewport_changed(viewport_idx);
                 } else {
                     warn!(
                         "Go to time: No timestamps count, even though waveforms should be loaded"
@@ -674,12 +781,17 @@ impl SystemState {
             }
             Message::SetTimeUnit(timeunit) => {
                 self.user.wanted_timeunit = timeunit;
+                self.user.wanted_timeunit_explicit = true;
                 self.invalidate_draw_commands();
             }
             Message::SetTimeStringFormatting(format) => {
                 self.user.time_string_format = format;
                 self.invalidate_draw_commands();
             }
+            Message::SetTimeLabelRadix(radix) => {
+                self.user.time_label_radix = Some(radix);
+                self.invalidate_draw_commands();
+            }
             Message::ZoomToRange {
                 start,
                 end,
@@ -688,8 +800,13 @@ impl SystemState {
                 let waves = self.user.waves.as_mut()?;
                 // If there are no timestamps, the file is not fully loaded
                 if let Some(num_timestamps) = waves.num_timestamps() {
+                    if start < BigInt::ZERO || end > num_timestamps {
+                        warn!(
+                            "Zoom to range: {start} to {end} is outside of the waveform, which ends at {num_timestamps}"
+                        );
+                    }
                     waves.viewports[viewport_idx].zoom_to_range(&start, &end, &num_timestamps);
-                    self.invalidate_draw_commands();
+                    self.viewport_changed(viewport_idx);
                 } else {
                     warn!(
                         "Zoom to range: No timestamps count, even though waveforms should be loaded"
@@ -856,6 +973,28 @@ impl SystemState {
                     }
                 }
             }
+            Message::AutoColorItems(rules) => {
+                self.save_current_canvas("Apply color scheme".to_string());
+                self.invalidate_draw_commands();
+                let waves = self.user.waves.as_mut()?;
+                waves.auto_color_items(&rules);
+            }
+            Message::SaveScheme(path) => self.save_scheme_file(path),
+            Message::ApplyScheme(path) => self.load_scheme_file(path),
+            Message::ApplyItemScheme(scheme) => {
+                self.save_current_canvas("Apply scheme".to_string());
+                self.invalidate_draw_commands();
+                let waves = self.user.waves.as_mut()?;
+                let misses = waves.apply_item_scheme(&scheme, &self.translators);
+                if !misses.is_empty() {
+                    warn!(
+                        "No displayed variable for {} of {} scheme entries: {}",
+                        misses.len(),
+                        scheme.items.len(),
+                        misses.join(", ")
+                    );
+                }
+            }
             Message::ItemBackgroundColorChange(vidx, color_name) => {
                 self.save_current_canvas(format!(
                     "Change item background color to {}",
@@ -919,6 +1058,20 @@ impl SystemState {
                     }
                 }
             }
+            Message::ResetAllItemHeights => {
+                self.save_current_canvas("Reset all item heights".to_string());
+                let waves = self.user.waves.as_mut()?;
+                for item in waves.displayed_items.values_mut() {
+                    item.reset_height_scaling_factor();
+                }
+            }
+            Message::SetAllItemHeights(scale) => {
+                self.save_current_canvas(format!("Set all item heights to {scale}"));
+                let waves = self.user.waves.as_mut()?;
+                for item in waves.displayed_items.values_mut() {
+                    item.set_height_scaling_factor(scale);
+                }
+            }
             Message::SetAnalogSettings(vidx, new_settings) => {
                 self.save_current_canvas("Set analog state".into());
                 self.invalidate_draw_commands();
@@ -960,6 +1113,221 @@ impl SystemState {
                     }
                 }
             }
+            Message::SetAnalogStyle(field_ref, style) => {
+                self.save_current_canvas("Set analog style".into());
+                self.invalidate_draw_commands();
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(variable)) =
+                    waves.displayed_items.get_mut(&field_ref.item)
+                    && let Some(analog) = &mut variable.analog
+                {
+                    analog.style = Some(style);
+                }
+            }
+            Message::SetAnalogUndefMode(field_ref, undef_mode) => {
+                self.save_current_canvas("Set analog undefined mode".into());
+                self.invalidate_draw_commands();
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(variable)) =
+                    waves.displayed_items.get_mut(&field_ref.item)
+                    && let Some(analog) = &mut variable.analog
+                {
+                    analog.undef_mode = undef_mode;
+                }
+            }
+            Message::AddToWatch(variable) => {
+                if self.watch_list.contains(&variable) {
+                    return None;
+                }
+                let waves = self.user.waves.as_mut()?;
+                match waves
+                    .inner
+                    .as_waves_mut()?
+                    .load_variables(std::iter::once(&variable))
+                {
+                    Ok(Some(cmd)) => self.load_variables(cmd),
+                    Ok(None) => {}
+                    Err(e) => {
+                        error!("{e:#?}");
+                        return None;
+                    }
+                }
+                self.watch_list.push(variable);
+            }
+            Message::RemoveFromWatch(variable) => {
+                self.watch_list.retain(|watched| *watched != variable);
+            }
+            Message::SetTranslatorConfig(name, key, value) => {
+                let config = self
+                    .user
+                    .translator_configs
+                    .entry(name.clone())
+                    .or_default();
+                config.insert(key, value);
+                if self
+                    .translators
+                    .all_translator_names()
+                    .contains(&name.as_str())
+                {
+                    self.translators
+                        .get_translator(&name)
+                        .set_config(&PluginConfig(config.clone()));
+                }
+                self.invalidate_draw_commands();
+            }
+            Message::ComputeActivityHistogram { scope, buckets } => {
+                let waves = self.user.waves.as_ref()?;
+                self.activity_histogram = waves.activity_histogram(scope, buckets);
+            }
+            Message::ShowCursorMarkerCompare(marker_idx) => {
+                self.cursor_marker_compare = Some(marker_idx);
+            }
+            Message::CloseCursorMarkerCompare => self.cursor_marker_compare = None,
+            Message::SummarizeChangesBetween(marker_a, marker_b) => {
+                let waves = self.user.waves.as_ref()?;
+                self.change_summary = waves.change_summary(marker_a, marker_b);
+            }
+            Message::SortChangeSummary(sort) => {
+                if let Some(summary) = &mut self.change_summary {
+                    summary.sort_by(sort);
+                }
+            }
+            Message::CloseChangeSummary => self.change_summary = None,
+            Message::ComputeEnumStats(field_ref, range) => {
+                self.enum_stats = self.enum_stats(&field_ref, range);
+            }
+            Message::CloseEnumStats => self.enum_stats = None,
+            Message::SaveActivityHistogram(path) => self.save_activity_histogram(path),
+            Message::CloseActivityHistogram => self.activity_histogram = None,
+            Message::ListTranslatorsForFocused => {
+                let meta = self.user.waves.as_ref().and_then(|waves| {
+                    let item_ref = waves.items_tree.get_visible(waves.focused_item?)?.item_ref;
+                    let DisplayedItem::Variable(variable) = waves.displayed_items.get(&item_ref)?
+                    else {
+                        return None;
+                    };
+                    waves
+                        .inner
+                        .as_waves()?
+                        .variable_meta(&variable.variable_ref)
+                        .ok()
+                });
+                if let Some(meta) = meta {
+                    info!("Translators for {}:", meta.var.full_path_string());
+                    for (name, preference) in self.translators.translator_applicability(&meta) {
+                        info!("  {name}: {preference:?}");
+                    }
+                } else {
+                    info!(
+                        "Translators: {}",
+                        self.translators
+                            .all_translator_names()
+                            .iter()
+                            .sorted()
+                            .join(", ")
+                    );
+                }
+                self.user.show_logs = true;
+            }
+            Message::ToggleAnalogDigital(vidx) => {
+                let waves = self.user.waves.as_ref()?;
+                if !waves.inner.as_waves()?.supports_analog() {
+                    return None;
+                }
+                let vidx = vidx.or(waves.focused_item)?;
+                let item_ref = waves.items_tree.get_visible(vidx)?.item_ref;
+                let Some(DisplayedItem::Variable(variable)) = waves.displayed_items.get(&item_ref)
+                else {
+                    return None;
+                };
+                if !variable.is_numeric() {
+                    return None;
+                }
+                self.save_current_canvas("Toggle analog".into());
+                self.invalidate_draw_commands();
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(variable)) =
+                    waves.displayed_items.get_mut(&item_ref)
+                {
+                    variable.toggle_analog();
+                }
+            }
+            Message::ToggleItemHidden(vidx) => {
+                let waves = self.user.waves.as_ref()?;
+                let vidx = vidx.or(waves.focused_item)?;
+                let hidden = waves.items_tree.get_visible(vidx)?.hidden;
+                self.save_current_canvas(if hidden { "Show item" } else { "Hide item" }.into());
+                self.invalidate_draw_commands();
+                let waves = self.user.waves.as_mut()?;
+                waves.items_tree.xhide(vidx, !hidden);
+            }
+            Message::SwapItems(a, b) => {
+                let waves = self.user.waves.as_ref()?;
+                let mut items_tree = waves.items_tree.clone();
+                if let Err(e) = items_tree.swap_items(a, b) {
+                    warn!("Cannot swap items {} and {}: {e:?}", a.0, b.0);
+                    return None;
+                }
+                let focused_item_ref = waves
+                    .focused_item
+                    .and_then(|vidx| waves.items_tree.get_visible(vidx))
+                    .map(|node| node.item_ref);
+                self.save_current_canvas("Swap items".into());
+                self.invalidate_draw_commands();
+                let waves = self.user.waves.as_mut()?;
+                waves.items_tree = items_tree;
+                waves.focused_item = focused_item_ref.and_then(|item_ref| {
+                    waves
+                        .items_tree
+                        .iter_visible()
+                        .position(|node| node.item_ref == item_ref)
+                        .map(VisibleItemIndex)
+                });
+            }
+            Message::GoToFirstNonZero {
+                variable,
+                unknown_is_nonzero,
+            } => {
+                let waves = self.user.waves.as_mut()?;
+                let Some(DisplayedItem::Variable(displayed_variable)) =
+                    waves.displayed_items.get(&variable.item)
+                else {
+                    return None;
+                };
+                let Some(time) =
+                    waves.first_nonzero_time(&displayed_variable.variable_ref, unknown_is_nonzero)
+                else {
+                    info!(
+                        "{} is never non-zero",
+                        displayed_variable.variable_ref.full_path_string()
+                    );
+                    return None;
+                };
+                waves.cursor = Some(time);
+                if waves.go_to_cursor_if_not_in_view() {
+                    self.invalidate_draw_commands();
+                }
+            }
+            Message::CursorToSignalEdge { first, variable } => {
+                let waves = self.user.waves.as_mut()?;
+                let Some(DisplayedItem::Variable(displayed_variable)) =
+                    waves.displayed_items.get(&variable.item)
+                else {
+                    return None;
+                };
+                let Some(time) = waves.signal_edge_time(&displayed_variable.variable_ref, first)
+                else {
+                    info!(
+                        "{} has no transitions",
+                        displayed_variable.variable_ref.full_path_string()
+                    );
+                    return None;
+                };
+                waves.cursor = Some(time);
+                if waves.go_to_cursor_if_not_in_view() {
+                    self.invalidate_draw_commands();
+                }
+            }
             Message::MoveCursorToTransition {
                 next,
                 variable,
@@ -982,7 +1350,8 @@ impl SystemState {
                         };
                     }
                     waves.set_cursor_at_transition(next, variable, skip_zero);
-                    let moved = waves.go_to_cursor_if_not_in_view();
+                    let moved =
+                        waves.follow_cursor(self.user.config.behavior.center_on_transition());
                     if moved {
                         self.invalidate_draw_commands();
                     }
@@ -992,6 +1361,78 @@ impl SystemState {
                     );
                 }
             }
+            Message::MoveCursorToValueChange { variable, next } => {
+                let waves = self.user.waves.as_ref()?;
+                let num_timestamps = waves.num_timestamps()?;
+                let cursor = waves.cursor.clone().unwrap_or_else(|| {
+                    let vp = waves.viewports[0];
+                    if next {
+                        vp.left_edge_time(&num_timestamps)
+                    } else {
+                        vp.right_edge_time(&num_timestamps)
+                    }
+                });
+                let time = BigInt::from(self.value_change_time(
+                    waves,
+                    &variable,
+                    &cursor.to_biguint()?,
+                    next,
+                )?);
+                let waves = self.user.waves.as_mut()?;
+                waves.cursor = Some(time);
+                if waves.follow_cursor(self.user.config.behavior.center_on_transition()) {
+                    self.invalidate_draw_commands();
+                }
+            }
+            Message::PrintVariableInfo(variable) => {
+                let waves = self.user.waves.as_ref()?;
+                let Some(DisplayedItem::Variable(displayed_variable)) =
+                    waves.displayed_items.get(&variable.item)
+                else {
+                    warn!("Can only print info for variables");
+                    return None;
+                };
+                let meta = match waves
+                    .inner
+                    .as_waves()?
+                    .variable_meta(&displayed_variable.variable_ref)
+                {
+                    Ok(meta) => meta,
+                    Err(e) => {
+                        warn!("{e:#?}");
+                        return None;
+                    }
+                };
+                let translator = waves
+                    .variable_translator_with_meta(&variable, &self.translators, &meta)
+                    .name();
+                info!("{}", tooltips::variable_info_text(&meta, &translator));
+            }
+            Message::MoveCursorBy { step } => match step {
+                CursorStep::Transition { next } => {
+                    self.update(Message::MoveCursorToTransition {
+                        next,
+                        variable: None,
+                        skip_zero: false,
+                    });
+                }
+                CursorStep::Time(delta) => {
+                    let waves = self.user.waves.as_mut()?;
+                    let num_timestamps = waves.num_timestamps()?;
+                    let start = waves.cursor.clone().unwrap_or_else(|| {
+                        let vp = waves.viewports[0];
+                        if delta < BigInt::ZERO {
+                            vp.right_edge_time(&num_timestamps)
+                        } else {
+                            vp.left_edge_time(&num_timestamps)
+                        }
+                    });
+                    waves.cursor = Some((start + delta).clamp(BigInt::ZERO, num_timestamps));
+                    if waves.follow_cursor(false) {
+                        self.invalidate_draw_commands();
+                    }
+                }
+            },
             Message::MoveTransaction { next } => {
                 let undo_msg = if next {
                     "Move to next transaction"
@@ -1003,6 +1444,17 @@ impl SystemState {
                 waves.move_to_transaction(next)?;
                 self.invalidate_draw_commands();
             }
+            Message::GoToTransactionById(id) => {
+                let waves = self.user.waves.as_ref()?;
+                let Some(messages) = usize::try_from(id)
+                    .ok()
+                    .and_then(|id| waves.go_to_transaction_messages(id))
+                else {
+                    error!("Go to transaction: No transaction with id {id}");
+                    return None;
+                };
+                self.update(Message::Batch(messages));
+            }
             Message::ResetVariableFormat(displayed_field_ref) => {
                 let waves = self.user.waves.as_mut()?;
                 if let Some(DisplayedItem::Variable(displayed_variable)) =
@@ -1018,10 +1470,106 @@ impl SystemState {
                     self.invalidate_draw_commands();
                 }
             }
+            Message::SetVariableAssertion(displayed_field_ref, assertion) => {
+                self.set_variable_assertion(&displayed_field_ref, Some(assertion));
+            }
+            Message::RemoveVariableAssertion(displayed_field_ref) => {
+                self.set_variable_assertion(&displayed_field_ref, None);
+            }
+            Message::SetVariableUnit(displayed_field_ref, unit) => {
+                if !displayed_field_ref.field.is_empty() {
+                    warn!("Units on fields of variables are not supported");
+                    return None;
+                }
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(displayed_variable)) =
+                    waves.displayed_items.get_mut(&displayed_field_ref.item)
+                {
+                    displayed_variable.unit = unit.filter(|unit| !unit.trim().is_empty());
+                    self.invalidate_draw_commands();
+                }
+            }
+            Message::SetValueTextAlign(displayed_field_ref, align) => {
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(displayed_variable)) =
+                    waves.displayed_items.get_mut(&displayed_field_ref.item)
+                {
+                    displayed_variable.value_text_align = Some(align);
+                }
+            }
+            Message::ToggleIntervalLabels(displayed_field_ref) => {
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(displayed_variable)) =
+                    waves.displayed_items.get_mut(&displayed_field_ref.item)
+                {
+                    displayed_variable.show_interval_labels =
+                        !displayed_variable.show_interval_labels;
+                    self.invalidate_draw_commands();
+                }
+            }
+            Message::SetLeadingZeroMode(displayed_field_ref, mode) => {
+                self.save_current_canvas(format!("Set leading zeros to {mode}"));
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(displayed_variable)) =
+                    waves.displayed_items.get_mut(&displayed_field_ref.item)
+                {
+                    displayed_variable.leading_zeros = mode;
+                    self.invalidate_draw_commands();
+                }
+            }
+            Message::ToggleRelativeTimeRuler => {
+                self.user.relative_time_ruler = !self.user.relative_time_ruler;
+                self.invalidate_draw_commands();
+            }
+            Message::SetNumericFormatOptions(displayed_field_ref, options) => {
+                if let Some(displayed_field_ref) = displayed_field_ref {
+                    self.save_current_canvas("Set numeric format".to_string());
+                    let waves = self.user.waves.as_mut()?;
+                    if let Some(DisplayedItem::Variable(displayed_variable)) =
+                        waves.displayed_items.get_mut(&displayed_field_ref.item)
+                    {
+                        displayed_variable.numeric_format = Some(options);
+                    }
+                } else {
+                    self.user.numeric_format = Some(options);
+                }
+                self.invalidate_draw_commands();
+            }
+            Message::SetFixedPointRadix {
+                item,
+                int_bits,
+                frac_bits,
+            } => {
+                self.save_current_canvas("Set fixed point radix".to_string());
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(displayed_variable)) =
+                    waves.displayed_items.get_mut(&item)
+                {
+                    displayed_variable.fixed_point_radix = Some(FixedPointRadix {
+                        int_bits,
+                        frac_bits,
+                    });
+                    self.invalidate_draw_commands();
+                }
+            }
+            Message::ResetFixedPointRadix(item) => {
+                self.save_current_canvas("Reset fixed point radix".to_string());
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(displayed_variable)) =
+                    waves.displayed_items.get_mut(&item)
+                {
+                    displayed_variable.fixed_point_radix = None;
+                    self.invalidate_draw_commands();
+                }
+            }
             Message::CursorSet(time) => {
                 let waves = self.user.waves.as_mut()?;
                 waves.cursor = Some(time);
             }
+            Message::SetTriggerTime(time) => {
+                let waves = self.user.waves.as_mut()?;
+                waves.trigger_time = time;
+            }
             Message::ExpandParameterSection => {
                 self.expand_parameter_section = true;
             }
@@ -1033,6 +1581,12 @@ impl SystemState {
                 #[cfg(target_arch = "wasm32")]
                 error!("Cannot load file from path in WASM");
             }
+            Message::LoadAdditionalFile(filename, load_options) => {
+                #[cfg(not(target_arch = "wasm32"))]
+                self.load_additional_file(filename, load_options);
+                #[cfg(target_arch = "wasm32")]
+                error!("Cannot load file from path in WASM");
+            }
             Message::LoadWaveformFileFromUrl(url, load_options) => {
                 self.user.selected_server_file_index = None;
                 *self.surver_selected_file.borrow_mut() = None;
@@ -1049,7 +1603,8 @@ impl SystemState {
                 try_log_error!(
                     self.translators.load_python_translator(filename),
                     "Error loading Python translator",
-                )
+                );
+                self.configure_translators();
             }
             #[cfg(all(not(target_arch = "wasm32"), feature = "wasm_plugins"))]
             Message::LoadWasmTranslator(path) => {
@@ -1119,6 +1674,11 @@ impl SystemState {
                     .map_err(|e| error!("{e:#?}"))
                     .ok();
             }
+            Message::FilesDropped(dropped_files) => {
+                self.load_from_dropped_files(dropped_files)
+                    .map_err(|e| error!("{e:#?}"))
+                    .ok();
+            }
             Message::StopProgressTracker => {
                 self.progress_tracker = None;
             }
@@ -1224,7 +1784,7 @@ impl SystemState {
                 }
 
                 // update viewports, now that we have the time table
-                waves.update_viewports();
+                self.update_viewports_after_load();
                 // make sure we redraw
                 self.invalidate_draw_commands();
                 // start loading parameters
@@ -1236,6 +1796,11 @@ impl SystemState {
                     self.load_variables(cmd);
                 }
             }
+            Message::RefreshDisplayedSignals => {
+                if let Some(cmd) = self.refresh_displayed_signals_cmd() {
+                    self.load_variables(cmd);
+                }
+            }
             Message::SignalsLoaded(start, res) => {
                 info!("Loaded {} variables in {:?}", res.len(), start.elapsed());
                 self.progress_tracker = None;
@@ -1252,29 +1817,26 @@ impl SystemState {
                 // make sure we redraw since now more variable data is available
                 self.invalidate_draw_commands();
             }
+            Message::AdditionalWavesLoaded(filename, load_options, new_waves) => {
+                self.on_additional_waves_loaded(filename, *new_waves, load_options);
+            }
             Message::WavesLoaded(filename, format, new_waves, load_options) => {
                 self.on_waves_loaded(filename, format, new_waves, load_options);
                 // here, the body and thus the number of timestamps is already loaded!
-                self.user
-                    .waves
-                    .as_mut()
-                    .expect("Waves should be loaded at this point!")
-                    .update_viewports();
+                self.update_viewports_after_load();
                 self.progress_tracker = None;
             }
             Message::TransactionStreamsLoaded(filename, format, new_ftr, loaded_options) => {
                 self.on_transaction_streams_loaded(filename, format, new_ftr, loaded_options);
-                self.user
-                    .waves
-                    .as_mut()
-                    .expect("Waves should be loaded at this point!")
-                    .update_viewports();
+                self.update_viewports_after_load();
             }
             Message::BlacklistTranslator(idx, translator) => {
                 self.user.blacklisted_translators.insert((idx, translator));
             }
             Message::Error(e) => {
                 error!("{e:?}");
+                // A failed reload should not leave its view to be applied to a later load
+                self.preserved_view = None;
                 self.user.show_logs = true;
             }
             Message::TranslatorLoaded(t) => {
@@ -1285,6 +1847,9 @@ impl SystemState {
                         .as_ref()
                         .map(|waves| waves.source.into_translation_type()),
                 );
+                if let Some(config) = self.user.translator_configs.get(&t.name()) {
+                    t.set_config(&PluginConfig(config.clone()));
+                }
 
                 self.translators.add_or_replace(AnyTranslator::Full(t));
             }
@@ -1326,6 +1891,14 @@ impl SystemState {
                 self.command_prompt.new_text = Some((text, selected.unwrap_or(String::new())));
                 self.command_prompt.visible = true;
             }
+            Message::ShowQuickOpen => {
+                self.quick_open = QuickOpen {
+                    visible: true,
+                    entries: self.quick_open_entries(),
+                    ..Default::default()
+                };
+            }
+            Message::HideQuickOpen => self.quick_open.visible = false,
             Message::FileDownloaded(url, bytes, load_options) => {
                 self.load_from_bytes(WaveSource::Url(url), bytes.to_vec(), load_options);
             }
@@ -1362,6 +1935,11 @@ impl SystemState {
                 } else {
                     LoadOptions::KeepAvailable
                 };
+                let merged_files = waves
+                    .inner
+                    .as_waves()
+                    .map(WaveContainer::merged_files)
+                    .unwrap_or_default();
                 match &waves.source {
                     WaveSource::File(filename) => {
                         self.load_from_file(filename.clone(), options).ok();
@@ -1377,12 +1955,31 @@ impl SystemState {
                         self.load_wave_from_url(url.clone(), options, false);
                     }
                 }
+                // The merged files are kept by the reloaded waveform and read again to update them
+                #[cfg(not(target_arch = "wasm32"))]
+                for filename in merged_files {
+                    self.load_additional_file(filename, options);
+                }
+                #[cfg(target_arch = "wasm32")]
+                let _ = merged_files;
 
                 for translator in self.translators.all_translators() {
                     translator.reload(self.channels.msg_sender.clone());
                 }
                 self.variable_name_info_cache.borrow_mut().clear();
             }
+            Message::ReloadPreservingView => {
+                let waves = self.user.waves.as_ref()?;
+                // Only keep the view if there is a reload to restore it after
+                let reloadable = match &waves.source {
+                    WaveSource::File(_) | WaveSource::Url(_) => true,
+                    WaveSource::DragAndDrop(filename) => filename.is_some(),
+                    WaveSource::Data | WaveSource::Cxxrtl(..) => false,
+                };
+                self.preserved_view =
+                    reloadable.then(|| (waves.source.clone(), waves.capture_view()));
+                self.update(Message::ReloadWaveform(self.user.config.behavior.keep_during_reload));
+            }
             Message::SuggestReloadWaveform => match self.autoreload_files() {
                 AutoLoad::Always => self.update(Message::ReloadWaveform(true))?,
                 AutoLoad::Never => (),
@@ -1412,7 +2009,9 @@ impl SystemState {
                     return None;
                 }
                 let waves = self.user.waves.as_ref()?;
-                let state_file_path = waves.source.sibling_state_file()?;
+                let state_file_path = waves
+                    .source
+                    .sibling_state_file(&self.user.config.sibling_state_file_pattern)?;
                 self.load_state_file(Some(state_file_path.clone().into_std_path_buf()));
             }
             Message::SuggestOpenSiblingStateFile => match self.autoload_sibling_state_files() {
@@ -1447,7 +2046,25 @@ impl SystemState {
             Message::SetClockHighlightType(new_type) => {
                 self.user.clock_highlight_type = Some(new_type);
             }
+            Message::SetClockHighlightEnabled(enabled) => {
+                self.user.clock_highlight_enabled = Some(enabled);
+                self.invalidate_draw_commands();
+            }
             Message::SetFillHighValues(fill) => self.user.fill_high_values = Some(fill),
+            Message::SetShowNoDataHatch(show) => self.user.show_no_data_hatch = Some(show),
+            Message::SetItemColumnSide(side) => self.user.item_column_side = Some(side),
+            Message::SetShowGlitchDensity(show) => {
+                self.user.show_glitch_density = Some(show);
+                self.invalidate_draw_commands();
+            }
+            Message::SetShowPartialUnknowns(show) => {
+                self.user.show_partial_unknowns = Some(show);
+                self.invalidate_draw_commands();
+            }
+            Message::SetSparklinesVisible(show) => {
+                self.user.show_sparklines = Some(show);
+                self.invalidate_draw_commands();
+            }
             Message::SetDinotraceStyle(dino_style) => {
                 self.user.use_dinotrace_style = Some(dino_style);
                 self.invalidate_draw_commands();
@@ -1455,15 +2072,83 @@ impl SystemState {
             Message::AddMarker {
                 time,
                 name,
+                idx,
                 move_focus,
             } => {
+                let time = self.snap_to_placement_grid(&time);
                 if let Some(name) = &name {
                     self.save_current_canvas(format!("Add marker {name} at {time}"));
                 } else {
                     self.save_current_canvas(format!("Add marker at {time}"));
                 }
                 let waves = self.user.waves.as_mut()?;
-                waves.add_marker(&time, name, move_focus);
+                if let Some(idx) = idx {
+                    waves.add_marker_with_idx(&time, name, idx, move_focus);
+                } else {
+                    waves.add_marker(&time, name, move_focus);
+                }
+            }
+            Message::ShowAddMarkerDialog => {
+                let waves = self.user.waves.as_ref()?;
+                self.user.show_add_marker_dialog = waves.add_marker_dialog();
+            }
+            Message::CloseAddMarkerDialog { add_marker } => {
+                let dialog = self.user.show_add_marker_dialog.take()?;
+                if !add_marker {
+                    return None;
+                }
+                let time = dialog.time.trim().parse().ok()?;
+                let name = Some(dialog.name.trim())
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string);
+                self.update(Message::AddMarker {
+                    time,
+                    name,
+                    idx: Some(dialog.idx),
+                    move_focus: self.user.config.layout.move_focus_on_inserted_marker(),
+                });
+                if dialog.color.is_some() {
+                    let waves = self.user.waves.as_mut()?;
+                    waves.set_marker_color(dialog.idx, &dialog.color);
+                }
+            }
+            Message::UpdateAddMarkerDialog(dialog) => {
+                self.user.show_add_marker_dialog = Some(dialog);
+            }
+            Message::AddMarkerAtCursorAutoNamed(prefix) => {
+                let waves = self.user.waves.as_ref()?;
+                if waves.cursor.is_none() || !waves.can_add_marker() {
+                    return None;
+                }
+                self.save_current_canvas(format!("Add marker {prefix} at cursor"));
+                let waves = self.user.waves.as_mut()?;
+                waves.add_marker_at_cursor_auto_named(&prefix);
+            }
+            Message::AddEvent {
+                name,
+                variable,
+                time,
+            } => {
+                self.save_current_canvas(format!("Add event {name}"));
+                let time = self.snap_to_placement_grid(&time);
+                let waves = self.user.waves.as_mut()?;
+                waves.events.push(WaveEvent {
+                    name,
+                    variable,
+                    time,
+                });
+            }
+            Message::GoToEvent(idx) => {
+                let waves = self.user.waves.as_ref()?;
+                let messages = waves.go_to_event_messages(idx);
+                self.update(Message::Batch(messages));
+            }
+            Message::RemoveEvent(idx) => {
+                let waves = self.user.waves.as_ref()?;
+                let event = waves.events.get(idx)?;
+                self.save_current_canvas(format!("Remove event {}", event.name));
+                let waves = self.user.waves.as_mut()?;
+                waves.events.remove(idx);
             }
             Message::SetMarker { id, time } => {
                 self.save_current_canvas(format!("Set marker {id} to {time}"));
@@ -1474,11 +2159,29 @@ impl SystemState {
                 let waves = self.user.waves.as_mut()?;
                 waves.remove_marker(id);
             }
+            Message::SetMarkerColor(id, color_name) => {
+                self.save_current_canvas(format!(
+                    "Change marker {id} color to {}",
+                    color_name.clone().unwrap_or("default".into())
+                ));
+                let waves = self.user.waves.as_mut()?;
+                waves.set_marker_color(id, &color_name);
+            }
             Message::MoveMarkerToCursor(idx) => {
                 self.save_current_canvas("Move marker".into());
                 let waves = self.user.waves.as_mut()?;
                 waves.move_marker_to_cursor(idx);
             }
+            Message::ShiftAllMarkers(delta) => {
+                self.save_current_canvas(format!("Shift markers by {delta}"));
+                let waves = self.user.waves.as_mut()?;
+                waves.shift_all_markers(&delta);
+            }
+            Message::AlignMarkerToCursor(idx) => {
+                let waves = self.user.waves.as_ref()?;
+                let delta = waves.marker_to_cursor_delta(idx)?;
+                self.update(Message::ShiftAllMarkers(delta));
+            }
             Message::GoToCursorIfNotInView => {
                 let waves = self.user.waves.as_mut()?;
                 if waves.go_to_cursor_if_not_in_view() {
@@ -1556,9 +2259,19 @@ impl SystemState {
                     self.translators.reload_python_translator(),
                     "Error reloading Python translator"
                 );
+                self.configure_translators();
                 self.invalidate_draw_commands();
             }
             Message::SaveStateFile(path) => self.save_state_file(path),
+            Message::ExportEffectiveConfig(path) => self.export_effective_config(path),
+            Message::ExportHierarchy { path, format } => self.export_hierarchy(path, format),
+            Message::ExportSvg(path) => self.export_svg(path),
+            Message::ExportTransactionsJson { path, selection } => {
+                self.export_transactions_json(path, &selection);
+            }
+            Message::ExportMarkers(path) => self.export_markers(path),
+            Message::ImportMarkers(path) => self.import_markers(&path),
+            Message::ExportReproBundle(path) => self.export_repro_bundle(&path),
             Message::LoadStateFromData(bytes) => self.load_state_from_bytes(bytes),
             Message::LoadStateFile(path) => self.load_state_file(path),
             Message::LoadState(state, path) => self.load_state(state, path),
@@ -1576,6 +2289,14 @@ impl SystemState {
             Message::SetAboutVisible(s) => self.user.show_about = s,
             Message::SetKeyHelpVisible(s) => self.user.show_keys = s,
             Message::SetGestureHelpVisible(s) => self.user.show_gestures = s,
+            Message::SetUrlToken { url, token } => match token {
+                Some(token) => {
+                    self.url_tokens.insert(url, token);
+                }
+                None => {
+                    self.url_tokens.remove(&url);
+                }
+            },
             Message::SetUrlEntryVisible(s, f) => {
                 self.user.show_url_entry = s;
                 self.url_callback = f;
@@ -1632,12 +2353,14 @@ impl SystemState {
                 );
             }
             Message::SetHierarchyStyle(style) => self.user.hierarchy_style = Some(style),
+            Message::SetGroupScopesByType(group) => self.user.group_scopes_by_type = Some(group),
             Message::SetArrowKeyBindings(bindings) => {
                 self.user.arrow_key_bindings = Some(bindings);
             }
             Message::SetPrimaryMouseDragBehavior(behavior) => {
                 self.user.primary_button_drag_behavior = Some(behavior);
             }
+            Message::SetPlacementGrid(grid) => self.user.placement_grid = Some(grid),
             Message::InvalidateDrawCommands => self.invalidate_draw_commands(),
             Message::UnpauseSimulation => {
                 let waves = self.user.waves.as_ref()?;
@@ -1659,9 +2382,15 @@ impl SystemState {
                 self.user.drag_source_idx = None;
                 let target = self.user.drag_target_idx.take();
 
-                if let (Some(cmd), _) =
-                    waves.add_variables(&self.translators, variables, target, true, false, None)
-                {
+                if let (Some(cmd), _) = waves.add_variables(
+                    &self.translators,
+                    variables,
+                    target,
+                    true,
+                    false,
+                    None,
+                    self.user.config.behavior.on_duplicate_add(),
+                ) {
                     self.load_variables(cmd);
                 }
                 self.invalidate_draw_commands();
@@ -1685,11 +2414,11 @@ impl SystemState {
                 self.invalidate_draw_commands();
                 let waves = self.user.waves.as_mut()?;
 
-                let focused_index = waves
+                // The focus is kept on the same item, wherever it ends up. It is only moved if
+                // it is selected or the dragged item itself.
+                let focused_item_ref = waves
                     .focused_item
-                    .and_then(|vidx| waves.items_tree.to_displayed(vidx));
-                let focused_item_ref = focused_index
-                    .and_then(|idx| waves.items_tree.get(idx))
+                    .and_then(|vidx| waves.items_tree.get_visible(vidx))
                     .map(|node| node.item_ref);
 
                 let mut to_move = waves
@@ -1697,9 +2426,6 @@ impl SystemState {
                     .iter_visible_extra()
                     .filter_map(|info| info.node.selected.then_some(info.idx))
                     .collect::<Vec<_>>();
-                if let Some(idx) = focused_index {
-                    to_move.push(idx);
-                }
                 if let Some(vidx) = waves.items_tree.to_displayed(source_vidx) {
                     to_move.push(vidx);
                 }
@@ -1738,6 +2464,47 @@ impl SystemState {
                     },
                 );
             }
+            Message::CaptureItemValue(vidx) => {
+                let waves = self.user.waves.as_ref()?;
+                let item_ref = waves
+                    .items_tree
+                    .get_visible(vidx.or(waves.focused_item)?)?
+                    .item_ref;
+                let cursor = waves.cursor.as_ref()?.to_biguint();
+                let value = self.get_variable_value(waves, &item_ref.into(), cursor.as_ref())?;
+                let waves = self.user.waves.as_mut()?;
+                if let Some(DisplayedItem::Variable(variable)) =
+                    waves.displayed_items.get_mut(&item_ref)
+                {
+                    variable.captured_value = Some(value);
+                }
+            }
+            Message::ClearCapturedValue(vidx) => {
+                let waves = self.user.waves.as_mut()?;
+                let item_ref = waves
+                    .items_tree
+                    .get_visible(vidx.or(waves.focused_item)?)?
+                    .item_ref;
+                if let Some(DisplayedItem::Variable(variable)) =
+                    waves.displayed_items.get_mut(&item_ref)
+                {
+                    variable.captured_value = None;
+                }
+            }
+            Message::CopySelectedValuesTable { header } => {
+                if let Some(table) = self.selected_values_table(header)
+                    && let Some(ctx) = &self.context
+                {
+                    ctx.copy_text(table);
+                }
+            }
+            Message::CopyAllValuesAtCursor => {
+                if let Some(table) = self.all_values_table()
+                    && let Some(ctx) = &self.context
+                {
+                    ctx.copy_text(table);
+                }
+            }
             Message::VariableNameToClipboard(vidx) => {
                 self.handle_variable_clipboard_operation(
                     vidx,
@@ -1974,6 +2741,26 @@ impl SystemState {
                 }
                 waves.items_tree.xfold_all(unfold);
             }
+            Message::CollapseAllExceptFocused => {
+                self.save_current_canvas("Collapse all except focused".to_owned());
+                self.invalidate_draw_commands();
+
+                let waves = self.user.waves.as_mut()?;
+                let focused = waves
+                    .focused_item
+                    .and_then(|vidx| waves.items_tree.to_displayed(vidx));
+                if let Some(item) = focused {
+                    waves.items_tree.xfold_all_except_ancestors(item);
+                    // the focused item stays visible, but may have moved up
+                    waves.focused_item = waves
+                        .items_tree
+                        .iter_visible_extra()
+                        .find(|info| info.idx == item)
+                        .map(|info| info.vidx);
+                } else {
+                    waves.items_tree.xfold_all(false);
+                }
+            }
             #[cfg(target_arch = "wasm32")]
             Message::StartWcpServer { .. } => {
                 error!("Wcp is not supported on wasm")
@@ -1990,6 +2777,7 @@ impl SystemState {
             Message::StopWcpServer => {
                 self.stop_wcp_server();
             }
+            Message::CopyServerConnectionInfo => self.copy_server_connection_info(),
             Message::SetupChannelWCP => {
                 #[cfg(target_arch = "wasm32")]
                 {
@@ -2055,12 +2843,6 @@ impl SystemState {
                     return None; // Shared existing entry (may still be building)
                 }
 
-                // Clone variable_ref only when we need to spawn builder
-                let variable_ref = match waves.displayed_items.get(&display_id)? {
-                    DisplayedItem::Variable(v) => v.variable_ref.clone(),
-                    _ => return None,
-                };
-
                 // Create new entry and spawn builder
                 let entry = std::sync::Arc::new(crate::analog_signal_cache::AnalogCacheEntry::new(
                     cache_key.clone(),
@@ -2078,9 +2860,13 @@ impl SystemState {
                     .inflight_caches
                     .insert(cache_key.clone(), entry.clone());
 
+                let DisplayedItem::Variable(variable) = waves.displayed_items.get(&display_id)?
+                else {
+                    return None;
+                };
                 waves.build_analog_cache_async(
                     entry,
-                    &variable_ref,
+                    variable,
                     translator,
                     &self.channels.msg_sender,
                 );
@@ -2107,6 +2893,9 @@ impl SystemState {
                 let viewport = Viewport::new();
                 waves.viewports.push(viewport);
                 self.draw_data.borrow_mut().push(None);
+                if self.viewports_linked() {
+                    self.viewport_changed(0);
+                }
             }
             Message::RemoveViewport => {
                 let waves = self.user.waves.as_mut()?;
@@ -2115,6 +2904,24 @@ impl SystemState {
                     self.draw_data.borrow_mut().pop();
                 }
             }
+            Message::SyncViewports { source } => {
+                let waves = self.user.waves.as_mut()?;
+                waves.sync_viewports(source);
+                self.invalidate_draw_commands();
+            }
+            Message::SetViewportsLinked(linked) => {
+                self.user.viewports_linked = Some(linked);
+                if linked {
+                    self.viewport_changed(0);
+                }
+            }
+            Message::SetViewportFollowCursor {
+                viewport_idx,
+                follow,
+            } => {
+                let waves = self.user.waves.as_mut()?;
+                waves.viewports.get_mut(viewport_idx)?.follow_cursor = follow;
+            }
             Message::SelectTheme(theme_name) => {
                 let theme = SurferTheme::new(theme_name)
                     .with_context(|| "Failed to set theme")
@@ -2146,6 +2953,22 @@ impl SystemState {
             Message::ExpandDrawnItem { item, levels } => {
                 self.items_to_expand.borrow_mut().push((item, levels));
             }
+            Message::ExpandAllToDepth(levels) => {
+                let waves = self.user.waves.as_ref()?;
+                self.items_to_expand.borrow_mut().extend(
+                    waves
+                        .displayed_items
+                        .iter()
+                        .filter(|(_, item)| {
+                            matches!(
+                                item,
+                                DisplayedItem::Variable(variable)
+                                    if matches!(variable.info, VariableInfo::Compound { .. })
+                            )
+                        })
+                        .map(|(item, _)| (*item, levels)),
+                );
+            }
             Message::AddCharToPrompt(c) => *self.char_to_add_to_prompt.borrow_mut() = Some(c),
         }
         Some(())
@@ -2191,6 +3014,7 @@ impl SystemState {
             false,
             false,
             variable_name_type,
+            OnDuplicateAdd::Allow,
         );
         let mut into_group_pos = TargetPosition {
             before: ItemIndex(into_group_pos.before.0 + variable_refs.len()),
@@ -2211,6 +3035,42 @@ impl SystemState {
         into_group_pos
     }
 
+    /// Names and values at the cursor of the selected variables, one `name\tvalue` row per
+    /// variable. Returns `None` if the cursor is not set.
+    pub(crate) fn selected_values_table(&self, header: bool) -> Option<String> {
+        self.values_table(true, header)
+    }
+
+    /// Like [`Self::selected_values_table`], but for all visible variables in displayed order
+    /// and without a header.
+    pub(crate) fn all_values_table(&self) -> Option<String> {
+        self.values_table(false, false)
+    }
+
+    fn values_table(&self, selected_only: bool, header: bool) -> Option<String> {
+        let waves = self.user.waves.as_ref()?;
+        let ucursor = waves.cursor.as_ref()?.to_biguint();
+        let mut rows = vec![];
+        if header {
+            rows.push("Name\tValue".to_string());
+        }
+        for node in waves
+            .items_tree
+            .iter_visible()
+            .filter(|node| !selected_only || node.selected)
+        {
+            let Some(item @ DisplayedItem::Variable(_)) = waves.displayed_items.get(&node.item_ref)
+            else {
+                continue;
+            };
+            let value = self
+                .get_variable_value(waves, &node.item_ref.into(), ucursor.as_ref())
+                .unwrap_or_default();
+            rows.push(format!("{}\t{value}", item.name()));
+        }
+        Some(rows.join("\n"))
+    }
+
     fn handle_variable_clipboard_operation<F>(
         &self,
         vidx: MessageTarget<VisibleItemIndex>,
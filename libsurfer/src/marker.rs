@@ -10,6 +10,7 @@ use crate::SystemState;
 use crate::drawing_canvas::draw_vertical_line;
 use crate::{
     config::SurferTheme,
+    dialog::AddMarkerDialog,
     displayed_item::{DisplayedItem, DisplayedItemRef, DisplayedMarker},
     message::Message,
     time::TimeFormatter,
@@ -25,22 +26,24 @@ const CURSOR_MARKER_IDX: u8 = 255;
 
 impl WaveData {
     /// Get the color for a marker by its index, falling back to cursor color if not found
-    fn get_marker_color(&self, idx: u8, theme: &SurferTheme) -> Color32 {
-        self.items_tree
-            .iter()
-            .find_map(|node| {
-                if let Some(DisplayedItem::Marker(marker)) =
-                    self.displayed_items.get(&node.item_ref)
-                    && marker.idx == idx
-                {
-                    return marker
-                        .color
-                        .as_ref()
-                        .and_then(|color| theme.get_color(color));
-                }
-                None
+    pub(crate) fn get_marker_color(&self, idx: u8, theme: &SurferTheme) -> Color32 {
+        self.displayed_items
+            .values()
+            .find(|item| matches!(item, DisplayedItem::Marker(marker) if marker.idx == idx))
+            .map_or(theme.cursor.color, |item| {
+                get_marker_background_color(item, theme)
             })
-            .unwrap_or(theme.cursor.color)
+    }
+
+    /// Set the color of the marker with the specified id. `None` resets it to the default.
+    pub fn set_marker_color(&mut self, idx: u8, color: &Option<String>) {
+        if let Some(item) = self
+            .displayed_items
+            .values_mut()
+            .find(|item| matches!(item, DisplayedItem::Marker(marker) if marker.idx == idx))
+        {
+            item.set_color(color);
+        }
     }
 
     pub fn draw_cursor(&self, theme: &SurferTheme, ctx: &mut DrawingContext, viewport: &Viewport) {
@@ -51,6 +54,18 @@ impl WaveData {
         }
     }
 
+    pub fn draw_trigger(&self, theme: &SurferTheme, ctx: &mut DrawingContext, viewport: &Viewport) {
+        if let Some(trigger) = &self.trigger_time {
+            let num_timestamps = self.safe_num_timestamps();
+            let x = viewport.pixel_from_time(trigger, ctx.cfg.canvas_width, &num_timestamps);
+            let stroke = Stroke {
+                color: theme.accent_warn.background,
+                width: theme.cursor.width * 2.,
+            };
+            draw_vertical_line(x, ctx, stroke);
+        }
+    }
+
     pub fn draw_markers(&self, theme: &SurferTheme, ctx: &mut DrawingContext, viewport: &Viewport) {
         let num_timestamps = self.safe_num_timestamps();
         for (idx, marker) in &self.markers {
@@ -79,12 +94,45 @@ impl WaveData {
             return None;
         }
 
-        let Some(idx) = (0..=MAX_MARKER_INDEX).find(|idx| !self.markers.contains_key(idx)) else {
+        let Some(idx) = self.free_marker_indices().next() else {
             // This shouldn't happen since can_add_marker() was already checked,
             // but handle it gracefully
             return None;
         };
 
+        self.add_marker_with_idx(location, name, idx, move_focus)
+    }
+
+    /// Model of the add marker dialog for the lowest unused marker index, placed at the cursor
+    /// or at the center of the first viewport if the cursor is not set
+    #[must_use]
+    pub fn add_marker_dialog(&self) -> Option<AddMarkerDialog> {
+        let idx = self.free_marker_indices().next()?;
+        let viewport = self.viewports.first()?;
+        let num_timestamps = self.safe_num_timestamps();
+        let center = (viewport.left_edge_time(&num_timestamps)
+            + viewport.right_edge_time(&num_timestamps))
+            / 2;
+        Some(AddMarkerDialog::new(self.cursor.as_ref(), &center, idx))
+    }
+
+    /// Marker indices which are not used by any marker, in increasing order
+    pub fn free_marker_indices(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=MAX_MARKER_INDEX).filter(|idx| !self.markers.contains_key(idx))
+    }
+
+    /// Add a marker using index `idx`. Does nothing if a marker with that index already exists.
+    pub fn add_marker_with_idx(
+        &mut self,
+        location: &BigInt,
+        name: Option<String>,
+        idx: u8,
+        move_focus: bool,
+    ) -> Option<DisplayedItemRef> {
+        if idx > MAX_MARKER_INDEX || self.markers.contains_key(&idx) {
+            return None;
+        }
+
         let item_ref = self.insert_item(
             DisplayedItem::Marker(DisplayedMarker {
                 color: None,
@@ -131,12 +179,46 @@ impl WaveData {
         self.markers.insert(idx, location.clone());
     }
 
+    /// Add `delta` to the times of all markers, keeping them between zero and the end of the
+    /// waveform
+    pub fn shift_all_markers(&mut self, delta: &BigInt) {
+        let end = self.num_timestamps();
+        for time in self.markers.values_mut() {
+            *time = shifted_marker_time(time, delta, end.as_ref());
+        }
+    }
+
+    /// Shift that moves marker `idx` to the cursor, or `None` if either is not set
+    #[must_use]
+    pub fn marker_to_cursor_delta(&self, idx: u8) -> Option<BigInt> {
+        Some(self.cursor.as_ref()? - self.markers.get(&idx)?)
+    }
+
     pub fn move_marker_to_cursor(&mut self, idx: u8) {
         if let Some(location) = self.cursor.clone() {
             self.set_marker_position(idx, &location);
         }
     }
 
+    /// Add a marker at the cursor named `prefix` followed by the lowest number, starting at 1,
+    /// that no other marker with the same prefix uses. Does nothing if the cursor is not set.
+    pub fn add_marker_at_cursor_auto_named(&mut self, prefix: &str) -> Option<DisplayedItemRef> {
+        let location = self.cursor.clone()?;
+        let prefix = prefix.trim();
+        let used_names = self
+            .displayed_items
+            .values()
+            .filter_map(|item| match item {
+                DisplayedItem::Marker(marker) => marker.name.clone(),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let name = (1..)
+            .map(|number| format!("{prefix} {number}"))
+            .find(|name| !used_names.contains(name))?;
+        self.add_marker(&location, Some(name), true)
+    }
+
     /// Draw text with background box at the specified position
     /// Returns the text and its background rectangle info for reuse if needed
     #[allow(clippy::too_many_arguments)]
@@ -404,3 +486,166 @@ fn marker_click_message(marker_idx: u8, cursor: Option<&BigInt>) -> Message {
         Message::GoToTime(cursor.cloned(), 0)
     }
 }
+
+/// `time` moved by `delta`, clamped to zero and `end` if given
+fn shifted_marker_time(time: &BigInt, delta: &BigInt, end: Option<&BigInt>) -> BigInt {
+    let shifted = (time + delta).max(BigInt::ZERO);
+    match end {
+        Some(end) => shifted.min(end.clone()),
+        None => shifted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SurferConfig;
+    use crate::tests::fixtures::load_counter_vcd;
+
+    fn marker(color: Option<&str>) -> DisplayedItem {
+        DisplayedItem::Marker(DisplayedMarker {
+            color: color.map(ToString::to_string),
+            background_color: None,
+            name: Some("Start".to_string()),
+            idx: 3,
+        })
+    }
+
+    #[test]
+    fn marker_color_round_trips_through_state() {
+        let encoded = ron::ser::to_string(&marker(Some("Blue"))).unwrap();
+        let decoded: DisplayedItem = ron::de::from_str(&encoded).unwrap();
+        assert_eq!(decoded.color(), Some("Blue"));
+
+        let encoded = ron::ser::to_string(&marker(None)).unwrap();
+        let decoded: DisplayedItem = ron::de::from_str(&encoded).unwrap();
+        assert_eq!(decoded.color(), None);
+    }
+
+    #[test]
+    fn shifted_marker_times_are_clamped() {
+        let end = BigInt::from(100);
+        let shift = |time: i32, delta: i32| {
+            shifted_marker_time(&BigInt::from(time), &BigInt::from(delta), Some(&end))
+        };
+        assert_eq!(shift(40, 15), BigInt::from(55));
+        assert_eq!(shift(40, -15), BigInt::from(25));
+        assert_eq!(shift(10, -15), BigInt::from(0));
+        assert_eq!(shift(90, 15), BigInt::from(100));
+        assert_eq!(
+            shifted_marker_time(&BigInt::from(90), &BigInt::from(15), None),
+            BigInt::from(105)
+        );
+    }
+
+    #[test]
+    fn marker_draw_color_resolution() {
+        let theme = SurferConfig::default().theme;
+
+        assert_eq!(
+            get_marker_background_color(&marker(Some("Blue")), &theme),
+            theme.get_color("Blue").unwrap()
+        );
+        assert_eq!(
+            get_marker_background_color(&marker(None), &theme),
+            theme.cursor.color
+        );
+        // Unknown color names fall back to the default
+        assert_eq!(
+            get_marker_background_color(&marker(Some("NotAColor")), &theme),
+            theme.cursor.color
+        );
+    }
+
+    #[test]
+    fn auto_named_markers_at_cursor_increment() {
+        let mut state = load_counter_vcd();
+
+        let markers = |state: &SystemState| {
+            let waves = state.user.waves.as_ref().unwrap();
+            let mut markers = waves
+                .displayed_items
+                .values()
+                .filter_map(|item| match item {
+                    DisplayedItem::Marker(marker) => Some((
+                        marker.idx,
+                        marker.name.clone().unwrap_or_default(),
+                        waves.markers[&marker.idx].clone(),
+                    )),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            markers.sort_by_key(|(idx, _, _)| *idx);
+            markers
+        };
+
+        // Nothing happens without a cursor
+        state.update(Message::AddMarkerAtCursorAutoNamed("event".to_string()));
+        assert!(markers(&state).is_empty());
+
+        state.update(Message::SetMarker {
+            id: 0,
+            time: 10.into(),
+        });
+        state.update(Message::CursorSet(100.into()));
+        state.update(Message::AddMarkerAtCursorAutoNamed("event".to_string()));
+        state.update(Message::CursorSet(150.into()));
+        state.update(Message::AddMarkerAtCursorAutoNamed("event".to_string()));
+        state.update(Message::AddMarkerAtCursorAutoNamed("edge".to_string()));
+        assert_eq!(
+            markers(&state),
+            vec![
+                (0, String::new(), BigInt::from(10)),
+                (1, "event 1".to_string(), BigInt::from(100)),
+                (2, "event 2".to_string(), BigInt::from(150)),
+                (3, "edge 1".to_string(), BigInt::from(150)),
+            ]
+        );
+
+        // Freed names and ids are reused
+        state.update(Message::RemoveMarker(1));
+        state.update(Message::CursorSet(170.into()));
+        state.update(Message::AddMarkerAtCursorAutoNamed("event".to_string()));
+        assert_eq!(
+            markers(&state)[1],
+            (1, "event 1".to_string(), BigInt::from(170))
+        );
+    }
+
+    #[test]
+    fn markers_shift_and_align_to_cursor() {
+        let mut state = load_counter_vcd();
+        state.update(Message::SetMarker {
+            id: 1,
+            time: BigInt::from(40),
+        });
+        state.update(Message::SetMarker {
+            id: 2,
+            time: BigInt::from(90),
+        });
+        let marker_times = |state: &SystemState| {
+            let markers = &state.user.waves.as_ref().unwrap().markers;
+            (markers[&1].clone(), markers[&2].clone())
+        };
+
+        state.update(Message::ShiftAllMarkers(BigInt::from(-15)));
+        assert_eq!(marker_times(&state), (BigInt::from(25), BigInt::from(75)));
+
+        // Nothing to align to without a cursor
+        state.update(Message::AlignMarkerToCursor(2));
+        assert_eq!(marker_times(&state), (BigInt::from(25), BigInt::from(75)));
+
+        // Marker 1 would end up before time zero and is clamped
+        state.update(Message::CursorSet(BigInt::from(30)));
+        assert_eq!(
+            state.user.waves.as_ref().unwrap().marker_to_cursor_delta(2),
+            Some(BigInt::from(-45))
+        );
+        state.update(Message::AlignMarkerToCursor(2));
+        assert_eq!(marker_times(&state), (BigInt::from(0), BigInt::from(30)));
+
+        // Each shift is a single undo step
+        state.update(Message::Undo(1));
+        assert_eq!(marker_times(&state), (BigInt::from(25), BigInt::from(75)));
+    }
+}
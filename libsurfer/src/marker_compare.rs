@@ -0,0 +1,171 @@
+//! Window comparing the values of all displayed variables at the cursor and at a marker.
+use egui::{Context, RichText, WidgetText, Window};
+use egui_extras::{Column, TableBuilder};
+
+use crate::SystemState;
+use crate::displayed_item::{DisplayedFieldRef, DisplayedItem};
+use crate::message::Message;
+use crate::wave_data::WaveData;
+
+/// Values of a displayed variable at the cursor and at the compared marker
+#[derive(Debug, PartialEq, Eq)]
+pub struct CompareRow {
+    pub name: String,
+    pub at_cursor: Option<String>,
+    pub at_marker: Option<String>,
+}
+
+impl CompareRow {
+    #[must_use]
+    pub fn differs(&self) -> bool {
+        self.at_cursor != self.at_marker
+    }
+}
+
+impl SystemState {
+    /// One row for every displayed variable, in display order, or `None` if the cursor or
+    /// the marker is not set.
+    #[must_use]
+    pub fn cursor_marker_compare_rows(
+        &self,
+        waves: &WaveData,
+        marker_idx: u8,
+    ) -> Option<Vec<CompareRow>> {
+        let cursor = waves.cursor.as_ref()?.to_biguint()?;
+        let marker = waves.markers.get(&marker_idx)?.to_biguint()?;
+        let rows = waves
+            .items_tree
+            .iter()
+            .filter_map(|node| {
+                let item = waves.displayed_items.get(&node.item_ref)?;
+                if !matches!(item, DisplayedItem::Variable(_)) {
+                    return None;
+                }
+                let field_ref = DisplayedFieldRef::from(node.item_ref);
+                Some(CompareRow {
+                    name: item.name(),
+                    at_cursor: self.get_variable_value(waves, &field_ref, Some(&cursor)),
+                    at_marker: self.get_variable_value(waves, &field_ref, Some(&marker)),
+                })
+            })
+            .collect();
+        Some(rows)
+    }
+
+    pub fn draw_cursor_marker_compare_window(
+        &self,
+        waves: &WaveData,
+        marker_idx: u8,
+        ctx: &Context,
+        msgs: &mut Vec<Message>,
+    ) {
+        let mut open = true;
+        let marker_text = waves
+            .displayed_items
+            .values()
+            .find_map(|item| match item {
+                DisplayedItem::Marker(marker) if marker.idx == marker_idx => {
+                    Some(marker.marker_text(self.get_item_text_color(item)))
+                }
+                _ => None,
+            })
+            .unwrap_or_else(|| WidgetText::from(format!("Marker {marker_idx}")));
+        let warn_color = self.user.config.theme.accent_warn.background;
+
+        Window::new("Compare cursor and marker")
+            .collapsible(true)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let Some(rows) = self.cursor_marker_compare_rows(waves, marker_idx) else {
+                    ui.label("Both the cursor and the marker need to be set");
+                    return;
+                };
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .columns(Column::auto().resizable(true), 3)
+                    .auto_shrink(emath::Vec2b::new(false, true))
+                    .header(row_height, |mut header| {
+                        header.col(|ui| {
+                            ui.strong("Variable");
+                        });
+                        header.col(|ui| {
+                            ui.strong("Cursor");
+                        });
+                        header.col(|ui| {
+                            ui.label(marker_text.clone());
+                        });
+                    })
+                    .body(|mut body| {
+                        for row in &rows {
+                            let text = |value: &Option<String>| {
+                                let text = RichText::new(value.clone().unwrap_or_default());
+                                if row.differs() {
+                                    text.color(warn_color)
+                                } else {
+                                    text
+                                }
+                            };
+                            body.row(row_height, |mut table_row| {
+                                table_row.col(|ui| {
+                                    ui.label(text(&Some(row.name.clone())));
+                                });
+                                table_row.col(|ui| {
+                                    ui.label(text(&row.at_cursor));
+                                });
+                                table_row.col(|ui| {
+                                    ui.label(text(&row.at_marker));
+                                });
+                            });
+                        }
+                    });
+                ui.add_space(15.);
+                ui.vertical_centered(|ui| {
+                    if ui.button("Close").clicked() {
+                        msgs.push(Message::CloseCursorMarkerCompare);
+                    }
+                });
+            });
+        if !open {
+            msgs.push(Message::CloseCursorMarkerCompare);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+
+    #[test]
+    fn cursor_marker_compare_rows_detect_differences() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk", "tb.dut.counter", "tb._tmp"]);
+        state.update(Message::CursorSet(145.into()));
+
+        let waves = state.user.waves.as_ref().unwrap();
+        assert!(state.cursor_marker_compare_rows(waves, 1).is_none());
+
+        state.update(Message::SetMarker {
+            id: 1,
+            time: 165.into(),
+        });
+        let waves = state.user.waves.as_ref().unwrap();
+        let rows = state.cursor_marker_compare_rows(waves, 1).unwrap();
+        let summary = rows
+            .iter()
+            .map(|row| (row.name.as_str(), row.differs()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![("clk", false), ("counter", true), ("_tmp", false)]
+        );
+        assert_eq!(rows[1].at_cursor.as_deref(), Some("2"));
+        assert_eq!(rows[1].at_marker.as_deref(), Some("3"));
+
+        state.update(Message::ShowCursorMarkerCompare(1));
+        assert_eq!(state.cursor_marker_compare, Some(1));
+        state.update(Message::CloseCursorMarkerCompare);
+        assert_eq!(state.cursor_marker_compare, None);
+    }
+}
@@ -0,0 +1,391 @@
+//! Export and import of the markers with their names and times as CSV.
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use camino::Utf8PathBuf;
+use num::BigInt;
+use tracing::{error, warn};
+
+use crate::SystemState;
+use crate::displayed_item::DisplayedItem;
+use crate::marker::DEFAULT_MARKER_NAME;
+use crate::time::{TimeFormatter, TimeScale, parse_time};
+use crate::wave_data::WaveData;
+
+const MARKERS_CSV_HEADER: &str = "id,name,time,ticks";
+
+/// Quote `field` if it contains characters with a special meaning in CSV
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// One `id,name,time,ticks` row per marker in `markers`, ordered by id. The time is formatted
+/// by `formatter` for reading, while `ticks` is the raw time which is read back on import.
+/// Markers without an entry in `names` get the default marker name.
+#[must_use]
+pub fn markers_to_csv(
+    markers: &HashMap<u8, BigInt>,
+    names: &HashMap<u8, String>,
+    formatter: &TimeFormatter,
+) -> String {
+    let mut csv = format!("{MARKERS_CSV_HEADER}\n");
+    let mut ids: Vec<_> = markers.keys().copied().collect();
+    ids.sort_unstable();
+    for id in ids {
+        let name = names.get(&id).map_or(DEFAULT_MARKER_NAME, String::as_str);
+        let _ = writeln!(
+            csv,
+            "{id},{},{},{}",
+            csv_field(name),
+            csv_field(&formatter.format(&markers[&id])),
+            markers[&id]
+        );
+    }
+    csv
+}
+
+/// Split a CSV line into its fields, undoing the quoting of `csv_field`
+fn parse_csv_row(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+/// Marker read from a row of an exported marker list
+#[derive(Debug, PartialEq, Eq)]
+pub struct ImportedMarker {
+    pub idx: u8,
+    /// `None` for markers with the default name
+    pub name: Option<String>,
+    pub time: BigInt,
+}
+
+/// Markers in the rows of `csv`, as written by [`markers_to_csv`]. The time is taken from the
+/// `ticks` column if there is one, as the formatted time depends on the locale. Otherwise, the
+/// `time` column is parsed in `timescale`, unless it contains a `,` which may be a decimal or
+/// a digit separator. Times must be between zero and `num_timestamps`.
+/// Invalid rows are logged and skipped.
+#[must_use]
+pub fn parse_markers_csv(
+    csv: &str,
+    timescale: &TimeScale,
+    num_timestamps: &BigInt,
+) -> Vec<ImportedMarker> {
+    let mut markers = vec![];
+    for (line_idx, line) in csv.lines().enumerate() {
+        if line.trim().is_empty()
+            || (line_idx == 0 && ["id,name,time", MARKERS_CSV_HEADER].contains(&line.trim()))
+        {
+            continue;
+        }
+        let line_number = line_idx + 1;
+        let fields = parse_csv_row(line);
+        let (idx, name, time) = match fields.as_slice() {
+            [idx, name, _, ticks] => (idx, name, ticks.trim().parse::<BigInt>().ok()),
+            // Depending on the locale, `,` is either a decimal or a digit separator
+            [_, _, time] if time.contains(',') => {
+                error!("Skipping marker on line {line_number}: ambiguous time {time:?}");
+                continue;
+            }
+            [idx, name, time] => (idx, name, parse_time(time, timescale)),
+            _ => {
+                error!(
+                    "Skipping marker on line {line_number}: expected three or four columns in {line:?}"
+                );
+                continue;
+            }
+        };
+        let Ok(idx) = idx.trim().parse::<u8>() else {
+            error!("Skipping marker on line {line_number}: invalid id {idx:?}");
+            continue;
+        };
+        let Some(time) = time else {
+            error!("Skipping marker on line {line_number}: invalid time in {line:?}");
+            continue;
+        };
+        if time < BigInt::ZERO || &time > num_timestamps {
+            error!("Skipping marker on line {line_number}: time {time} is outside the waveform");
+            continue;
+        }
+        markers.push(ImportedMarker {
+            idx,
+            name: (name != DEFAULT_MARKER_NAME).then(|| name.clone()),
+            time,
+        });
+    }
+    markers
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_markers_file(path: &Utf8PathBuf) -> Option<String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| error!("Failed to read markers from {path}: {e}"))
+        .ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_markers_file(path: &Utf8PathBuf) -> Option<String> {
+    warn!("Importing markers from {path} is not supported on wasm");
+    None
+}
+
+impl WaveData {
+    /// Names of the markers which have been given one
+    #[must_use]
+    pub fn marker_names(&self) -> HashMap<u8, String> {
+        self.displayed_items
+            .values()
+            .filter_map(|item| match item {
+                DisplayedItem::Marker(marker) => Some((marker.idx, marker.name.clone()?)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Move or add the marker with the id of each of `markers` and give it its name
+    pub fn import_markers(&mut self, markers: Vec<ImportedMarker>) {
+        for ImportedMarker { idx, name, time } in markers {
+            if !self.markers.contains_key(&idx) {
+                if self.add_marker_with_idx(&time, name, idx, false).is_none() {
+                    error!("Cannot add marker {idx}");
+                }
+                continue;
+            }
+            self.set_marker_position(idx, &time);
+            for item in self.displayed_items.values_mut() {
+                if let DisplayedItem::Marker(marker) = item
+                    && marker.idx == idx
+                {
+                    marker.name.clone_from(&name);
+                }
+            }
+        }
+    }
+}
+
+impl SystemState {
+    /// The markers of the loaded waveform as CSV, with times formatted as in the UI
+    #[must_use]
+    pub fn encode_markers_csv(&self) -> Option<String> {
+        let waves = self.user.waves.as_ref()?;
+        let formatter = TimeFormatter::new(
+            &waves.inner.metadata().timescale,
+            &self.user.wanted_timeunit,
+            &self.get_time_format(),
+        );
+        Some(markers_to_csv(
+            &waves.markers,
+            &waves.marker_names(),
+            &formatter,
+        ))
+    }
+
+    /// Read markers written by [`SystemState::export_markers`] from `path` into the loaded
+    /// waveform, replacing markers with the same id
+    pub fn import_markers(&mut self, path: &Utf8PathBuf) {
+        let Some(csv) = read_markers_file(path) else {
+            return;
+        };
+        let Some(waves) = &self.user.waves else {
+            return;
+        };
+        let Some(num_timestamps) = waves.num_timestamps() else {
+            warn!("Cannot import markers before the waveform is loaded");
+            return;
+        };
+        let markers = parse_markers_csv(&csv, &waves.inner.metadata().timescale, &num_timestamps);
+        self.save_current_canvas(format!("Import {} markers", markers.len()));
+        if let Some(waves) = self.user.waves.as_mut() {
+            waves.import_markers(markers);
+        }
+    }
+
+    /// Write the markers to `path`. On wasm, the file is picked in a save dialog instead.
+    pub fn export_markers(&mut self, path: Utf8PathBuf) {
+        let Some(encoded) = self.encode_markers_csv() else {
+            return;
+        };
+
+        self.save_bytes_to(
+            Some(path),
+            "Export markers",
+            ("CSV files (*.csv)".to_string(), vec!["csv".to_string()]),
+            encoded.into_bytes(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use crate::tests::fixtures::load_counter_vcd;
+    use crate::time::{TimeFormat, TimeScale, TimeUnit};
+
+    #[test]
+    fn markers_round_trip_through_csv() {
+        let markers = HashMap::from([
+            (7, BigInt::from(2500)),
+            (0, BigInt::from(100)),
+            (3, BigInt::from(0)),
+        ]);
+        let names = HashMap::from([
+            (0, "start".to_string()),
+            (7, "done, \"really\"".to_string()),
+        ]);
+        let timescale = TimeScale {
+            unit: TimeUnit::PicoSeconds,
+            multiplier: None,
+        };
+        let formatter =
+            TimeFormatter::new(&timescale, &TimeUnit::NanoSeconds, &TimeFormat::default());
+
+        let csv = markers_to_csv(&markers, &names, &formatter);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(MARKERS_CSV_HEADER));
+        let rows: Vec<_> = lines.map(parse_csv_row).collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["0", "start", &formatter.format(&BigInt::from(100)), "100"],
+                vec![
+                    "3",
+                    DEFAULT_MARKER_NAME,
+                    &formatter.format(&BigInt::from(0)),
+                    "0"
+                ],
+                vec![
+                    "7",
+                    "done, \"really\"",
+                    &formatter.format(&BigInt::from(2500)),
+                    "2500"
+                ],
+            ]
+        );
+
+        assert_eq!(
+            parse_markers_csv(&csv, &timescale, &BigInt::from(10000)),
+            vec![
+                ImportedMarker {
+                    idx: 0,
+                    name: Some("start".to_string()),
+                    time: BigInt::from(100),
+                },
+                ImportedMarker {
+                    idx: 3,
+                    name: None,
+                    time: BigInt::from(0),
+                },
+                ImportedMarker {
+                    idx: 7,
+                    name: Some("done, \"really\"".to_string()),
+                    time: BigInt::from(2500),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn ticks_are_used_over_formatted_times() {
+        let timescale = TimeScale {
+            unit: TimeUnit::PicoSeconds,
+            multiplier: None,
+        };
+        // Formatted using a locale with `,` as decimal point, which is not parsed back
+        let csv = "id,name,time,ticks\n\
+                   1,first,\"1.234,5 ns\",1234500\n\
+                   2,second,\"2,5 ns\",bad\n";
+        assert_eq!(
+            parse_markers_csv(csv, &timescale, &BigInt::from(10_000_000)),
+            vec![ImportedMarker {
+                idx: 1,
+                name: Some("first".to_string()),
+                time: BigInt::from(1_234_500),
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_marker_rows_are_skipped() {
+        let timescale = TimeScale {
+            unit: TimeUnit::NanoSeconds,
+            multiplier: None,
+        };
+        let csv = "id,name,time\n\
+                   1,first,10 ns\n\
+                   2,too many,20 ns,20,columns\n\
+                   300,large id,30 ns\n\
+                   4,bad time,soon\n\
+                   7,ambiguous,\"2,5 ns\"\n\
+                   5,late,2 us\n\
+                   \n\
+                   6,last,1000\n";
+        assert_eq!(
+            parse_markers_csv(csv, &timescale, &BigInt::from(1000)),
+            vec![
+                ImportedMarker {
+                    idx: 1,
+                    name: Some("first".to_string()),
+                    time: BigInt::from(10),
+                },
+                ImportedMarker {
+                    idx: 6,
+                    name: Some("last".to_string()),
+                    time: BigInt::from(1000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn markers_are_imported_from_csv() {
+        let mut state = load_counter_vcd();
+        state.update(Message::SetMarker {
+            id: 1,
+            time: BigInt::from(10),
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = camino::Utf8PathBuf::try_from(dir.path().join("markers.csv")).unwrap();
+        std::fs::write(
+            &path,
+            "id,name,time\n1,start,40 s\n2,Marker,90\n3,outside,900 s\n",
+        )
+        .unwrap();
+        state.update(Message::ImportMarkers(path));
+
+        // The marker outside the waveform is skipped
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(
+            waves.markers,
+            HashMap::from([(1, BigInt::from(40)), (2, BigInt::from(90))])
+        );
+        assert_eq!(
+            waves.marker_names(),
+            HashMap::from([(1, "start".to_string())])
+        );
+
+        // The import is a single undo step
+        state.update(Message::Undo(1));
+        assert_eq!(
+            state.user.waves.as_ref().unwrap().markers,
+            HashMap::from([(1, BigInt::from(10))])
+        );
+    }
+}
@@ -6,9 +6,12 @@ use itertools::Itertools;
 use std::sync::atomic::Ordering;
 use surfer_translation_types::{TranslationPreference, Translator};
 
-use crate::config::{PrimaryMouseDrag, TransitionValue};
+use crate::color_scheme::BuiltinColorScheme;
+use crate::config::{ItemColumnSide, PrimaryMouseDrag, TransitionValue, ValueTextAlign};
 use crate::displayed_item_tree::VisibleItemIndex;
+use crate::enum_stats::EnumStatsRange;
 use crate::hierarchy::{HierarchyStyle, ParameterDisplayLocation, ScopeExpandType};
+use crate::hierarchy_export::HierarchyFormat;
 use crate::keyboard_shortcuts::ShortcutAction;
 use crate::message::MessageTarget;
 use crate::wave_container::{FieldRef, VariableRefExt};
@@ -18,10 +21,12 @@ use crate::{
     SystemState,
     clock_highlighting::clock_highlight_type_menu,
     config::ArrowKeyBindings,
-    displayed_item::{DisplayedFieldRef, DisplayedItem},
+    displayed_item::{
+        DisplayedFieldRef, DisplayedItem, FixedPointRadix, LeadingZeroMode, NumericFormatOptions,
+    },
     file_dialog::OpenMode,
     message::Message,
-    time::{timeformat_menu, timeunit_menu},
+    time::{radix_menu, timeformat_menu, timeunit_menu},
     variable_name_type::VariableNameType,
 };
 use surfer_wcp::{WcpEvent, WcpSCMessage};
@@ -106,6 +111,14 @@ impl SystemState {
                         .format_shortcut(ShortcutAction::SwitchFile),
                 )
                 .add_closing_menu(msgs, ui);
+            b("Quick open...", Message::ShowQuickOpen)
+                .shortcut(
+                    self.user
+                        .config
+                        .shortcuts
+                        .format_shortcut(ShortcutAction::ShowQuickOpen),
+                )
+                .add_closing_menu(msgs, ui);
             b(
                 "Reload",
                 Message::ReloadWaveform(self.user.config.behavior.keep_during_reload),
@@ -118,6 +131,15 @@ impl SystemState {
             )
             .enabled(self.user.waves.is_some())
             .add_closing_menu(msgs, ui);
+            b("Reload keeping view", Message::ReloadPreservingView)
+                .enabled(self.user.waves.is_some())
+                .add_closing_menu(msgs, ui);
+            b(
+                "Waveform information",
+                Message::SetWaveMetadataVisible(true),
+            )
+            .enabled(waves_loaded)
+            .add_closing_menu(msgs, ui);
 
             b("Load state...", Message::LoadStateFile(None)).add_closing_menu(msgs, ui);
             #[cfg(not(target_arch = "wasm32"))]
@@ -140,6 +162,32 @@ impl SystemState {
                 .add_closing_menu(msgs, ui);
             }
             b("Save state as...", Message::SaveStateFile(None)).add_closing_menu(msgs, ui);
+            b(
+                "Export configuration...",
+                Message::ExportEffectiveConfig(None),
+            )
+            .add_closing_menu(msgs, ui);
+            b(
+                "Export hierarchy as JSON...",
+                Message::ExportHierarchy {
+                    path: None,
+                    format: HierarchyFormat::Json,
+                },
+            )
+            .enabled(waves_loaded)
+            .add_closing_menu(msgs, ui);
+            b(
+                "Export hierarchy as text...",
+                Message::ExportHierarchy {
+                    path: None,
+                    format: HierarchyFormat::Text,
+                },
+            )
+            .enabled(waves_loaded)
+            .add_closing_menu(msgs, ui);
+            b("Export as SVG...", Message::ExportSvg(None))
+                .enabled(waves_loaded)
+                .add_closing_menu(msgs, ui);
             b(
                 "Open URL...",
                 Message::SetUrlEntryVisible(
@@ -238,6 +286,9 @@ impl SystemState {
                 )
                 .enabled(waves_loaded)
                 .add_closing_menu(msgs, ui);
+            b("Events", Message::SetEventsWindowVisible(true))
+                .enabled(waves_loaded)
+                .add_closing_menu(msgs, ui);
             ui.separator();
             b("Add viewport", Message::AddViewport)
                 .enabled(waves_loaded)
@@ -245,6 +296,30 @@ impl SystemState {
             b("Remove viewport", Message::RemoveViewport)
                 .enabled(waves_loaded)
                 .add_closing_menu(msgs, ui);
+            b("Sync viewports", Message::SyncViewports { source: 0 })
+                .enabled(waves_loaded)
+                .add_closing_menu(msgs, ui);
+            let first_viewport_follows = self
+                .user
+                .waves
+                .as_ref()
+                .and_then(|waves| waves.viewports.first())
+                .is_some_and(|viewport| viewport.follow_cursor);
+            b(
+                "Toggle follow cursor",
+                Message::SetViewportFollowCursor {
+                    viewport_idx: 0,
+                    follow: !first_viewport_follows,
+                },
+            )
+            .enabled(waves_loaded)
+            .add_closing_menu(msgs, ui);
+            b(
+                "Toggle viewport link",
+                Message::SetViewportsLinked(!self.viewports_linked()),
+            )
+            .enabled(waves_loaded)
+            .add_closing_menu(msgs, ui);
             ui.separator();
 
             b(
@@ -304,6 +379,27 @@ impl SystemState {
                         .add_closing_menu(msgs, ui);
                 }
             });
+            ui.menu_button("Color scheme", |ui| {
+                ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
+                for scheme in enum_iterator::all::<BuiltinColorScheme>() {
+                    b(scheme.to_string(), Message::AutoColorItems(scheme.rules()))
+                        .add_closing_menu(msgs, ui);
+                }
+                if !self.user.config.color_schemes.is_empty() {
+                    ui.separator();
+                }
+                for (name, rules) in &self.user.config.color_schemes {
+                    let rules = rules.iter().cloned().map(Into::into).collect();
+                    b(name.clone(), Message::AutoColorItems(rules)).add_closing_menu(msgs, ui);
+                }
+                ui.separator();
+                b("Save scheme...", Message::SaveScheme(None))
+                    .enabled(waves_loaded)
+                    .add_closing_menu(msgs, ui);
+                b("Apply scheme...", Message::ApplyScheme(None))
+                    .enabled(waves_loaded)
+                    .add_closing_menu(msgs, ui);
+            });
             ui.menu_button("UI zoom factor", |ui| {
                 ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
                 for scale in &self.user.config.layout.zoom_factors {
@@ -319,6 +415,14 @@ impl SystemState {
 
         ui.menu_button("Settings", |ui| {
             ui.menu_button("Clock highlighting", |ui| {
+                let mut clock_highlight_enabled = self.clock_highlight_enabled();
+                if ui
+                    .checkbox(&mut clock_highlight_enabled, "Enabled")
+                    .clicked()
+                {
+                    msgs.push(Message::SetClockHighlightEnabled(clock_highlight_enabled));
+                }
+                ui.separator();
                 clock_highlight_type_menu(ui, msgs, self.clock_highlight_type());
             });
             ui.menu_button("Time unit", |ui| {
@@ -327,6 +431,9 @@ impl SystemState {
             ui.menu_button("Time format", |ui| {
                 timeformat_menu(ui, msgs, &self.get_time_format());
             });
+            ui.menu_button("Time label radix", |ui| {
+                radix_menu(ui, msgs, self.time_label_radix());
+            });
             if let Some(waves) = &self.user.waves {
                 let variable_name_type = waves.default_variable_name_type;
                 ui.menu_button("Variable names", |ui| {
@@ -352,6 +459,16 @@ impl SystemState {
                 });
             });
 
+            ui.menu_button("Name and value column side", |ui| {
+                for side in enum_iterator::all::<ItemColumnSide>() {
+                    ui.radio(self.item_column_side() == side, side.to_string())
+                        .clicked()
+                        .then(|| {
+                            msgs.push(Message::SetItemColumnSide(side));
+                        });
+                }
+            });
+
             ui.menu_button("Hierarchy", |ui| {
                 self.hierarchy_menu(msgs, ui);
             });
@@ -411,6 +528,14 @@ impl SystemState {
                     msgs.push(Message::SetTickLines(!self.show_ticks()));
                 });
 
+            let mut relative_time_ruler = self.user.relative_time_ruler;
+            if ui
+                .checkbox(&mut relative_time_ruler, "Ruler relative to cursor")
+                .clicked()
+            {
+                msgs.push(Message::ToggleRelativeTimeRuler);
+            }
+
             ui.radio(self.show_tooltip(), "Show variable tooltip")
                 .clicked()
                 .then(|| {
@@ -468,6 +593,28 @@ impl SystemState {
                 .then(|| {
                     msgs.push(Message::SetDinotraceStyle(!self.use_dinotrace_style()));
                 });
+            ui.radio(self.show_no_data_hatch(), "Hatch outside of data")
+                .clicked()
+                .then(|| {
+                    msgs.push(Message::SetShowNoDataHatch(!self.show_no_data_hatch()));
+                });
+            ui.radio(self.show_glitch_density(), "Glitch markers")
+                .clicked()
+                .then(|| {
+                    msgs.push(Message::SetShowGlitchDensity(!self.show_glitch_density()));
+                });
+            let mut show_partial_unknowns = self.show_partial_unknowns();
+            if ui
+                .checkbox(&mut show_partial_unknowns, "Partially unknown values")
+                .clicked()
+            {
+                msgs.push(Message::SetShowPartialUnknowns(show_partial_unknowns));
+            }
+            ui.radio(self.show_sparklines(), "Sparklines")
+                .clicked()
+                .then(|| {
+                    msgs.push(Message::SetSparklinesVisible(!self.show_sparklines()));
+                });
         });
         ui.menu_button("Help", |ui| {
             b("Quick start", Message::SetQuickStartVisible(true)).add_closing_menu(msgs, ui);
@@ -492,6 +639,14 @@ impl SystemState {
                     msgs.push(Message::SetHierarchyStyle(style));
                 });
         }
+        ui.separator();
+        let mut group_scopes_by_type = self.group_scopes_by_type();
+        if ui
+            .checkbox(&mut group_scopes_by_type, "Group scopes by type")
+            .clicked()
+        {
+            msgs.push(Message::SetGroupScopesByType(group_scopes_by_type));
+        }
     }
 
     pub fn item_context_menu(
@@ -519,6 +674,16 @@ impl SystemState {
                 field: path.field.clone(),
             };
             self.add_format_menu(&dfr, clicked_item, path, msgs, ui, group_target);
+            ui.menu_button("Value statistics", |ui| {
+                for (label, range) in [
+                    ("Whole run", EnumStatsRange::All),
+                    ("Visible range", EnumStatsRange::Visible),
+                ] {
+                    if ui.button(label).clicked() {
+                        msgs.push(Message::ComputeEnumStats(dfr.clone(), range));
+                    }
+                }
+            });
         }
 
         ui.menu_button("Color", |ui| {
@@ -582,8 +747,93 @@ impl SystemState {
                             msgs.push(Message::ItemHeightScalingFactorChange(group_target, *size));
                         });
                 }
+                ui.separator();
+                if ui.button("Reset all heights").clicked() {
+                    msgs.push(Message::ResetAllItemHeights);
+                }
             });
 
+            ui.menu_button("Value alignment", |ui| {
+                let selected_align = variable
+                    .value_text_align
+                    .unwrap_or_else(|| self.user.config.layout.value_text_align());
+                for align in enum_iterator::all::<ValueTextAlign>() {
+                    ui.radio(selected_align == align, align.to_string())
+                        .clicked()
+                        .then(|| {
+                            msgs.push(Message::SetValueTextAlign(clicked_item_ref.into(), align));
+                        });
+                }
+            });
+
+            ui.menu_button("Leading zeros", |ui| {
+                for mode in enum_iterator::all::<LeadingZeroMode>() {
+                    ui.radio(variable.leading_zeros == mode, mode.to_string())
+                        .clicked()
+                        .then(|| {
+                            msgs.push(Message::SetLeadingZeroMode(clicked_item_ref.into(), mode));
+                        });
+                }
+            });
+
+            ui.menu_button("Numeric format", |ui| {
+                let options = variable
+                    .numeric_format
+                    .unwrap_or_else(|| self.numeric_format());
+                let mut set_options = |options| {
+                    msgs.push(Message::SetNumericFormatOptions(
+                        Some(clicked_item_ref.into()),
+                        options,
+                    ));
+                };
+                let mut thousands_separator = options.thousands_separator.is_some();
+                if ui
+                    .checkbox(&mut thousands_separator, "Thousands separator")
+                    .clicked()
+                {
+                    set_options(NumericFormatOptions {
+                        thousands_separator: thousands_separator.then_some(','),
+                        ..options
+                    });
+                }
+                ui.separator();
+                ui.label("Decimal places");
+                for places in [None, Some(0), Some(1), Some(2), Some(3), Some(4), Some(6)] {
+                    let label = places.map_or_else(|| "Shortest".to_string(), |p| p.to_string());
+                    if ui.radio(options.decimal_places == places, label).clicked() {
+                        set_options(NumericFormatOptions {
+                            decimal_places: places,
+                            ..options
+                        });
+                    }
+                }
+            });
+
+            if ui
+                .radio(variable.show_interval_labels, "Interval labels")
+                .clicked()
+            {
+                msgs.push(Message::ToggleIntervalLabels(clicked_item_ref.into()));
+            }
+
+            if variable.captured_value.is_some() {
+                if ui.button("Clear captured value").clicked() {
+                    msgs.push(Message::ClearCapturedValue(Some(vidx)));
+                }
+            } else if waves.cursor.is_some() && ui.button("Capture value at cursor").clicked() {
+                msgs.push(Message::CaptureItemValue(Some(vidx)));
+            }
+
+            if let Some(cursor) = &waves.cursor
+                && ui.button("Add event at cursor").clicked()
+            {
+                msgs.push(Message::AddEvent {
+                    name: clicked_item.name(),
+                    variable: variable.variable_ref.clone(),
+                    time: cursor.clone(),
+                });
+            }
+
             if self.wcp_greeted_signal.load(Ordering::Relaxed) {
                 if self.wcp_client_capabilities.goto_declaration
                     && ui.button("Go to declaration").clicked()
@@ -625,11 +875,24 @@ impl SystemState {
                 )));
             }
 
+            if matches!(clicked_item, DisplayedItem::Variable(_))
+                && ui.button("Reveal in hierarchy").clicked()
+            {
+                msgs.push(Message::RevealInHierarchy(Some(vidx)));
+            }
+
+            if matches!(clicked_item, DisplayedItem::Variable(_))
+                && !self.watch_list.contains(&path.root)
+                && ui.button("Add to watch").clicked()
+            {
+                msgs.push(Message::AddToWatch(path.root.clone()));
+            }
+
             if let DisplayedItem::Variable(variable) = clicked_item
                 && wave_container.supports_analog()
             {
                 ui.menu_button("Analog", |ui| {
-                    use crate::displayed_item::AnalogSettings;
+                    use crate::displayed_item::{AnalogSettings, AnalogStyle, AnalogUndefMode};
                     let current = variable.analog.as_ref().map(|a| a.settings);
 
                     let options = [
@@ -651,6 +914,55 @@ impl SystemState {
                             msgs.push(Message::SetAnalogSettings(group_target, config));
                         }
                     }
+
+                    if let Some(analog) = &variable.analog {
+                        ui.separator();
+                        let style = analog
+                            .style
+                            .unwrap_or_else(|| self.user.config.layout.analog_style());
+                        if ui.radio(style.point_markers, "Point markers").clicked() {
+                            msgs.push(Message::SetAnalogStyle(
+                                clicked_item_ref.into(),
+                                AnalogStyle {
+                                    point_markers: !style.point_markers,
+                                    ..style
+                                },
+                            ));
+                        }
+                        ui.menu_button("Line width", |ui| {
+                            let widths = [
+                                ("Default", None),
+                                ("1", Some(1.0)),
+                                ("2", Some(2.0)),
+                                ("3", Some(3.0)),
+                                ("4", Some(4.0)),
+                            ];
+                            for (label, line_width) in widths {
+                                if ui.radio(style.line_width == line_width, label).clicked() {
+                                    msgs.push(Message::SetAnalogStyle(
+                                        clicked_item_ref.into(),
+                                        AnalogStyle {
+                                            line_width,
+                                            ..style
+                                        },
+                                    ));
+                                }
+                            }
+                        });
+                        ui.menu_button("Undefined values", |ui| {
+                            for undef_mode in enum_iterator::all::<AnalogUndefMode>() {
+                                if ui
+                                    .radio(analog.undef_mode == undef_mode, undef_mode.to_string())
+                                    .clicked()
+                                {
+                                    msgs.push(Message::SetAnalogUndefMode(
+                                        clicked_item_ref.into(),
+                                        undef_mode,
+                                    ));
+                                }
+                            }
+                        });
+                    }
                 });
             }
         }
@@ -668,6 +980,14 @@ impl SystemState {
             msgs.push(Message::ItemNameReset(group_target));
         }
 
+        let hidden = waves
+            .items_tree
+            .get_visible(vidx)
+            .is_some_and(|node| node.hidden);
+        if ui.button(if hidden { "Show" } else { "Hide" }).clicked() {
+            msgs.push(Message::ToggleItemHidden(Some(vidx)));
+        }
+
         if ui.button("Remove").clicked() {
             if waves
                 .items_tree
@@ -697,6 +1017,12 @@ impl SystemState {
                         MessageTarget::Explicit(vidx),
                     ));
                 }
+                if waves.cursor.is_some() && ui.button("Selected values as table").clicked() {
+                    msgs.push(Message::CopySelectedValuesTable { header: true });
+                }
+                if waves.cursor.is_some() && ui.button("All values as table").clicked() {
+                    msgs.push(Message::CopyAllValuesAtCursor);
+                }
             });
         }
         ui.separator();
@@ -749,11 +1075,28 @@ impl SystemState {
                 }
             }
         });
-        if let DisplayedItem::Marker(_) = clicked_item {
+        if let DisplayedItem::Marker(marker) = clicked_item {
             ui.separator();
             if ui.button("View markers").clicked() {
                 msgs.push(Message::SetCursorWindowVisible(true));
             }
+            if ui.button("Compare with cursor").clicked() {
+                msgs.push(Message::ShowCursorMarkerCompare(marker.idx));
+            }
+            if let Some(waves) = &self.user.waves {
+                ui.menu_button("Summarize changes to", |ui| {
+                    for other in waves
+                        .markers
+                        .keys()
+                        .filter(|idx| **idx != marker.idx)
+                        .sorted()
+                    {
+                        if ui.button(format!("#{other}")).clicked() {
+                            msgs.push(Message::SummarizeChangesBetween(marker.idx, *other));
+                        }
+                    }
+                });
+            }
         }
     }
 
@@ -848,6 +1191,83 @@ impl SystemState {
                     }
                 });
             }
+            if let DisplayedItem::Variable(variable) = clicked_item
+                && path.field.is_empty()
+                && matches!(
+                    waves
+                        .variable_translator(clicked_field_ref, &self.translators)
+                        .name()
+                        .as_str(),
+                    "Unsigned fixed point" | "Signed fixed point"
+                )
+                && let Some(num_bits) = waves
+                    .inner
+                    .as_waves()
+                    .and_then(|wave_container| wave_container.variable_meta(&path.root).ok())
+                    .and_then(|meta| meta.num_bits)
+            {
+                ui.separator();
+                ui.menu_button("Binary point", |ui| {
+                    if ui
+                        .radio(variable.fixed_point_radix.is_none(), "From index")
+                        .clicked()
+                    {
+                        msgs.push(Message::ResetFixedPointRadix(clicked_field_ref.item));
+                    }
+                    egui::ScrollArea::vertical()
+                        .max_height(300.)
+                        .show(ui, |ui| {
+                            for frac_bits in 0..=num_bits {
+                                let radix = FixedPointRadix {
+                                    int_bits: num_bits - frac_bits,
+                                    frac_bits,
+                                };
+                                let label = format!("Q{}.{}", radix.int_bits, radix.frac_bits);
+                                if ui
+                                    .radio(variable.fixed_point_radix == Some(radix), label)
+                                    .clicked()
+                                {
+                                    msgs.push(Message::SetFixedPointRadix {
+                                        item: clicked_field_ref.item,
+                                        int_bits: radix.int_bits,
+                                        frac_bits: radix.frac_bits,
+                                    });
+                                }
+                            }
+                        });
+                });
+            }
+            if matches!(clicked_item, DisplayedItem::Variable(_)) {
+                let translator = waves
+                    .variable_translator(clicked_field_ref, &self.translators)
+                    .name();
+                let current = self
+                    .user
+                    .translator_configs
+                    .get(&translator)
+                    .filter(|config| !config.is_empty())
+                    .map_or_else(
+                        || "Not configured".to_string(),
+                        |config| {
+                            config
+                                .iter()
+                                .sorted()
+                                .map(|(key, value)| format!("{key} = {value}"))
+                                .join("\n")
+                        },
+                    );
+                ui.separator();
+                if ui
+                    .button("Configure translator...")
+                    .on_hover_text(current)
+                    .clicked()
+                {
+                    msgs.push(Message::ShowCommandPrompt(
+                        format!("translator_config_set {translator}, "),
+                        None,
+                    ));
+                }
+            }
         });
     }
 }
@@ -0,0 +1,583 @@
+//! Wave container combining the hierarchies of several waveform files. The scopes of the first
+//! file are kept as they are, while each added file is placed under a root scope named after it.
+//!
+//! Only the first file is recorded in state files, so the merged files have to be merged again
+//! after loading a state file.
+use std::collections::VecDeque;
+
+use camino::Utf8PathBuf;
+use derive_more::Debug;
+use eyre::{Result, anyhow};
+use num::BigUint;
+
+use crate::wave_container::{
+    MetaData, QueryResult, ScopeId, ScopeRef, ScopeRefExt, SignalId, VariableMeta, VariableRef,
+};
+use crate::wellen::{
+    BodyResult, LoadSignalsCmd, LoadSignalsResult, WellenContainer, WellenSignalAccessor,
+};
+
+/// Waveform file in a [`MergedContainer`]
+#[derive(Debug)]
+pub struct MergedFile {
+    /// Root scope the hierarchy of the file is placed under
+    pub name: String,
+    pub path: Utf8PathBuf,
+    pub waves: WellenContainer,
+}
+
+#[derive(Debug)]
+pub struct MergedContainer {
+    /// The first file, whose scopes are not renamed
+    primary: WellenContainer,
+    files: Vec<MergedFile>,
+    /// Commands to load signals of several files are handed out one at a time, the others
+    /// wait here until the next call which may return a command
+    #[debug(skip)]
+    pending_cmds: VecDeque<LoadSignalsCmd>,
+}
+
+/// Name based on `stem` which is not in `taken`
+#[must_use]
+pub fn unique_scope_name(stem: &str, taken: &[String]) -> String {
+    (1..)
+        .map(|n| {
+            if n == 1 {
+                stem.to_string()
+            } else {
+                format!("{stem}_{n}")
+            }
+        })
+        .find(|name| !taken.contains(name))
+        .unwrap()
+}
+
+impl MergedContainer {
+    #[must_use]
+    pub fn new(primary: WellenContainer) -> Self {
+        Self {
+            primary,
+            files: vec![],
+            pending_cmds: VecDeque::new(),
+        }
+    }
+
+    /// Paths of the added files
+    #[must_use]
+    pub fn merged_files(&self) -> Vec<Utf8PathBuf> {
+        self.files.iter().map(|file| file.path.clone()).collect()
+    }
+
+    /// Add the waves read from `path` under a root scope named after the file, replacing the
+    /// waves of a previously added file with the same path. Returns the name of the root scope.
+    pub fn add_file(&mut self, path: Utf8PathBuf, waves: WellenContainer) -> String {
+        if let Some(file) = self.files.iter_mut().find(|file| file.path == path) {
+            file.waves = waves;
+            return file.name.clone();
+        }
+        let taken: Vec<_> = self
+            .primary
+            .root_scopes()
+            .iter()
+            .map(ScopeRefExt::name)
+            .chain(self.files.iter().map(|file| file.name.clone()))
+            .collect();
+        let name = unique_scope_name(path.file_stem().unwrap_or("merged"), &taken);
+        self.files.push(MergedFile {
+            name: name.clone(),
+            path,
+            waves,
+        });
+        name
+    }
+
+    /// Replace the first file by `primary`, keeping the added files and the commands to load
+    /// their signals
+    #[must_use]
+    pub fn with_primary(mut self, primary: WellenContainer) -> Self {
+        let old_id = self.primary.unique_id();
+        self.pending_cmds
+            .retain(|cmd| cmd.from_unique_id() != old_id);
+        self.primary = primary;
+        self
+    }
+
+    /// Index of the added file `strs` is in, if any, and the path inside that file
+    fn locate<'a>(&self, strs: &'a [String]) -> (Option<usize>, &'a [String]) {
+        strs.first()
+            .and_then(|first| self.files.iter().position(|file| &file.name == first))
+            .map_or((None, strs), |idx| (Some(idx), &strs[1..]))
+    }
+
+    fn waves(&self, file: Option<usize>) -> &WellenContainer {
+        file.map_or(&self.primary, |idx| &self.files[idx].waves)
+    }
+
+    fn inner_scope(&self, scope: &ScopeRef) -> (Option<usize>, ScopeRef) {
+        let (file, strs) = self.locate(&scope.strs);
+        (
+            file,
+            ScopeRef {
+                strs: strs.to_vec(),
+                id: scope.id.clone(),
+            },
+        )
+    }
+
+    fn inner_variable(&self, variable: &VariableRef) -> (Option<usize>, VariableRef) {
+        let (file, path) = self.inner_scope(&variable.path);
+        (
+            file,
+            VariableRef {
+                path,
+                ..variable.clone()
+            },
+        )
+    }
+
+    fn outer_scope(&self, file: Option<usize>, mut scope: ScopeRef) -> ScopeRef {
+        if let Some(idx) = file {
+            scope.strs.insert(0, self.files[idx].name.clone());
+        }
+        scope
+    }
+
+    fn outer_variable(&self, file: Option<usize>, variable: VariableRef) -> VariableRef {
+        VariableRef {
+            path: self.outer_scope(file, variable.path.clone()),
+            ..variable
+        }
+    }
+
+    fn all_waves(&self) -> impl Iterator<Item = (Option<usize>, &WellenContainer)> {
+        std::iter::once((None, &self.primary)).chain(
+            self.files
+                .iter()
+                .enumerate()
+                .map(|(idx, file)| (Some(idx), &file.waves)),
+        )
+    }
+
+    fn all_waves_mut(&mut self) -> impl Iterator<Item = &mut WellenContainer> {
+        std::iter::once(&mut self.primary).chain(self.files.iter_mut().map(|file| &mut file.waves))
+    }
+
+    /// Queue `cmd` and return the command to dispatch next
+    fn queue_cmds(
+        &mut self,
+        cmds: impl IntoIterator<Item = Option<LoadSignalsCmd>>,
+    ) -> Option<LoadSignalsCmd> {
+        self.pending_cmds.extend(cmds.into_iter().flatten());
+        self.pending_cmds.pop_front()
+    }
+
+    #[must_use]
+    pub fn body_loaded(&self) -> bool {
+        self.all_waves().all(|(_, waves)| waves.body_loaded())
+    }
+
+    /// Add `body` to the first file, which is the only one that is loaded in two steps
+    pub fn add_body(&mut self, body: BodyResult) -> Result<Option<LoadSignalsCmd>> {
+        let cmd = self.primary.add_body(body)?;
+        Ok(self.queue_cmds([cmd]))
+    }
+
+    #[must_use]
+    pub fn metadata(&self) -> MetaData {
+        self.primary.metadata()
+    }
+
+    #[must_use]
+    pub fn header_fields(&self) -> Vec<(&'static str, String)> {
+        self.primary.header_fields()
+    }
+
+    #[must_use]
+    pub fn max_timestamp(&self) -> Option<BigUint> {
+        self.all_waves()
+            .filter_map(|(_, waves)| waves.max_timestamp())
+            .max()
+    }
+
+    #[must_use]
+    pub fn is_fully_loaded(&self) -> bool {
+        self.pending_cmds.is_empty() && self.all_waves().all(|(_, waves)| waves.is_fully_loaded())
+    }
+
+    #[must_use]
+    pub fn variable_names(&self) -> Vec<String> {
+        self.all_waves()
+            .flat_map(|(file, waves)| {
+                let prefix = file.map(|idx| format!("{}.", self.files[idx].name));
+                waves
+                    .variable_names()
+                    .into_iter()
+                    .map(move |name| format!("{}{name}", prefix.as_deref().unwrap_or_default()))
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn scope_names(&self) -> Vec<String> {
+        self.primary
+            .scope_names()
+            .into_iter()
+            .chain(self.files.iter().flat_map(|file| {
+                std::iter::once(file.name.clone()).chain(
+                    file.waves
+                        .scope_names()
+                        .into_iter()
+                        .map(|name| format!("{}.{name}", file.name)),
+                )
+            }))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn variables(&self) -> Vec<VariableRef> {
+        self.all_waves()
+            .flat_map(|(file, waves)| {
+                waves
+                    .variables()
+                    .into_iter()
+                    .map(move |variable| self.outer_variable(file, variable))
+            })
+            .collect()
+    }
+
+    #[must_use]
+    pub fn variables_in_scope(&self, scope: &ScopeRef) -> Vec<VariableRef> {
+        let (file, inner) = self.inner_scope(scope);
+        self.waves(file)
+            .variables_in_scope(&inner)
+            .into_iter()
+            .map(|variable| self.outer_variable(file, variable))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn parameters_in_scope(&self, scope: &ScopeRef) -> Vec<VariableRef> {
+        let (file, inner) = self.inner_scope(scope);
+        self.waves(file)
+            .parameters_in_scope(&inner)
+            .into_iter()
+            .map(|variable| self.outer_variable(file, variable))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn no_variables_in_scope(&self, scope: &ScopeRef) -> bool {
+        let (file, inner) = self.inner_scope(scope);
+        self.waves(file).no_variables_in_scope(&inner)
+    }
+
+    pub fn load_variables<S: AsRef<VariableRef>, T: Iterator<Item = S>>(
+        &mut self,
+        variables: T,
+    ) -> Result<Option<LoadSignalsCmd>> {
+        let mut primary_variables = vec![];
+        let mut file_variables = vec![vec![]; self.files.len()];
+        for variable in variables {
+            match self.inner_variable(variable.as_ref()) {
+                (None, variable) => primary_variables.push(variable),
+                (Some(idx), variable) => file_variables[idx].push(variable),
+            }
+        }
+        let mut cmds = vec![self.primary.load_variables(primary_variables.iter())?];
+        for (file, variables) in self.files.iter_mut().zip(file_variables) {
+            cmds.push(file.waves.load_variables(variables.iter())?);
+        }
+        Ok(self.queue_cmds(cmds))
+    }
+
+    pub fn load_all_params(&mut self) -> Result<Option<LoadSignalsCmd>> {
+        let cmds = self
+            .all_waves_mut()
+            .map(WellenContainer::load_all_params)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(self.queue_cmds(cmds))
+    }
+
+    pub fn on_signals_loaded(&mut self, res: LoadSignalsResult) -> Result<Option<LoadSignalsCmd>> {
+        let from_unique_id = res.from_unique_id();
+        let cmd = match self
+            .all_waves_mut()
+            .find(|waves| waves.unique_id() == from_unique_id)
+        {
+            Some(waves) => waves.on_signals_loaded(res)?,
+            // the signals are from a file that has been replaced
+            None => None,
+        };
+        Ok(self.queue_cmds([cmd]))
+    }
+
+    pub fn variable_meta(&self, variable: &VariableRef) -> Result<VariableMeta> {
+        let (file, inner) = self.inner_variable(variable);
+        let mut meta = self.waves(file).variable_to_meta(&inner)?;
+        meta.var = variable.clone();
+        Ok(meta)
+    }
+
+    pub fn query_variable(
+        &self,
+        variable: &VariableRef,
+        time: &BigUint,
+    ) -> Result<Option<QueryResult>> {
+        let (file, inner) = self.inner_variable(variable);
+        self.waves(file).query_variable(&inner, time)
+    }
+
+    pub fn signal_accessor(&self, signal_id: SignalId) -> Result<WellenSignalAccessor> {
+        match signal_id {
+            SignalId::Wellen(signal_ref) => self.primary.signal_accessor(signal_ref),
+            SignalId::Merged(idx, signal_ref) => self
+                .files
+                .get(idx)
+                .ok_or_else(|| anyhow!("No merged file {idx}"))?
+                .waves
+                .signal_accessor(signal_ref),
+            SignalId::None => Err(anyhow!("Invalid signal accessor combination")),
+        }
+    }
+
+    pub fn signal_id(&self, variable: &VariableRef) -> Result<SignalId> {
+        let (file, inner) = self.inner_variable(variable);
+        let signal_ref = self.waves(file).signal_ref(&inner)?;
+        Ok(match file {
+            Some(idx) => SignalId::Merged(idx, signal_ref),
+            None => SignalId::Wellen(signal_ref),
+        })
+    }
+
+    #[must_use]
+    pub fn is_signal_loaded(&self, signal_id: &SignalId) -> bool {
+        match signal_id {
+            SignalId::Wellen(signal_ref) => self.primary.is_signal_loaded(*signal_ref),
+            SignalId::Merged(idx, signal_ref) => self
+                .files
+                .get(*idx)
+                .is_some_and(|file| file.waves.is_signal_loaded(*signal_ref)),
+            SignalId::None => false,
+        }
+    }
+
+    #[must_use]
+    pub fn update_variable_ref(&self, variable: &VariableRef) -> Option<VariableRef> {
+        let (file, inner) = self.inner_variable(variable);
+        self.waves(file)
+            .update_variable_ref(&inner)
+            .map(|variable| self.outer_variable(file, variable))
+    }
+
+    #[must_use]
+    pub fn root_scopes(&self) -> Vec<ScopeRef> {
+        self.primary
+            .root_scopes()
+            .into_iter()
+            .chain(
+                self.files
+                    .iter()
+                    .map(|file| ScopeRef::from_strs_with_id(&[&file.name], ScopeId::None)),
+            )
+            .collect()
+    }
+
+    pub fn child_scopes(&self, scope: &ScopeRef) -> Result<Vec<ScopeRef>> {
+        let (file, inner) = self.inner_scope(scope);
+        let children = if file.is_some() && inner.has_empty_strs() {
+            self.waves(file).root_scopes()
+        } else {
+            self.waves(file).child_scopes(&inner)?
+        };
+        Ok(children
+            .into_iter()
+            .map(|child| self.outer_scope(file, child))
+            .collect())
+    }
+
+    #[must_use]
+    pub fn scope_exists(&self, scope: &ScopeRef) -> bool {
+        let (file, inner) = self.inner_scope(scope);
+        self.waves(file).scope_exists(&inner)
+    }
+
+    #[must_use]
+    pub fn scope_is_variable(&self, scope: &ScopeRef) -> bool {
+        let (file, inner) = self.inner_scope(scope);
+        !inner.has_empty_strs() && self.waves(file).scope_is_variable(&inner)
+    }
+
+    #[must_use]
+    pub fn get_scope_tooltip_data(&self, scope: &ScopeRef) -> String {
+        match self.inner_scope(scope) {
+            (Some(idx), inner) if inner.has_empty_strs() => {
+                format!("Merged from {}", self.files[idx].path)
+            }
+            (file, inner) => self.waves(file).get_scope_tooltip_data(&inner),
+        }
+    }
+
+    #[must_use]
+    pub fn get_scope_type(&self, scope: &ScopeRef) -> Option<wellen::ScopeType> {
+        let (file, inner) = self.inner_scope(scope);
+        (!inner.has_empty_strs())
+            .then(|| self.waves(file).get_scope_type(&inner))
+            .flatten()
+    }
+
+    #[must_use]
+    pub fn get_scope_component(&self, scope: &ScopeRef) -> Option<String> {
+        let (file, inner) = self.inner_scope(scope);
+        (!inner.has_empty_strs())
+            .then(|| self.waves(file).get_scope_component(&inner))
+            .flatten()
+    }
+
+    /// Only the first file can be loaded from a remote server, so only its variables are
+    /// refreshed
+    pub fn refresh_variables<S: AsRef<VariableRef>, T: Iterator<Item = S>>(
+        &mut self,
+        variables: T,
+    ) -> Result<Option<LoadSignalsCmd>> {
+        let primary_variables: Vec<_> = variables
+            .filter_map(|variable| match self.inner_variable(variable.as_ref()) {
+                (None, variable) => Some(variable),
+                (Some(_), _) => None,
+            })
+            .collect();
+        let cmd = self.primary.refresh_variables(primary_variables.iter())?;
+        Ok(self.queue_cmds([cmd]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigInt;
+    use project_root::get_project_root;
+
+    use super::*;
+    use crate::SystemState;
+    use crate::displayed_item::DisplayedItem;
+    use crate::message::Message;
+    use crate::tests::fixtures::{load_counter_vcd, wait_for_waves_fully_loaded};
+    use crate::wave_container::VariableRefExt;
+    use crate::wave_source::LoadOptions;
+
+    #[test]
+    fn scope_names_do_not_collide() {
+        let taken = vec!["tb".to_string(), "dut".to_string(), "dut_2".to_string()];
+        assert_eq!(unique_scope_name("counter", &taken), "counter");
+        assert_eq!(unique_scope_name("tb", &taken), "tb_2");
+        assert_eq!(unique_scope_name("dut", &taken), "dut_3");
+    }
+
+    #[test]
+    fn merged_file_is_added_under_its_own_scope() {
+        let mut state = load_counter_vcd();
+        // The merged file is changed before reloading, so it is copied
+        let dir = tempfile::tempdir().unwrap();
+        let merged_file = Utf8PathBuf::try_from(dir.path().join("counter2.vcd")).unwrap();
+        std::fs::copy(
+            get_project_root().unwrap().join("examples/counter2.vcd"),
+            &merged_file,
+        )
+        .unwrap();
+        state.update(Message::LoadAdditionalFile(
+            merged_file.clone(),
+            LoadOptions::KeepAvailable,
+        ));
+        let root_scopes = |state: &SystemState| -> Vec<String> {
+            state
+                .user
+                .waves
+                .as_ref()
+                .and_then(|waves| waves.inner.as_waves())
+                .map(|waves| waves.root_scopes().iter().map(ScopeRef::name).collect())
+                .unwrap_or_default()
+        };
+        let load_start = std::time::Instant::now();
+        while root_scopes(&state).len() < 2 {
+            state.handle_async_messages();
+            assert!(load_start.elapsed().as_secs() < 10, "Timeout after 10s!");
+        }
+
+        assert_eq!(root_scopes(&state), vec!["tb", "counter2"]);
+        let waves = state.user.waves.as_ref().unwrap();
+        let child_scopes: Vec<_> = waves
+            .inner
+            .as_waves()
+            .unwrap()
+            .child_scopes(&ScopeRef::from_strs(&["counter2"]))
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(child_scopes, vec!["counter2.tb"]);
+        assert_eq!(waves.num_timestamps(), Some(BigInt::from(800)));
+
+        let counter = VariableRef::from_hierarchy_string("tb.dut.counter");
+        let merged_counter = VariableRef::from_hierarchy_string("counter2.tb.dut.counter");
+        state.update(Message::AddVariables(vec![
+            counter.clone(),
+            merged_counter.clone(),
+        ]));
+        wait_for_waves_fully_loaded(&mut state, 10);
+
+        let value_at = |state: &SystemState, variable: &VariableRef, time: u32| {
+            state
+                .user
+                .waves
+                .as_ref()?
+                .inner
+                .as_waves()?
+                .query_variable(variable, &BigUint::from(time))
+                .ok()??
+                .current
+        };
+        // counter2.vcd is the start of counter.vcd, ending at 260
+        assert_eq!(
+            value_at(&state, &merged_counter, 270),
+            value_at(&state, &counter, 250)
+        );
+        assert_eq!(
+            value_at(&state, &merged_counter, 270).map(|(time, _)| time),
+            Some(BigUint::from(250u32))
+        );
+        assert_eq!(
+            value_at(&state, &counter, 270).map(|(time, _)| time),
+            Some(BigUint::from(270u32))
+        );
+
+        // Reloading reads both files again and keeps the variables of the merged one
+        let mut appended = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&merged_file)
+            .unwrap();
+        std::io::Write::write_all(&mut appended, b"#270\nb1111 $\n").unwrap();
+        state.update(Message::ReloadWaveform(false));
+        let load_start = std::time::Instant::now();
+        while !(state.waves_fully_loaded()
+            && value_at(&state, &merged_counter, 270).map(|(time, _)| time)
+                == Some(BigUint::from(270u32)))
+        {
+            state.handle_async_messages();
+            assert!(load_start.elapsed().as_secs() < 10, "Timeout after 10s!");
+        }
+        let waves = state.user.waves.as_ref().unwrap();
+        let displayed: Vec<_> = waves
+            .items_tree
+            .iter()
+            .filter_map(|node| match waves.displayed_items.get(&node.item_ref) {
+                Some(DisplayedItem::Variable(variable)) => {
+                    Some(variable.variable_ref.full_path_string())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(displayed, vec!["tb.dut.counter", "counter2.tb.dut.counter"]);
+        assert_eq!(
+            value_at(&state, &merged_counter, 260),
+            value_at(&state, &counter, 250)
+        );
+    }
+}
@@ -10,11 +10,17 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use surver::SurverStatus;
 
+use crate::activity_histogram::HistogramScope;
+use crate::assertion::Assertion;
 use crate::async_util::AsyncJob;
-use crate::config::{PrimaryMouseDrag, TransitionValue};
+use crate::change_summary::ChangeSummarySort;
+use crate::color_scheme::{ItemScheme, Matcher};
+use crate::config::{ItemColumnSide, PrimaryMouseDrag, TransitionValue, ValueTextAlign};
 use crate::displayed_item_tree::{ItemIndex, VisibleItemIndex};
+use crate::enum_stats::EnumStatsRange;
 use crate::graphics::{Graphic, GraphicId};
 use crate::hierarchy::{ParameterDisplayLocation, ScopeExpandType};
+use crate::hierarchy_export::HierarchyFormat;
 use crate::state::UserState;
 use crate::transaction_container::{
     StreamScopeRef, TransactionContainer, TransactionRef, TransactionStreamRef,
@@ -26,16 +32,16 @@ use crate::{
     MoveDir, VariableNameFilterType, WaveSource,
     clock_highlighting::ClockHighlightType,
     config::ArrowKeyBindings,
-    dialog::{OpenSiblingStateFileDialog, ReloadWaveformDialog},
-    displayed_item::{DisplayedFieldRef, DisplayedItemRef},
+    dialog::{AddMarkerDialog, OpenSiblingStateFileDialog, ReloadWaveformDialog},
+    displayed_item::{DisplayedFieldRef, DisplayedItemRef, LeadingZeroMode, NumericFormatOptions},
     file_dialog::OpenMode,
     hierarchy::HierarchyStyle,
-    time::{TimeStringFormatting, TimeUnit},
+    time::{Radix, TimeStringFormatting, TimeUnit},
     variable_filter::VariableIOFilterType,
     variable_name_type::VariableNameType,
-    wave_container::{AnalogCacheKey, ScopeRef, VariableRef, WaveContainer},
+    wave_container::{AnalogCacheKey, ScopeRef, VariableRef, VariableRefExt, WaveContainer},
     wave_source::{CxxrtlKind, LoadOptions, WaveFormat},
-    wellen::{BodyResult, HeaderResult, LoadSignalsResult},
+    wellen::{BodyResult, HeaderResult, LoadSignalsResult, WellenContainer},
 };
 
 type CommandCount = usize;
@@ -67,12 +73,47 @@ impl<T> From<Option<T>> for MessageTarget<T> {
 
 impl<T: Copy> Copy for MessageTarget<T> {}
 
+/// How far [`Message::MoveCursorBy`] moves the cursor
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub enum CursorStep {
+    /// To the next or previous transition of the focused variable
+    Transition { next: bool },
+    /// By a fixed amount of time, backwards if negative
+    Time(BigInt),
+}
+
 #[derive(Debug, Deserialize)]
 /// The design of Surfer relies on sending messages to trigger actions.
 pub enum Message {
     /// Set active scope, None corresponds to the top-level scope.
     SetActiveScope(Option<ScopeType>),
     ExpandScope(ScopeExpandType),
+    /// Record whether a scope is expanded in the hierarchy panel, to restore it with the state.
+    SetScopeExpanded(ScopeRef, bool),
+    SetHierarchyScrollOffset(f32),
+    /// Make the scope of the given (or focused) variable active, expand its ancestors and
+    /// highlight the variable in the hierarchy panel.
+    RevealInHierarchy(Option<VisibleItemIndex>),
+    /// Log the names of all translators and, if a variable is focused, whether each of them
+    /// can translate it.
+    ListTranslatorsForFocused,
+    /// Show the value of the variable at the cursor in the watch window, without adding it to
+    /// the waveform view.
+    AddToWatch(VariableRef),
+    RemoveFromWatch(VariableRef),
+    /// Set `key` to `value` in the configuration of the translator called `name`. The
+    /// configuration is saved in the state file and passed to the translator when it is loaded.
+    SetTranslatorConfig(String, String, String),
+    /// Count the transitions of the variables in `scope` in `buckets` equally wide time
+    /// ranges covering the whole waveform, and show the result in the activity window.
+    ComputeActivityHistogram {
+        scope: HistogramScope,
+        buckets: usize,
+    },
+    /// Write the last computed activity histogram as CSV. Opens a save dialog if no path is
+    /// given.
+    SaveActivityHistogram(Option<Utf8PathBuf>),
+    CloseActivityHistogram,
     /// Add one or more variables to wave view.
     AddVariables(Vec<VariableRef>),
     /// Add scope to wave view. If second argument is true, add subscopes recursively.
@@ -93,15 +134,26 @@ pub enum Message {
     ItemSelectRange(VisibleItemIndex),
     /// Select all waves/items.
     ItemSelectAll,
+    /// Select the visible items matched by the matcher and deselect all others.
+    SelectItemsMatching(Matcher),
     SetItemSelected(VisibleItemIndex, bool),
     /// Unfocus a wave/item.
     UnfocusItem,
+    /// Show only the focused item, stretched to fill the item list, or restore the normal layout.
+    ToggleFocusedItemMaximized,
     MoveFocus(MoveDir, CommandCount, bool),
     MoveFocusedItem(MoveDir, CommandCount),
+    /// Exchange the positions of two items, moving groups together with their content.
+    SwapItems(VisibleItemIndex, VisibleItemIndex),
     FocusTransaction(Option<TransactionRef>, Option<Transaction>),
+    /// Add the transaction to the selection, or remove it if it is already selected.
+    ToggleTransactionSelected(TransactionRef),
+    ClearTransactionSelection,
     VerticalScroll(MoveDir, CommandCount),
     /// Scroll in vertical direction so that the item at a given location in the list is at the top (or visible).
     ScrollToItem(usize),
+    /// Scroll so that the focused item is vertically centered
+    CenterFocusedItem,
     SetScrollOffset(f32),
     /// Change format (translator) of a variable. Passing None as first element means all selected variables.
     VariableFormatChange(MessageTarget<DisplayedFieldRef>, String),
@@ -110,21 +162,65 @@ pub enum Message {
     ItemColorChange(MessageTarget<VisibleItemIndex>, Option<String>),
     /// Change background color of waves/items. If first argument is None, change for selected items. If second argument is None, change to default value.
     ItemBackgroundColorChange(MessageTarget<VisibleItemIndex>, Option<String>),
+    /// Color all displayed items matched by a rule, the first matching rule wins.
+    AutoColorItems(Vec<(Matcher, String)>),
     ItemNameChange(Option<VisibleItemIndex>, Option<String>),
     ItemNameReset(MessageTarget<VisibleItemIndex>),
     /// Change scaling factor/height of waves/items. If first argument is None, change for selected items.
     ItemHeightScalingFactorChange(MessageTarget<VisibleItemIndex>, f32),
+    /// Clear the height scaling factor of all displayed items.
+    ResetAllItemHeights,
+    /// Set the height scaling factor of all displayed items to the same value.
+    SetAllItemHeights(f32),
     /// Change variable name type of waves/items. If first argument is None, change for selected items.
     ChangeVariableNameType(MessageTarget<VisibleItemIndex>, VariableNameType),
     ForceVariableNameTypes(VariableNameType),
     /// Set or unset right alignment of names
     SetNameAlignRight(bool),
+    /// Place the item name and value columns on this side of the waveform view
+    SetItemColumnSide(ItemColumnSide),
     SetClockHighlightType(ClockHighlightType),
+    /// Turn clock highlighting off or back on without changing the highlight type. While off,
+    /// clock edges are not collected when drawing.
+    SetClockHighlightEnabled(bool),
     SetFillHighValues(bool),
     SetDinotraceStyle(bool),
+    SetShowNoDataHatch(bool),
+    SetShowGlitchDensity(bool),
+    /// Cross-hatch values where only some of the bits are undefined or high-impedance
+    SetShowPartialUnknowns(bool),
+    /// Show a trend line of the visible values next to the names of numeric variables
+    SetSparklinesVisible(bool),
     // Reset the translator for this variable back to default. Sub-variables,
     // i.e. those with the variable idx and a shared path are also reset
     ResetVariableFormat(DisplayedFieldRef),
+    /// Mark the times where the variable does not fulfill the assertion on the canvas.
+    /// Only the root field of a variable can have an assertion.
+    SetVariableAssertion(DisplayedFieldRef, Assertion),
+    RemoveVariableAssertion(DisplayedFieldRef),
+    /// Set the unit that is shown after numeric values of the variable, or remove it.
+    /// Only the root field of a variable can have a unit.
+    SetVariableUnit(DisplayedFieldRef, Option<String>),
+    /// Set the placement of the value text of the variable, which also applies to its fields.
+    SetValueTextAlign(DisplayedFieldRef, ValueTextAlign),
+    /// Show or hide the labels with the time since the previous transition of the variable.
+    ToggleIntervalLabels(DisplayedFieldRef),
+    /// Set how leading zeros of hexadecimal, octal and binary values of the variable are shown.
+    SetLeadingZeroMode(DisplayedFieldRef, LeadingZeroMode),
+    /// Set the formatting of decimal and floating point values of a variable, or of all
+    /// variables that do not set their own if no variable is given.
+    SetNumericFormatOptions(Option<DisplayedFieldRef>, NumericFormatOptions),
+    /// Split the bits of a variable into `int_bits` integer and `frac_bits` fraction bits
+    /// for the fixed point translators, instead of using the index of the variable.
+    SetFixedPointRadix {
+        item: DisplayedItemRef,
+        int_bits: u32,
+        frac_bits: u32,
+    },
+    /// Use the index of the variable for the fixed point translators again.
+    ResetFixedPointRadix(DisplayedItemRef),
+    /// Switch the ruler between absolute times and times relative to the cursor.
+    ToggleRelativeTimeRuler,
     CanvasScroll {
         delta: Vec2,
         viewport_idx: usize,
@@ -141,10 +237,16 @@ pub enum Message {
     },
     /// Set cursor at time.
     CursorSet(BigInt),
+    /// Set the trigger, the origin of relative times, or remove it
+    SetTriggerTime(Option<BigInt>),
     #[serde(skip)]
     SetSurverStatus(web_time::Instant, String, SurverStatus),
     /// Load file from file path.
     LoadFile(Utf8PathBuf, LoadOptions),
+    /// Load a waveform file from file path and merge it into the loaded waveform, under a new
+    /// root scope named after the file. Loading a file which was merged before replaces it,
+    /// with `LoadOptions::KeepAll` keeping its displayed variables which no longer exist.
+    LoadAdditionalFile(Utf8PathBuf, LoadOptions),
     /// Load file from URL.
     LoadWaveformFileFromUrl(String, LoadOptions),
     /// Load file from data.
@@ -162,6 +264,13 @@ pub enum Message {
     LoadCommandFromData(Vec<u8>),
     /// Load command file from URL.
     LoadCommandFileFromUrl(String),
+    /// Send `token` as bearer token when fetching `url`, or stop sending one. The token is
+    /// only kept in memory.
+    SetUrlToken {
+        url: String,
+        #[debug(skip)]
+        token: Option<String>,
+    },
     SetupCxxrtl(CxxrtlKind),
     #[serde(skip)]
     /// Message sent when waveform file header is loaded.
@@ -181,8 +290,18 @@ pub enum Message {
         #[debug(skip)] Box<WaveContainer>,
         LoadOptions,
     ),
+    /// Message sent when a waveform file to merge into the loaded one is loaded.
+    #[serde(skip)]
+    AdditionalWavesLoaded(
+        Utf8PathBuf,
+        LoadOptions,
+        #[debug(skip)] Box<WellenContainer>,
+    ),
     #[serde(skip)]
     SignalsLoaded(web_time::Instant, #[debug(skip)] LoadSignalsResult),
+    /// Fetch the signals of the displayed variables from the remote server again, updating
+    /// their data in place.
+    RefreshDisplayedSignals,
     #[serde(skip)]
     TransactionStreamsLoaded(
         WaveSource,
@@ -199,8 +318,13 @@ pub enum Message {
     BlacklistTranslator(VariableRef, String),
     HideCommandPrompt,
     ShowCommandPrompt(String, Option<String>),
+    /// Show the palette searching recently opened files, commands and variables.
+    ShowQuickOpen,
+    HideQuickOpen,
     /// Message sent when file is loadedropped onto Surfer.
     FileDropped(DroppedFile),
+    /// Message sent when several files are dropped onto Surfer at once.
+    FilesDropped(Vec<DroppedFile>),
     #[serde(skip)]
     /// Message sent when download of a waveform file is complete.
     FileDownloaded(String, Bytes, LoadOptions),
@@ -209,6 +333,9 @@ pub enum Message {
     CommandFileDownloaded(String, Bytes),
     ReloadConfig,
     ReloadWaveform(bool),
+    /// Reload the waveform like `ReloadWaveform`, then move the cursor, markers and viewports
+    /// back to the times they were at, clamped to the time range of the reloaded file.
+    ReloadPreservingView,
     /// Suggest reloading the current waveform as the file on disk has changed.
     /// This should first take the user's confirmation before reloading the waveform.
     /// However, there is a configuration setting that the user can overwrite.
@@ -273,6 +400,8 @@ pub enum Message {
     SetTimeUnit(TimeUnit),
     /// Set how to format the time strings. Passing None resets it to default.
     SetTimeStringFormatting(Option<TimeStringFormatting>),
+    /// Set the number base of the time labels of the ticks.
+    SetTimeLabelRadix(Radix),
     SetHighlightFocused(bool),
     CommandPromptClear,
     CommandPromptUpdate {
@@ -288,6 +417,44 @@ pub enum Message {
     #[cfg(feature = "python")]
     ReloadPythonPlugin,
     SaveStateFile(Option<PathBuf>),
+    /// Write the configuration, with runtime changes to settings applied, as a TOML file.
+    /// Opens a save dialog if no path is given.
+    ExportEffectiveConfig(Option<Utf8PathBuf>),
+    /// Write the color, format and height overrides of the displayed variables, keyed by
+    /// full path, to a scheme file. Opens a save dialog if no path is given.
+    SaveScheme(Option<PathBuf>),
+    /// Read a scheme file and apply it to the displayed variables with matching full paths.
+    /// Opens a file dialog if no path is given.
+    ApplyScheme(Option<PathBuf>),
+    /// Apply a loaded scheme to the displayed variables with matching full paths and report
+    /// the paths that did not match.
+    ApplyItemScheme(ItemScheme),
+    /// Write all scopes and variables of the loaded waveform, with their widths and types.
+    /// Opens a save dialog if no path is given.
+    ExportHierarchy {
+        path: Option<Utf8PathBuf>,
+        format: HierarchyFormat,
+    },
+    /// Write the waveforms of the first viewport, with item names and timeline, as an SVG
+    /// image. Opens a save dialog if no path is given.
+    ExportSvg(Option<Utf8PathBuf>),
+    /// Write the ids, times, attributes and relations of the transactions in `selection` as
+    /// JSON. An empty `selection` exports the selected transactions, or the focused transaction
+    /// if none are selected. Opens a save dialog if no path is given.
+    ExportTransactionsJson {
+        path: Option<Utf8PathBuf>,
+        selection: Vec<TransactionRef>,
+    },
+    /// Write the id, name and time of all markers as CSV, with times in the displayed time
+    /// unit. On wasm, the file is downloaded instead.
+    ExportMarkers(Utf8PathBuf),
+    /// Read markers in the format of [`Message::ExportMarkers`] and move or add the markers
+    /// with the same ids. Rows that cannot be parsed or are outside the waveform are skipped.
+    ImportMarkers(Utf8PathBuf),
+    /// Write the displayed variables in the visible time range as VCD, together with a state
+    /// file opening it, to the directory at the path. On wasm, the files are downloaded as a zip
+    /// archive instead.
+    ExportReproBundle(Utf8PathBuf),
     /// Load state from data.
     /// Note: the internal state is not a stable format and this should not be
     /// relied on to work across revisions.
@@ -317,9 +484,18 @@ pub enum Message {
     SetPerformanceVisible(bool),
     SetContinuousRedraw(bool),
     SetCursorWindowVisible(bool),
+    /// Show the header fields, timescale and other information about the loaded file.
+    SetWaveMetadataVisible(bool),
+    /// Show the list of events, see [`Message::AddEvent`].
+    SetEventsWindowVisible(bool),
     SetHierarchyStyle(HierarchyStyle),
+    /// Group sibling scopes which are instances of the same module in the hierarchy
+    SetGroupScopesByType(bool),
     SetArrowKeyBindings(ArrowKeyBindings),
     SetPrimaryMouseDragBehavior(PrimaryMouseDrag),
+    /// Round the times of new markers and events to multiples of this, in the current time
+    /// unit. 0 turns the grid off.
+    SetPlacementGrid(BigInt),
     // Second argument is position to insert after, None inserts after focused item,
     // or last if no focused item
     AddDivider(Option<String>, Option<VisibleItemIndex>),
@@ -329,8 +505,34 @@ pub enum Message {
     AddMarker {
         time: BigInt,
         name: Option<String>,
+        /// Index of the new marker, the lowest unused index if not set
+        #[serde(default)]
+        idx: Option<u8>,
         move_focus: bool,
     },
+    /// Show a dialog for adding a marker with a name, index and color, placed at the cursor
+    /// or at the center of the view if the cursor is not set.
+    ShowAddMarkerDialog,
+    /// Close the add marker dialog, adding the marker it describes if `add_marker` is true.
+    #[serde(skip)]
+    CloseAddMarkerDialog {
+        add_marker: bool,
+    },
+    /// Update the add marker dialog UI with the provided dialog model.
+    #[serde(skip)]
+    UpdateAddMarkerDialog(AddMarkerDialog),
+    /// Add a marker at the cursor named by the prefix followed by the next unused number,
+    /// e.g. `event 1`, `event 2`. Does nothing if the cursor is not set.
+    AddMarkerAtCursorAutoNamed(String),
+    /// Add a named event remembering a variable and a time.
+    AddEvent {
+        name: String,
+        variable: VariableRef,
+        time: BigInt,
+    },
+    /// Focus the variable of the event with the specified index and go to its time.
+    GoToEvent(usize),
+    RemoveEvent(usize),
     /// Set a marker at a specific position. If it doesn't exist, it will be created
     SetMarker {
         id: u8,
@@ -338,20 +540,82 @@ pub enum Message {
     },
     /// Remove marker.
     RemoveMarker(u8),
+    /// Set the color of a marker. `None` resets it to the default.
+    SetMarkerColor(u8, Option<String>),
+    /// Show the values of all displayed variables at the cursor and at the marker with the
+    /// given index side by side.
+    ShowCursorMarkerCompare(u8),
+    CloseCursorMarkerCompare,
+    /// Count the changes of every displayed variable between the markers with the given
+    /// indices, and show the variables that changed, most active first.
+    SummarizeChangesBetween(u8, u8),
+    SortChangeSummary(ChangeSummarySort),
+    CloseChangeSummary,
+    /// Accumulate how long and how often the field holds each of its translated values in
+    /// the range, with undefined and high impedance values in a separate bucket.
+    ComputeEnumStats(DisplayedFieldRef, EnumStatsRange),
+    CloseEnumStats,
     /// Set or move a marker to the position of the current cursor.
     MoveMarkerToCursor(u8),
+    /// Move all markers by a signed number of ticks, keeping them within the waveform.
+    ShiftAllMarkers(BigInt),
+    /// Move all markers by the same amount so that the marker with the given index ends up
+    /// at the cursor.
+    AlignMarkerToCursor(u8),
     /// Scroll in horizontal direction so that the cursor is visible.
     GoToCursorIfNotInView,
     GoToMarkerPosition(u8, usize),
+    /// Move the cursor to the first time the variable is non-zero. Values with `x`, `z` or
+    /// other non-0/1 bits are skipped, unless `unknown_is_nonzero` is set.
+    GoToFirstNonZero {
+        variable: DisplayedFieldRef,
+        unknown_is_nonzero: bool,
+    },
+    /// Move the cursor to the first or last transition of the variable.
+    CursorToSignalEdge {
+        first: bool,
+        variable: DisplayedFieldRef,
+    },
     MoveCursorToTransition {
         next: bool,
         variable: Option<VisibleItemIndex>,
         skip_zero: bool,
     },
+    /// Move the cursor to the next or previous time the translated value of the field changes.
+    /// Unlike [`Message::MoveCursorToTransition`], changes of the raw value that translate to
+    /// the same value are skipped.
+    MoveCursorToValueChange {
+        variable: DisplayedFieldRef,
+        next: bool,
+    },
+    /// Print the metadata and current translator of the variable to the logs.
+    PrintVariableInfo(DisplayedFieldRef),
+    /// Move the cursor by a step and scroll the first viewport so that it stays visible.
+    /// Without a cursor, steps start at the left edge of the view, or at the right edge when
+    /// going backwards.
+    MoveCursorBy {
+        step: CursorStep,
+    },
+    /// Focus the transaction with the specified id and center the first viewport on its start.
+    GoToTransactionById(u64),
     MoveTransaction {
         next: bool,
     },
     VariableValueToClipbord(MessageTarget<VisibleItemIndex>),
+    /// Keep the value at the cursor of the variable, or of the focused item if none is given,
+    /// and show it next to its name as a reference while the cursor moves.
+    CaptureItemValue(Option<VisibleItemIndex>),
+    /// Stop showing the captured value of the variable, or of the focused item if none is
+    /// given.
+    ClearCapturedValue(Option<VisibleItemIndex>),
+    /// Copy the names and values at the cursor of the selected variables as tab separated rows,
+    /// optionally preceded by a header row.
+    CopySelectedValuesTable {
+        header: bool,
+    },
+    /// Copy the names and values at the cursor of all visible variables as tab separated rows,
+    /// in the order they are displayed.
+    CopyAllValuesAtCursor,
     VariableNameToClipboard(MessageTarget<VisibleItemIndex>),
     VariableFullNameToClipboard(MessageTarget<VisibleItemIndex>),
     InvalidateDrawCommands,
@@ -375,10 +639,22 @@ pub enum Message {
         item: DisplayedItemRef,
         levels: usize,
     },
+    /// Expand all displayed compound variables `levels` layers deep, the same as
+    /// [`Message::ExpandDrawnItem`]. 0 collapses them completely
+    ExpandAllToDepth(usize),
     SetAnalogSettings(
         MessageTarget<VisibleItemIndex>,
         Option<crate::displayed_item::AnalogSettings>,
     ),
+    /// Set the line width and point markers of an analog trace.
+    SetAnalogStyle(DisplayedFieldRef, crate::displayed_item::AnalogStyle),
+    /// Set how undefined (X) and high-impedance (Z) values are drawn in an analog trace.
+    SetAnalogUndefMode(DisplayedFieldRef, crate::displayed_item::AnalogUndefMode),
+    /// Switch the given (or focused) numeric variable between digital and analog rendering.
+    ToggleAnalogDigital(Option<VisibleItemIndex>),
+    /// Hide the given (or focused) item from the waveform view, or show it again. Hidden items
+    /// keep their place in the item list.
+    ToggleItemHidden(Option<VisibleItemIndex>),
     BuildAnalogCache {
         display_id: DisplayedItemRef,
         cache_key: AnalogCacheKey,
@@ -399,6 +675,17 @@ pub enum Message {
     Batch(Vec<Message>),
     AddViewport,
     RemoveViewport,
+    /// Show the time range of viewport `source` in all other viewports.
+    SyncViewports {
+        source: usize,
+    },
+    /// Link the viewports such that scrolling or zooming one moves all of them.
+    SetViewportsLinked(bool),
+    /// Recenter viewport `viewport_idx` on the cursor whenever the cursor is out of view.
+    SetViewportFollowCursor {
+        viewport_idx: usize,
+        follow: bool,
+    },
     /// Select Theme
     SelectTheme(Option<String>),
     /// Enable animations
@@ -420,12 +707,17 @@ pub enum Message {
     GroupUnfoldRecursive(Option<DisplayedItemRef>),
     GroupFoldAll,
     GroupUnfoldAll,
+    /// Fold all groups except the ones containing the focused item. Folds all groups if no
+    /// item is focused.
+    CollapseAllExceptFocused,
     /// WCP Server
     StartWcpServer {
         address: Option<String>,
         initiate: bool,
     },
     StopWcpServer,
+    /// Copy the address of the remote waveform or the running WCP server to the clipboard
+    CopyServerConnectionInfo,
     /// Configures the WCP system to listen for messages over internal channels.
     /// This is used to start WCP on wasm
     SetupChannelWCP,
@@ -436,3 +728,46 @@ pub enum Message {
     ExpandParameterSection,
     AsyncDone(AsyncJob),
 }
+
+impl Message {
+    /// Load a waveform from `url`, replacing the currently loaded one
+    #[must_use]
+    pub fn load_url(url: String) -> Self {
+        Message::LoadWaveformFileFromUrl(url, LoadOptions::Clear)
+    }
+
+    /// Add the variable with the full hierarchical `path`, e.g., `top.cpu.clk`
+    #[must_use]
+    pub fn add_variable(path: &str) -> Self {
+        Message::AddVariables(vec![VariableRef::from_hierarchy_string(path)])
+    }
+
+    /// Run one or more commands, separated by `;` or newlines, as in a command file
+    #[must_use]
+    pub fn run_commands(commands: String) -> Self {
+        Message::LoadCommandFromData(commands.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn web_api_messages() {
+        assert!(matches!(
+            Message::load_url("https://example.com/counter.vcd".to_string()),
+            Message::LoadWaveformFileFromUrl(url, LoadOptions::Clear)
+                if url == "https://example.com/counter.vcd"
+        ));
+        assert!(matches!(
+            Message::add_variable("top.cpu.clk"),
+            Message::AddVariables(variables)
+                if variables == [VariableRef::from_hierarchy_string("top.cpu.clk")]
+        ));
+        assert!(matches!(
+            Message::run_commands("variable_add top.clk; zoom_fit".to_string()),
+            Message::LoadCommandFromData(commands) if commands == b"variable_add top.clk; zoom_fit"
+        ));
+    }
+}
@@ -3,7 +3,7 @@ use derive_more::Display;
 use egui::{Context, Painter, PointerButton, Response, RichText, Sense, Window};
 use emath::{Align2, Pos2, Rect, RectTransform, Vec2};
 use epaint::{FontId, Stroke};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::config::{SurferConfig, SurferTheme};
 use crate::time::TimeFormatter;
@@ -24,7 +24,7 @@ fn create_gesture_stroke(config: &SurferConfig, is_measure: bool) -> Stroke {
 }
 
 /// The supported mouse gesture operations.
-#[derive(Clone, PartialEq, Copy, Display, Debug, Deserialize)]
+#[derive(Clone, PartialEq, Copy, Display, Debug, Deserialize, Serialize)]
 enum GestureKind {
     #[display("Zoom to fit")]
     ZoomToFit,
@@ -40,7 +40,7 @@ enum GestureKind {
 }
 
 /// The supported mouse gesture zones.
-#[derive(Clone, PartialEq, Copy, Debug, Deserialize)]
+#[derive(Clone, PartialEq, Copy, Debug, Deserialize, Serialize)]
 pub struct GestureZones {
     north: GestureKind,
     northeast: GestureKind,
@@ -59,7 +59,8 @@ impl SystemState {
                     .rect_filled(rect, CornerRadius::ZERO, fill_color);
             });
 
-        // Draw cursor
+        // Draw trigger and cursor
+        waves.draw_trigger(&self.user.config.theme, &mut ctx, &viewport_all);
         waves.draw_cursor(&self.user.config.theme, &mut ctx, &viewport_all);
 
         // Draw ticks
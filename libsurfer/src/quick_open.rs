@@ -0,0 +1,357 @@
+//! Palette searching recently opened files, commands and variables from a single prompt.
+use std::collections::HashMap;
+use std::iter::zip;
+
+use camino::Utf8PathBuf;
+use derive_more::Display;
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Key, ScrollArea, TextEdit};
+use emath::{Align, Align2, Vec2};
+use epaint::{FontFamily, FontId};
+use itertools::Itertools;
+
+use crate::SystemState;
+use crate::command_parser::get_parser;
+use crate::command_prompt::SuggestionLabel;
+use crate::displayed_item::DisplayedItem;
+use crate::displayed_item_tree::VisibleItemIndex;
+use crate::fzcmd::{expand_command, fuzzy_score, parse_command};
+use crate::message::Message;
+use crate::wave_container::{VariableRef, VariableRefExt};
+use crate::wave_source::LoadOptions;
+
+/// Number of recently opened files offered in the palette
+pub const MAX_RECENT_FILES: usize = 10;
+/// Number of results listed in the palette
+const MAX_RESULTS: usize = 50;
+
+/// Kind of result, in the order used to break ties between equally good matches
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, PartialOrd, Ord)]
+pub enum QuickOpenCategory {
+    File,
+    Command,
+    Variable,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QuickOpenAction {
+    /// Load the file, replacing the current waveform
+    OpenFile(Utf8PathBuf),
+    /// Run the command, or open the command prompt with it if it needs arguments
+    RunCommand(String),
+    AddVariable(VariableRef),
+    /// Focus a variable that is already displayed
+    FocusItem(VisibleItemIndex),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuickOpenEntry {
+    pub label: String,
+    pub action: QuickOpenAction,
+}
+
+impl QuickOpenEntry {
+    #[must_use]
+    pub fn category(&self) -> QuickOpenCategory {
+        match self.action {
+            QuickOpenAction::OpenFile(_) => QuickOpenCategory::File,
+            QuickOpenAction::RunCommand(_) => QuickOpenCategory::Command,
+            QuickOpenAction::AddVariable(_) | QuickOpenAction::FocusItem(_) => {
+                QuickOpenCategory::Variable
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct QuickOpen {
+    pub visible: bool,
+    pub query: String,
+    pub selected: usize,
+    /// Entries to search, built when the palette is opened
+    pub entries: Vec<QuickOpenEntry>,
+}
+
+/// Entries matching `query`, best match first, together with the matched characters of their
+/// labels. Equally good matches are ordered by category and then by label length. An empty
+/// query keeps the entries in the given order.
+#[must_use]
+pub fn rank_quick_open_entries(
+    entries: &[QuickOpenEntry],
+    query: &str,
+) -> Vec<(QuickOpenEntry, Vec<bool>)> {
+    if query.is_empty() {
+        return entries
+            .iter()
+            .take(MAX_RESULTS)
+            .map(|entry| {
+                let matches = vec![false; entry.label.len()];
+                (entry.clone(), matches)
+            })
+            .collect();
+    }
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let (score, matches) = fuzzy_score(&entry.label, query);
+            (score > 0).then_some((score, entry, matches))
+        })
+        .sorted_by_key(|(score, entry, _)| (-*score, entry.category(), entry.label.len()))
+        .take(MAX_RESULTS)
+        .map(|(_, entry, matches)| (entry.clone(), matches))
+        .collect()
+}
+
+impl SystemState {
+    /// Remember `path` as the most recently opened file
+    pub(crate) fn push_recent_file(&mut self, path: &Utf8PathBuf) {
+        self.user.recent_files.retain(|recent| recent != path);
+        self.user.recent_files.insert(0, path.clone());
+        self.user.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Recently opened files, all commands and all variables of the loaded waveform
+    #[must_use]
+    pub fn quick_open_entries(&self) -> Vec<QuickOpenEntry> {
+        let files = self.user.recent_files.iter().map(|path| QuickOpenEntry {
+            label: path.to_string(),
+            action: QuickOpenAction::OpenFile(path.clone()),
+        });
+        let commands = expand_command("", get_parser(self))
+            .suggestions
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, _)| QuickOpenEntry {
+                label: name.clone(),
+                action: QuickOpenAction::RunCommand(name),
+            });
+        let variables = self
+            .user
+            .waves
+            .as_ref()
+            .and_then(|waves| {
+                let displayed = waves
+                    .items_tree
+                    .iter_visible()
+                    .enumerate()
+                    .filter_map(
+                        |(vidx, node)| match waves.displayed_items.get(&node.item_ref) {
+                            Some(DisplayedItem::Variable(variable)) => Some((
+                                variable.variable_ref.full_path_string(),
+                                VisibleItemIndex(vidx),
+                            )),
+                            _ => None,
+                        },
+                    )
+                    .collect::<HashMap<_, _>>();
+                let variables = waves.inner.as_waves()?.variables();
+                Some(
+                    variables
+                        .into_iter()
+                        .map(|variable| {
+                            let label = variable.full_path_string();
+                            let action = match displayed.get(&label) {
+                                Some(vidx) => QuickOpenAction::FocusItem(*vidx),
+                                None => QuickOpenAction::AddVariable(variable),
+                            };
+                            QuickOpenEntry { label, action }
+                        })
+                        .collect_vec(),
+                )
+            })
+            .unwrap_or_default();
+        files.chain(commands).chain(variables).collect()
+    }
+
+    /// Messages closing the palette and carrying out `action`
+    #[must_use]
+    pub fn quick_open_messages(&self, action: QuickOpenAction) -> Vec<Message> {
+        let message = match action {
+            QuickOpenAction::OpenFile(path) => Message::LoadFile(path, LoadOptions::Clear),
+            QuickOpenAction::RunCommand(name) => parse_command(&name, get_parser(self))
+                .unwrap_or_else(|_| Message::ShowCommandPrompt(format!("{name} "), None)),
+            QuickOpenAction::AddVariable(variable) => Message::AddVariables(vec![variable]),
+            QuickOpenAction::FocusItem(vidx) => Message::FocusItem(vidx),
+        };
+        vec![Message::HideQuickOpen, message]
+    }
+}
+
+pub fn show_quick_open(
+    state: &mut SystemState,
+    ctx: &egui::Context,
+    // Window size if known. If unknown defaults to a width of 200pts
+    window_size: Option<Vec2>,
+    msgs: &mut Vec<Message>,
+) {
+    if ctx.input(|i| i.key_pressed(Key::Escape)) {
+        msgs.push(Message::HideQuickOpen);
+        return;
+    }
+
+    let results = rank_quick_open_entries(&state.quick_open.entries, &state.quick_open.query);
+    let (up, down, enter) = ctx.input(|i| {
+        (
+            i.key_pressed(Key::ArrowUp),
+            i.key_pressed(Key::ArrowDown),
+            i.key_pressed(Key::Enter),
+        )
+    });
+    if down {
+        state.quick_open.selected =
+            (state.quick_open.selected + 1).min(results.len().saturating_sub(1));
+    }
+    if up {
+        state.quick_open.selected = state.quick_open.selected.saturating_sub(1);
+    }
+    if enter {
+        if let Some((entry, _)) = results.get(state.quick_open.selected) {
+            msgs.extend(state.quick_open_messages(entry.action.clone()));
+        }
+        return;
+    }
+
+    let theme = &state.user.config.theme;
+    let category_color = theme.primary_ui_color.foreground.gamma_multiply(0.5);
+    let text_color = theme.primary_ui_color.foreground;
+    let highlight_color = theme.accent_info.background;
+
+    egui::Window::new("Quick open")
+        .anchor(Align2::CENTER_TOP, Vec2::ZERO)
+        .title_bar(false)
+        .min_width(window_size.map_or(200., |s| s.x * 0.3))
+        .resizable(true)
+        .show(ctx, |ui| {
+            let response = ui.add(
+                TextEdit::singleline(&mut state.quick_open.query)
+                    .hint_text("Search files, commands and variables")
+                    .desired_width(f32::INFINITY)
+                    .lock_focus(true),
+            );
+            if response.changed() {
+                state.quick_open.selected = 0;
+            }
+            response.request_focus();
+
+            ScrollArea::vertical().max_height(400.).show(ui, |ui| {
+                for (idx, (entry, matches)) in results.iter().enumerate() {
+                    let selected = idx == state.quick_open.selected;
+                    let font_id = FontId::new(14.0, FontFamily::Monospace);
+                    let mut job = LayoutJob::default();
+                    job.append(
+                        &format!("{:<9}", entry.category().to_string()),
+                        0.0,
+                        TextFormat {
+                            font_id: font_id.clone(),
+                            color: category_color,
+                            ..Default::default()
+                        },
+                    );
+                    for (c, highlight) in zip(entry.label.chars(), matches) {
+                        let mut tmp = [0u8; 4];
+                        job.append(
+                            c.encode_utf8(&mut tmp),
+                            0.0,
+                            TextFormat {
+                                font_id: font_id.clone(),
+                                color: if selected || *highlight {
+                                    highlight_color
+                                } else {
+                                    text_color
+                                },
+                                ..Default::default()
+                            },
+                        );
+                    }
+
+                    let resp = ui.allocate_ui_with_layout(
+                        ui.available_size(),
+                        egui::Layout::top_down(Align::LEFT).with_cross_justify(true),
+                        |ui| ui.add(SuggestionLabel::new(job, selected)),
+                    );
+                    if selected && (up || down) {
+                        resp.response.scroll_to_me(Some(Align::Center));
+                    }
+                    if resp.inner.clicked() {
+                        msgs.extend(state.quick_open_messages(entry.action.clone()));
+                    }
+                }
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(label: &str, category: QuickOpenCategory) -> QuickOpenEntry {
+        let action = match category {
+            QuickOpenCategory::File => QuickOpenAction::OpenFile(Utf8PathBuf::from(label)),
+            QuickOpenCategory::Command => QuickOpenAction::RunCommand(label.to_string()),
+            QuickOpenCategory::Variable => {
+                QuickOpenAction::AddVariable(VariableRef::from_hierarchy_string(label))
+            }
+        };
+        QuickOpenEntry {
+            label: label.to_string(),
+            action,
+        }
+    }
+
+    fn labels(ranked: &[(QuickOpenEntry, Vec<bool>)]) -> Vec<(&str, QuickOpenCategory)> {
+        ranked
+            .iter()
+            .map(|(entry, _)| (entry.label.as_str(), entry.category()))
+            .collect()
+    }
+
+    #[test]
+    fn quick_open_ranks_mixed_categories_by_match() {
+        let entries = vec![
+            entry("tb.z_out_of_memory", QuickOpenCategory::Variable),
+            entry("goto_start", QuickOpenCategory::Command),
+            entry("zoom_fit", QuickOpenCategory::Command),
+            entry("zoom.vcd", QuickOpenCategory::File),
+        ];
+        let ranked = rank_quick_open_entries(&entries, "zoom");
+        assert_eq!(
+            labels(&ranked),
+            vec![
+                // Equally good matches, so files come before commands
+                ("zoom.vcd", QuickOpenCategory::File),
+                ("zoom_fit", QuickOpenCategory::Command),
+                ("tb.z_out_of_memory", QuickOpenCategory::Variable),
+            ]
+        );
+        assert_eq!(
+            ranked[0].1,
+            vec![true, true, true, true, false, false, false, false]
+        );
+    }
+
+    #[test]
+    fn quick_open_orders_ties_by_category() {
+        let entries = vec![
+            entry("counter", QuickOpenCategory::Variable),
+            entry("counter", QuickOpenCategory::Command),
+            entry("counter", QuickOpenCategory::File),
+        ];
+        assert_eq!(
+            labels(&rank_quick_open_entries(&entries, "cnt")),
+            vec![
+                ("counter", QuickOpenCategory::File),
+                ("counter", QuickOpenCategory::Command),
+                ("counter", QuickOpenCategory::Variable),
+            ]
+        );
+        // Without a query all entries are kept in order
+        assert_eq!(
+            labels(&rank_quick_open_entries(&entries, "")),
+            vec![
+                ("counter", QuickOpenCategory::Variable),
+                ("counter", QuickOpenCategory::Command),
+                ("counter", QuickOpenCategory::File),
+            ]
+        );
+    }
+}
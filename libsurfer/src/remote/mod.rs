@@ -1,6 +1,10 @@
 mod client;
 
 use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::SystemState;
+use crate::wave_source::WaveSource;
 
 pub use client::{
     ReloadError, get_hierarchy_from_server, get_server_status, get_signals,
@@ -12,3 +16,169 @@ pub struct HierarchyResponse {
     pub hierarchy: wellen::Hierarchy,
     pub file_format: wellen::FileFormat,
 }
+
+/// Address for someone else to connect to the same waveform
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServerConnectionInfo {
+    /// URL of the remote waveform, which contains the access token if it is served by a Surver
+    Remote { url: String, has_token: bool },
+    /// Address the WCP server listens on
+    Wcp(String),
+}
+
+impl ServerConnectionInfo {
+    /// A remote waveform takes precedence over a running WCP server. Only the URL of a Surver,
+    /// as indicated by `is_surver`, can contain an access token.
+    #[must_use]
+    pub fn new(
+        remote_url: Option<&str>,
+        is_surver: bool,
+        wcp_address: Option<&str>,
+    ) -> Option<Self> {
+        if let Some(url) = remote_url {
+            let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+            let has_token = is_surver
+                && without_scheme
+                    .split_once('/')
+                    .is_some_and(|(_, path)| !path.trim_matches('/').is_empty());
+            return Some(Self::Remote {
+                url: url.to_string(),
+                has_token,
+            });
+        }
+        wcp_address.map(|address| Self::Wcp(address.to_string()))
+    }
+
+    /// Text to copy, which for a remote waveform on the web is a link to `page` loading it
+    #[must_use]
+    pub fn share_text(&self, page: Option<&str>) -> String {
+        match (self, page) {
+            (Self::Remote { url, .. }, Some(page)) => {
+                format!("{page}?load_url={}", encode_query_value(url))
+            }
+            (Self::Remote { url, .. }, None) => url.clone(),
+            (Self::Wcp(address), _) => address.clone(),
+        }
+    }
+}
+
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                char::from(b).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+impl SystemState {
+    #[must_use]
+    pub fn server_connection_info(&self) -> Option<ServerConnectionInfo> {
+        let remote_url = self
+            .user
+            .waves
+            .as_ref()
+            .and_then(|waves| match &waves.source {
+                WaveSource::Url(url) => Some(url.as_str()),
+                _ => None,
+            });
+        let is_surver = self.user.surver_url.is_some() || self.user.surver_file_infos.is_some();
+        ServerConnectionInfo::new(remote_url, is_surver, self.wcp_server_address.as_deref())
+    }
+
+    pub(crate) fn copy_server_connection_info(&self) {
+        let Some(info) = self.server_connection_info() else {
+            warn!("Not connected to a remote server and no WCP server is running");
+            return;
+        };
+        if let ServerConnectionInfo::Remote {
+            has_token: true, ..
+        } = info
+        {
+            warn!(
+                "The copied URL contains the access token of the server, only share it with people who may see the waveform"
+            );
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        let page = web_sys::window().and_then(|window| {
+            let location = window.location();
+            Some(format!(
+                "{}{}",
+                location.origin().ok()?,
+                location.pathname().ok()?
+            ))
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        let page: Option<String> = None;
+
+        let text = info.share_text(page.as_deref());
+        if let Some(ctx) = &self.context {
+            ctx.copy_text(text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_info_of_remote_source() {
+        let info = ServerConnectionInfo::new(
+            Some("http://127.0.0.1:8911/ae42d3c0"),
+            true,
+            Some("127.0.0.1:54321"),
+        )
+        .unwrap();
+        assert_eq!(
+            info,
+            ServerConnectionInfo::Remote {
+                url: "http://127.0.0.1:8911/ae42d3c0".to_string(),
+                has_token: true,
+            }
+        );
+        assert_eq!(info.share_text(None), "http://127.0.0.1:8911/ae42d3c0");
+        assert_eq!(
+            info.share_text(Some("https://app.surfer-project.org/")),
+            "https://app.surfer-project.org/?load_url=http%3A%2F%2F127.0.0.1%3A8911%2Fae42d3c0"
+        );
+
+        let info = ServerConnectionInfo::new(Some("http://127.0.0.1:8911/"), true, None).unwrap();
+        assert_eq!(
+            info,
+            ServerConnectionInfo::Remote {
+                url: "http://127.0.0.1:8911/".to_string(),
+                has_token: false,
+            }
+        );
+
+        // Plain files on the web have no access token
+        let info = ServerConnectionInfo::new(Some("https://example.com/waves/a.vcd"), false, None)
+            .unwrap();
+        assert_eq!(
+            info,
+            ServerConnectionInfo::Remote {
+                url: "https://example.com/waves/a.vcd".to_string(),
+                has_token: false,
+            }
+        );
+    }
+
+    #[test]
+    fn connection_info_of_wcp_server() {
+        let info = ServerConnectionInfo::new(None, false, Some("127.0.0.1:54321")).unwrap();
+        assert_eq!(
+            info,
+            ServerConnectionInfo::Wcp("127.0.0.1:54321".to_string())
+        );
+        assert_eq!(
+            info.share_text(Some("https://app.surfer-project.org/")),
+            "127.0.0.1:54321"
+        );
+        assert_eq!(ServerConnectionInfo::new(None, false, None), None);
+    }
+}
@@ -0,0 +1,595 @@
+//! Bundle of the displayed variables in the visible time range as VCD, together with a state
+//! file showing them, to reproduce issues without sharing the full waveform.
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+
+use camino::Utf8PathBuf;
+use eyre::{Result, bail};
+use num::{BigUint, ToPrimitive};
+use surfer_translation_types::{VariableEncoding, VariableValue};
+use tracing::{error, warn};
+
+use crate::SystemState;
+use crate::displayed_item::DisplayedItem;
+use crate::time::{TimeScale, TimeUnit};
+use crate::wave_container::{ScopeRefExt, VariableRef, WaveContainer};
+use crate::wave_source::{STATE_FILE_EXTENSION, WaveSource};
+
+/// Name of the waveform in a bundle
+pub const BUNDLE_WAVES_FILE: &str = "waves.vcd";
+
+/// Name of the state file in a bundle
+#[must_use]
+pub fn bundle_state_file() -> String {
+    format!("state.{STATE_FILE_EXTENSION}")
+}
+
+/// Variable to write to a VCD file
+#[derive(Debug)]
+pub struct VcdVariable {
+    /// Names of the enclosing scopes, outermost first
+    pub scope: Vec<String>,
+    pub name: String,
+    /// Bit range such as `[3:0]`, if any
+    pub index: Option<String>,
+    pub num_bits: u32,
+    pub encoding: VariableEncoding,
+    /// Values with the time they start at, in increasing order
+    pub changes: Vec<(BigUint, VariableValue)>,
+}
+
+/// Identifier code of the variable with index `idx`, made of printable ASCII characters
+fn vcd_identifier(mut idx: usize) -> String {
+    let chars: Vec<char> = ('!'..='~').collect();
+    let mut id = String::new();
+    loop {
+        id.push(chars[idx % chars.len()]);
+        idx /= chars.len();
+        if idx == 0 {
+            return id;
+        }
+        idx -= 1;
+    }
+}
+
+/// Argument of the `$timescale` section for `timescale`, `None` if the section is left out
+fn vcd_timescale(timescale: &TimeScale) -> Result<Option<String>> {
+    let unit = match timescale.unit {
+        // The section is optional, and leaving it out keeps the times unitless on loading
+        TimeUnit::None => return Ok(None),
+        TimeUnit::Auto => bail!("The timescale of the waveform has no unit"),
+        TimeUnit::MicroSeconds => "us".to_string(),
+        unit => unit.to_string(),
+    };
+    Ok(Some(format!(
+        "{} {unit}",
+        timescale.multiplier.unwrap_or(1)
+    )))
+}
+
+/// Value change line setting `variable`, with identifier code `id`, to `value`. Fails for
+/// strings which are empty or contain whitespace, as VCD cannot represent them.
+fn vcd_value(value: &VariableValue, variable: &VcdVariable, id: &str) -> Result<String> {
+    let line = match variable.encoding {
+        VariableEncoding::Real => {
+            let real = match value {
+                VariableValue::BigUint(bits) => bits.to_u64().map_or(f64::NAN, f64::from_bits),
+                VariableValue::String(text) => text.parse().unwrap_or(f64::NAN),
+            };
+            format!("r{real} {id}")
+        }
+        VariableEncoding::String => {
+            let text = value.to_string();
+            if text.is_empty() || text.contains(char::is_whitespace) {
+                bail!(
+                    "The value {text:?} of {} cannot be written to VCD",
+                    variable.name
+                );
+            }
+            format!("s{text} {id}")
+        }
+        VariableEncoding::Event => format!("1{id}"),
+        VariableEncoding::BitVector => {
+            let bits = match value {
+                VariableValue::BigUint(number) => {
+                    format!("{number:0width$b}", width = variable.num_bits as usize)
+                }
+                VariableValue::String(bits) => bits.clone(),
+            };
+            if variable.num_bits == 1 && bits.len() == 1 {
+                format!("{bits}{id}")
+            } else {
+                format!("b{bits} {id}")
+            }
+        }
+    };
+    Ok(line)
+}
+
+/// VCD file with `variables`, grouped by scope. The values at the earliest change are written
+/// as the initial values. Fails if the unit of `timescale` or a string value cannot be written
+/// to VCD.
+pub fn write_vcd(variables: &[VcdVariable], timescale: &TimeScale) -> Result<String> {
+    let mut vcd = String::new();
+    let _ = writeln!(vcd, "$version Surfer $end");
+    if let Some(timescale) = vcd_timescale(timescale)? {
+        let _ = writeln!(vcd, "$timescale {timescale} $end");
+    }
+
+    let mut order: Vec<usize> = (0..variables.len()).collect();
+    order.sort_by(|a, b| variables[*a].scope.cmp(&variables[*b].scope));
+    let mut open_scopes: &[String] = &[];
+    for idx in order {
+        let variable = &variables[idx];
+        let common = open_scopes
+            .iter()
+            .zip(&variable.scope)
+            .take_while(|(open, scope)| open == scope)
+            .count();
+        for _ in common..open_scopes.len() {
+            let _ = writeln!(vcd, "$upscope $end");
+        }
+        for scope in &variable.scope[common..] {
+            let _ = writeln!(vcd, "$scope module {scope} $end");
+        }
+        open_scopes = &variable.scope;
+
+        let var_type = match variable.encoding {
+            VariableEncoding::Real => "real",
+            VariableEncoding::String => "string",
+            VariableEncoding::Event => "event",
+            VariableEncoding::BitVector => "wire",
+        };
+        let index = variable
+            .index
+            .as_ref()
+            .map(|index| format!(" {index}"))
+            .unwrap_or_default();
+        let _ = writeln!(
+            vcd,
+            "$var {var_type} {} {} {}{index} $end",
+            variable.num_bits,
+            vcd_identifier(idx),
+            variable.name
+        );
+    }
+    for _ in open_scopes {
+        let _ = writeln!(vcd, "$upscope $end");
+    }
+    let _ = writeln!(vcd, "$enddefinitions $end");
+
+    let mut changes: BTreeMap<&BigUint, Vec<String>> = BTreeMap::new();
+    for (idx, variable) in variables.iter().enumerate() {
+        let id = vcd_identifier(idx);
+        for (time, value) in &variable.changes {
+            changes
+                .entry(time)
+                .or_default()
+                .push(vcd_value(value, variable, &id)?);
+        }
+    }
+    for (idx, (time, values)) in changes.iter().enumerate() {
+        let _ = writeln!(vcd, "#{time}");
+        if idx == 0 {
+            let _ = writeln!(vcd, "$dumpvars");
+        }
+        for value in values {
+            let _ = writeln!(vcd, "{value}");
+        }
+        if idx == 0 {
+            let _ = writeln!(vcd, "$end");
+        }
+    }
+    Ok(vcd)
+}
+
+/// Values of `variable` from `start` to `end`, with values set before `start` moved to it
+fn variable_changes(
+    wave_container: &WaveContainer,
+    variable: &VariableRef,
+    start: &BigUint,
+    end: &BigUint,
+) -> Vec<(BigUint, VariableValue)> {
+    let mut changes = vec![];
+    let mut time = start.clone();
+    while let Ok(Some(result)) = wave_container.query_variable(variable, &time) {
+        if let Some((change_time, value)) = result.current {
+            changes.push((change_time.max(start.clone()), value));
+        }
+        match result.next {
+            Some(next) if next > time && &next <= end => time = next,
+            _ => break,
+        }
+    }
+    changes
+}
+
+/// CRC-32 checksum of `bytes`, as used by zip archives
+#[cfg(any(target_arch = "wasm32", test))]
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Zip archive storing `files`, given as names and contents, without compression
+#[cfg(any(target_arch = "wasm32", test))]
+fn zip_archive(files: &[(&str, &[u8])]) -> Vec<u8> {
+    // Version 2.0, no flags, stored, dated 1980-01-01 00:00
+    const FILE_HEADER: [u16; 5] = [20, 0, 0, 0, 33];
+    let mut archive = vec![];
+    let mut directory = vec![];
+    for (name, contents) in files {
+        let offset = archive.len() as u32;
+        let mut sizes = vec![];
+        sizes.extend(crc32(contents).to_le_bytes());
+        sizes.extend((contents.len() as u32).to_le_bytes());
+        sizes.extend((contents.len() as u32).to_le_bytes());
+        sizes.extend((name.len() as u16).to_le_bytes());
+        // No extra field
+        sizes.extend(0u16.to_le_bytes());
+
+        archive.extend(0x0403_4b50u32.to_le_bytes());
+        archive.extend(FILE_HEADER.iter().flat_map(|field| field.to_le_bytes()));
+        archive.extend(&sizes);
+        archive.extend(name.as_bytes());
+        archive.extend(*contents);
+
+        directory.extend(0x0201_4b50u32.to_le_bytes());
+        // Made by version 2.0
+        directory.extend(20u16.to_le_bytes());
+        directory.extend(FILE_HEADER.iter().flat_map(|field| field.to_le_bytes()));
+        directory.extend(&sizes);
+        // No comment, disk 0, no attributes
+        directory.extend([0; 10]);
+        directory.extend(offset.to_le_bytes());
+        directory.extend(name.as_bytes());
+    }
+    let directory_offset = archive.len() as u32;
+    let directory_size = directory.len() as u32;
+    archive.extend(directory);
+    archive.extend(0x0605_4b50u32.to_le_bytes());
+    // Single disk
+    archive.extend([0; 4]);
+    archive.extend((files.len() as u16).to_le_bytes());
+    archive.extend((files.len() as u16).to_le_bytes());
+    archive.extend(directory_size.to_le_bytes());
+    archive.extend(directory_offset.to_le_bytes());
+    // No comment
+    archive.extend(0u16.to_le_bytes());
+    archive
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_bundle(path: &Utf8PathBuf, vcd: &str, state: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(path)?;
+    std::fs::write(path.join(BUNDLE_WAVES_FILE), vcd)?;
+    std::fs::write(path.join(bundle_state_file()), state)
+}
+
+impl SystemState {
+    /// The displayed variables in the time range of the first viewport as VCD
+    #[must_use]
+    pub fn encode_visible_vcd(&self) -> Option<String> {
+        let waves = self.user.waves.as_ref()?;
+        let wave_container = waves.inner.as_waves()?;
+        let (start, end) = waves.visible_time_range()?;
+        let mut seen = HashSet::new();
+        let variables: Vec<_> = waves
+            .items_tree
+            .iter()
+            .filter_map(|node| match waves.displayed_items.get(&node.item_ref)? {
+                DisplayedItem::Variable(variable) => Some(&variable.variable_ref),
+                _ => None,
+            })
+            .filter(|variable| seen.insert(*variable))
+            .filter_map(|variable| {
+                let meta = wave_container.variable_meta(variable).ok()?;
+                Some(VcdVariable {
+                    scope: variable.path.strs().to_vec(),
+                    name: variable.name.clone(),
+                    index: meta.index.map(|index| index.to_string()),
+                    num_bits: meta.num_bits.unwrap_or(1),
+                    encoding: meta.encoding,
+                    changes: variable_changes(wave_container, variable, &start, &end),
+                })
+            })
+            .collect();
+        write_vcd(&variables, &waves.inner.metadata().timescale)
+            .map_err(|e| error!("Failed to write the displayed variables as VCD: {e:#}"))
+            .ok()
+    }
+
+    /// Write the displayed variables in the visible time range as VCD to the directory `path`,
+    /// together with a state file which opens it. On wasm, the two files are downloaded as a
+    /// zip archive instead.
+    pub fn export_repro_bundle(&mut self, path: &Utf8PathBuf) {
+        let Some(vcd) = self.encode_visible_vcd() else {
+            warn!("Cannot export a bundle before the waveform is loaded");
+            return;
+        };
+        let Some(waves) = self.user.waves.as_mut() else {
+            return;
+        };
+        // The state is picked up as a sibling of the bundled waveform, so it refers to the
+        // waveform by its name in the bundle rather than by the original path
+        let source = std::mem::replace(
+            &mut waves.source,
+            WaveSource::File(Utf8PathBuf::from(BUNDLE_WAVES_FILE)),
+        );
+        let state = self.encode_state();
+        if let Some(waves) = self.user.waves.as_mut() {
+            waves.source = source;
+        }
+        let Some(state) = state else {
+            return;
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(e) = write_bundle(path, &vcd, &state) {
+            error!("Failed to write bundle to {path}: {e}");
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = path;
+            let archive = zip_archive(&[
+                (BUNDLE_WAVES_FILE, vcd.as_bytes()),
+                (&bundle_state_file(), state.as_bytes()),
+            ]);
+            self.save_bytes_to(
+                None::<Utf8PathBuf>,
+                "Save repro bundle",
+                ("Zip archives (*.zip)".to_string(), vec!["zip".to_string()]),
+                archive,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigInt;
+
+    use super::*;
+    use crate::StartupParams;
+    use crate::message::Message;
+    use crate::tests::fixtures::{load_counter_vcd_with_variables, wait_for_waves_fully_loaded};
+    use crate::wave_container::VariableRefExt;
+    use crate::wave_data::WaveData;
+
+    fn variable(scope: &[&str], name: &str, num_bits: u32) -> VcdVariable {
+        VcdVariable {
+            scope: scope.iter().map(ToString::to_string).collect(),
+            name: name.to_string(),
+            index: None,
+            num_bits,
+            encoding: VariableEncoding::BitVector,
+            changes: vec![],
+        }
+    }
+
+    #[test]
+    fn identifiers_are_unique() {
+        let ids: HashSet<_> = (0..10_000).map(vcd_identifier).collect();
+        assert_eq!(ids.len(), 10_000);
+        assert_eq!(vcd_identifier(0), "!");
+        assert_eq!(vcd_identifier(93), "~");
+        assert_eq!(vcd_identifier(94), "!!");
+    }
+
+    #[test]
+    fn variables_are_written_in_their_scopes() {
+        let mut clk = variable(&["tb"], "clk", 1);
+        clk.changes = vec![
+            (
+                BigUint::from(100u32),
+                VariableValue::BigUint(BigUint::from(0u32)),
+            ),
+            (
+                BigUint::from(110u32),
+                VariableValue::BigUint(BigUint::from(1u32)),
+            ),
+        ];
+        let mut counter = variable(&["tb", "dut"], "counter", 4);
+        counter.index = Some("[3:0]".to_string());
+        counter.changes = vec![
+            (
+                BigUint::from(100u32),
+                VariableValue::String("x01z".to_string()),
+            ),
+            (
+                BigUint::from(110u32),
+                VariableValue::BigUint(BigUint::from(5u32)),
+            ),
+        ];
+        let mut real = variable(&["top"], "level", 64);
+        real.encoding = VariableEncoding::Real;
+        real.changes = vec![(
+            BigUint::from(120u32),
+            VariableValue::BigUint(BigUint::from(1.5f64.to_bits())),
+        )];
+        let timescale = TimeScale {
+            unit: TimeUnit::MicroSeconds,
+            multiplier: Some(10),
+        };
+
+        assert_eq!(
+            write_vcd(&[counter, clk, real], &timescale).unwrap(),
+            "$version Surfer $end\n\
+             $timescale 10 us $end\n\
+             $scope module tb $end\n\
+             $var wire 1 \" clk $end\n\
+             $scope module dut $end\n\
+             $var wire 4 ! counter [3:0] $end\n\
+             $upscope $end\n\
+             $upscope $end\n\
+             $scope module top $end\n\
+             $var real 64 # level $end\n\
+             $upscope $end\n\
+             $enddefinitions $end\n\
+             #100\n\
+             $dumpvars\n\
+             bx01z !\n\
+             0\"\n\
+             $end\n\
+             #110\n\
+             b0101 !\n\
+             1\"\n\
+             #120\n\
+             r1.5 #\n"
+        );
+    }
+
+    #[test]
+    fn strings_with_whitespace_are_rejected() {
+        let mut message = variable(&["tb"], "message", 1);
+        message.encoding = VariableEncoding::String;
+        message.changes = vec![(
+            BigUint::from(0u32),
+            VariableValue::String("ready".to_string()),
+        )];
+        let timescale = TimeScale {
+            unit: TimeUnit::NanoSeconds,
+            multiplier: None,
+        };
+        assert!(
+            write_vcd(std::slice::from_ref(&message), &timescale)
+                .unwrap()
+                .ends_with("#0\n$dumpvars\nsready !\n$end\n")
+        );
+
+        message.changes[0].1 = VariableValue::String("not ready".to_string());
+        let error = write_vcd(&[message], &timescale).unwrap_err();
+        assert!(error.to_string().contains("message"));
+    }
+
+    #[test]
+    fn zip_archive_stores_files() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+
+        let archive = zip_archive(&[("a.txt", b"first"), ("b.txt", b"second")]);
+        let u16_at = |at: usize| u16::from_le_bytes([archive[at], archive[at + 1]]);
+        let u32_at = |at: usize| u32::from_le_bytes(archive[at..at + 4].try_into().unwrap());
+        assert_eq!(u32_at(0), 0x0403_4b50);
+        assert_eq!(&archive[30..35], b"a.txt");
+        assert_eq!(&archive[35..40], b"first");
+        assert_eq!(u32_at(40), 0x0403_4b50);
+        assert_eq!(&archive[70..81], b"b.txtsecond");
+
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(end), 0x0605_4b50);
+        assert_eq!(u16_at(end + 10), 2);
+        let directory_size = u32_at(end + 12) as usize;
+        let directory_offset = u32_at(end + 16) as usize;
+        assert_eq!(directory_offset, 81);
+        assert_eq!(directory_offset + directory_size, end);
+        assert_eq!(u32_at(directory_offset), 0x0201_4b50);
+        assert_eq!(u32_at(directory_offset + 16), crc32(b"first"));
+        assert_eq!(
+            &archive[directory_offset + 46..directory_offset + 51],
+            b"a.txt"
+        );
+        assert_eq!(u32_at(directory_offset + 51 + 42), 40);
+    }
+
+    #[test]
+    fn unitless_timescale_is_left_out() {
+        let timescale = |unit| TimeScale {
+            unit,
+            multiplier: None,
+        };
+        assert_eq!(
+            write_vcd(&[], &timescale(TimeUnit::None)).unwrap(),
+            "$version Surfer $end\n$enddefinitions $end\n"
+        );
+        assert!(write_vcd(&[], &timescale(TimeUnit::Auto)).is_err());
+        assert!(
+            write_vcd(&[], &timescale(TimeUnit::NanoSeconds))
+                .unwrap()
+                .contains("$timescale 1 ns $end\n")
+        );
+    }
+
+    #[test]
+    fn repro_bundle_contains_visible_waves_and_state() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk", "tb.dut.counter"]);
+        state.update(Message::ZoomToRange {
+            start: BigInt::from(100),
+            end: BigInt::from(300),
+            viewport_idx: 0,
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = camino::Utf8PathBuf::try_from(dir.path().join("bundle")).unwrap();
+        state.update(Message::ExportReproBundle(path.clone()));
+
+        let waves_path = path.join(BUNDLE_WAVES_FILE);
+        let vcd = std::fs::read_to_string(&waves_path).unwrap();
+        assert!(vcd.contains("$scope module tb $end\n$var wire 1 ! clk $end\n"));
+        assert!(vcd.contains("$scope module dut $end\n$var wire 4 \" counter [3:0] $end\n"));
+        assert!(vcd.contains("#100\n$dumpvars\n"));
+        let times: Vec<u32> = vcd
+            .lines()
+            .filter_map(|line| line.strip_prefix('#')?.parse().ok())
+            .collect();
+        assert_eq!(times.first(), Some(&100));
+        assert_eq!(times.last(), Some(&300));
+
+        // The state refers to the bundled waveform, while the original one stays loaded
+        let state_path = path.join(bundle_state_file());
+        let bundled_state = std::fs::read_to_string(&state_path).unwrap();
+        assert!(bundled_state.contains(&format!("File({BUNDLE_WAVES_FILE:?})")));
+        assert!(!bundled_state.contains(path.as_str()));
+        assert!(bundled_state.contains("counter"));
+        assert!(matches!(
+            &state.user.waves.as_ref().unwrap().source,
+            WaveSource::File(file) if file.ends_with("examples/counter.vcd")
+        ));
+
+        // The bundled waveform can be loaded and has the values at the start of the range
+        let mut bundled = SystemState::new_default_config()
+            .unwrap()
+            .with_params(StartupParams {
+                waves: Some(WaveSource::File(waves_path)),
+                ..Default::default()
+            });
+        wait_for_waves_fully_loaded(&mut bundled, 10);
+        assert_eq!(
+            bundled
+                .user
+                .waves
+                .as_ref()
+                .unwrap()
+                .source
+                .sibling_state_file(""),
+            Some(state_path)
+        );
+        let counter = VariableRef::from_hierarchy_string("tb.dut.counter");
+        bundled.update(Message::AddVariables(vec![counter.clone()]));
+        wait_for_waves_fully_loaded(&mut bundled, 10);
+        let original = state.user.waves.as_ref().unwrap();
+        let reloaded = bundled.user.waves.as_ref().unwrap();
+        assert_eq!(reloaded.num_timestamps(), Some(BigInt::from(300)));
+        for time in [100u32, 130, 300] {
+            let value_at = |waves: &WaveData| {
+                waves
+                    .inner
+                    .as_waves()
+                    .unwrap()
+                    .query_variable(&counter, &BigUint::from(time))
+                    .unwrap()
+                    .and_then(|result| result.current)
+                    .map(|(_, value)| value)
+            };
+            assert_eq!(value_at(reloaded), value_at(original), "counter at {time}");
+        }
+    }
+}
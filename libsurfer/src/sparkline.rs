@@ -0,0 +1,158 @@
+//! Small trend line of numeric variables next to their names.
+use ecolor::Color32;
+use egui::Ui;
+use emath::{Pos2, Rect};
+use epaint::{Shape, Stroke};
+use num::{BigUint, ToPrimitive};
+use surfer_translation_types::VariableValue;
+
+use crate::displayed_item::DisplayedVariable;
+use crate::wave_data::WaveData;
+
+/// Width of a sparkline in pixels
+pub const SPARKLINE_WIDTH: f32 = 40.;
+/// Number of times in the visible range the value is sampled at
+const SPARKLINE_SAMPLES: usize = 40;
+
+/// Points of a step-shaped sparkline `width` pixels wide from `(time, value)` samples in time
+/// order. The x coordinates cover `start..=end` and the y coordinates go from 0 for the
+/// smallest to 1 for the largest value. A constant value is drawn at 0.5.
+#[must_use]
+pub fn sparkline_points(samples: &[(f64, f64)], start: f64, end: f64, width: f32) -> Vec<Pos2> {
+    let (min, max) = samples.iter().fold(
+        (f64::INFINITY, f64::NEG_INFINITY),
+        |(min, max), (_, value)| (min.min(*value), max.max(*value)),
+    );
+    let duration = end - start;
+    let x = |time: f64| {
+        if duration > 0. {
+            (((time - start) / duration).clamp(0., 1.) as f32) * width
+        } else {
+            0.
+        }
+    };
+    let y = |value: f64| {
+        if max > min {
+            ((value - min) / (max - min)) as f32
+        } else {
+            0.5
+        }
+    };
+
+    let mut points: Vec<Pos2> = vec![];
+    for (time, value) in samples {
+        if let Some(last) = points.last().copied() {
+            points.push(Pos2::new(x(*time), last.y));
+        }
+        points.push(Pos2::new(x(*time), y(*value)));
+    }
+    if let Some(last) = points.last().copied() {
+        points.push(Pos2::new(width, last.y));
+    }
+    points
+}
+
+fn numeric_value(value: &VariableValue) -> Option<f64> {
+    match value.parse_biguint() {
+        Ok(value) => value.to_f64(),
+        Err(_) => value.to_string().parse().ok(),
+    }
+}
+
+/// Numeric values of a variable sampled over the time range `start..=end`
+#[derive(Debug, Default)]
+pub struct SparklineSamples {
+    pub samples: Vec<(f64, f64)>,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl WaveData {
+    /// Values of `variable` sampled evenly over the visible time range of the first viewport,
+    /// leaving out values that are not numbers
+    #[must_use]
+    pub fn sparkline_samples(&self, variable: &DisplayedVariable) -> SparklineSamples {
+        let num_timestamps = self.safe_num_timestamps();
+        let Some(viewport) = self.viewports.first() else {
+            return SparklineSamples::default();
+        };
+        let start = viewport.curr_left.absolute(&num_timestamps).0.max(0.);
+        let end = viewport
+            .curr_right
+            .absolute(&num_timestamps)
+            .0
+            .min(num_timestamps.to_f64().unwrap_or(f64::MAX));
+        let Some(wave_container) = self.inner.as_waves() else {
+            return SparklineSamples {
+                samples: vec![],
+                start,
+                end,
+            };
+        };
+        let samples = (0..SPARKLINE_SAMPLES)
+            .filter_map(|idx| {
+                let time = start + (end - start) * idx as f64 / (SPARKLINE_SAMPLES - 1) as f64;
+                let value = wave_container
+                    .query_variable(&variable.variable_ref, &BigUint::from(time as u64))
+                    .ok()??
+                    .current?
+                    .1;
+                Some((time, numeric_value(&value)?))
+            })
+            .collect();
+        SparklineSamples {
+            samples,
+            start,
+            end,
+        }
+    }
+}
+
+/// Draws the sparkline of `samples` in `rect`
+pub fn draw_sparkline(samples: &SparklineSamples, rect: Rect, color: Color32, ui: &Ui) {
+    let area = rect.shrink2(emath::vec2(2., rect.height() * 0.2));
+    let points = sparkline_points(&samples.samples, samples.start, samples.end, area.width())
+        .into_iter()
+        .map(|point| {
+            Pos2::new(
+                area.left() + point.x,
+                area.bottom() - point.y * area.height(),
+            )
+        })
+        .collect::<Vec<_>>();
+    if points.len() > 1 {
+        ui.painter().add(Shape::line(
+            points,
+            Stroke::new(1., color.gamma_multiply(0.7)),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparkline_steps_between_samples() {
+        let points = sparkline_points(&[(0., 2.), (50., 6.), (75., 4.)], 0., 100., 40.);
+        assert_eq!(
+            points,
+            vec![
+                Pos2::new(0., 0.),
+                Pos2::new(20., 0.),
+                Pos2::new(20., 1.),
+                Pos2::new(30., 1.),
+                Pos2::new(30., 0.5),
+                Pos2::new(40., 0.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn sparkline_of_constant_value_is_centered() {
+        let points = sparkline_points(&[(10., 3.), (20., 3.)], 10., 20., 10.);
+        assert!(points.iter().all(|point| point.y == 0.5));
+        assert_eq!(points.last(), Some(&Pos2::new(10., 0.5)));
+        assert!(sparkline_points(&[], 0., 100., 40.).is_empty());
+    }
+}
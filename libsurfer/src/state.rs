@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
     mem,
     path::PathBuf,
 };
@@ -7,21 +7,26 @@ use std::{
 use crate::{
     CanvasState, StartupParams,
     clock_highlighting::ClockHighlightType,
-    config::{ArrowKeyBindings, AutoLoad, PrimaryMouseDrag, SurferConfig, TransitionValue},
+    config::{
+        ArrowKeyBindings, AutoLoad, ItemColumnSide, PrimaryMouseDrag, SurferConfig, TransitionValue,
+    },
     data_container::DataContainer,
-    dialog::{OpenSiblingStateFileDialog, ReloadWaveformDialog},
+    dialog::{AddMarkerDialog, OpenSiblingStateFileDialog, ReloadWaveformDialog},
+    displayed_item::NumericFormatOptions,
     displayed_item_tree::{DisplayedItemTree, VisibleItemIndex},
     hierarchy::{HierarchyStyle, ParameterDisplayLocation},
     message::Message,
     system_state::SystemState,
-    time::{TimeStringFormatting, TimeUnit},
+    time::{Radix, TimeStringFormatting, TimeUnit},
     transaction_container::TransactionContainer,
     variable_filter::VariableFilter,
     viewport::Viewport,
     wave_container::{ScopeRef, VariableRef, WaveContainer},
     wave_data::WaveData,
     wave_source::{LoadOptions, WaveFormat, WaveSource},
+    wellen::WellenContainer,
 };
+use camino::Utf8PathBuf;
 use egui::{
     Visuals,
     style::{Selection, WidgetVisuals, Widgets},
@@ -29,8 +34,9 @@ use egui::{
 use epaint::{CornerRadius, Stroke};
 use eyre::{Context, Result};
 use itertools::Itertools;
+use num::BigInt;
 use serde::{Deserialize, Serialize};
-use surfer_translation_types::Translator;
+use surfer_translation_types::{PluginConfig, Translator};
 use surver::SurverFileInfo;
 use tracing::{error, info, trace, warn};
 
@@ -67,13 +73,29 @@ pub struct UserState {
     #[serde(default)]
     pub(crate) arrow_key_bindings: Option<ArrowKeyBindings>,
     #[serde(default)]
+    pub(crate) placement_grid: Option<BigInt>,
+    #[serde(default)]
     pub(crate) clock_highlight_type: Option<ClockHighlightType>,
     #[serde(default)]
+    pub(crate) clock_highlight_enabled: Option<bool>,
+    #[serde(default)]
+    pub(crate) viewports_linked: Option<bool>,
+    #[serde(default)]
+    pub(crate) group_scopes_by_type: Option<bool>,
+    #[serde(default)]
+    pub(crate) numeric_format: Option<NumericFormatOptions>,
+    /// Label the ruler ticks relative to the cursor
+    #[serde(default)]
+    pub(crate) relative_time_ruler: bool,
+    #[serde(default)]
     pub(crate) hierarchy_style: Option<HierarchyStyle>,
     #[serde(default)]
     pub(crate) autoload_sibling_state_files: Option<AutoLoad>,
     #[serde(default)]
     pub(crate) autoreload_files: Option<AutoLoad>,
+    /// Files opened from the file system, most recent first
+    #[serde(default)]
+    pub(crate) recent_files: Vec<Utf8PathBuf>,
 
     pub(crate) waves: Option<WaveData>,
     pub(crate) drag_started: bool,
@@ -96,7 +118,15 @@ pub struct UserState {
     pub(crate) show_performance: bool,
     pub(crate) show_logs: bool,
     pub(crate) show_cursor_window: bool,
+    #[serde(default)]
+    pub(crate) show_wave_metadata: bool,
+    #[serde(default)]
+    pub(crate) show_events_window: bool,
     pub(crate) wanted_timeunit: TimeUnit,
+    /// Set once a time unit is picked, after which loading a waveform keeps the unit instead
+    /// of switching to the unit of its timescale
+    #[serde(default)]
+    pub(crate) wanted_timeunit_explicit: bool,
     pub(crate) time_string_format: Option<TimeStringFormatting>,
     pub(crate) show_url_entry: bool,
     /// Show a confirmation dialog asking the user for confirmation
@@ -105,6 +135,8 @@ pub struct UserState {
     pub(crate) show_reload_suggestion: Option<ReloadWaveformDialog>,
     #[serde(skip, default)]
     pub(crate) show_open_sibling_state_file_suggestion: Option<OpenSiblingStateFileDialog>,
+    #[serde(skip, default)]
+    pub(crate) show_add_marker_dialog: Option<AddMarkerDialog>,
     pub(crate) variable_name_filter_focused: bool,
     pub(crate) variable_filter: VariableFilter,
     //Sidepanel width
@@ -115,6 +147,16 @@ pub struct UserState {
     pub(crate) animation_enabled: Option<bool>,
     #[serde(default)]
     pub(crate) use_dinotrace_style: Option<bool>,
+    #[serde(default)]
+    pub(crate) show_no_data_hatch: Option<bool>,
+    #[serde(default)]
+    pub(crate) item_column_side: Option<ItemColumnSide>,
+    #[serde(default)]
+    pub(crate) show_glitch_density: Option<bool>,
+    #[serde(default)]
+    pub(crate) show_partial_unknowns: Option<bool>,
+    #[serde(default)]
+    pub(crate) show_sparklines: Option<bool>,
     #[serde(skip, default)]
     pub(crate) show_server_file_window: bool,
     #[serde(skip, default)]
@@ -125,6 +167,17 @@ pub struct UserState {
     pub(crate) surver_url: Option<String>,
     #[serde(default)]
     pub(crate) transition_value: Option<TransitionValue>,
+    #[serde(default)]
+    pub(crate) time_label_radix: Option<Radix>,
+    /// Key-value configuration of translators, by translator name, passed to the
+    /// translators through [`Translator::set_config`]
+    #[serde(default)]
+    pub(crate) translator_configs: HashMap<String, HashMap<String, String>>,
+    /// Full names of the scopes that are expanded in the hierarchy panel
+    #[serde(default)]
+    pub(crate) expanded_scopes: BTreeSet<String>,
+    #[serde(default)]
+    pub(crate) hierarchy_scroll_offset: f32,
 
     // Path of last saved-to state file
     // Do not serialize as this causes a few issues and doesn't help:
@@ -179,10 +232,17 @@ impl Default for UserState {
             fill_high_values: None,
             primary_button_drag_behavior: None,
             arrow_key_bindings: None,
+            placement_grid: None,
             clock_highlight_type: None,
+            clock_highlight_enabled: None,
+            viewports_linked: None,
+            group_scopes_by_type: None,
+            numeric_format: None,
+            relative_time_ruler: false,
             hierarchy_style: None,
             autoload_sibling_state_files: None,
             autoreload_files: None,
+            recent_files: vec![],
             waves: None,
             drag_started: false,
             drag_source_idx: None,
@@ -198,11 +258,15 @@ impl Default for UserState {
             show_performance: false,
             show_logs: false,
             show_cursor_window: false,
+            show_wave_metadata: false,
+            show_events_window: false,
             wanted_timeunit: TimeUnit::None,
+            wanted_timeunit_explicit: false,
             time_string_format: None,
             show_url_entry: false,
             show_reload_suggestion: None,
             show_open_sibling_state_file_suggestion: None,
+            show_add_marker_dialog: None,
             variable_name_filter_focused: false,
             variable_filter: VariableFilter::new(),
             sidepanel_width: None,
@@ -210,11 +274,20 @@ impl Default for UserState {
             state_file: None,
             animation_enabled: None,
             use_dinotrace_style: None,
+            show_no_data_hatch: None,
+            item_column_side: None,
+            show_glitch_density: None,
+            show_partial_unknowns: None,
+            show_sparklines: None,
             selected_server_file_index: None,
             show_server_file_window: false,
             surver_file_infos: None,
             surver_url: None,
             transition_value: None,
+            time_label_radix: None,
+            translator_configs: HashMap::new(),
+            expanded_scopes: BTreeSet::new(),
+            hierarchy_scroll_offset: 0.,
         }
     }
 }
@@ -252,6 +325,16 @@ impl SystemState {
             });
         }
 
+        // after loading, since zooming needs the time range of the waveform, and before the
+        // commands, which may change the view themselves
+        if let Some((start, end)) = args.view_range {
+            self.add_batch_message(Message::ZoomToRange {
+                start,
+                end,
+                viewport_idx: 0,
+            });
+        }
+
         self.add_batch_commands(args.startup_commands);
 
         self
@@ -285,6 +368,68 @@ impl SystemState {
         variables
     }
 
+    /// Update the viewports once the number of timestamps of newly loaded waves is known and
+    /// put back the view kept by `ReloadPreservingView`, if any
+    pub(crate) fn update_viewports_after_load(&mut self) {
+        let reload_viewport = self.user.config.behavior.reload_viewport();
+        let preserved_view = self.preserved_view.take();
+        let Some(waves) = self.user.waves.as_mut() else {
+            return;
+        };
+        waves.update_viewports(reload_viewport);
+        if let Some((source, view)) = preserved_view
+            && source == waves.source
+        {
+            waves.restore_view(&view);
+        }
+    }
+
+    pub(crate) fn on_additional_waves_loaded(
+        &mut self,
+        filename: Utf8PathBuf,
+        new_waves: WellenContainer,
+        load_options: LoadOptions,
+    ) {
+        let Some(waves) = self.user.waves.as_mut() else {
+            warn!("Cannot merge {filename} before a waveform is loaded");
+            return;
+        };
+        let timescale = waves.inner.metadata().timescale;
+        let new_timescale = new_waves.metadata().timescale;
+        if (timescale.unit, timescale.multiplier) != (new_timescale.unit, new_timescale.multiplier)
+        {
+            error!("Cannot merge {filename}, its timescale differs from the loaded waveform");
+            return;
+        }
+        let old_num_timestamps = waves.num_timestamps();
+        let Some(inner) = waves.inner.as_waves_mut() else {
+            error!("Cannot merge {filename} into transactions");
+            return;
+        };
+        match inner.merge_file(filename.clone(), new_waves) {
+            Ok(name) => info!("Merged {filename} as {name}"),
+            Err(e) => {
+                error!("Cannot merge {filename}: {e:#}");
+                return;
+            }
+        }
+
+        self.user.variable_filter.clear_scope_cache();
+
+        let cmd =
+            waves.update_with_merged_waves(&self.translators, load_options == LoadOptions::KeepAll);
+        // While the body of a reloaded waveform is parsed, the viewports are updated once it is
+        // done
+        if waves.inner.body_loaded() {
+            waves.old_num_timestamps = old_num_timestamps;
+            waves.update_viewports(self.user.config.behavior.reload_viewport());
+        }
+        if let Some(cmd) = cmd {
+            self.load_variables(cmd);
+        }
+        self.invalidate_draw_commands();
+    }
+
     pub(crate) fn on_waves_loaded(
         &mut self,
         filename: WaveSource,
@@ -293,12 +438,17 @@ impl SystemState {
         load_options: LoadOptions,
     ) {
         info!("{format} file loaded");
+        self.user.variable_filter.clear_scope_cache();
+        if load_options == LoadOptions::Clear {
+            self.preserved_view = None;
+        }
         let viewport = Viewport::new();
         let viewports = [viewport].to_vec();
 
         for translator in self.translators.all_translators() {
             translator.set_wave_source(Some(filename.into_translation_type()));
         }
+        self.configure_translators();
 
         let ((new_wave, load_commands), is_reload) =
             if load_options != LoadOptions::Clear && self.user.waves.is_some() {
@@ -336,14 +486,19 @@ impl SystemState {
                             viewports,
                             cursor: None,
                             markers: HashMap::new(),
+                            trigger_time: None,
+                            events: vec![],
                             focused_item: None,
                             focused_transaction: (None, None),
+                            selected_transactions: vec![],
                             default_variable_name_type: self.user.config.default_variable_name_type,
                             display_variable_indices: self.show_variable_indices(),
                             scroll_offset: 0.,
                             drawing_infos: vec![],
+                            zebra_shading: vec![],
                             top_item_draw_offset: 0.,
                             total_height: 0.,
+                            focused_item_maximized: false,
                             display_item_ref_counter: 0,
                             old_num_timestamps: None,
                             graphics: HashMap::new(),
@@ -362,12 +517,26 @@ impl SystemState {
         self.invalidate_draw_commands();
 
         self.user.waves = Some(new_wave);
+        self.retain_existing_expanded_scopes();
+
+        // Watched variables have to be loaded again from the new file, and are dropped if they
+        // no longer exist
+        let watch_list = std::mem::take(&mut self.watch_list);
+        if is_reload {
+            for variable in watch_list {
+                self.update(Message::AddToWatch(variable));
+            }
+        }
 
-        if !is_reload && let Some(waves) = &mut self.user.waves {
-            // Set time unit
-            self.user.wanted_timeunit = waves.inner.metadata().timescale.unit;
+        if !is_reload && let Some(waves) = &self.user.waves {
+            let timescale = waves.inner.metadata().timescale;
+            let has_sibling_state_file = waves
+                .source
+                .sibling_state_file(&self.user.config.sibling_state_file_pattern)
+                .is_some();
+            self.set_default_time_unit(&timescale);
             // Possibly open state file load dialog
-            if waves.source.sibling_state_file().is_some() {
+            if has_sibling_state_file {
                 self.update(Message::SuggestOpenSiblingStateFile);
             }
         }
@@ -395,14 +564,19 @@ impl SystemState {
             viewports,
             cursor: None,
             markers: HashMap::new(),
+            trigger_time: None,
+            events: vec![],
             focused_item: None,
             focused_transaction: (None, None),
+            selected_transactions: vec![],
             default_variable_name_type: self.user.config.default_variable_name_type,
             display_variable_indices: self.show_variable_indices(),
             scroll_offset: 0.,
             drawing_infos: vec![],
+            zebra_shading: vec![],
             top_item_draw_offset: 0.,
             total_height: 0.,
+            focused_item_maximized: false,
             display_item_ref_counter: 0,
             old_num_timestamps: None,
             graphics: HashMap::new(),
@@ -413,7 +587,7 @@ impl SystemState {
         self.invalidate_draw_commands();
 
         self.user.config.theme.alt_frequency = 0;
-        self.user.wanted_timeunit = new_transaction_streams.inner.metadata().timescale.unit;
+        self.set_default_time_unit(&new_transaction_streams.inner.metadata().timescale);
         self.user.waves = Some(new_transaction_streams);
     }
 
@@ -526,6 +700,11 @@ impl SystemState {
         self.user.drag_source_idx = None;
         self.user.drag_target_idx = None;
 
+        // keep the files opened in this session ahead of those in the state file
+        for path in loaded_state.recent_files.iter().rev() {
+            self.push_recent_file(path);
+        }
+
         // reset previous_waves & count to prevent unintuitive state here
         self.user.previous_waves = None;
         self.user.count = None;
@@ -533,9 +712,23 @@ impl SystemState {
         // use just loaded path since path is not part of the export as it might have changed anyways
         self.user.state_file = path;
 
+        self.retain_existing_expanded_scopes();
+        self.hierarchy_generation = self.hierarchy_generation.wrapping_add(1);
+        *self.restore_hierarchy_scroll.borrow_mut() = true;
+
+        self.configure_translators();
         self.invalidate_draw_commands();
         if let Some(waves) = &mut self.user.waves {
-            waves.update_viewports();
+            waves.update_viewports(self.user.config.behavior.reload_viewport());
+        }
+    }
+
+    /// Pass the configurations in `translator_configs` to the translators they are for
+    pub(crate) fn configure_translators(&self) {
+        for translator in self.translators.all_translators() {
+            if let Some(config) = self.user.translator_configs.get(&translator.name()) {
+                translator.set_config(&PluginConfig(config.clone()));
+            }
         }
     }
 
@@ -650,3 +843,48 @@ impl SystemState {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use project_root::get_project_root;
+
+    use super::*;
+    use crate::tests::fixtures::wait_for_waves_fully_loaded;
+
+    #[test]
+    fn startup_view_range_zooms_after_loading() {
+        let mut state = SystemState::new_default_config()
+            .unwrap()
+            .with_params(StartupParams {
+                waves: Some(WaveSource::File(
+                    get_project_root()
+                        .unwrap()
+                        .join("examples/counter.vcd")
+                        .try_into()
+                        .unwrap(),
+                )),
+                startup_commands: vec!["toggle_menu".to_string()],
+                view_range: Some((BigInt::from(100), BigInt::from(300))),
+                ..Default::default()
+            });
+        // The zoom is deferred until the waveform is loaded, but runs before the startup commands
+        assert!(matches!(
+            state.batch_messages.make_contiguous(),
+            [
+                Message::LoadFile(..),
+                Message::ZoomToRange {
+                    viewport_idx: 0,
+                    ..
+                },
+                Message::SetMenuVisible(_)
+            ]
+        ));
+        wait_for_waves_fully_loaded(&mut state, 10);
+
+        let waves = state.user.waves.as_ref().unwrap();
+        let num_timestamps = waves.num_timestamps().unwrap();
+        let viewport = waves.viewports[0];
+        assert_eq!(viewport.left_edge_time(&num_timestamps), BigInt::from(100));
+        assert_eq!(viewport.right_edge_time(&num_timestamps), BigInt::from(300));
+    }
+}
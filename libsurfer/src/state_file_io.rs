@@ -1,15 +1,17 @@
 use std::path::PathBuf;
 
-#[cfg(not(target_arch = "wasm32"))]
 use camino::Utf8PathBuf;
 use eyre::Context;
+use num::ToPrimitive;
 use rfd::FileHandle;
+use serde::Serialize;
 use tracing::error;
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::async_util::perform_async_work;
 use crate::channels::{checked_send, checked_send_many};
 
+use crate::color_scheme::ItemScheme;
 use crate::{
     SystemState, async_util::AsyncJob, message::Message, wave_source::STATE_FILE_EXTENSION,
 };
@@ -144,6 +146,185 @@ impl SystemState {
         );
     }
 
+    /// Write the configuration with the runtime overrides applied as TOML to `path`, or
+    /// to a file picked in a save dialog. On wasm, the file is always picked in a dialog.
+    pub fn export_effective_config(&mut self, path: Option<Utf8PathBuf>) {
+        let Some(encoded) = self.encode_effective_config() else {
+            return;
+        };
+
+        self.save_bytes_to(
+            path,
+            "Export configuration",
+            (
+                "Surfer config files (*.toml)".to_string(),
+                vec!["toml".to_string()],
+            ),
+            encoded.into_bytes(),
+        );
+    }
+
+    /// Write the color, format and height overrides of the displayed variables as TOML to
+    /// `path`, or to a file picked in a save dialog. On wasm, the file is always picked in a
+    /// dialog.
+    pub fn save_scheme_file(&mut self, path: Option<PathBuf>) {
+        let Some(encoded) = self.encode_item_scheme() else {
+            return;
+        };
+
+        self.save_bytes_to(
+            path,
+            "Save scheme",
+            (
+                "Surfer scheme files (*.toml)".to_string(),
+                vec!["toml".to_string()],
+            ),
+            encoded.into_bytes(),
+        );
+    }
+
+    /// Read a scheme file from `path`, or from a file picked in a dialog, and apply it to the
+    /// displayed variables. On wasm, the file is always picked in a dialog.
+    pub fn load_scheme_file(&mut self, path: Option<PathBuf>) {
+        let apply = |bytes: &[u8]| match decode_item_scheme(bytes) {
+            Ok(scheme) => vec![Message::ApplyItemScheme(scheme)],
+            Err(e) => {
+                error!("Failed to load scheme: {e:#?}");
+                vec![Message::Error(e)]
+            }
+        };
+        #[cfg(not(target_arch = "wasm32"))]
+        let messages = move |path: PathBuf| match std::fs::read(&path) {
+            Ok(bytes) => apply(&bytes),
+            Err(e) => {
+                error!("Failed to read scheme file: {path:#?} {e:#?}");
+                vec![Message::Error(eyre::eyre!(
+                    "Failed to read scheme file '{}': {e}",
+                    path.display()
+                ))]
+            }
+        };
+        #[cfg(target_arch = "wasm32")]
+        let messages = move |bytes: Vec<u8>| apply(&bytes);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = path {
+            let sender = self.channels.msg_sender.clone();
+            checked_send_many(&sender, messages(path));
+            return;
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = path;
+        self.file_dialog_open(
+            "Apply scheme",
+            (
+                "Surfer scheme files (*.toml)".to_string(),
+                vec!["toml".to_string()],
+            ),
+            messages,
+        );
+    }
+
+    pub fn encode_item_scheme(&self) -> Option<String> {
+        let scheme = self.user.waves.as_ref()?.item_scheme();
+        toml::to_string_pretty(&scheme)
+            .context("Failed to encode scheme")
+            .map_err(|e| error!("Failed to encode scheme. {e:#?}"))
+            .ok()
+    }
+
+    /// The configuration with the settings changed at runtime applied, as TOML
+    pub fn encode_effective_config(&self) -> Option<String> {
+        self.effective_config()
+            .and_then(|config| toml::to_string_pretty(&config).map_err(Into::into))
+            .context("Failed to encode config")
+            .map_err(|e| error!("Failed to encode config. {e:#?}"))
+            .ok()
+    }
+
+    /// `SurferConfig` as a TOML table, with the values overridden in `UserState` replacing
+    /// the configured ones
+    fn effective_config(&self) -> eyre::Result<toml::Table> {
+        let mut config = toml::Table::try_from(&self.user.config)?;
+        set_value(
+            &mut config,
+            "default_clock_highlight_type",
+            self.clock_highlight_type(),
+        )?;
+        set_value(&mut config, "autoreload_files", self.autoreload_files())?;
+        set_value(
+            &mut config,
+            "autoload_sibling_state_files",
+            self.autoload_sibling_state_files(),
+        )?;
+        set_value(&mut config, "animation_enabled", self.animation_enabled())?;
+
+        let layout = config_section(&mut config, "layout")?;
+        set_value(layout, "show_hierarchy", self.show_hierarchy())?;
+        set_value(layout, "show_menu", self.show_menu())?;
+        set_value(layout, "show_toolbar", self.show_toolbar())?;
+        set_value(layout, "show_ticks", self.show_ticks())?;
+        set_value(layout, "show_tooltip", self.show_tooltip())?;
+        set_value(layout, "show_scope_tooltip", self.show_scope_tooltip())?;
+        set_value(layout, "show_overview", self.show_overview())?;
+        if let Some(show_statusbar) = self.user.show_statusbar {
+            set_value(layout, "show_statusbar", show_statusbar)?;
+        }
+        set_value(
+            layout,
+            "show_variable_indices",
+            self.show_variable_indices(),
+        )?;
+        set_value(
+            layout,
+            "show_variable_direction",
+            self.show_variable_direction(),
+        )?;
+        set_value(
+            layout,
+            "show_default_timeline",
+            self.show_default_timeline(),
+        )?;
+        set_value(layout, "show_empty_scopes", self.show_empty_scopes())?;
+        set_value(layout, "show_hierarchy_icons", self.show_hierarchy_icons())?;
+        set_value(
+            layout,
+            "parameter_display_location",
+            self.parameter_display_location(),
+        )?;
+        set_value(layout, "align_names_right", self.align_names_right())?;
+        set_value(layout, "hierarchy_style", self.hierarchy_style())?;
+        set_value(layout, "default_zoom_factor", self.ui_zoom_factor())?;
+        set_value(layout, "highlight_focused", self.highlight_focused())?;
+        set_value(layout, "fill_high_values", self.fill_high_values())?;
+        set_value(layout, "use_dinotrace_style", self.use_dinotrace_style())?;
+        set_value(layout, "transition_value", self.transition_value())?;
+        set_value(layout, "time_label_radix", self.time_label_radix())?;
+        set_value(layout, "show_no_data_hatch", self.show_no_data_hatch())?;
+        set_value(layout, "item_column_side", self.item_column_side())?;
+        set_value(layout, "show_glitch_density", self.show_glitch_density())?;
+        set_value(
+            layout,
+            "show_partial_unknowns",
+            self.show_partial_unknowns(),
+        )?;
+        set_value(layout, "show_sparklines", self.show_sparklines())?;
+
+        let behavior = config_section(&mut config, "behavior")?;
+        set_value(behavior, "arrow_key_bindings", self.arrow_key_bindings())?;
+        set_value(
+            behavior,
+            "primary_button_drag_behavior",
+            self.primary_button_drag_behavior(),
+        )?;
+        set_value(
+            behavior,
+            "placement_grid",
+            self.placement_grid().to_u64().unwrap_or_default(),
+        )?;
+        Ok(config)
+    }
+
     pub fn encode_state(&self) -> Option<String> {
         let opt = ron::Options::default();
 
@@ -166,10 +347,74 @@ impl SystemState {
     }
 }
 
+fn config_section<'a>(
+    config: &'a mut toml::Table,
+    name: &str,
+) -> eyre::Result<&'a mut toml::Table> {
+    config
+        .get_mut(name)
+        .and_then(toml::Value::as_table_mut)
+        .ok_or_else(|| eyre::eyre!("No [{name}] section in the config"))
+}
+
+fn set_value(table: &mut toml::Table, key: &str, value: impl Serialize) -> eyre::Result<()> {
+    table.insert(key.to_string(), toml::Value::try_from(value)?);
+    Ok(())
+}
+
+pub fn decode_item_scheme(bytes: &[u8]) -> eyre::Result<ItemScheme> {
+    let text = std::str::from_utf8(bytes).context("Scheme file is not valid UTF-8")?;
+    toml::from_str(text).context("Failed to parse scheme")
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use surfer_translation_types::{
+        PluginConfig, TranslationPreference, TranslationResult, Translator, VariableInfo,
+        VariableValue,
+    };
+
     use super::*;
     use crate::StartupParams;
+    use crate::config::{ArrowKeyBindings, SurferConfig};
+    use crate::hierarchy::HierarchyStyle;
+    use crate::state::UserState;
+    use crate::tests::fixtures::{load_counter_vcd, load_example};
+    use crate::time::Radix;
+    use crate::wave_container::{ScopeId, VarId, VariableMeta};
+    use crate::wave_container::{ScopeRef, ScopeRefExt};
+
+    /// Translator which remembers the last configuration it was given
+    struct ConfigRecorder(Arc<Mutex<Option<PluginConfig>>>);
+
+    impl Translator<VarId, ScopeId, Message> for ConfigRecorder {
+        fn name(&self) -> String {
+            "Config recorder".to_string()
+        }
+
+        fn set_config(&self, config: &PluginConfig) {
+            *self.0.lock().unwrap() = Some(config.clone());
+        }
+
+        fn translate(
+            &self,
+            _variable: &VariableMeta,
+            _value: &VariableValue,
+        ) -> eyre::Result<TranslationResult> {
+            eyre::bail!("Not a real translator")
+        }
+
+        fn variable_info(&self, _variable: &VariableMeta) -> eyre::Result<VariableInfo> {
+            Ok(VariableInfo::String)
+        }
+
+        fn translates(&self, _variable: &VariableMeta) -> eyre::Result<TranslationPreference> {
+            Ok(TranslationPreference::No)
+        }
+    }
 
     #[test]
     fn test_encode_state() {
@@ -182,6 +427,92 @@ mod tests {
         assert!(encoded.contains("show_about"));
     }
 
+    #[test]
+    fn test_encode_effective_config() {
+        let mut state = SystemState::new_default_config()
+            .unwrap()
+            .with_params(StartupParams::default());
+        state.user.show_hierarchy = Some(!state.user.config.layout.show_hierarchy());
+        state.user.hierarchy_style = Some(HierarchyStyle::Variables);
+        state.user.time_label_radix = Some(Radix::Hexadecimal);
+
+        let encoded = state.encode_effective_config().unwrap();
+        let table: toml::Table = toml::from_str(&encoded).unwrap();
+        let layout = table["layout"].as_table().unwrap();
+        assert_eq!(
+            layout["show_hierarchy"].as_bool(),
+            Some(!state.user.config.layout.show_hierarchy())
+        );
+        assert_eq!(layout["hierarchy_style"].as_str(), Some("Variables"));
+        assert_eq!(layout["time_label_radix"].as_str(), Some("Hexadecimal"));
+        assert_eq!(
+            layout["show_menu"].as_bool(),
+            Some(state.user.config.layout.show_menu())
+        );
+        let arrow_key_bindings: ArrowKeyBindings = table["behavior"]["arrow_key_bindings"]
+            .clone()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            arrow_key_bindings,
+            state.user.config.behavior.arrow_key_bindings()
+        );
+        // Settings that cannot be changed at runtime are exported as configured
+        assert_eq!(
+            table["snap_distance"].as_float(),
+            Some(f64::from(state.user.config.snap_distance))
+        );
+
+        // The export can be used as a config file
+        let config: SurferConfig = toml::from_str(&encoded).unwrap();
+        assert_eq!(
+            config.layout.show_hierarchy(),
+            !state.user.config.layout.show_hierarchy()
+        );
+        assert_eq!(config.theme.name, state.user.config.theme.name);
+        assert_eq!(config.shortcuts.undo, state.user.config.shortcuts.undo);
+    }
+
+    #[test]
+    fn translator_config_round_trips_through_state() {
+        let mut state = SystemState::new_default_config()
+            .unwrap()
+            .with_params(StartupParams::default());
+        let recorded = Arc::new(Mutex::new(None));
+        state.update(Message::TranslatorLoaded(Arc::new(ConfigRecorder(
+            recorded.clone(),
+        ))));
+        assert_eq!(*recorded.lock().unwrap(), None);
+
+        state.update(Message::SetTranslatorConfig(
+            "Config recorder".to_string(),
+            "mode".to_string(),
+            "fast".to_string(),
+        ));
+        let expected = PluginConfig(HashMap::from([("mode".to_string(), "fast".to_string())]));
+        assert_eq!(*recorded.lock().unwrap(), Some(expected.clone()));
+
+        let encoded = state.encode_state().unwrap();
+        let mut loaded = SystemState::new_default_config()
+            .unwrap()
+            .with_params(StartupParams::default());
+        let reloaded = Arc::new(Mutex::new(None));
+        loaded.update(Message::TranslatorLoaded(Arc::new(ConfigRecorder(
+            reloaded.clone(),
+        ))));
+        loaded.load_state_from_bytes(encoded.as_bytes().to_vec());
+        let msg = loaded.channels.msg_receiver.try_recv().unwrap();
+        loaded.update(msg);
+        assert_eq!(*reloaded.lock().unwrap(), Some(expected.clone()));
+
+        // Translators loaded after the state get the configuration when they are loaded
+        let late = Arc::new(Mutex::new(None));
+        loaded.update(Message::TranslatorLoaded(Arc::new(ConfigRecorder(
+            late.clone(),
+        ))));
+        assert_eq!(*late.lock().unwrap(), Some(expected));
+    }
+
     #[test]
     fn test_load_state_from_bytes() {
         let mut state = SystemState::new_default_config()
@@ -198,4 +529,71 @@ mod tests {
             _ => panic!("Expected LoadState message, got {:?}", msg),
         }
     }
+
+    #[test]
+    fn recent_files_survive_state_round_trip() {
+        let file_names = |state: &SystemState| {
+            state
+                .user
+                .recent_files
+                .iter()
+                .map(|path| path.file_name().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let state = load_counter_vcd();
+        assert_eq!(file_names(&state), vec!["counter.vcd"]);
+        let encoded = state.encode_state().unwrap();
+
+        // Files opened in the current session stay ahead of those in the state file
+        let mut state = load_example("examples/counter2.vcd");
+        let loaded = ron::from_str::<UserState>(&encoded).unwrap();
+        state.update(Message::LoadState(Box::new(loaded), None));
+        assert_eq!(file_names(&state), vec!["counter2.vcd", "counter.vcd"]);
+
+        state.update(Message::ShowQuickOpen);
+        assert!(
+            state
+                .quick_open
+                .entries
+                .iter()
+                .take(2)
+                .map(|entry| entry.label.as_str())
+                .eq(state.user.recent_files.iter().map(|path| path.as_str()))
+        );
+    }
+
+    #[test]
+    fn hierarchy_expansion_survives_state_round_trip() {
+        let mut state = load_counter_vcd();
+        for scope in ["tb", "tb.dut", "tb.gone"] {
+            state.update(Message::SetScopeExpanded(
+                ScopeRef::from_hierarchy_string(scope),
+                true,
+            ));
+        }
+        state.update(Message::SetScopeExpanded(
+            ScopeRef::from_hierarchy_string("tb.dut"),
+            false,
+        ));
+        state.update(Message::SetScopeExpanded(
+            ScopeRef::from_hierarchy_string("tb.dut"),
+            true,
+        ));
+        state.update(Message::SetHierarchyScrollOffset(42.));
+        let encoded = state.encode_state().unwrap();
+
+        let mut state = load_counter_vcd();
+        let generation = state.hierarchy_generation;
+        let loaded = ron::from_str::<UserState>(&encoded).unwrap();
+        state.update(Message::LoadState(Box::new(loaded), None));
+
+        assert_eq!(
+            state.user.expanded_scopes.iter().collect::<Vec<_>>(),
+            vec!["tb", "tb.dut"]
+        );
+        assert_eq!(state.user.hierarchy_scroll_offset, 42.);
+        assert_ne!(state.hierarchy_generation, generation);
+        assert!(*state.restore_hierarchy_scroll.borrow());
+    }
 }
@@ -2,13 +2,15 @@
 
 use ecolor::Color32;
 use egui::Modifiers;
+use num::BigInt;
 
 use crate::{
     SystemState,
     clock_highlighting::ClockHighlightType,
-    config::{ArrowKeyBindings, AutoLoad, PrimaryMouseDrag, TransitionValue},
-    displayed_item::DisplayedItem,
+    config::{ArrowKeyBindings, AutoLoad, ItemColumnSide, PrimaryMouseDrag, TransitionValue},
+    displayed_item::{DisplayedItem, NumericFormatOptions},
     hierarchy::{HierarchyStyle, ParameterDisplayLocation},
+    time::Radix,
 };
 
 impl SystemState {
@@ -162,6 +164,14 @@ impl SystemState {
             .unwrap_or_else(|| self.user.config.behavior.arrow_key_bindings())
     }
 
+    #[inline]
+    pub fn placement_grid(&self) -> BigInt {
+        self.user
+            .placement_grid
+            .clone()
+            .unwrap_or_else(|| BigInt::from(self.user.config.behavior.placement_grid()))
+    }
+
     #[inline]
     pub fn clock_highlight_type(&self) -> ClockHighlightType {
         self.user
@@ -169,6 +179,32 @@ impl SystemState {
             .unwrap_or_else(|| self.user.config.default_clock_highlight_type())
     }
 
+    /// Whether clock edges are collected and highlighted, independently of the highlight type
+    #[inline]
+    pub fn clock_highlight_enabled(&self) -> bool {
+        self.user.clock_highlight_enabled.unwrap_or(true)
+    }
+
+    /// Whether moving one viewport moves all others to the same time range
+    #[inline]
+    pub fn viewports_linked(&self) -> bool {
+        self.user.viewports_linked.unwrap_or(false)
+    }
+
+    /// Whether sibling scopes instantiating the same module are grouped in the hierarchy
+    #[inline]
+    pub fn group_scopes_by_type(&self) -> bool {
+        self.user.group_scopes_by_type.unwrap_or(false)
+    }
+
+    /// Formatting of decimal and floating point values of variables that do not set their own
+    #[inline]
+    pub fn numeric_format(&self) -> NumericFormatOptions {
+        self.user
+            .numeric_format
+            .unwrap_or_else(|| self.user.config.layout.numeric_format())
+    }
+
     #[inline]
     pub fn hierarchy_style(&self) -> HierarchyStyle {
         self.user
@@ -204,6 +240,41 @@ impl SystemState {
             .unwrap_or_else(|| self.user.config.layout.use_dinotrace_style())
     }
 
+    #[inline]
+    pub fn show_no_data_hatch(&self) -> bool {
+        self.user
+            .show_no_data_hatch
+            .unwrap_or_else(|| self.user.config.layout.show_no_data_hatch())
+    }
+
+    #[inline]
+    pub fn item_column_side(&self) -> ItemColumnSide {
+        self.user
+            .item_column_side
+            .unwrap_or_else(|| self.user.config.layout.item_column_side())
+    }
+
+    #[inline]
+    pub fn show_glitch_density(&self) -> bool {
+        self.user
+            .show_glitch_density
+            .unwrap_or_else(|| self.user.config.layout.show_glitch_density())
+    }
+
+    #[inline]
+    pub fn show_partial_unknowns(&self) -> bool {
+        self.user
+            .show_partial_unknowns
+            .unwrap_or_else(|| self.user.config.layout.show_partial_unknowns())
+    }
+
+    #[inline]
+    pub fn show_sparklines(&self) -> bool {
+        self.user
+            .show_sparklines
+            .unwrap_or_else(|| self.user.config.layout.show_sparklines())
+    }
+
     #[inline]
     pub fn transition_value(&self) -> TransitionValue {
         self.user
@@ -211,6 +282,13 @@ impl SystemState {
             .unwrap_or_else(|| self.user.config.layout.transition_value())
     }
 
+    #[inline]
+    pub fn time_label_radix(&self) -> Radix {
+        self.user
+            .time_label_radix
+            .unwrap_or_else(|| self.user.config.layout.time_label_radix())
+    }
+
     #[inline]
     pub fn align_names_right(&self) -> bool {
         self.user
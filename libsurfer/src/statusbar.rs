@@ -1,8 +1,10 @@
-use egui::{Context, Frame, Layout, Margin, TopBottomPanel, Ui};
+use std::collections::VecDeque;
+
+use egui::{Context, Frame, Label, Layout, Margin, Sense, TopBottomPanel, Ui};
 use emath::Align;
 use web_time::{Duration, Instant};
 
-use crate::time::{time_string, timeunit_menu};
+use crate::time::{time_string, timeunit_menu, trigger_relative_time_string};
 use crate::wave_source::draw_progress_information;
 use crate::{SystemState, message::Message, wave_data::WaveData};
 
@@ -10,6 +12,38 @@ use crate::{SystemState, message::Message, wave_data::WaveData};
 /// Progress is only shown after this duration to avoid flicker on fast operations
 const PROGRESS_DEBOUNCE_MS: u64 = 100;
 
+/// Longer wave sources are shortened in the status bar, the full source is shown on hover
+const SOURCE_MAX_CHARS: usize = 60;
+
+/// Number of frames the frame time in the status bar is averaged over
+const FRAME_TIME_SAMPLES: usize = 30;
+
+/// Moving average of the time spent on the last [`FRAME_TIME_SAMPLES`] frames
+#[derive(Default)]
+pub struct FrameTimeAverage {
+    samples: VecDeque<f32>,
+    sum: f32,
+}
+
+impl FrameTimeAverage {
+    /// Add the time spent on a frame, in seconds
+    pub fn push(&mut self, frame_time: f32) {
+        self.samples.push_back(frame_time);
+        self.sum += frame_time;
+        if self.samples.len() > FRAME_TIME_SAMPLES
+            && let Some(oldest) = self.samples.pop_front()
+        {
+            self.sum -= oldest;
+        }
+    }
+
+    /// Average frame time in seconds, if any frame has been recorded
+    #[must_use]
+    pub fn average(&self) -> Option<f32> {
+        (!self.samples.is_empty()).then(|| self.sum / self.samples.len() as f32)
+    }
+}
+
 impl SystemState {
     pub fn add_statusbar_panel(
         &self,
@@ -45,7 +79,18 @@ impl SystemState {
     /// Draw left-aligned status bar elements: wave source and generation date
     fn draw_statusbar_left(&self, ui: &mut Ui, waves: Option<&WaveData>) {
         if let Some(waves) = waves {
-            ui.label(waves.source.to_string());
+            let description = waves.source.short_description(SOURCE_MAX_CHARS);
+            if let Some(location) = waves.source.copyable_location() {
+                let response = ui
+                    .add(Label::new(description).sense(Sense::click()))
+                    .on_hover_text(format!("{location}\nClick to copy"));
+                if response.clicked() {
+                    ui.ctx().copy_text(location);
+                }
+            } else {
+                ui.label(description)
+                    .on_hover_text(waves.source.to_string());
+            }
             if let Some(idx) = self.user.selected_server_file_index
                 && let Some(infos) = self.user.surver_file_infos.as_ref()
                 && let Some(file) = infos.get(idx)
@@ -87,7 +132,7 @@ impl SystemState {
         }
     }
 
-    /// Draw right-aligned status bar elements: cursor time, undo info, and count
+    /// Draw right-aligned status bar elements: cursor time, undo info, count and frame time
     fn draw_statusbar_right(&self, ui: &mut Ui, waves: Option<&WaveData>, msgs: &mut Vec<Message>) {
         if let Some(waves) = waves {
             ui.with_layout(Layout::right_to_left(Align::RIGHT), |ui| {
@@ -99,6 +144,18 @@ impl SystemState {
                         &self.get_time_format(),
                     ))
                     .context_menu(|ui| timeunit_menu(ui, msgs, &self.user.wanted_timeunit));
+                    if self.user.config.layout.show_trigger_relative_time()
+                        && let Some(trigger) = &waves.trigger_time
+                    {
+                        ui.separator();
+                        ui.label(trigger_relative_time_string(
+                            time,
+                            trigger,
+                            &waves.inner.metadata().timescale,
+                            &self.user.wanted_timeunit,
+                            &self.get_time_format(),
+                        ));
+                    }
                 }
                 if let Some(undo_op) = &self.undo_stack.last() {
                     ui.separator();
@@ -108,7 +165,39 @@ impl SystemState {
                     ui.separator();
                     ui.label(format!("Count: {count}"));
                 }
+                if self.user.config.layout.show_fps()
+                    && let Some(frame_time) = self.frame_time.average()
+                {
+                    ui.separator();
+                    ui.label(format!("{:.1} ms/frame", frame_time * 1000.));
+                }
             });
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_time_average_covers_last_samples() {
+        let mut frame_time = FrameTimeAverage::default();
+        assert_eq!(frame_time.average(), None);
+
+        frame_time.push(0.01);
+        frame_time.push(0.03);
+        assert!((frame_time.average().unwrap() - 0.02).abs() < 1e-6);
+
+        // Older samples are dropped once the window is full
+        for _ in 0..FRAME_TIME_SAMPLES {
+            frame_time.push(0.005);
+        }
+        assert!((frame_time.average().unwrap() - 0.005).abs() < 1e-6);
+
+        frame_time.push(0.035);
+        let expected =
+            (0.005 * (FRAME_TIME_SAMPLES - 1) as f32 + 0.035) / FRAME_TIME_SAMPLES as f32;
+        assert!((frame_time.average().unwrap() - expected).abs() < 1e-6);
+    }
+}
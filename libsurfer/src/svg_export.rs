@@ -0,0 +1,576 @@
+//! Export of the waveform view as an SVG image, drawn from the same draw commands as the canvas.
+use std::fmt::Write;
+
+use camino::Utf8PathBuf;
+use ecolor::Color32;
+use emath::{Align, Align2, Pos2, Vec2};
+use epaint::Stroke;
+use itertools::Itertools;
+use tracing::error;
+
+use crate::analog_renderer::{AnalogDrawingCommand, analog_stroke, select_value_range};
+use crate::config::ValueTextAlign;
+use crate::displayed_item::{AnalogRenderStyle, DisplayedFieldRef, DisplayedItem};
+use crate::displayed_item_tree::VisibleItemIndex;
+use crate::drawing_canvas::{
+    AnalogDrawingCommands, DigitalDrawingCommands, DigitalDrawingType, DrawingCommands,
+    VariableExt, fit_value_text,
+};
+use crate::translation::ValueKindExt;
+use crate::view::DrawConfig;
+use crate::{CachedDrawData, SystemState};
+
+/// Horizontal space around the variable names
+const NAME_PADDING: f32 = 8.;
+
+/// Builds an SVG document from lines, polygons, rectangles and text.
+pub struct SvgWriter {
+    width: f32,
+    height: f32,
+    body: String,
+}
+
+impl SvgWriter {
+    #[must_use]
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    /// Filled rectangle, fully transparent rectangles are left out
+    pub fn rect(&mut self, min: Pos2, max: Pos2, fill: Color32) {
+        if fill.a() == 0 {
+            return;
+        }
+        let _ = writeln!(
+            self.body,
+            r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#,
+            min.x,
+            min.y,
+            max.x - min.x,
+            max.y - min.y,
+            paint("fill", fill)
+        );
+    }
+
+    pub fn polyline(&mut self, points: &[Pos2], stroke: Stroke) {
+        let _ = writeln!(
+            self.body,
+            r#"<polyline points="{}" fill="none"{}/>"#,
+            point_list(points),
+            stroke_attributes(stroke)
+        );
+    }
+
+    pub fn polygon(&mut self, points: &[Pos2], fill: Color32, stroke: Stroke) {
+        let fill = if fill.a() == 0 {
+            r#" fill="none""#.to_string()
+        } else {
+            paint("fill", fill)
+        };
+        let _ = writeln!(
+            self.body,
+            r#"<polygon points="{}"{fill}{}/>"#,
+            point_list(points),
+            stroke_attributes(stroke)
+        );
+    }
+
+    pub fn text(&mut self, pos: Pos2, anchor: Align2, text: &str, size: f32, color: Color32) {
+        let text_anchor = match anchor.x() {
+            Align::Min => "start",
+            Align::Center => "middle",
+            Align::Max => "end",
+        };
+        let baseline = match anchor.y() {
+            Align::Min => "hanging",
+            Align::Center => "central",
+            Align::Max => "text-after-edge",
+        };
+        let _ = writeln!(
+            self.body,
+            r#"<text x="{}" y="{}" font-family="monospace" font-size="{size}" text-anchor="{text_anchor}" dominant-baseline="{baseline}"{}>{}</text>"#,
+            pos.x,
+            pos.y,
+            paint("fill", color),
+            escape(text)
+        );
+    }
+
+    /// Start a group of elements that is shifted right by `x` and clipped to `size`. Must be
+    /// closed by [`SvgWriter::end_group`].
+    pub fn begin_clipped_group(&mut self, id: &str, x: f32, size: Vec2) {
+        let _ = writeln!(
+            self.body,
+            r#"<clipPath id="{id}"><rect x="0" y="0" width="{}" height="{}"/></clipPath>"#,
+            size.x, size.y
+        );
+        let _ = writeln!(
+            self.body,
+            r#"<g transform="translate({x} 0)" clip-path="url(#{id})">"#
+        );
+    }
+
+    pub fn end_group(&mut self) {
+        self.body.push_str("</g>\n");
+    }
+
+    #[must_use]
+    pub fn finish(self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}</svg>\n",
+            self.body,
+            w = self.width,
+            h = self.height,
+        )
+    }
+}
+
+/// Color attribute `name`, with a separate opacity attribute for translucent colors
+fn paint(name: &str, color: Color32) -> String {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    let mut attribute = format!(r##" {name}="#{r:02x}{g:02x}{b:02x}""##);
+    if a < u8::MAX {
+        let _ = write!(attribute, r#" {name}-opacity="{}""#, f32::from(a) / 255.);
+    }
+    attribute
+}
+
+fn stroke_attributes(stroke: Stroke) -> String {
+    format!(
+        r#"{} stroke-width="{}""#,
+        paint("stroke", stroke.color),
+        stroke.width
+    )
+}
+
+fn point_list(points: &[Pos2]) -> String {
+    points.iter().map(|p| format!("{},{}", p.x, p.y)).join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl SystemState {
+    /// SVG image of the waveforms in viewport `viewport_idx`, with the item names to the left and
+    /// the timeline on top. The waveforms are drawn from draw commands generated for `cfg`.
+    /// Only the root of each variable is drawn, expanded fields are left out.
+    #[must_use]
+    pub fn encode_svg(&self, viewport_idx: usize, cfg: &DrawConfig) -> Option<String> {
+        let waves = self.user.waves.as_ref()?;
+        self.generate_draw_commands(cfg, &mut vec![], viewport_idx);
+        let draw_data = self.draw_data.borrow();
+        let Some(CachedDrawData::WaveDrawData(draw_data)) = &draw_data[viewport_idx] else {
+            error!("Only waveforms can be exported as SVG");
+            return None;
+        };
+        let theme = &self.user.config.theme;
+        let char_width = cfg.text_size * (20. / 31.);
+
+        let rows = waves
+            .items_tree
+            .iter_visible()
+            .enumerate()
+            .filter(|(_, node)| !node.hidden)
+            .filter_map(|(vidx, node)| {
+                let item = waves.displayed_items.get(&node.item_ref)?;
+                let height = cfg.line_height * item.height_scaling_factor();
+                Some((VisibleItemIndex(vidx), node.item_ref, item, height))
+            })
+            .collect_vec();
+        let names_width = rows
+            .iter()
+            .map(|(_, _, item, _)| item.name().chars().count())
+            .max()
+            .unwrap_or(0) as f32
+            * char_width
+            + 2. * NAME_PADDING;
+        let ruler_height = if self.show_default_timeline() {
+            cfg.line_height
+        } else {
+            0.
+        };
+        let height = ruler_height + rows.iter().map(|(_, _, _, h)| h).sum::<f32>();
+        let width = names_width + cfg.canvas_width;
+
+        let mut svg = SvgWriter::new(width, height);
+        svg.rect(
+            Pos2::ZERO,
+            Pos2::new(width, height),
+            theme.canvas_colors.background,
+        );
+
+        let mut offset = ruler_height;
+        for (item_count, (vidx, _, item, row_height)) in rows.iter().enumerate() {
+            let background = self.get_background_color(waves, *vidx, item_count);
+            svg.rect(
+                Pos2::new(0., offset),
+                Pos2::new(width, offset + row_height),
+                background,
+            );
+            let color = item
+                .color()
+                .and_then(|color| theme.get_color(color))
+                .unwrap_or(theme.foreground);
+            svg.text(
+                Pos2::new(NAME_PADDING, offset + row_height / 2.),
+                Align2::LEFT_CENTER,
+                &item.name(),
+                cfg.text_size,
+                color,
+            );
+            offset += row_height;
+        }
+
+        svg.begin_clipped_group(
+            "waveforms",
+            names_width,
+            Vec2::new(cfg.canvas_width, height),
+        );
+        if self.show_ticks() {
+            let stroke = Stroke::from(&theme.ticks.style);
+            for (_, x) in &draw_data.ticks {
+                svg.polyline(
+                    &[Pos2::new(*x, ruler_height), Pos2::new(*x, height)],
+                    stroke,
+                );
+            }
+        }
+        if self.show_default_timeline() {
+            for (label, x) in &draw_data.ticks {
+                svg.text(
+                    Pos2::new(*x, 0.),
+                    Align2::CENTER_TOP,
+                    label,
+                    cfg.text_size,
+                    theme.foreground,
+                );
+            }
+        }
+
+        let mut offset = ruler_height;
+        for (item_count, (vidx, item_ref, item, row_height)) in rows.iter().enumerate() {
+            if let DisplayedItem::Variable(variable) = item
+                && let Some(commands) = draw_data
+                    .draw_commands
+                    .get(&DisplayedFieldRef::from(*item_ref))
+            {
+                let color = item
+                    .color()
+                    .and_then(|color| theme.get_color(color))
+                    .unwrap_or(theme.variable_default);
+                match commands {
+                    DrawingCommands::Digital(commands) => {
+                        let background = self.get_background_color(waves, *vidx, item_count);
+                        let text_align = variable
+                            .value_text_align
+                            .unwrap_or_else(|| self.user.config.layout.value_text_align());
+                        self.digital_svg(
+                            &mut svg,
+                            commands,
+                            color,
+                            theme.get_best_text_color(background),
+                            text_align,
+                            (offset, *row_height),
+                            cfg.text_size,
+                        );
+                    }
+                    DrawingCommands::Analog(commands) => {
+                        self.analog_svg(&mut svg, commands, color, (offset, *row_height));
+                    }
+                }
+            }
+            offset += row_height;
+        }
+
+        let viewport = &waves.viewports[viewport_idx];
+        let num_timestamps = waves.safe_num_timestamps();
+        let cursor = waves
+            .cursor
+            .iter()
+            .map(|time| (time, Stroke::from(&theme.cursor)));
+        let markers = waves.markers.iter().map(|(idx, time)| {
+            (
+                time,
+                Stroke::new(theme.cursor.width, waves.get_marker_color(*idx, theme)),
+            )
+        });
+        for (time, stroke) in cursor.chain(markers) {
+            let x = viewport.pixel_from_time(time, cfg.canvas_width, &num_timestamps);
+            svg.polyline(&[Pos2::new(x, ruler_height), Pos2::new(x, height)], stroke);
+        }
+        svg.end_group();
+
+        Some(svg.finish())
+    }
+
+    /// Draws a digital trace in the row `(offset, height)` the way the canvas does.
+    #[allow(clippy::too_many_arguments)]
+    fn digital_svg(
+        &self,
+        svg: &mut SvgWriter,
+        commands: &DigitalDrawingCommands,
+        color: Color32,
+        text_color: Color32,
+        text_align: ValueTextAlign,
+        (offset, height): (f32, f32),
+        text_size: f32,
+    ) {
+        let theme = &self.user.config.theme;
+        let at = |x: f32, y: f32| Pos2::new(x, y * height + offset);
+        match commands.drawing_type {
+            DigitalDrawingType::Bool | DigitalDrawingType::Clock => {
+                for ((old_x, prev), (new_x, new)) in commands.values.iter().tuple_windows() {
+                    let (Some(prev), Some(new)) = (&prev.inner, &new.inner) else {
+                        continue;
+                    };
+                    let (old_height, old_color, old_bg) =
+                        prev.value.bool_drawing_spec(color, theme, prev.kind);
+                    let (new_height, _, _) = new.value.bool_drawing_spec(color, theme, new.kind);
+                    if let (Some(old_bg), true) = (old_bg, self.fill_high_values()) {
+                        svg.rect(at(*old_x, 0.), at(*new_x, 1.), old_bg);
+                    }
+                    svg.polyline(
+                        &[
+                            at(*old_x, 1. - old_height),
+                            at(*new_x, 1. - old_height),
+                            at(*new_x, 1. - new_height),
+                        ],
+                        Stroke::new(theme.linewidth, old_color),
+                    );
+                }
+            }
+            DigitalDrawingType::Event => {
+                for (x, region) in &commands.values {
+                    if region.inner.is_some() {
+                        svg.polyline(
+                            &[at(*x, 0.), at(*x, 1.)],
+                            Stroke::new(theme.linewidth, color),
+                        );
+                    }
+                }
+            }
+            DigitalDrawingType::Vector => {
+                let char_width = text_size * (20. / 31.);
+                for ((old_x, prev), (new_x, _)) in commands.values.iter().tuple_windows() {
+                    let Some(prev) = &prev.inner else {
+                        continue;
+                    };
+                    let color = prev.kind.color(color, theme);
+                    let transition_width = (new_x - old_x).min(theme.vector_transition_width);
+                    svg.polygon(
+                        &[
+                            at(*old_x, 0.5),
+                            at(old_x + transition_width / 2., 0.),
+                            at(new_x - transition_width / 2., 0.),
+                            at(*new_x, 0.5),
+                            at(new_x - transition_width / 2., 1.),
+                            at(old_x + transition_width / 2., 1.),
+                        ],
+                        color.gamma_multiply(theme.wide_opacity),
+                        Stroke::new(theme.linewidth, color),
+                    );
+
+                    let num_chars =
+                        (((new_x - old_x) - transition_width) / char_width).floor() as usize;
+                    if num_chars >= 1 {
+                        let (x, anchor) = match text_align {
+                            ValueTextAlign::Left => (old_x + transition_width, Align2::LEFT_CENTER),
+                            ValueTextAlign::Right => {
+                                (new_x - transition_width, Align2::RIGHT_CENTER)
+                            }
+                        };
+                        svg.text(
+                            at(x, 0.5),
+                            anchor,
+                            &fit_value_text(&prev.value, num_chars, text_align),
+                            text_size,
+                            text_color,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Draws an analog trace in the row `(offset, height)` as polylines broken at undefined
+    /// values.
+    fn analog_svg(
+        &self,
+        svg: &mut SvgWriter,
+        commands: &AnalogDrawingCommands,
+        color: Color32,
+        (offset, height): (f32, f32),
+    ) {
+        let AnalogDrawingCommands::Ready {
+            viewport_min,
+            viewport_max,
+            global_min,
+            global_max,
+            values,
+            analog_settings,
+            style,
+            ..
+        } = commands
+        else {
+            return;
+        };
+        let (min, max) = select_value_range(
+            *viewport_min,
+            *viewport_max,
+            *global_min,
+            *global_max,
+            analog_settings,
+        );
+        let at = |x: f32, value: f64| {
+            let normalized = if (max - min).abs() <= f64::EPSILON {
+                0.5
+            } else {
+                ((value - min) / (max - min)) as f32
+            };
+            Pos2::new(x, (1. - normalized) * height + offset)
+        };
+        let stroke = analog_stroke(style, color, self.user.config.theme.linewidth);
+
+        let mut points = vec![];
+        for command in values {
+            match command {
+                AnalogDrawingCommand::Flat {
+                    start_px,
+                    start_val,
+                    end_px,
+                    end_val,
+                } if start_val.is_finite() => {
+                    points.push(at(*start_px, *start_val));
+                    let end_val = match analog_settings.render_style {
+                        AnalogRenderStyle::Interpolated if end_val.is_finite() => *end_val,
+                        _ => *start_val,
+                    };
+                    points.push(at(*end_px, end_val));
+                }
+                AnalogDrawingCommand::Flat { .. } => {
+                    if points.len() > 1 {
+                        svg.polyline(&points, stroke);
+                    }
+                    points.clear();
+                }
+                AnalogDrawingCommand::Range {
+                    px,
+                    min_val,
+                    max_val,
+                } => {
+                    if min_val.is_finite() && max_val.is_finite() {
+                        svg.polyline(&[at(*px, *min_val), at(*px, *max_val)], stroke);
+                    }
+                }
+            }
+        }
+        if points.len() > 1 {
+            svg.polyline(&points, stroke);
+        }
+    }
+
+    /// Write the waveforms of the first viewport as an SVG image, sized like the canvas. Opens a
+    /// save dialog, which downloads the image on the web, if no path is given.
+    pub fn export_svg(&mut self, path: Option<Utf8PathBuf>) {
+        let size = self
+            .last_canvas_rect
+            .borrow()
+            .map_or(Vec2::new(1280., 720.), |rect| rect.size());
+        let cfg = DrawConfig::new(
+            size.y,
+            size.x,
+            self.user.config.layout.waveforms_line_height,
+            self.user.config.layout.waveforms_text_size,
+        );
+        let encoded = self.encode_svg(0, &cfg);
+        // The draw commands were generated for the export, let the canvas regenerate its own
+        self.invalidate_draw_commands();
+        let Some(encoded) = encoded else {
+            return;
+        };
+
+        self.save_bytes_to(
+            path,
+            "Export SVG",
+            ("SVG files (*.svg)".to_string(), vec!["svg".to_string()]),
+            encoded.into_bytes(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::BigInt;
+
+    use super::*;
+    use crate::message::Message;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+
+    #[test]
+    fn svg_writer_emits_elements() {
+        let mut svg = SvgWriter::new(100., 20.);
+        svg.rect(Pos2::ZERO, Pos2::new(100., 20.), Color32::BLACK);
+        svg.rect(Pos2::ZERO, Pos2::new(10., 10.), Color32::TRANSPARENT);
+        svg.polyline(
+            &[Pos2::new(0., 20.), Pos2::new(50., 20.), Pos2::new(50., 0.)],
+            Stroke::new(1., Color32::from_rgb(0, 255, 0)),
+        );
+        svg.text(
+            Pos2::new(60., 10.),
+            Align2::LEFT_CENTER,
+            "a<b",
+            14.,
+            Color32::WHITE,
+        );
+
+        assert_eq!(
+            svg.finish(),
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="20" viewBox="0 0 100 20">
+<rect x="0" y="0" width="100" height="20" fill="#000000"/>
+<polyline points="0,20 50,20 50,0" fill="none" stroke="#00ff00" stroke-width="1"/>
+<text x="60" y="10" font-family="monospace" font-size="14" text-anchor="start" dominant-baseline="central" fill="#ffffff">a&lt;b</text>
+</svg>
+"##
+        );
+    }
+
+    #[test]
+    fn translucent_colors_get_opacity() {
+        assert_eq!(
+            paint("fill", Color32::from_rgba_unmultiplied(255, 0, 0, 51)),
+            r##" fill="#ff0000" fill-opacity="0.2""##
+        );
+    }
+
+    #[test]
+    fn svg_export_contains_waveform_elements() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk", "tb.dut.counter"]);
+        state.update(Message::CursorSet(BigInt::from(100)));
+
+        let svg = state
+            .encode_svg(0, &DrawConfig::new(100., 400., 16., 14.))
+            .unwrap();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+        assert!(svg.ends_with("</svg>\n"));
+        // Variable names
+        assert!(svg.contains(">clk</text>"));
+        assert!(svg.contains(">counter</text>"));
+        // Clock trace, vector regions with their values and the cursor
+        assert!(svg.contains("<polyline"));
+        assert!(svg.contains("<polygon"));
+        assert!(svg.contains(">0</text>"));
+        let cursor = state.user.config.theme.cursor.color.to_srgba_unmultiplied();
+        assert!(svg.contains(&format!(
+            "stroke=\"#{:02x}{:02x}{:02x}\"",
+            cursor[0], cursor[1], cursor[2]
+        )));
+    }
+}
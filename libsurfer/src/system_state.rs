@@ -10,14 +10,21 @@ use egui::{Pos2, Rect};
 use surfer_translation_types::translator::VariableNameInfo;
 
 use crate::{
-    CachedDrawData, CanvasState, Channels, WcpClientCapabilities, command_prompt,
+    CachedDrawData, CanvasState, Channels, WcpClientCapabilities,
+    activity_histogram::ActivityHistogram,
+    change_summary::ChangeSummary,
+    command_prompt,
     displayed_item::DisplayedItemRef,
+    enum_stats::EnumStats,
     hierarchy::ScopeExpandType,
     message::Message,
+    quick_open::QuickOpen,
     state::UserState,
+    statusbar::FrameTimeAverage,
     translation::{TranslatorList, all_translators},
     wave_container::VariableRef,
-    wave_source::{LoadOptions, LoadProgress},
+    wave_data::PreservedView,
+    wave_source::{LoadOptions, LoadProgress, WaveSource},
 };
 
 #[cfg(feature = "performance_plot")]
@@ -36,6 +43,8 @@ pub struct SystemState {
 
     /// Buffer for the command input
     pub(crate) command_prompt: command_prompt::CommandPrompt,
+    /// Query and selection of the quick open palette
+    pub(crate) quick_open: QuickOpen,
 
     /// The context to egui, we need this to change the visual settings when the config is reloaded
     pub(crate) context: Option<Arc<egui::Context>>,
@@ -69,6 +78,10 @@ pub struct SystemState {
 
     // Egui requires a place to store text field content between frames
     pub(crate) url: RefCell<String>,
+    /// Contents of the bearer token field of the URL dialog
+    pub(crate) url_token: RefCell<String>,
+    /// Bearer tokens sent when fetching URLs. Only kept in memory and never saved.
+    pub(crate) url_tokens: HashMap<String, String>,
     pub(crate) command_prompt_text: RefCell<String>,
     pub(crate) last_canvas_rect: RefCell<Option<Rect>>,
     pub(crate) surver_selected_file: RefCell<Option<usize>>,
@@ -82,6 +95,29 @@ pub struct SystemState {
     pub(crate) char_to_add_to_prompt: RefCell<Option<char>>,
     // This item works with the expand scope feature to determine what hierarchys to open
     pub scope_ref_to_expand: RefCell<Option<ScopeExpandType>>,
+    /// Changed when a state is loaded, to restore the stored expansion of the hierarchy
+    pub(crate) hierarchy_generation: u32,
+    /// Set when the hierarchy should be scrolled to the stored position in the next frame
+    pub(crate) restore_hierarchy_scroll: RefCell<bool>,
+    /// Variable highlighted in the hierarchy after a reveal, until another scope is selected
+    pub(crate) revealed_variable: Option<VariableRef>,
+    /// Set when the revealed variable should be scrolled into view in the next frame
+    pub(crate) scroll_to_revealed_variable: RefCell<bool>,
+    /// Variables whose values are shown in the watch window, independently of `items_tree`
+    pub(crate) watch_list: Vec<VariableRef>,
+    /// Result of the last `ComputeActivityHistogram`, shown until the window is closed
+    pub(crate) activity_histogram: Option<ActivityHistogram>,
+    /// Index of the marker compared with the cursor in the compare window, if it is open
+    pub(crate) cursor_marker_compare: Option<u8>,
+    /// Result of the last `SummarizeChangesBetween`, shown until the window is closed
+    pub(crate) change_summary: Option<ChangeSummary>,
+    /// Result of the last `ComputeEnumStats`, shown until the window is closed
+    pub(crate) enum_stats: Option<EnumStats>,
+    /// View captured by `ReloadPreservingView` together with the reloaded source, restored once
+    /// the waves from that source have their time table
+    pub(crate) preserved_view: Option<(WaveSource, PreservedView)>,
+    /// Frame times for the status bar readout, only recorded if `layout.show_fps` is set
+    pub(crate) frame_time: FrameTimeAverage,
 
     // Benchmarking stuff
     /// Invalidate draw commands every frame to make performance comparison easier
@@ -123,6 +159,7 @@ impl SystemState {
             channels,
             progress_tracker: None,
             command_prompt: Default::default(),
+            quick_open: QuickOpen::default(),
             context: None,
             wcp_server_thread: None,
             wcp_server_address: None,
@@ -135,6 +172,8 @@ impl SystemState {
             batch_messages: VecDeque::new(),
             batch_messages_completed: false,
             url: RefCell::new(String::new()),
+            url_token: RefCell::new(String::new()),
+            url_tokens: HashMap::new(),
             command_prompt_text: RefCell::new(String::new()),
             draw_data: RefCell::new(vec![None]),
             variable_name_info_cache: RefCell::new(HashMap::new()),
@@ -143,6 +182,17 @@ impl SystemState {
             items_to_expand: RefCell::new(vec![]),
             char_to_add_to_prompt: RefCell::new(None),
             scope_ref_to_expand: RefCell::new(None),
+            hierarchy_generation: 0,
+            restore_hierarchy_scroll: RefCell::new(false),
+            revealed_variable: None,
+            scroll_to_revealed_variable: RefCell::new(false),
+            watch_list: vec![],
+            activity_histogram: None,
+            cursor_marker_compare: None,
+            change_summary: None,
+            enum_stats: None,
+            preserved_view: None,
+            frame_time: FrameTimeAverage::default(),
             surver_selected_file: RefCell::new(None),
             surver_load_options: RefCell::new(LoadOptions::Clear),
             expand_parameter_section: false,
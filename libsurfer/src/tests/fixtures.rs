@@ -0,0 +1,51 @@
+//! Example waveforms loaded into a [`SystemState`], for tests of behavior that needs data.
+use project_root::get_project_root;
+
+use crate::message::Message;
+use crate::wave_container::{VariableRef, VariableRefExt};
+use crate::{StartupParams, SystemState, WaveSource};
+
+/// wait for GUI to converge
+#[inline]
+pub fn wait_for_waves_fully_loaded(state: &mut SystemState, timeout_s: u64) {
+    let load_start = std::time::Instant::now();
+    while !(state.waves_fully_loaded() && state.batch_commands_completed()) {
+        state.handle_async_messages();
+        state.handle_batch_commands();
+        if load_start.elapsed().as_secs() > timeout_s {
+            panic!("Timeout after {timeout_s}s!");
+        }
+    }
+}
+
+/// State with the file at `path`, relative to the project root, fully loaded
+pub(crate) fn load_example(path: &str) -> SystemState {
+    let mut state = SystemState::new_default_config()
+        .unwrap()
+        .with_params(StartupParams {
+            waves: Some(WaveSource::File(
+                get_project_root().unwrap().join(path).try_into().unwrap(),
+            )),
+            ..Default::default()
+        });
+    wait_for_waves_fully_loaded(&mut state, 10);
+    state
+}
+
+/// State with `examples/counter.vcd` fully loaded
+pub(crate) fn load_counter_vcd() -> SystemState {
+    load_example("examples/counter.vcd")
+}
+
+/// State with `examples/counter.vcd` and the variables with the given full paths loaded
+pub(crate) fn load_counter_vcd_with_variables(variables: &[&str]) -> SystemState {
+    let mut state = load_counter_vcd();
+    state.update(Message::AddVariables(
+        variables
+            .iter()
+            .map(|variable| VariableRef::from_hierarchy_string(variable))
+            .collect(),
+    ));
+    wait_for_waves_fully_loaded(&mut state, 10);
+    state
+}
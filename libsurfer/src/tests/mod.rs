@@ -1,3 +1,4 @@
+pub(crate) mod fixtures;
 mod remote;
 pub(crate) mod snapshot;
 mod wcp;
@@ -1,9 +1,11 @@
 // tests for the surfer:remote submodule
 
-use super::snapshot::{render_and_compare, wait_for_waves_fully_loaded};
+use super::fixtures::wait_for_waves_fully_loaded;
+use super::snapshot::render_and_compare;
 use crate::SystemState;
+use crate::displayed_item::DisplayedItemRef;
 use crate::message::Message;
-use crate::wave_container::{ScopeRef, ScopeRefExt};
+use crate::wave_container::{ScopeRef, ScopeRefExt, SignalId, VariableRef, VariableRefExt};
 use crate::wave_source::LoadOptions;
 use std::path::PathBuf;
 use std::sync::atomic::Ordering;
@@ -126,3 +128,53 @@ snapshot_ui_remote!(
         Message::SetToolbarVisible(true),
     ]
 );
+
+#[test]
+fn refresh_requests_only_displayed_signals() {
+    let port_offset = UNIQUE_PORT_COUNT.fetch_add(1, Ordering::SeqCst);
+    let port = BASE_PORT + port_offset as u16;
+    let project_root: camino::Utf8PathBuf = project_root::get_project_root()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let filenames = vec![project_root.join("examples/counter.vcd").to_string()];
+    let mut state = run_with_server(DEFAULT_IP, port, DEFAULT_TOKEN, &filenames, || {
+        vec![
+            Message::AddVariables(vec![
+                VariableRef::from_hierarchy_string("tb.clk"),
+                VariableRef::from_hierarchy_string("tb.reset"),
+                VariableRef::from_hierarchy_string("tb.dut.counter"),
+            ]),
+            // tb.reset stays loaded, but is no longer displayed
+            Message::RemoveItems(vec![DisplayedItemRef(2)]),
+        ]
+    });
+
+    let signal = |state: &SystemState, path: &str| {
+        let waves = state.user.waves.as_ref().unwrap().inner.as_waves().unwrap();
+        let SignalId::Wellen(signal) = waves
+            .signal_id(&VariableRef::from_hierarchy_string(path))
+            .unwrap()
+        else {
+            panic!("Expected a wellen signal");
+        };
+        signal
+    };
+    let mut expected = vec![signal(&state, "tb.clk"), signal(&state, "tb.dut.counter")];
+    expected.sort();
+
+    let cmd = state.refresh_displayed_signals_cmd().unwrap();
+    assert_eq!(cmd.signals(), expected.as_slice());
+    assert!(!cmd.signals().contains(&signal(&state, "tb.reset")));
+
+    // The refreshed data replaces the loaded data
+    state.load_variables(cmd);
+    wait_for_waves_fully_loaded(&mut state, 10);
+    let waves = state.user.waves.as_ref().unwrap().inner.as_waves().unwrap();
+    for path in ["tb.clk", "tb.dut.counter"] {
+        let id = waves
+            .signal_id(&VariableRef::from_hierarchy_string(path))
+            .unwrap();
+        assert!(waves.is_signal_loaded(&id));
+    }
+}
@@ -14,6 +14,7 @@ use project_root::get_project_root;
 use test_log::test;
 use tracing::info;
 
+use super::fixtures::wait_for_waves_fully_loaded;
 use crate::{
     Message, MoveDir, StartupParams, SystemState, WaveSource,
     async_util::AsyncJob,
@@ -1913,19 +1914,6 @@ snapshot_ui!(signals_can_be_added_after_file_switch, || {
     state
 });
 
-/// wait for GUI to converge
-#[inline]
-pub fn wait_for_waves_fully_loaded(state: &mut SystemState, timeout_s: u64) {
-    let load_start = std::time::Instant::now();
-    while !(state.waves_fully_loaded() && state.batch_commands_completed()) {
-        state.handle_async_messages();
-        state.handle_batch_commands();
-        if load_start.elapsed().as_secs() > timeout_s {
-            panic!("Timeout after {timeout_s}s!");
-        }
-    }
-}
-
 snapshot_ui_with_theme!(theme_dark_high_contrast, "dark-high-contrast");
 snapshot_ui_with_theme!(theme_dark_plus, "dark+");
 snapshot_ui_with_theme!(theme_default, "default");
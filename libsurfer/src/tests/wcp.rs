@@ -173,6 +173,7 @@ async fn greet(tx: &Sender<WcpCSMessage>, rx: &mut Receiver<WcpSCMessage>) -> Re
         "zoom_to_fit",
         "add_markers",
         "set_viewport_range_to",
+        "get_cursor",
     ];
     assert_eq!(commands, e_commands);
 
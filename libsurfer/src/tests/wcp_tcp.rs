@@ -4,13 +4,14 @@ use crate::wave_source::LoadOptions;
 
 use port_check::free_local_ipv4_port_in_range;
 use serde_json::Error as serde_Error;
-use surfer_wcp::{WcpCSMessage, WcpCommand, WcpSCMessage};
+use surfer_wcp::{WcpCSMessage, WcpCommand, WcpResponse, WcpSCMessage};
 use test_log::test;
 use tokio::io::AsyncWriteExt;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::time::{Duration, sleep, timeout};
 
 use itertools::Itertools;
+use num::BigInt;
 use std::future::Future;
 use std::sync::atomic::Ordering;
 use std::sync::{Arc, LazyLock, Mutex};
@@ -289,3 +290,142 @@ fn response_and_event() {
             .expect("failed to get waveforms_loaded response");
     });
 }
+
+#[test]
+fn get_cursor() {
+    run_test(async {
+        let mut state = SystemState::new_default_config().unwrap();
+        let port = get_test_port();
+        state.update(Message::StartWcpServer {
+            address: Some(format!("127.0.0.1:{port}").to_string()),
+            initiate: false,
+        });
+        let msg_sender = state.channels.msg_sender.clone();
+        let mut stream = connect(port).await;
+        get_json_response(&stream, &mut state)
+            .await
+            .expect("failed to get WCP greeting");
+        msg_sender
+            .send(Message::LoadFile(
+                "../examples/counter.vcd".into(),
+                LoadOptions::Clear,
+            ))
+            .unwrap();
+        get_json_response(&stream, &mut state)
+            .await
+            .expect("failed to get waveforms_loaded response");
+
+        // Without a cursor, the time is null rather than zero
+        send_message(&mut stream, &WcpCSMessage::command(WcpCommand::get_cursor)).await;
+        let response = get_json_response(&stream, &mut state)
+            .await
+            .expect("failed to get get_cursor response");
+        assert_eq!(
+            response,
+            WcpSCMessage::response(WcpResponse::get_cursor { time: None })
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"type":"response","command":"get_cursor","time":null}"#
+        );
+
+        state.update(Message::CursorSet(BigInt::from(130)));
+        send_message(&mut stream, &WcpCSMessage::command(WcpCommand::get_cursor)).await;
+        let response = get_json_response(&stream, &mut state)
+            .await
+            .expect("failed to get get_cursor response");
+        assert_eq!(
+            response,
+            WcpSCMessage::response(WcpResponse::get_cursor {
+                time: Some("130 s".to_string())
+            })
+        );
+    });
+}
+
+#[test]
+fn set_cursor() {
+    run_test(async {
+        let mut state = SystemState::new_default_config().unwrap();
+        let port = get_test_port();
+        state.update(Message::StartWcpServer {
+            address: Some(format!("127.0.0.1:{port}").to_string()),
+            initiate: false,
+        });
+        let msg_sender = state.channels.msg_sender.clone();
+        let mut stream = connect(port).await;
+        get_json_response(&stream, &mut state)
+            .await
+            .expect("failed to get WCP greeting");
+        msg_sender
+            .send(Message::LoadFile(
+                "../examples/counter.vcd".into(),
+                LoadOptions::Clear,
+            ))
+            .unwrap();
+        get_json_response(&stream, &mut state)
+            .await
+            .expect("failed to get waveforms_loaded response");
+
+        let cursor_after = async |stream: &mut TcpStream, state: &mut SystemState, time: &str| {
+            let command = WcpCommand::set_cursor {
+                timestamp: BigInt::ZERO,
+                time: Some(time.to_string()),
+                scroll: true,
+            };
+            send_message(stream, &WcpCSMessage::command(command)).await;
+            let response = get_json_response(stream, state)
+                .await
+                .expect("failed to get set_cursor response");
+            (response, state.user.waves.as_ref().unwrap().cursor.clone())
+        };
+
+        for (time, expected) in [("130 s", 130), ("25", 25), ("5000", 800), ("-3 s", 0)] {
+            assert_eq!(
+                cursor_after(&mut stream, &mut state, time).await,
+                (
+                    WcpSCMessage::response(WcpResponse::ack),
+                    Some(BigInt::from(expected))
+                ),
+                "set_cursor to {time}"
+            );
+        }
+
+        let (response, cursor) = cursor_after(&mut stream, &mut state, "soon").await;
+        assert!(
+            matches!(response, WcpSCMessage::error { ref error, .. } if error == "set_cursor"),
+            "unexpected response {response:?}"
+        );
+        assert_eq!(cursor, Some(BigInt::ZERO));
+
+        // Earlier clients only send the time as a number of ticks called `timestamp`
+        let legacy: WcpCSMessage =
+            serde_json::from_str(r#"{"type":"command","command":"set_cursor","timestamp":42}"#)
+                .unwrap();
+        let legacy_command = || WcpCommand::set_cursor {
+            timestamp: BigInt::from(42),
+            time: None,
+            scroll: false,
+        };
+        assert_eq!(legacy, WcpCSMessage::command(legacy_command()));
+        send_message(&mut stream, &legacy).await;
+        assert_eq!(
+            get_json_response(&stream, &mut state).await.unwrap(),
+            WcpSCMessage::response(WcpResponse::ack)
+        );
+        assert_eq!(
+            state.user.waves.as_ref().unwrap().cursor,
+            Some(BigInt::from(42))
+        );
+
+        // ... and commands without the new fields are written as before
+        assert_eq!(
+            serde_json::to_value(WcpCSMessage::command(legacy_command())).unwrap(),
+            serde_json::json!({
+                "type": "command",
+                "command": "set_cursor",
+                "timestamp": BigInt::from(42),
+            })
+        );
+    });
+}
@@ -1,5 +1,5 @@
 //! Time handling and formatting.
-use derive_more::Display;
+use derive_more::{Display, FromStr};
 use ecolor::Color32;
 use egui::Ui;
 use emath::{Align2, Pos2};
@@ -7,7 +7,7 @@ use enum_iterator::Sequence;
 use epaint::{FontId, Stroke};
 use ftr_parser::types::Timescale;
 use itertools::Itertools;
-use num::{BigInt, BigRational, ToPrimitive, Zero};
+use num::{BigInt, BigRational, Integer, ToPrimitive, Zero, bigint::Sign};
 use pure_rust_locales::{Locale, locale_match};
 use serde::{Deserialize, Serialize};
 use std::sync::OnceLock;
@@ -266,6 +266,28 @@ pub enum TimeStringFormatting {
     SI,
 }
 
+/// Number base of the time labels of the ticks.
+#[derive(Debug, Clone, Copy, Display, Eq, FromStr, PartialEq, Serialize, Deserialize, Sequence)]
+pub enum Radix {
+    /// Decimal numbers.
+    Decimal,
+
+    /// Hexadecimal numbers with a `0x` prefix.
+    Hexadecimal,
+
+    /// Octal numbers with a `0o` prefix.
+    Octal,
+}
+
+/// Create menu for selecting the number base of the time labels.
+pub fn radix_menu(ui: &mut Ui, msgs: &mut Vec<Message>, wanted_radix: Radix) {
+    for radix in enum_iterator::all::<Radix>() {
+        if ui.radio(wanted_radix == radix, radix.to_string()).clicked() {
+            msgs.push(Message::SetTimeLabelRadix(radix));
+        }
+    }
+}
+
 /// Get rid of trailing zeros if the string contains a ., i.e., being fractional
 /// If the resulting string ends with ., remove that as well.
 fn strip_trailing_zeros_and_period(time: String) -> String {
@@ -286,6 +308,20 @@ fn split_and_format_number(time: &str, format: TimeStringFormatting) -> String {
     }
 }
 
+/// Format number in the given [`Radix`]. Only integers are converted, fractional numbers
+/// are formatted as decimal numbers based on [`TimeStringFormatting`].
+fn format_number_in_radix(time: &str, radix: Radix, format: TimeStringFormatting) -> String {
+    let integer = match radix {
+        Radix::Decimal => None,
+        Radix::Hexadecimal | Radix::Octal => time.parse::<BigInt>().ok(),
+    };
+    match (radix, integer) {
+        (Radix::Hexadecimal, Some(integer)) => format!("{integer:#x}"),
+        (Radix::Octal, Some(integer)) => format!("{integer:#o}"),
+        _ => split_and_format_number(time, format),
+    }
+}
+
 fn format_si(time: &str) -> String {
     if let Some((integer_part, fractional_part)) = time.split_once('.') {
         let integer_result = if integer_part.len() > 4 {
@@ -350,6 +386,7 @@ pub struct TimeFormatter {
     timescale: TimeScale,
     wanted_unit: TimeUnit,
     time_format: TimeFormat,
+    radix: Radix,
     /// Cached exponent difference (wanted - data)
     exponent_diff: i8,
     /// Cached unit string (empty if `show_unit` is false)
@@ -384,6 +421,7 @@ impl TimeFormatter {
             timescale: timescale.clone(),
             wanted_unit: *wanted_unit,
             time_format: time_format.clone(),
+            radix: Radix::Decimal,
             exponent_diff,
             unit_string,
             space_string: if time_format.show_space {
@@ -394,11 +432,18 @@ impl TimeFormatter {
         }
     }
 
+    /// Set the number base of the formatted times.
+    #[must_use]
+    pub fn with_radix(mut self, radix: Radix) -> Self {
+        self.radix = radix;
+        self
+    }
+
     /// Format a single time value.
     #[must_use]
     pub fn format(&self, time: &BigInt) -> String {
         if self.wanted_unit == TimeUnit::None {
-            return split_and_format_number(&time.to_string(), self.time_format.format);
+            return format_number_in_radix(&time.to_string(), self.radix, self.time_format.format);
         }
 
         // Handle Auto unit by resolving it for this specific time value
@@ -439,7 +484,7 @@ impl TimeFormatter {
 
         format!(
             "{scaledtime}{space}{unit}",
-            scaledtime = split_and_format_number(&timestring, self.time_format.format),
+            scaledtime = format_number_in_radix(&timestring, self.radix, self.time_format.format),
             space = &self.space_string,
             unit = &unit_string
         )
@@ -459,6 +504,100 @@ pub fn time_string(
     formatter.format(time)
 }
 
+/// Time from the trigger at `trigger` to `time`, e.g. `T-20 ns` for a time before the trigger.
+#[must_use]
+pub fn trigger_relative_time_string(
+    time: &BigInt,
+    trigger: &BigInt,
+    timescale: &TimeScale,
+    wanted_timeunit: &TimeUnit,
+    wanted_time_format: &TimeFormat,
+) -> String {
+    let relative = time - trigger;
+    let sign = if relative < BigInt::zero() { '-' } else { '+' };
+    let magnitude = BigInt::from(relative.magnitude().clone());
+    format!(
+        "T{sign}{}",
+        time_string(&magnitude, timescale, wanted_timeunit, wanted_time_format)
+    )
+}
+
+/// Number of ticks of `timescale` in `amount` of `unit`, rounded towards zero. Amounts
+/// without a physical unit are taken to be ticks.
+#[must_use]
+pub fn time_to_ticks(amount: &BigInt, unit: &TimeUnit, timescale: &TimeScale) -> BigInt {
+    if matches!(unit, TimeUnit::None | TimeUnit::Auto) || timescale.unit == TimeUnit::None {
+        return amount.clone();
+    }
+    let exponent_diff = i32::from(unit.exponent() - timescale.unit.exponent());
+    let scale = BigInt::from(10).pow(exponent_diff.unsigned_abs());
+    let multiplier = BigInt::from(timescale.multiplier.unwrap_or(1));
+    if exponent_diff >= 0 {
+        amount * scale / multiplier
+    } else {
+        amount / (scale * multiplier)
+    }
+}
+
+/// Number of ticks of `timescale` in a time such as `2.5 ns` or `120` as formatted by
+/// [`TimeFormatter`], rounded towards zero. Spaces and `,`/`_` digit separators are ignored and
+/// times without a unit are taken to be ticks.
+#[must_use]
+pub fn parse_time(text: &str, timescale: &TimeScale) -> Option<BigInt> {
+    let text = text.trim();
+    let (number, unit) = text.split_at(text.rfind(|c: char| c.is_ascii_digit())? + 1);
+    let unit = match unit.trim() {
+        "" => TimeUnit::None,
+        "zs" => TimeUnit::ZeptoSeconds,
+        "as" => TimeUnit::AttoSeconds,
+        "fs" => TimeUnit::FemtoSeconds,
+        "ps" => TimeUnit::PicoSeconds,
+        "ns" => TimeUnit::NanoSeconds,
+        "μs" | "us" => TimeUnit::MicroSeconds,
+        "ms" => TimeUnit::MilliSeconds,
+        "s" => TimeUnit::Seconds,
+        _ => return None,
+    };
+    let number: String = number
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, ',' | '_'))
+        .collect();
+    let (integer, fraction) = number.split_once('.').unwrap_or((&number, ""));
+    if !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let amount: BigInt = format!("{integer}{fraction}").parse().ok()?;
+    let fraction_scale = BigInt::from(10).pow(u32::try_from(fraction.len()).ok()?);
+    Some(time_to_ticks(&amount, &unit, timescale) / fraction_scale)
+}
+
+/// `time` rounded to the nearest multiple of `grid`, with ties rounded up. Grids that are
+/// not positive leave the time unchanged.
+#[must_use]
+pub fn snap_to_grid(time: &BigInt, grid: &BigInt) -> BigInt {
+    if grid <= &BigInt::zero() {
+        return time.clone();
+    }
+    (time + grid / 2).div_floor(grid) * grid
+}
+
+/// Label of the tick at `tick` on a ruler relative to `reference`, e.g. `-20 ns` for a tick
+/// before the reference and `+20 ns` for a tick after it.
+#[must_use]
+pub fn relative_tick_label(
+    time_formatter: &TimeFormatter,
+    tick: &BigInt,
+    reference: &BigInt,
+) -> String {
+    let relative = tick - reference;
+    let magnitude = time_formatter.format(&BigInt::from(relative.magnitude().clone()));
+    match relative.sign() {
+        Sign::Minus => format!("-{magnitude}"),
+        Sign::NoSign => magnitude,
+        Sign::Plus => format!("+{magnitude}"),
+    }
+}
+
 impl WaveData {
     pub fn draw_tick_line(&self, x: f32, ctx: &mut DrawingContext, stroke: &Stroke) {
         let Pos2 {
@@ -503,6 +642,28 @@ impl SystemState {
         }
     }
 
+    /// `time` rounded to the nearest point of the placement grid, or unchanged if no grid is
+    /// set. The grid is given in the current time unit.
+    #[must_use]
+    pub fn snap_to_placement_grid(&self, time: &BigInt) -> BigInt {
+        let Some(waves) = &self.user.waves else {
+            return time.clone();
+        };
+        let grid = time_to_ticks(
+            &self.placement_grid(),
+            &self.user.wanted_timeunit,
+            &waves.inner.metadata().timescale,
+        );
+        snap_to_grid(time, &grid)
+    }
+
+    /// Show times in the unit of `timescale` unless a time unit has been picked explicitly.
+    pub(crate) fn set_default_time_unit(&mut self, timescale: &TimeScale) {
+        if !self.user.wanted_timeunit_explicit {
+            self.user.wanted_timeunit = timescale.unit;
+        }
+    }
+
     pub fn get_ticks_for_viewport_idx(
         &self,
         waves: &WaveData,
@@ -525,15 +686,28 @@ impl SystemState {
             cfg.text_size,
             &self.user.wanted_timeunit,
             &self.get_time_format(),
+            self.time_label_radix(),
             self.user.config.theme.ticks.density,
             &waves.safe_num_timestamps(),
+            self.relative_time_reference(waves),
         )
     }
+
+    /// Time the ruler labels are relative to, `None` if they show absolute times
+    #[must_use]
+    pub fn relative_time_reference<'a>(&self, waves: &'a WaveData) -> Option<&'a BigInt> {
+        if self.user.relative_time_ruler {
+            waves.cursor.as_ref()
+        } else {
+            None
+        }
+    }
 }
 
 /// Get suitable tick locations for the current view port.
 /// The method is based on guessing the length of the time string and
 /// is inspired by the corresponding code in Matplotlib.
+/// With a `reference`, the ticks are aligned to and labeled relative to it.
 #[allow(clippy::too_many_arguments)]
 #[must_use]
 fn get_ticks_internal(
@@ -543,8 +717,10 @@ fn get_ticks_internal(
     text_size: f32,
     wanted_timeunit: &TimeUnit,
     time_format: &TimeFormat,
+    radix: Radix,
     density: f32,
     num_timestamps: &BigInt,
+    reference: Option<&BigInt>,
 ) -> Vec<(String, f32)> {
     let char_width = text_size * (20. / 31.);
     let rightexp = viewport
@@ -572,18 +748,21 @@ fn get_ticks_internal(
         .floor(),
     );
 
+    let offset = reference.and_then(ToPrimitive::to_f64).unwrap_or(0.);
     let mut ticks: Vec<(String, f32)> = [].to_vec();
     for step in &TICK_STEPS {
         let scaled_step = scale * step;
         let rounded_min_label_time =
-            (viewport.curr_left.absolute(num_timestamps).inner() / scaled_step).floor()
-                * scaled_step;
+            ((viewport.curr_left.absolute(num_timestamps).inner() - offset) / scaled_step).floor()
+                * scaled_step
+                + offset;
         let high = ((viewport.curr_right.absolute(num_timestamps).inner() - rounded_min_label_time)
             / scaled_step)
             .ceil() as f32
             + 1.;
         if high <= max_labels {
-            let time_formatter = TimeFormatter::new(timescale, wanted_timeunit, time_format);
+            let time_formatter =
+                TimeFormatter::new(timescale, wanted_timeunit, time_format).with_radix(radix);
             ticks = (0..high as i16)
                 .map(|v| {
                     BigInt::from((f64::from(v) * scaled_step + rounded_min_label_time) as i128)
@@ -592,7 +771,12 @@ fn get_ticks_internal(
                 .map(|tick| {
                     (
                         // Time string
-                        time_formatter.format(&tick),
+                        match reference {
+                            Some(reference) => {
+                                relative_tick_label(&time_formatter, &tick, reference)
+                            }
+                            None => time_formatter.format(&tick),
+                        },
                         // X position
                         viewport.pixel_from_time(&tick, frame_width, num_timestamps),
                     )
@@ -608,7 +792,155 @@ fn get_ticks_internal(
 mod test {
     use num::BigInt;
 
-    use crate::time::{TimeFormat, TimeScale, TimeStringFormatting, TimeUnit, time_string};
+    use crate::time::{
+        Radix, TimeFormat, TimeFormatter, TimeScale, TimeStringFormatting, TimeUnit,
+        get_ticks_internal, parse_time, relative_tick_label, snap_to_grid, time_string,
+        time_to_ticks, trigger_relative_time_string,
+    };
+    use crate::viewport::Viewport;
+
+    #[test]
+    fn trigger_relative_time_from_arbitrary_trigger() {
+        let timescale = TimeScale {
+            multiplier: Some(1),
+            unit: TimeUnit::NanoSeconds,
+        };
+        let relative = |time: i64, trigger: i64| {
+            trigger_relative_time_string(
+                &BigInt::from(time),
+                &BigInt::from(trigger),
+                &timescale,
+                &TimeUnit::NanoSeconds,
+                &TimeFormat::default(),
+            )
+        };
+        assert_eq!(relative(150, 130), "T+20 ns");
+        assert_eq!(relative(110, 130), "T-20 ns");
+        assert_eq!(relative(130, 130), "T+0 ns");
+        assert_eq!(relative(0, 2500), "T-2500 ns");
+    }
+
+    #[test]
+    fn placement_times_are_rounded_to_the_grid() {
+        let grid = BigInt::from(10);
+        let snap = |time: i32| snap_to_grid(&BigInt::from(time), &grid);
+        assert_eq!(snap(0), BigInt::from(0));
+        assert_eq!(snap(14), BigInt::from(10));
+        assert_eq!(snap(15), BigInt::from(20));
+        assert_eq!(snap(26), BigInt::from(30));
+        assert_eq!(snap(-14), BigInt::from(-10));
+        assert_eq!(snap(-16), BigInt::from(-20));
+        // A grid of zero is off
+        assert_eq!(
+            snap_to_grid(&BigInt::from(17), &BigInt::from(0)),
+            BigInt::from(17)
+        );
+
+        let timescale = TimeScale {
+            multiplier: Some(10),
+            unit: TimeUnit::PicoSeconds,
+        };
+        assert_eq!(
+            time_to_ticks(&BigInt::from(2), &TimeUnit::NanoSeconds, &timescale),
+            BigInt::from(200)
+        );
+        assert_eq!(
+            time_to_ticks(&BigInt::from(30), &TimeUnit::FemtoSeconds, &timescale),
+            BigInt::from(0)
+        );
+        assert_eq!(
+            time_to_ticks(&BigInt::from(7), &TimeUnit::None, &timescale),
+            BigInt::from(7)
+        );
+    }
+
+    #[test]
+    fn formatted_times_are_parsed_to_ticks() {
+        let timescale = TimeScale {
+            multiplier: Some(10),
+            unit: TimeUnit::PicoSeconds,
+        };
+        let parse = |text: &str| parse_time(text, &timescale);
+        assert_eq!(parse("2.5 ns"), Some(BigInt::from(250)));
+        assert_eq!(parse("2.5ns"), Some(BigInt::from(250)));
+        assert_eq!(parse("1,000 ps"), Some(BigInt::from(100)));
+        assert_eq!(parse("3 μs"), Some(BigInt::from(300_000)));
+        assert_eq!(parse("15 ps"), Some(BigInt::from(1)));
+        assert_eq!(parse(" 42 "), Some(BigInt::from(42)));
+        assert_eq!(parse("-20 ns"), Some(BigInt::from(-2000)));
+        assert_eq!(parse("2.5 lightyears"), None);
+        assert_eq!(parse("ns"), None);
+        assert_eq!(parse("2.x5 ns"), None);
+
+        let formatter =
+            TimeFormatter::new(&timescale, &TimeUnit::NanoSeconds, &TimeFormat::default());
+        let time = BigInt::from(12345);
+        assert_eq!(parse(&formatter.format(&time)), Some(time));
+    }
+
+    #[test]
+    fn relative_tick_labels_are_signed_offsets_from_reference() {
+        let timescale = TimeScale {
+            multiplier: Some(1),
+            unit: TimeUnit::NanoSeconds,
+        };
+        let formatter =
+            TimeFormatter::new(&timescale, &TimeUnit::NanoSeconds, &TimeFormat::default());
+        let labels = [100, 120, 130, 140, 200]
+            .iter()
+            .map(|tick| relative_tick_label(&formatter, &BigInt::from(*tick), &BigInt::from(130)))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["-30 ns", "-10 ns", "0 ns", "+10 ns", "+70 ns"]);
+    }
+
+    #[test]
+    fn relative_ticks_are_aligned_to_reference() {
+        let timescale = TimeScale {
+            multiplier: Some(1),
+            unit: TimeUnit::NanoSeconds,
+        };
+        let num_timestamps = BigInt::from(1000);
+        let viewport = Viewport::new();
+        let ticks = |reference: Option<&BigInt>| {
+            get_ticks_internal(
+                &viewport,
+                &timescale,
+                1000.,
+                12.,
+                &TimeUnit::NanoSeconds,
+                &TimeFormat::default(),
+                Radix::Decimal,
+                1.,
+                &num_timestamps,
+                reference,
+            )
+        };
+
+        let reference = BigInt::from(133);
+        let relative = ticks(Some(&reference));
+        let (_, x) = relative
+            .iter()
+            .find(|(label, _)| label == "0 ns")
+            .expect("the reference has a tick");
+        assert_eq!(
+            *x,
+            viewport.pixel_from_time(&reference, 1000., &num_timestamps)
+        );
+        for (label, x) in &relative {
+            if *x < viewport.pixel_from_time(&reference, 1000., &num_timestamps) {
+                assert!(label.starts_with('-'), "{label}");
+            } else if label != "0 ns" {
+                assert!(label.starts_with('+'), "{label}");
+            }
+        }
+
+        // Without a reference, the ticks show absolute times
+        assert!(
+            ticks(None)
+                .iter()
+                .all(|(label, _)| !label.starts_with(['+', '-']))
+        );
+    }
 
     #[test]
     fn print_time_standard() {
@@ -1351,6 +1683,42 @@ mod test {
     }
 }
 
+#[cfg(test)]
+mod default_time_unit_tests {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn default_time_unit_follows_timescale() {
+        let mut state = SystemState::new_default_config().unwrap();
+        let timescale = TimeScale {
+            unit: TimeUnit::NanoSeconds,
+            multiplier: Some(1),
+        };
+        state.set_default_time_unit(&timescale);
+        assert_eq!(state.user.wanted_timeunit, TimeUnit::NanoSeconds);
+
+        let timescale = TimeScale {
+            unit: TimeUnit::PicoSeconds,
+            multiplier: Some(10),
+        };
+        state.set_default_time_unit(&timescale);
+        assert_eq!(state.user.wanted_timeunit, TimeUnit::PicoSeconds);
+    }
+
+    #[test]
+    fn explicit_time_unit_is_kept() {
+        let mut state = SystemState::new_default_config().unwrap();
+        state.update(Message::SetTimeUnit(TimeUnit::MicroSeconds));
+        let timescale = TimeScale {
+            unit: TimeUnit::NanoSeconds,
+            multiplier: Some(1),
+        };
+        state.set_default_time_unit(&timescale);
+        assert_eq!(state.user.wanted_timeunit, TimeUnit::MicroSeconds);
+    }
+}
+
 #[cfg(test)]
 mod get_ticks_tests {
     use super::*;
@@ -1380,6 +1748,7 @@ mod get_ticks_tests {
             text_size,
             &wanted,
             &time_format,
+            Radix::Decimal,
             config.theme.ticks.density,
             &num_timestamps,
         );
@@ -1442,6 +1811,7 @@ mod get_ticks_tests {
             text_size,
             &wanted,
             &time_format,
+            Radix::Decimal,
             config.theme.ticks.density,
             &num_timestamps,
         );
@@ -1469,4 +1839,59 @@ mod get_ticks_tests {
         let unique_labels = labels.iter().unique().count();
         assert_eq!(labels.len(), unique_labels, "duplicate tick labels found");
     }
+
+    #[test]
+    fn tick_labels_in_hexadecimal_and_octal() {
+        let timescale = TimeScale {
+            unit: TimeUnit::NanoSeconds,
+            multiplier: Some(1),
+        };
+        let hex = TimeFormatter::new(&timescale, &TimeUnit::None, &TimeFormat::default())
+            .with_radix(Radix::Hexadecimal);
+        assert_eq!(hex.format(&BigInt::from(0)), "0x0");
+        assert_eq!(hex.format(&BigInt::from(16)), "0x10");
+        assert_eq!(hex.format(&BigInt::from(255)), "0xff");
+        assert_eq!(hex.format(&BigInt::from(4096)), "0x1000");
+
+        let octal = TimeFormatter::new(&timescale, &TimeUnit::NanoSeconds, &TimeFormat::default())
+            .with_radix(Radix::Octal);
+        assert_eq!(octal.format(&BigInt::from(8)), "0o10 ns");
+        assert_eq!(octal.format(&BigInt::from(100)), "0o144 ns");
+
+        // Fractional times are kept in decimal
+        let hex_us =
+            TimeFormatter::new(&timescale, &TimeUnit::MicroSeconds, &TimeFormat::default())
+                .with_radix(Radix::Hexadecimal);
+        assert_eq!(hex_us.format(&BigInt::from(2500)), "2.5 μs");
+        assert_eq!(hex_us.format(&BigInt::from(16000)), "0x10 μs");
+    }
+
+    #[test]
+    fn get_ticks_in_hexadecimal() {
+        let vp = crate::viewport::Viewport::default();
+        let timescale = TimeScale {
+            unit: TimeUnit::NanoSeconds,
+            multiplier: Some(1),
+        };
+        let time_format = TimeFormat::default().with_unit(false);
+        let num_timestamps = BigInt::from(4096);
+
+        let ticks = get_ticks_internal(
+            &vp,
+            &timescale,
+            800.0,
+            12.0,
+            &TimeUnit::None,
+            &time_format,
+            Radix::Hexadecimal,
+            1.0,
+            &num_timestamps,
+        );
+
+        assert!(!ticks.is_empty(), "expected at least one tick");
+        assert!(
+            ticks.iter().all(|(label, _)| label.starts_with("0x")),
+            "expected hexadecimal labels, got {ticks:?}"
+        );
+    }
 }
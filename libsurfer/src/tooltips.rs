@@ -44,6 +44,24 @@ pub fn variable_tooltip_text(meta: Option<&VariableMeta>, variable: &VariableRef
     }
 }
 
+/// Single line summary of `meta` printed by the `info` command
+#[must_use]
+pub fn variable_info_text(meta: &VariableMeta, translator: &str) -> String {
+    format!(
+        "{}: width {}, type {}, direction {}, encoding {:?}, translator {translator}",
+        meta.var.full_path_string(),
+        meta.num_bits
+            .map_or_else(|| "unknown".to_string(), |bits| bits.to_string()),
+        meta.variable_type_name
+            .clone()
+            .or_else(|| meta.variable_type.map(|t| t.to_string()))
+            .unwrap_or_else(|| "unknown".to_string()),
+        meta.direction
+            .map_or_else(|| "unknown".to_string(), |direction| format!("{direction}")),
+        meta.encoding,
+    )
+}
+
 #[must_use]
 pub fn scope_tooltip_text(wave: &WaveData, scope: &ScopeRef, include_parameters: bool) -> String {
     let mut parts = vec![format!("{scope}")];
@@ -144,3 +162,37 @@ fn transaction_tooltip_table(ui: &mut Ui, tx: &Transaction) {
             });
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use surfer_translation_types::{VariableDirection, VariableEncoding, VariableType};
+
+    use super::*;
+
+    #[test]
+    fn variable_info_text_lists_metadata() {
+        let mut meta = VariableMeta {
+            var: VariableRef::from_hierarchy_string("tb.dut.data"),
+            num_bits: Some(8),
+            variable_type: Some(VariableType::VCDReg),
+            variable_type_name: None,
+            index: None,
+            direction: Some(VariableDirection::Output),
+            enum_map: Default::default(),
+            encoding: VariableEncoding::BitVector,
+        };
+        assert_eq!(
+            variable_info_text(&meta, "Hexadecimal"),
+            "tb.dut.data: width 8, type reg, direction output, encoding BitVector, translator Hexadecimal"
+        );
+
+        meta.num_bits = None;
+        meta.variable_type_name = Some("logic".to_string());
+        meta.direction = None;
+        meta.encoding = VariableEncoding::String;
+        assert_eq!(
+            variable_info_text(&meta, "String"),
+            "tb.dut.data: width unknown, type logic, direction unknown, encoding String, translator String"
+        );
+    }
+}
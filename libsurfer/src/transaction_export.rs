@@ -0,0 +1,305 @@
+//! Export of transactions with their attributes and relations as JSON.
+use camino::Utf8PathBuf;
+use ftr_parser::types::Transaction;
+use serde::Serialize;
+use tracing::error;
+
+use crate::SystemState;
+use crate::transaction_container::{TransactionContainer, TransactionRef};
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct TransactionJson {
+    pub id: usize,
+    /// Name of the generator the transaction belongs to
+    pub generator: String,
+    /// Times are strings as they may not fit in a JSON number
+    pub start: String,
+    pub end: String,
+    pub attributes: Vec<TransactionAttributeJson>,
+    pub incoming_relations: Vec<TransactionRelationJson>,
+    pub outgoing_relations: Vec<TransactionRelationJson>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct TransactionAttributeJson {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct TransactionRelationJson {
+    pub source: usize,
+    pub sink: usize,
+}
+
+impl TransactionJson {
+    fn new(transaction: &Transaction, generator: String) -> Self {
+        TransactionJson {
+            id: transaction.get_tx_id(),
+            generator,
+            start: transaction.get_start_time().to_string(),
+            end: transaction.get_end_time().to_string(),
+            attributes: transaction
+                .attributes
+                .iter()
+                .map(|attribute| TransactionAttributeJson {
+                    name: attribute.name.clone(),
+                    value: attribute.value().to_string(),
+                })
+                .collect(),
+            incoming_relations: transaction
+                .inc_relations
+                .iter()
+                .map(|relation| TransactionRelationJson {
+                    source: relation.source_tx_id,
+                    sink: relation.sink_tx_id,
+                })
+                .collect(),
+            outgoing_relations: transaction
+                .out_relations
+                .iter()
+                .map(|relation| TransactionRelationJson {
+                    source: relation.source_tx_id,
+                    sink: relation.sink_tx_id,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TransactionContainer {
+    /// The transactions in `selection` in the same order, skipping those that do not exist
+    #[must_use]
+    pub fn transactions_json(&self, selection: &[TransactionRef]) -> Vec<TransactionJson> {
+        selection
+            .iter()
+            .filter_map(|transaction_ref| {
+                let transaction = self.get_transaction(transaction_ref)?;
+                let generator = self
+                    .get_generator(transaction.get_gen_id())
+                    .map_or_else(|| "unknown".to_string(), |g| g.name.clone());
+                Some(TransactionJson::new(transaction, generator))
+            })
+            .collect()
+    }
+}
+
+impl SystemState {
+    /// JSON of the transactions in `selection`. If it is empty, the selected transactions are
+    /// used, or the focused transaction if none are selected.
+    #[must_use]
+    pub fn encode_transactions_json(&self, selection: &[TransactionRef]) -> Option<String> {
+        let waves = self.user.waves.as_ref()?;
+        let transactions = waves.inner.as_transactions()?;
+        let selection = if !selection.is_empty() {
+            selection.to_vec()
+        } else if !waves.selected_transactions.is_empty() {
+            waves.selected_transactions.clone()
+        } else {
+            vec![waves.focused_transaction.0.clone()?]
+        };
+        serde_json::to_string_pretty(&transactions.transactions_json(&selection))
+            .map_err(|e| error!("Failed to encode transactions. {e:#?}"))
+            .ok()
+    }
+
+    pub fn export_transactions_json(
+        &mut self,
+        path: Option<Utf8PathBuf>,
+        selection: &[TransactionRef],
+    ) {
+        let Some(encoded) = self.encode_transactions_json(selection) else {
+            return;
+        };
+
+        self.save_bytes_to(
+            path,
+            "Export transactions",
+            ("JSON files (*.json)".to_string(), vec!["json".to_string()]),
+            encoded.into_bytes(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use crate::tests::fixtures::load_example;
+
+    #[test]
+    fn transactions_serialize_with_attributes_and_relations() {
+        let transactions = vec![
+            TransactionJson {
+                id: 3,
+                generator: "read".to_string(),
+                start: "10".to_string(),
+                end: "40".to_string(),
+                attributes: vec![TransactionAttributeJson {
+                    name: "addr".to_string(),
+                    value: "0x20".to_string(),
+                }],
+                incoming_relations: vec![],
+                outgoing_relations: vec![TransactionRelationJson { source: 3, sink: 7 }],
+            },
+            TransactionJson {
+                id: 7,
+                generator: "data".to_string(),
+                start: "40".to_string(),
+                end: "50".to_string(),
+                attributes: vec![],
+                incoming_relations: vec![TransactionRelationJson { source: 3, sink: 7 }],
+                outgoing_relations: vec![],
+            },
+        ];
+        let json = serde_json::to_value(&transactions).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {
+                    "id": 3,
+                    "generator": "read",
+                    "start": "10",
+                    "end": "40",
+                    "attributes": [{"name": "addr", "value": "0x20"}],
+                    "incoming_relations": [],
+                    "outgoing_relations": [{"source": 3, "sink": 7}],
+                },
+                {
+                    "id": 7,
+                    "generator": "data",
+                    "start": "40",
+                    "end": "50",
+                    "attributes": [],
+                    "incoming_relations": [{"source": 3, "sink": 7}],
+                    "outgoing_relations": [],
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn transactions_json_reads_from_container() {
+        let mut state = load_example("examples/my_db.ftr");
+        assert!(state.encode_transactions_json(&[]).is_none());
+
+        state.update(Message::FocusTransaction(
+            Some(TransactionRef { id: 4 }),
+            None,
+        ));
+        let focused: serde_json::Value =
+            serde_json::from_str(&state.encode_transactions_json(&[]).unwrap()).unwrap();
+        let focused = focused.as_array().unwrap();
+        assert_eq!(focused.len(), 1);
+        assert_eq!(focused[0]["id"], 4);
+        for key in [
+            "generator",
+            "start",
+            "end",
+            "attributes",
+            "incoming_relations",
+            "outgoing_relations",
+        ] {
+            assert!(focused[0].get(key).is_some(), "missing {key}");
+        }
+
+        // Unknown transactions are skipped
+        let selected: serde_json::Value = serde_json::from_str(
+            &state
+                .encode_transactions_json(&[
+                    TransactionRef { id: 4 },
+                    TransactionRef { id: usize::MAX },
+                ])
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(selected.as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn transaction_selection_is_exported_as_json() {
+        let mut state = load_example("examples/my_db.ftr");
+
+        for id in [6, 4, 5] {
+            state.update(Message::ToggleTransactionSelected(TransactionRef { id }));
+        }
+        state.update(Message::ToggleTransactionSelected(TransactionRef { id: 5 }));
+        assert_eq!(
+            state.user.waves.as_ref().unwrap().selected_transactions,
+            vec![TransactionRef { id: 6 }, TransactionRef { id: 4 }]
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = camino::Utf8PathBuf::try_from(dir.path().join("transactions.json")).unwrap();
+        state.update(Message::ExportTransactionsJson {
+            path: Some(path.clone()),
+            selection: vec![],
+        });
+
+        // The file is written in the background
+        let start = std::time::Instant::now();
+        let exported: serde_json::Value = loop {
+            if let Some(json) = std::fs::read_to_string(&path)
+                .ok()
+                .and_then(|s| serde_json::from_str(&s).ok())
+            {
+                break json;
+            }
+            assert!(
+                start.elapsed().as_secs() < 10,
+                "transactions were not written"
+            );
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        };
+        let exported = exported.as_array().unwrap();
+        assert_eq!(
+            exported.iter().map(|tx| &tx["id"]).collect::<Vec<_>>(),
+            vec![6, 4]
+        );
+        let transactions = state
+            .user
+            .waves
+            .as_ref()
+            .unwrap()
+            .inner
+            .as_transactions()
+            .unwrap();
+        for tx in exported {
+            let id = tx["id"].as_u64().unwrap() as usize;
+            let loaded = transactions
+                .get_transaction(&TransactionRef { id })
+                .unwrap();
+            assert_eq!(tx["start"], loaded.get_start_time().to_string());
+            assert_eq!(tx["end"], loaded.get_end_time().to_string());
+            assert_eq!(
+                tx["generator"],
+                transactions
+                    .get_generator(loaded.get_gen_id())
+                    .unwrap()
+                    .name
+                    .as_str()
+            );
+            assert_eq!(
+                tx["attributes"].as_array().unwrap().len(),
+                loaded.attributes.len()
+            );
+            for relation in tx["outgoing_relations"].as_array().unwrap() {
+                assert_eq!(relation["source"], id);
+            }
+            for relation in tx["incoming_relations"].as_array().unwrap() {
+                assert_eq!(relation["sink"], id);
+            }
+        }
+
+        state.update(Message::ClearTransactionSelection);
+        assert!(
+            state
+                .user
+                .waves
+                .as_ref()
+                .unwrap()
+                .selected_transactions
+                .is_empty()
+        );
+    }
+}
@@ -42,6 +42,10 @@ const SINK_TX_LABEL: &str = "Sink Tx";
 const ATTR_NAME_LABEL: &str = "Name";
 const ATTR_VALUE_LABEL: &str = "Value";
 
+const EXPORT_JSON_LABEL: &str = "Export as JSON...";
+const EXPORT_SELECTED_JSON_LABEL: &str = "Export selected as JSON...";
+const CLEAR_SELECTION_LABEL: &str = "Clear selection";
+
 // Information label
 const STREAM_NOT_FOUND_LABEL: &str = "Stream not found";
 
@@ -75,6 +79,30 @@ impl SystemState {
                 ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
                 self.handle_pointer_in_ui(ui, msgs);
                 draw_focused_transaction_details(ui, transactions, focused_transaction);
+                ui.add_space(SECTION_GAP);
+                if ui.button(EXPORT_JSON_LABEL).clicked() {
+                    msgs.push(Message::ExportTransactionsJson {
+                        path: None,
+                        selection: vec![transaction_ref.clone()],
+                    });
+                }
+                if !waves.selected_transactions.is_empty() {
+                    ui.label(format!(
+                        "{} selected (Ctrl+click to change)",
+                        waves.selected_transactions.len()
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button(EXPORT_SELECTED_JSON_LABEL).clicked() {
+                            msgs.push(Message::ExportTransactionsJson {
+                                path: None,
+                                selection: waves.selected_transactions.clone(),
+                            });
+                        }
+                        if ui.button(CLEAR_SELECTION_LABEL).clicked() {
+                            msgs.push(Message::ClearTransactionSelection);
+                        }
+                    });
+                }
             });
     }
 }
@@ -187,6 +215,22 @@ impl WaveData {
         self.focused_transaction = (tx, self.focused_transaction.1.clone());
         Some(())
     }
+
+    /// Messages focusing the transaction with id `id` and centering the first viewport on its
+    /// start, or `None` if there is no such transaction.
+    #[must_use]
+    pub fn go_to_transaction_messages(&self, id: usize) -> Option<Vec<Message>> {
+        let transaction_ref = TransactionRef { id };
+        let start = self
+            .inner
+            .as_transactions()?
+            .get_transaction(&transaction_ref)?
+            .get_start_time();
+        Some(vec![
+            Message::FocusTransaction(Some(transaction_ref), None),
+            Message::GoToTime(Some(start.into()), 0),
+        ])
+    }
 }
 
 fn draw_focused_transaction_details(
@@ -455,3 +499,47 @@ fn subheader(body: &mut TableBody, left: &str, right: &str) {
         });
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use num::{BigInt, ToPrimitive};
+
+    use super::*;
+    use crate::tests::fixtures::load_example;
+    use crate::viewport::ViewportStrategy;
+
+    #[test]
+    fn goto_transaction_focuses_and_centers_it() {
+        let mut state = load_example("examples/my_db.ftr");
+        let waves = state.user.waves.as_mut().unwrap();
+        waves.viewports[0].move_strategy = ViewportStrategy::Instant;
+        let num_timestamps = waves.safe_num_timestamps();
+        let start = waves
+            .inner
+            .as_transactions()
+            .unwrap()
+            .get_transaction(&TransactionRef { id: 34 })
+            .unwrap()
+            .get_start_time()
+            .to_f64()
+            .unwrap();
+        assert!(waves.go_to_transaction_messages(usize::MAX).is_none());
+        state.update(Message::ZoomToRange {
+            start: BigInt::from(0),
+            end: &num_timestamps / 100,
+            viewport_idx: 0,
+        });
+
+        state.update(Message::GoToTransactionById(34));
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(waves.focused_transaction.0, Some(TransactionRef { id: 34 }));
+        let viewport = &waves.viewports[0];
+        assert!(viewport.curr_left.absolute(&num_timestamps).0 <= start);
+        assert!(viewport.curr_right.absolute(&num_timestamps).0 >= start);
+
+        // Unknown ids leave the focus alone
+        state.update(Message::GoToTransactionById(u64::MAX));
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(waves.focused_transaction.0, Some(TransactionRef { id: 34 }));
+    }
+}
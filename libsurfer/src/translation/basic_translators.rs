@@ -5,7 +5,7 @@ use eyre::Result;
 use itertools::Itertools;
 use num::{One, Zero};
 use surfer_translation_types::{
-    BasicTranslator, VariableValue, check_vector_variable, extend_string,
+    BasicTranslator, NumberNotation, VariableValue, check_vector_variable, extend_string,
     kind_for_binary_representation, parse_value_to_numeric,
 };
 
@@ -96,6 +96,10 @@ impl BasicTranslator<VarId, ScopeId> for HexTranslator {
             VariableValue::String(s) => map_to_radix(s, 4, num_bits),
         }
     }
+
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Unsigned { radix: 16 })
+    }
 }
 
 pub struct BitTranslator {}
@@ -154,6 +158,10 @@ impl BasicTranslator<VarId, ScopeId> for OctalTranslator {
             VariableValue::String(s) => map_to_radix(s, 3, num_bits),
         }
     }
+
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Unsigned { radix: 8 })
+    }
 }
 
 pub struct GroupingBinaryTranslator {}
@@ -198,6 +206,10 @@ impl BasicTranslator<VarId, ScopeId> for BinaryTranslator {
             ),
         }
     }
+
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Unsigned { radix: 2 })
+    }
 }
 
 pub struct ASCIITranslator {}
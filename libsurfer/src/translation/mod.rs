@@ -35,9 +35,9 @@ pub use instruction_translators::*;
 use itertools::Itertools;
 pub use numeric_translators::*;
 use surfer_translation_types::{
-    BasicTranslator, HierFormatResult, SubFieldFlatTranslationResult, TranslatedValue,
-    TranslationPreference, TranslationResult, Translator, ValueKind, ValueRepr, VariableEncoding,
-    VariableInfo, VariableValue,
+    BasicTranslator, HierFormatResult, NumberNotation, PluginConfig, SubFieldFlatTranslationResult,
+    TranslatedValue, TranslationPreference, TranslationResult, Translator, ValueKind, ValueRepr,
+    VariableEncoding, VariableInfo, VariableValue,
 };
 
 use crate::config::SurferTheme;
@@ -101,6 +101,15 @@ impl Translator<VarId, ScopeId, Message> for AnyTranslator {
         }
     }
 
+    fn set_config(&self, config: &PluginConfig) {
+        match self {
+            AnyTranslator::Full(translator) => translator.set_config(config),
+            AnyTranslator::Basic(_) => {}
+            #[cfg(feature = "python")]
+            AnyTranslator::Python(translator) => translator.set_config(config),
+        }
+    }
+
     fn translate(
         &self,
         variable: &VariableMeta,
@@ -166,6 +175,15 @@ impl Translator<VarId, ScopeId, Message> for AnyTranslator {
             }
         }
     }
+
+    fn number_notation(&self) -> Option<NumberNotation> {
+        match self {
+            AnyTranslator::Full(t) => t.number_notation(),
+            AnyTranslator::Basic(t) => t.number_notation(),
+            #[cfg(feature = "python")]
+            AnyTranslator::Python(_) => None,
+        }
+    }
 }
 
 /// Look inside the config directory and inside "$(cwd)/.surfer" for user-defined decoders
@@ -364,8 +382,10 @@ pub fn all_translators() -> TranslatorList {
         Arc::new(LebTranslator {}),
         Arc::new(UnsignedTranslator {}),
         Arc::new(SignedTranslator {}),
+        Arc::new(GrayCodeTranslator {}),
         Arc::new(SinglePrecisionTranslator {}),
         Arc::new(DoublePrecisionTranslator {}),
+        Arc::new(EngineeringTranslator {}),
         Arc::new(HalfPrecisionTranslator {}),
         Arc::new(BFloat16Translator {}),
         Arc::new(Posit32Translator {}),
@@ -500,6 +520,26 @@ impl TranslatorList {
             .unwrap_or(false)
     }
 
+    /// How well each translator, sorted by name, handles the variable described by `meta`.
+    /// Translators that fail to decide are reported as [`TranslationPreference::No`].
+    #[must_use]
+    pub fn translator_applicability(
+        &self,
+        meta: &VariableMeta,
+    ) -> Vec<(&str, TranslationPreference)> {
+        self.all_translator_names()
+            .into_iter()
+            .sorted()
+            .map(|name| {
+                let preference = self
+                    .get_translator(name)
+                    .translates(meta)
+                    .unwrap_or(TranslationPreference::No);
+                (name, preference)
+            })
+            .collect()
+    }
+
     #[cfg(feature = "python")]
     pub fn load_python_translator(&mut self, filename: camino::Utf8PathBuf) -> Result<()> {
         tracing::debug!("Reading Python code from disk: {filename}");
@@ -836,6 +876,49 @@ mod tests {
         assert!(basic_names.contains(&"Binary"));
     }
 
+    #[test]
+    fn translator_applicability_covers_all_translators() {
+        use crate::wave_container::{VariableRef, VariableRefExt};
+
+        let translators = all_translators();
+        let meta = VariableMeta {
+            var: VariableRef::from_hierarchy_string("tb.dut.data"),
+            num_bits: Some(8),
+            variable_type: None,
+            variable_type_name: None,
+            index: None,
+            direction: None,
+            enum_map: Default::default(),
+            encoding: VariableEncoding::BitVector,
+        };
+
+        let applicability = translators.translator_applicability(&meta);
+
+        let names = applicability
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>();
+        let expected_names = translators
+            .all_translator_names()
+            .into_iter()
+            .sorted()
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected_names);
+
+        let preference_of = |name: &str| {
+            applicability
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, preference)| preference)
+                .unwrap()
+        };
+        assert_ne!(preference_of("Hexadecimal"), &TranslationPreference::No);
+        assert_eq!(
+            preference_of("FP: 32-bit IEEE 754"),
+            &TranslationPreference::No
+        );
+    }
+
     #[test]
     fn variable_info_has_subpath() {
         use surfer_translation_types::VariableInfo;
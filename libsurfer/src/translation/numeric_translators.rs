@@ -4,11 +4,12 @@ use crate::variable_meta::VariableMetaExt;
 use crate::wave_container::{ScopeId, VarId};
 use eyre::Result;
 use half::{bf16, f16};
-use num::{BigUint, One};
+use num::{BigUint, One, Zero};
 use softposit::{P8E0, P16E1, P32E2, Q8E0, Q16E1};
 use surfer_translation_types::{
-    BasicTranslator, TranslationResult, Translator, ValueKind, ValueRepr, VariableInfo,
-    VariableMeta, VariableValue, biguint_to_f64, parse_value_to_numeric, translates_all_bit_types,
+    BasicTranslator, NAN_UNDEF, NumberNotation, TranslationResult, Translator, ValueKind,
+    ValueRepr, VariableInfo, VariableMeta, VariableValue, biguint_to_f64, is_nan_highimp,
+    parse_value_to_numeric, translates_all_bit_types,
 };
 
 use super::{TranslationPreference, check_single_wordlength};
@@ -20,6 +21,31 @@ fn shortest_float_representation<T: std::fmt::LowerExp + std::fmt::Display>(v: T
     if dec.len() > exp.len() { exp } else { dec }
 }
 
+/// `value` with a mantissa of one to three integer digits and an exponent that is a multiple
+/// of three, e.g. `12.3e-9`. The exponent is left out if it is zero.
+fn engineering_notation(value: f64) -> String {
+    if value == 0. || !value.is_finite() {
+        return format!("{value}");
+    }
+    let scientific = format!("{value:e}");
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or_default();
+    let (sign, mantissa) = mantissa
+        .strip_prefix('-')
+        .map_or(("", mantissa), |mantissa| ("-", mantissa));
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let integer_digits = usize::try_from(exponent.rem_euclid(3)).unwrap_or_default() + 1;
+    let digits = format!("{digits:0<integer_digits$}");
+    let (integer, fraction) = digits.split_at(integer_digits);
+    let exponent = exponent - exponent.rem_euclid(3);
+    match (fraction.is_empty(), exponent) {
+        (true, 0) => format!("{sign}{integer}"),
+        (true, _) => format!("{sign}{integer}e{exponent}"),
+        (false, 0) => format!("{sign}{integer}.{fraction}"),
+        (false, _) => format!("{sign}{integer}.{fraction}e{exponent}"),
+    }
+}
+
 /// If `value` is a biguint or consists only of 1 or 0, translates the value using
 /// `biguint_translator`. If `value` contains other values such as X, Z etc. the result
 /// is the corresponding `ValueKind`
@@ -40,6 +66,10 @@ impl BasicTranslator<VarId, ScopeId> for UnsignedTranslator {
         String::from("Unsigned")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Unsigned { radix: 10 })
+    }
+
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(std::string::ToString::to_string, v)
     }
@@ -64,6 +94,10 @@ impl BasicTranslator<VarId, ScopeId> for SignedTranslator {
         String::from("Signed")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Signed)
+    }
+
     fn basic_translate(&self, num_bits: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(|val| compute_signed_value(val, num_bits), v)
     }
@@ -100,6 +134,40 @@ fn compute_signed_value(v: &BigUint, num_bits: u32) -> String {
     }
 }
 
+/// Decodes a Gray code value, each binary bit being the XOR of all Gray code bits at and
+/// above it.
+fn gray_to_binary(v: &BigUint) -> BigUint {
+    let mut binary = v.clone();
+    let mut shifted = v >> 1u32;
+    while !shifted.is_zero() {
+        binary ^= &shifted;
+        shifted >>= 1u32;
+    }
+    binary
+}
+
+pub struct GrayCodeTranslator {}
+
+impl BasicTranslator<VarId, ScopeId> for GrayCodeTranslator {
+    fn name(&self) -> String {
+        String::from("Gray code")
+    }
+
+    fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
+        translate_numeric(|val| gray_to_binary(val).to_string(), v)
+    }
+
+    fn basic_translate_numeric(&self, _num_bits: u32, value: &VariableValue) -> Option<f64> {
+        Some(parse_value_to_numeric(value, |v| {
+            biguint_to_f64(&gray_to_binary(v))
+        }))
+    }
+
+    fn translates(&self, variable: &VariableMeta<VarId, ScopeId>) -> Result<TranslationPreference> {
+        translates_all_bit_types(variable)
+    }
+}
+
 pub struct SinglePrecisionTranslator {}
 
 impl BasicTranslator<VarId, ScopeId> for SinglePrecisionTranslator {
@@ -107,6 +175,10 @@ impl BasicTranslator<VarId, ScopeId> for SinglePrecisionTranslator {
         String::from("FP: 32-bit IEEE 754")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
+
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -135,6 +207,10 @@ impl BasicTranslator<VarId, ScopeId> for DoublePrecisionTranslator {
     fn name(&self) -> String {
         String::from("FP: 64-bit IEEE 754")
     }
+
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -159,6 +235,41 @@ impl BasicTranslator<VarId, ScopeId> for DoublePrecisionTranslator {
     }
 }
 
+/// 64-bit IEEE 754 values in engineering notation, for reading very small or large analog
+/// values
+pub struct EngineeringTranslator {}
+
+impl BasicTranslator<VarId, ScopeId> for EngineeringTranslator {
+    fn name(&self) -> String {
+        String::from("Engineering notation")
+    }
+    fn basic_translate(&self, num_bits: u32, v: &VariableValue) -> (String, ValueKind) {
+        let value = self
+            .basic_translate_numeric(num_bits, v)
+            .unwrap_or(NAN_UNDEF);
+        if is_nan_highimp(value) {
+            ("Z".to_string(), ValueKind::HighImp)
+        } else if value.to_bits() == NAN_UNDEF.to_bits() {
+            ("X".to_string(), ValueKind::Undef)
+        } else {
+            (engineering_notation(value), ValueKind::Normal)
+        }
+    }
+    fn basic_translate_numeric(&self, _num_bits: u32, value: &VariableValue) -> Option<f64> {
+        Some(parse_value_to_numeric(value, |v| {
+            f64::from_bits(v.iter_u64_digits().next().unwrap_or(0))
+        }))
+    }
+    fn translates(&self, variable: &VariableMeta<VarId, ScopeId>) -> Result<TranslationPreference> {
+        // Offered for reals, which are shown as plain floats by default
+        if variable.is_real() {
+            Ok(TranslationPreference::Yes)
+        } else {
+            check_single_wordlength(variable.num_bits, 64)
+        }
+    }
+}
+
 #[cfg(feature = "f128")]
 pub struct QuadPrecisionTranslator {}
 
@@ -167,6 +278,10 @@ impl BasicTranslator<VarId, ScopeId> for QuadPrecisionTranslator {
     fn name(&self) -> String {
         String::from("FP: 128-bit IEEE 754")
     }
+
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -194,6 +309,10 @@ impl BasicTranslator<VarId, ScopeId> for HalfPrecisionTranslator {
     fn name(&self) -> String {
         String::from("FP: 16-bit IEEE 754")
     }
+
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -222,6 +341,10 @@ impl BasicTranslator<VarId, ScopeId> for BFloat16Translator {
     fn name(&self) -> String {
         String::from("FP: bfloat16")
     }
+
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -251,6 +374,10 @@ impl BasicTranslator<VarId, ScopeId> for Posit32Translator {
         String::from("Posit: 32-bit (two exponent bits)")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
+
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -281,6 +408,10 @@ impl BasicTranslator<VarId, ScopeId> for Posit16Translator {
         String::from("Posit: 16-bit (one exponent bit)")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
+
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -311,6 +442,10 @@ impl BasicTranslator<VarId, ScopeId> for Posit8Translator {
         String::from("Posit: 8-bit (no exponent bit)")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
+
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -341,6 +476,10 @@ impl BasicTranslator<VarId, ScopeId> for PositQuire8Translator {
         String::from("Posit: quire for 8-bit (no exponent bit)")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
+
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -372,6 +511,10 @@ impl BasicTranslator<VarId, ScopeId> for PositQuire16Translator {
         String::from("Posit: quire for 16-bit (one exponent bit)")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
+
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| {
@@ -464,6 +607,10 @@ impl BasicTranslator<VarId, ScopeId> for E5M2Translator {
         String::from("FP: 8-bit (E5M2)")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
+
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| decode_e5m2(v.iter_u32_digits().next().unwrap_or(0) as u8),
@@ -494,6 +641,10 @@ impl BasicTranslator<VarId, ScopeId> for E4M3Translator {
         String::from("FP: 8-bit (E4M3)")
     }
 
+    fn number_notation(&self) -> Option<NumberNotation> {
+        Some(NumberNotation::Decimal)
+    }
+
     fn basic_translate(&self, _: u32, v: &VariableValue) -> (String, ValueKind) {
         translate_numeric(
             |v| decode_e4m3(v.iter_u32_digits().next().unwrap_or(0) as u8),
@@ -594,6 +745,27 @@ mod test {
     use super::*;
     use surfer_translation_types::VariableValue;
 
+    #[test]
+    fn numeric_translators_report_their_notation() {
+        assert_eq!(
+            UnsignedTranslator {}.number_notation(),
+            Some(NumberNotation::Unsigned { radix: 10 })
+        );
+        assert_eq!(
+            SignedTranslator {}.number_notation(),
+            Some(NumberNotation::Signed)
+        );
+        assert_eq!(
+            SinglePrecisionTranslator {}.number_notation(),
+            Some(NumberNotation::Decimal)
+        );
+        assert_eq!(
+            Posit16Translator {}.number_notation(),
+            Some(NumberNotation::Decimal)
+        );
+        assert_eq!(GrayCodeTranslator {}.number_notation(), None);
+    }
+
     #[test]
     fn signed_translation_from_string() {
         assert_eq!(
@@ -674,6 +846,46 @@ mod test {
         );
     }
 
+    #[test]
+    fn gray_code_translation() {
+        let translate = |bits: &str| {
+            GrayCodeTranslator {}.basic_translate(4, &VariableValue::String(bits.to_string()))
+        };
+        // The Gray code sequence counts up by one
+        for (expected, bits) in [
+            "0000", "0001", "0011", "0010", "0110", "0111", "0101", "0100", "1100",
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            assert_eq!(translate(bits), (expected.to_string(), ValueKind::Normal));
+        }
+        assert_eq!(translate("1000").0, "15");
+        assert_eq!(
+            GrayCodeTranslator {}
+                .basic_translate(70, &VariableValue::BigUint(BigUint::one() << 69u32))
+                .0,
+            ((BigUint::one() << 70u32) - 1u32).to_string()
+        );
+        assert_eq!(
+            GrayCodeTranslator {}
+                .basic_translate_numeric(4, &VariableValue::String("0110".to_string())),
+            Some(4.)
+        );
+    }
+
+    #[test]
+    fn gray_code_translation_of_unknown_bits() {
+        let translate = |bits: &str| {
+            GrayCodeTranslator {}.basic_translate(4, &VariableValue::String(bits.to_string()))
+        };
+        assert_eq!(translate("01x0"), ("UNDEF".to_string(), ValueKind::Undef));
+        assert_eq!(
+            translate("zzzz"),
+            ("HIGHIMP".to_string(), ValueKind::HighImp)
+        );
+    }
+
     #[test]
     fn e4m3_translation_from_biguint() {
         assert_eq!(
@@ -1179,6 +1391,38 @@ mod test {
         );
     }
 
+    #[test]
+    fn engineering_notation_uses_exponents_divisible_by_three() {
+        assert_eq!(engineering_notation(1.23e-8), "12.3e-9");
+        assert_eq!(engineering_notation(-4.7e-12), "-4.7e-12");
+        assert_eq!(engineering_notation(123_456.), "123.456e3");
+        assert_eq!(engineering_notation(1500.), "1.5e3");
+        assert_eq!(engineering_notation(0.5), "500e-3");
+        assert_eq!(engineering_notation(100.), "100");
+        assert_eq!(engineering_notation(12.5), "12.5");
+        assert_eq!(engineering_notation(1e21), "1e21");
+        assert_eq!(engineering_notation(0.), "0");
+        assert_eq!(engineering_notation(f64::NEG_INFINITY), "-inf");
+    }
+
+    #[test]
+    fn engineering_translation_from_biguint() {
+        let translate = |value: f64| {
+            EngineeringTranslator {}
+                .basic_translate(64, &VariableValue::BigUint(BigUint::from(value.to_bits())))
+        };
+        assert_eq!(translate(3.3e-6), ("3.3e-6".to_string(), ValueKind::Normal));
+        assert_eq!(translate(-25e6), ("-25e6".to_string(), ValueKind::Normal));
+        assert_eq!(
+            EngineeringTranslator {}.basic_translate(64, &VariableValue::String("x".to_string())),
+            ("X".to_string(), ValueKind::Undef)
+        );
+        assert_eq!(
+            EngineeringTranslator {}.basic_translate(64, &VariableValue::String("z".to_string())),
+            ("Z".to_string(), ValueKind::HighImp)
+        );
+    }
+
     #[test]
     fn double_translation_from_bigunit() {
         assert_eq!(
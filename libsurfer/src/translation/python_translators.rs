@@ -4,7 +4,7 @@ use pyo3::types::{PyAnyMethods, PyDict, PyModule, PyStringMethods};
 use pyo3::{Bound, Py, Python};
 use std::ffi::{CStr, CString};
 use surfer_translation_types::python::{PythonValueKind, surfer_pyo3_module};
-use surfer_translation_types::{BasicTranslator, ValueKind, VariableValue};
+use surfer_translation_types::{BasicTranslator, PluginConfig, ValueKind, VariableValue};
 use tracing::{error, info};
 
 use crate::wave_container::{ScopeId, VarId};
@@ -44,6 +44,21 @@ impl PythonTranslator {
         })
         .map_err(|e| anyhow!("Error initializing Python translator: {e}"))
     }
+
+    /// Call `set_config` on the translator class with the configuration as a dict, if the
+    /// class defines it.
+    pub fn set_config(&self, config: &PluginConfig) {
+        let result = Python::attach(|py| -> pyo3::PyResult<_> {
+            let class = self.module.bind(py).getattr(self.class_name.as_str())?;
+            if class.hasattr("set_config")? {
+                class.getattr("set_config")?.call1((config.0.clone(),))?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            error!("Error passing config to Python translator: {e}");
+        }
+    }
 }
 
 impl BasicTranslator<VarId, ScopeId> for PythonTranslator {
@@ -10,7 +10,7 @@ use extism_manifest::MemoryOptions;
 use eyre::{Context, anyhow};
 use surfer_translation_types::plugin_types::TranslateParams;
 use surfer_translation_types::{
-    TranslationPreference, TranslationResult, Translator, VariableInfo, VariableMeta,
+    PluginConfig, TranslationPreference, TranslationResult, Translator, VariableInfo, VariableMeta,
     VariableNameInfo, VariableValue,
 };
 use tracing::{error, info, warn};
@@ -168,6 +168,21 @@ impl Translator<VarId, ScopeId, Message> for PluginTranslator {
         }
     }
 
+    fn set_config(&self, config: &PluginConfig) {
+        let mut plugin = self.plugin.lock().unwrap();
+        if plugin.function_exists("set_config") {
+            plugin
+                .call::<_, ()>("set_config", config.clone())
+                .map_err(|e| {
+                    error!(
+                        "Failed to set_config on {}. {e}",
+                        self.file.to_string_lossy()
+                    );
+                })
+                .ok();
+        }
+    }
+
     fn translate(
         &self,
         variable: &VariableMeta<VarId, ScopeId>,
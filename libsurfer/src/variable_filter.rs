@@ -10,11 +10,12 @@ use itertools::Itertools;
 use regex::{Regex, RegexBuilder, escape};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
+use std::collections::HashMap;
 
 use crate::data_container::DataContainer::Transactions;
 use crate::transaction_container::{StreamScopeRef, TransactionStreamRef};
 use crate::variable_direction::VariableDirectionExt;
-use crate::wave_container::{VariableRefExt, WaveContainer};
+use crate::wave_container::{ScopeRef, VariableRefExt, WaveContainer};
 use crate::wave_data::ScopeType;
 use crate::{SystemState, message::Message, wave_container::VariableRef};
 use surfer_translation_types::VariableDirection;
@@ -50,6 +51,8 @@ pub struct VariableFilter {
     pub(crate) group_by_direction: bool,
     #[serde(skip)]
     cache: RefCell<VariableFilterRegexCache>,
+    #[serde(skip)]
+    scope_cache: RefCell<ScopeDirectionCache>,
 }
 
 // Lightweight cache for compiled regex and fuzzy matcher to avoid repeated compilation
@@ -62,6 +65,14 @@ struct VariableFilterRegexCache {
     regex_error: Option<String>,
 }
 
+/// Whether scopes have variables passing the IO filter, for the `include_*` settings in
+/// `include`
+#[derive(Default)]
+struct ScopeDirectionCache {
+    include: [bool; 4],
+    visible: HashMap<ScopeRef, bool>,
+}
+
 #[derive(Debug, Deserialize)]
 pub enum VariableIOFilterType {
     Input,
@@ -91,6 +102,7 @@ impl VariableFilter {
 
             group_by_direction: false,
             cache: RefCell::new(Default::default()),
+            scope_cache: RefCell::new(Default::default()),
         }
     }
 
@@ -177,7 +189,13 @@ impl VariableFilter {
     }
 
     fn kind_filter(&self, vr: &VariableRef, wave_container_opt: Option<&WaveContainer>) -> bool {
-        match get_variable_direction(vr, wave_container_opt) {
+        self.includes_direction(get_variable_direction(vr, wave_container_opt))
+    }
+
+    /// Whether variables with `direction` pass the IO filter
+    #[must_use]
+    pub fn includes_direction(&self, direction: VariableDirection) -> bool {
+        match direction {
             VariableDirection::Input => self.include_inputs,
             VariableDirection::Output => self.include_outputs,
             VariableDirection::InOut => self.include_inouts,
@@ -185,6 +203,48 @@ impl VariableFilter {
         }
     }
 
+    /// Whether the IO filter hides variables of any direction
+    #[must_use]
+    pub fn filters_directions(&self) -> bool {
+        !(self.include_inputs && self.include_outputs && self.include_inouts && self.include_others)
+    }
+
+    /// Whether `scope`, or any scope below it, has a variable passing the IO filter. The result
+    /// is cached until the IO filter changes or [`VariableFilter::clear_scope_cache`] is called.
+    pub fn scope_has_included_direction(
+        &self,
+        scope: &ScopeRef,
+        wave_container: &WaveContainer,
+    ) -> bool {
+        let include = [
+            self.include_inputs,
+            self.include_outputs,
+            self.include_inouts,
+            self.include_others,
+        ];
+        let mut cache = self.scope_cache.borrow_mut();
+        if cache.include != include {
+            cache.include = include;
+            cache.visible.clear();
+        }
+        scope_visibility(
+            scope,
+            &mut cache.visible,
+            &|scope| {
+                wave_container
+                    .variables_in_scope(scope)
+                    .iter()
+                    .any(|vr| self.kind_filter(vr, Some(wave_container)))
+            },
+            &|scope| wave_container.child_scopes(scope).unwrap_or_default(),
+        )
+    }
+
+    /// Forget which scopes pass the IO filter, as needed when the hierarchy changes
+    pub(crate) fn clear_scope_cache(&self) {
+        self.scope_cache.borrow_mut().visible.clear();
+    }
+
     pub fn matching_variables(
         &self,
         variables: &[VariableRef],
@@ -528,6 +588,27 @@ impl SystemState {
     }
 }
 
+/// Whether `scope`, or a scope below it, has a variable passing the IO filter, which
+/// `variables_included` tells for a single scope. The result for `scope` and all scopes below
+/// it is stored in `visible`, so that every scope is only checked once.
+fn scope_visibility(
+    scope: &ScopeRef,
+    visible: &mut HashMap<ScopeRef, bool>,
+    variables_included: &dyn Fn(&ScopeRef) -> bool,
+    child_scopes: &dyn Fn(&ScopeRef) -> Vec<ScopeRef>,
+) -> bool {
+    if let Some(known) = visible.get(scope) {
+        return *known;
+    }
+    // All children are visited, as they are typically drawn right after their parent
+    let mut result = variables_included(scope);
+    for child in child_scopes(scope) {
+        result |= scope_visibility(&child, visible, variables_included, child_scopes);
+    }
+    visible.insert(scope.clone(), result);
+    result
+}
+
 fn get_variable_direction(
     vr: &VariableRef,
     wave_container_opt: Option<&WaveContainer>,
@@ -545,6 +626,7 @@ fn get_variable_direction(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::wave_container::ScopeRefExt;
 
     #[test]
     fn test_empty_filter_matches_all() {
@@ -801,6 +883,107 @@ mod tests {
         assert!(fn2("test")); // Now case insensitive
     }
 
+    #[test]
+    fn test_flat_list_filters_on_full_path() {
+        let variables = [
+            "top.cpu.clk",
+            "top.cpu.alu.result",
+            "top.mem.clk",
+            "top.mem.data",
+        ]
+        .map(VariableRef::from_hierarchy_string);
+
+        let mut filter = VariableFilter::new();
+        filter.name_filter_type = VariableNameFilterType::Contain;
+        filter.name_filter_str = "cpu.".to_string();
+
+        // Scope names are part of what is matched when using full paths
+        let matching = filter
+            .matching_variables(&variables, None, true)
+            .iter()
+            .map(VariableRefExt::full_path_string)
+            .collect_vec();
+        assert_eq!(matching, vec!["top.cpu.clk", "top.cpu.alu.result"]);
+
+        // ... but not when only matching on variable names
+        assert!(
+            filter
+                .matching_variables(&variables, None, false)
+                .is_empty()
+        );
+
+        filter.name_filter_type = VariableNameFilterType::Start;
+        filter.name_filter_str = "top.mem".to_string();
+        let matching = filter
+            .matching_variables(&variables, None, true)
+            .iter()
+            .map(VariableRefExt::full_path_string)
+            .collect_vec();
+        assert_eq!(matching, vec!["top.mem.clk", "top.mem.data"]);
+    }
+
+    #[test]
+    fn test_direction_filter() {
+        let mut filter = VariableFilter::new();
+        assert!(!filter.filters_directions());
+
+        filter.include_outputs = false;
+        filter.include_others = false;
+        assert!(filter.filters_directions());
+        assert!(filter.includes_direction(VariableDirection::Input));
+        assert!(filter.includes_direction(VariableDirection::InOut));
+        assert!(!filter.includes_direction(VariableDirection::Output));
+        assert!(!filter.includes_direction(VariableDirection::Buffer));
+        assert!(!filter.includes_direction(VariableDirection::Unknown));
+
+        // Without a wave container the direction is unknown, so the variables count as others
+        let variables = ["top.clk", "top.data"].map(VariableRef::from_hierarchy_string);
+        assert!(
+            filter
+                .matching_variables(&variables, None, false)
+                .is_empty()
+        );
+        filter.include_others = true;
+        assert_eq!(filter.matching_variables(&variables, None, false).len(), 2);
+    }
+
+    #[test]
+    fn test_scope_visibility_follows_children() {
+        // top has the scopes a and b, where only a.inner has a variable passing the filter
+        let scope = |path: &str| ScopeRef::from_hierarchy_string(path);
+        let children = |s: &ScopeRef| match s.to_string().as_str() {
+            "top" => vec![scope("top.a"), scope("top.b")],
+            "top.a" => vec![scope("top.a.inner")],
+            _ => vec![],
+        };
+        let checked = RefCell::new(vec![]);
+        let included = |s: &ScopeRef| {
+            checked.borrow_mut().push(s.to_string());
+            s.to_string() == "top.a.inner"
+        };
+
+        let mut visible = HashMap::new();
+        assert!(scope_visibility(
+            &scope("top"),
+            &mut visible,
+            &included,
+            &children
+        ));
+        assert_eq!(visible.get(&scope("top.a")), Some(&true));
+        assert_eq!(visible.get(&scope("top.a.inner")), Some(&true));
+        assert_eq!(visible.get(&scope("top.b")), Some(&false));
+
+        // Known scopes are not checked again
+        let checked_before = checked.borrow().len();
+        assert!(!scope_visibility(
+            &scope("top.b"),
+            &mut visible,
+            &included,
+            &children
+        ));
+        assert_eq!(checked.borrow().len(), checked_before);
+    }
+
     #[test]
     fn test_default_filter_settings() {
         let filter = VariableFilter::new();
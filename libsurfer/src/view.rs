@@ -1,7 +1,12 @@
 use crate::{
-    config::{ThemeColorPair, TransitionValue},
-    dialog::{draw_open_sibling_state_file_dialog, draw_reload_waveform_dialog},
+    config::{
+        GroupConnectorStyle, GroupDropLevel, ItemColumnSide, ThemeColorPair, TransitionValue,
+    },
+    dialog::{
+        draw_add_marker_dialog, draw_open_sibling_state_file_dialog, draw_reload_waveform_dialog,
+    },
     displayed_item::DisplayedVariable,
+    drawing_canvas::is_outside_of_data,
     fzcmd::expand_command,
     menus::generic_context_menu,
     tooltips::variable_tooltip_text,
@@ -34,7 +39,7 @@ use crate::benchmark::NUM_PERF_SAMPLES;
 use crate::command_parser::get_parser;
 use crate::config::SurferTheme;
 use crate::displayed_item::{DisplayedFieldRef, DisplayedItem, DisplayedItemRef};
-use crate::displayed_item_tree::{ItemIndex, VisibleItemIndex};
+use crate::displayed_item_tree::{DisplayedItemTree, ItemIndex, VisibleItemIndex};
 use crate::help::{
     draw_about_window, draw_control_help_window, draw_license_window, draw_quickstart_help_window,
 };
@@ -42,9 +47,15 @@ use crate::time::time_string;
 use crate::transaction_container::TransactionStreamRef;
 use crate::translation::TranslationResultExt;
 use crate::util::get_alpha_focus_id;
+use crate::viewport::Viewport;
 use crate::wave_container::{FieldRef, FieldRefExt, VariableRef};
+use crate::wave_data::item_height_scaling_factor;
 use crate::{
-    Message, MoveDir, SystemState, command_prompt::show_command_prompt, hierarchy::HierarchyStyle,
+    CachedDrawData, Message, MoveDir, SystemState,
+    command_prompt::show_command_prompt,
+    hierarchy::HierarchyStyle,
+    quick_open::show_quick_open,
+    sparkline::{SPARKLINE_WIDTH, draw_sparkline},
     wave_data::WaveData,
 };
 
@@ -236,6 +247,15 @@ impl eframe::App for SystemState {
         #[cfg(target_arch = "wasm32")]
         self.handle_wasm_external_messages();
 
+        if self
+            .user
+            .waves
+            .as_mut()
+            .is_some_and(WaveData::keep_cursor_in_following_viewports)
+        {
+            self.invalidate_draw_commands();
+        }
+
         let viewport_is_moving = if let Some(waves) = &mut self.user.waves {
             let mut is_moving = false;
             for vp in &mut waves.viewports {
@@ -275,6 +295,12 @@ impl eframe::App for SystemState {
             ctx.request_repaint();
         }
 
+        if self.user.config.layout.show_fps()
+            && let Some(prev_cpu) = frame.info().cpu_usage
+        {
+            self.frame_time.push(prev_cpu);
+        }
+
         #[cfg(feature = "performance_plot")]
         if let Some(prev_cpu) = frame.info().cpu_usage {
             self.rendering_cpu_times.push_back(prev_cpu);
@@ -327,6 +353,14 @@ impl SystemState {
             draw_open_sibling_state_file_dialog(ctx, dialog, &mut msgs);
         }
 
+        if let Some(dialog) = &self.user.show_add_marker_dialog
+            && let Some(waves) = &self.user.waves
+        {
+            let free_indices = waves.free_marker_indices().collect_vec();
+            let color_names = self.user.config.theme.colors.keys().cloned().collect_vec();
+            draw_add_marker_dialog(ctx, dialog, &free_indices, &color_names, &mut msgs);
+        }
+
         if self.user.show_performance {
             #[cfg(feature = "performance_plot")]
             self.draw_performance_graph(ctx, &mut msgs);
@@ -338,6 +372,44 @@ impl SystemState {
             self.draw_marker_window(waves, ctx, &mut msgs);
         }
 
+        if self.user.show_events_window
+            && let Some(waves) = &self.user.waves
+        {
+            self.draw_events_window(waves, ctx, &mut msgs);
+        }
+
+        if self.user.show_wave_metadata
+            && let Some(waves) = &self.user.waves
+        {
+            self.draw_wave_metadata_window(waves, ctx, &mut msgs);
+        }
+
+        if !self.watch_list.is_empty()
+            && let Some(waves) = &self.user.waves
+        {
+            self.draw_watch_window(waves, ctx, &mut msgs);
+        }
+
+        if let Some(marker_idx) = self.cursor_marker_compare
+            && let Some(waves) = &self.user.waves
+        {
+            self.draw_cursor_marker_compare_window(waves, marker_idx, ctx, &mut msgs);
+        }
+
+        if let Some(histogram) = &self.activity_histogram {
+            self.draw_activity_histogram_window(histogram, ctx, &mut msgs);
+        }
+
+        if let Some(summary) = &self.change_summary {
+            self.draw_change_summary_window(summary, ctx, &mut msgs);
+        }
+
+        if let Some(stats) = &self.enum_stats
+            && let Some(waves) = &self.user.waves
+        {
+            self.draw_enum_stats_window(stats, waves, ctx, &mut msgs);
+        }
+
         if self
             .user
             .show_menu
@@ -386,6 +458,10 @@ impl SystemState {
                 });
         }
 
+        if self.quick_open.visible {
+            show_quick_open(self, ctx, window_size, &mut msgs);
+        }
+
         if self.command_prompt.visible {
             show_command_prompt(self, ctx, window_size, &mut msgs);
             if let Some(new_idx) = self.command_prompt.new_selection {
@@ -401,72 +477,87 @@ impl SystemState {
                     && expand_command(&self.command_prompt_text.borrow(), get_parser(self))
                         .expanded
                         .starts_with("item_focus");
-                if draw_focus_ids {
-                    SidePanel::left("focus id list")
-                        .default_width(40.)
-                        .width_range(40.0..=max_width)
-                        .show(ctx, |ui| {
-                            let response = ScrollArea::both()
-                                .vertical_scroll_offset(scroll_offset)
-                                .show(ui, |ui| {
-                                    self.draw_item_focus_list(ui);
+                let side = self.item_column_side().panel_side();
+                for panel in item_panel_order(self.item_column_side()) {
+                    match panel {
+                        ItemPanel::FocusIds if draw_focus_ids => {
+                            SidePanel::new(side, "focus id list")
+                                .default_width(40.)
+                                .width_range(40.0..=max_width)
+                                .show(ctx, |ui| {
+                                    let response = ScrollArea::both()
+                                        .vertical_scroll_offset(scroll_offset)
+                                        .show(ui, |ui| {
+                                            self.draw_item_focus_list(ui);
+                                        });
+                                    self.user.waves.as_mut().unwrap().top_item_draw_offset =
+                                        response.inner_rect.min.y;
+                                    self.user.waves.as_mut().unwrap().total_height =
+                                        response.inner_rect.height();
+                                    if (scroll_offset - response.state.offset.y).abs() > 5. {
+                                        msgs.push(Message::SetScrollOffset(
+                                            response.state.offset.y,
+                                        ));
+                                    }
                                 });
-                            self.user.waves.as_mut().unwrap().top_item_draw_offset =
-                                response.inner_rect.min.y;
-                            self.user.waves.as_mut().unwrap().total_height =
-                                response.inner_rect.height();
-                            if (scroll_offset - response.state.offset.y).abs() > 5. {
-                                msgs.push(Message::SetScrollOffset(response.state.offset.y));
-                            }
-                        });
-                }
-
-                SidePanel::left("variable list")
-                    .default_width(100.)
-                    .width_range(100.0..=max_width)
-                    .show(ctx, |ui| {
-                        ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
-                        if self.show_default_timeline() {
-                            ui.label(RichText::new("Time").italics());
                         }
-
-                        let response = ScrollArea::both()
-                            .auto_shrink([false; 2])
-                            .vertical_scroll_offset(scroll_offset)
-                            .show(ui, |ui| {
-                                self.draw_item_list(&mut msgs, ui, ctx);
-                            });
-                        self.user.waves.as_mut().unwrap().top_item_draw_offset =
-                            response.inner_rect.min.y;
-                        self.user.waves.as_mut().unwrap().total_height =
-                            response.inner_rect.height();
-                        if (scroll_offset - response.state.offset.y).abs() > 5. {
-                            msgs.push(Message::SetScrollOffset(response.state.offset.y));
+                        ItemPanel::FocusIds => {}
+                        ItemPanel::Names => {
+                            SidePanel::new(side, "variable list")
+                                .default_width(100.)
+                                .width_range(100.0..=max_width)
+                                .show(ctx, |ui| {
+                                    ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
+                                    if self.show_default_timeline() {
+                                        ui.label(RichText::new("Time").italics());
+                                    }
+
+                                    let response = ScrollArea::both()
+                                        .auto_shrink([false; 2])
+                                        .vertical_scroll_offset(scroll_offset)
+                                        .show(ui, |ui| {
+                                            self.draw_item_list(&mut msgs, ui, ctx);
+                                        });
+                                    self.user.waves.as_mut().unwrap().top_item_draw_offset =
+                                        response.inner_rect.min.y;
+                                    self.user.waves.as_mut().unwrap().total_height =
+                                        response.inner_rect.height();
+                                    if (scroll_offset - response.state.offset.y).abs() > 5. {
+                                        msgs.push(Message::SetScrollOffset(
+                                            response.state.offset.y,
+                                        ));
+                                    }
+                                });
                         }
-                    });
-
-                // Will only draw if a transaction is focused
-                self.draw_transaction_detail_panel(ctx, max_width, &mut msgs);
-
-                SidePanel::left("variable values")
-                    .frame(
-                        Frame::default()
-                            .inner_margin(0)
-                            .outer_margin(0)
-                            .fill(self.user.config.theme.secondary_ui_color.background),
-                    )
-                    .default_width(100.)
-                    .width_range(10.0..=max_width)
-                    .show(ctx, |ui| {
-                        ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
-                        let response = ScrollArea::both()
-                            .auto_shrink([false; 2])
-                            .vertical_scroll_offset(scroll_offset)
-                            .show(ui, |ui| self.draw_var_values(ui, &mut msgs));
-                        if (scroll_offset - response.state.offset.y).abs() > 5. {
-                            msgs.push(Message::SetScrollOffset(response.state.offset.y));
+                        // Will only draw if a transaction is focused
+                        ItemPanel::TransactionDetails => {
+                            self.draw_transaction_detail_panel(ctx, max_width, &mut msgs);
                         }
-                    });
+                        ItemPanel::Values => {
+                            SidePanel::new(side, "variable values")
+                                .frame(
+                                    Frame::default()
+                                        .inner_margin(0)
+                                        .outer_margin(0)
+                                        .fill(self.user.config.theme.secondary_ui_color.background),
+                                )
+                                .default_width(100.)
+                                .width_range(10.0..=max_width)
+                                .show(ctx, |ui| {
+                                    ui.style_mut().wrap_mode = Some(TextWrapMode::Extend);
+                                    let response = ScrollArea::both()
+                                        .auto_shrink([false; 2])
+                                        .vertical_scroll_offset(scroll_offset)
+                                        .show(ui, |ui| self.draw_var_values(ui, &mut msgs));
+                                    if (scroll_offset - response.state.offset.y).abs() > 5. {
+                                        msgs.push(Message::SetScrollOffset(
+                                            response.state.offset.y,
+                                        ));
+                                    }
+                                });
+                        }
+                    }
+                }
                 let std_stroke = ctx.style().visuals.widgets.noninteractive.bg_stroke;
                 ctx.style_mut(|style| {
                     style.visuals.widgets.noninteractive.bg_stroke =
@@ -532,15 +623,23 @@ impl SystemState {
                 });
         }
 
-        ctx.input(|i| {
-            i.raw.dropped_files.iter().for_each(|file| {
+        ctx.input(|i| match i.raw.dropped_files.as_slice() {
+            [] => {}
+            [file] => {
                 info!("Got dropped file");
                 msgs.push(Message::FileDropped(file.clone()));
-            });
+            }
+            files => {
+                info!("Got {} dropped files", files.len());
+                msgs.push(Message::FilesDropped(files.to_vec()));
+            }
         });
 
         // If some dialogs are open, skip decoding keypresses
-        if !self.user.show_url_entry && self.user.show_reload_suggestion.is_none() {
+        if !self.user.show_url_entry
+            && self.user.show_reload_suggestion.is_none()
+            && self.user.show_add_marker_dialog.is_none()
+        {
             self.handle_pressed_keys(ctx, &mut msgs);
         }
         msgs
@@ -556,11 +655,23 @@ impl SystemState {
                 ui.vertical_centered(|ui| {
                     let url = &mut *self.url.borrow_mut();
                     let response = ui.text_edit_singleline(url);
+                    let token = &mut *self.url_token.borrow_mut();
+                    let token_response = ui.add(
+                        egui::TextEdit::singleline(token)
+                            .password(true)
+                            .hint_text("Bearer token (optional)"),
+                    );
                     ui.horizontal(|ui| {
                         if ui.button("Load URL").clicked()
-                            || (response.lost_focus()
+                            || ((response.lost_focus() || token_response.lost_focus())
                                 && ui.input(|i| i.key_pressed(egui::Key::Enter)))
                         {
+                            if !token.is_empty() {
+                                msgs.push(Message::SetUrlToken {
+                                    url: url.clone(),
+                                    token: Some(std::mem::take(token)),
+                                });
+                            }
                             if let Some(callback) = &self.url_callback {
                                 msgs.push(callback(url.clone()));
                             }
@@ -648,20 +759,22 @@ impl SystemState {
         unfolded: bool,
         alignment: Align,
     ) -> egui::Response {
-        let (rect, response) = ui.allocate_exact_size(
-            Vec2::splat(self.user.config.layout.waveforms_text_size),
-            Sense::click(),
-        );
+        let text_size = self.user.config.layout.waveforms_text_size;
+        let icon_width = self
+            .user
+            .config
+            .layout
+            .group_connector_style()
+            .icon_width(text_size);
+        let (rect, response) =
+            ui.allocate_exact_size(Vec2::new(icon_width, text_size), Sense::click());
         if !has_children {
             return response;
         }
 
         // fixme: use the much nicer remixicon arrow? do a layout here and paint the galley into the rect?
         // or alternatively: change how the tree iterator works and use the egui facilities (cross widget?)
-        let icon_rect = Rect::from_center_size(
-            rect.center(),
-            emath::vec2(rect.width(), rect.height()) * 0.75,
-        );
+        let icon_rect = Rect::from_center_size(rect.center(), Vec2::splat(icon_width) * 0.75);
         let mut points = vec![
             icon_rect.left_top(),
             icon_rect.right_top(),
@@ -698,6 +811,7 @@ impl SystemState {
             .items_tree
             .iter()
             .any(|node| node.level > 0);
+        let maximized_item = self.user.waves.as_ref().unwrap().maximized_item();
         let alignment = self.get_name_alignment();
         ui.with_layout(Layout::top_down(alignment).with_cross_justify(true), |ui| {
             let available_rect = ui.available_rect_before_wrap();
@@ -731,6 +845,25 @@ impl SystemState {
                 else {
                     continue;
                 };
+                let Some(height_scaling_factor) = item_height_scaling_factor(
+                    vidx,
+                    displayed_item.height_scaling_factor(),
+                    maximized_item,
+                    self.user.waves.as_ref().unwrap().total_height,
+                    self.user.config.layout.waveforms_line_height,
+                ) else {
+                    continue;
+                };
+                // The maximized height is not stored in the item, so draw a stretched copy
+                let maximized_displayed_item;
+                let displayed_item = if maximized_item == Some(vidx) {
+                    let mut item = displayed_item.clone();
+                    item.set_height_scaling_factor(height_scaling_factor);
+                    maximized_displayed_item = item;
+                    &maximized_displayed_item
+                } else {
+                    displayed_item
+                };
 
                 ui.with_layout(
                     if alignment == Align::LEFT {
@@ -739,7 +872,10 @@ impl SystemState {
                         Layout::right_to_left(Align::TOP)
                     },
                     |ui| {
-                        ui.add_space(10.0 * f32::from(*level));
+                        ui.add_space(group_indent(
+                            *level,
+                            self.user.config.layout.group_indent_px(),
+                        ));
                         if any_groups {
                             let response =
                                 self.hierarchy_icon(ui, has_children, *unfolded, alignment);
@@ -821,7 +957,9 @@ impl SystemState {
             );
         });
 
-        self.user.waves.as_mut().unwrap().drawing_infos = item_offsets;
+        let waves = self.user.waves.as_mut().unwrap();
+        waves.drawing_infos = item_offsets;
+        waves.zebra_shading = waves.items_tree.zebra_shading();
 
         // Context menu for the unused part
         let response = ui.allocate_response(ui.available_size(), Sense::click());
@@ -1009,8 +1147,15 @@ impl SystemState {
             | VariableInfo::String
             | VariableInfo::Event
             | VariableInfo::Real => {
+                let show_sparkline = self.show_sparklines()
+                    && field.field.is_empty()
+                    && matches!(info, VariableInfo::Bits | VariableInfo::Real);
                 let label = ui
                     .with_layout(Layout::top_down(alignment).with_cross_justify(true), |ui| {
+                        // Leave room for the sparkline to the right of the label
+                        if show_sparkline {
+                            ui.set_max_width(ui.available_width() - SPARKLINE_WIDTH);
+                        }
                         self.draw_variable_label(
                             vidx,
                             displayed_item,
@@ -1024,6 +1169,17 @@ impl SystemState {
                     })
                     .inner;
                 self.draw_drag_source(msgs, vidx, &label, ctx.input(|e| e.modifiers));
+                if show_sparkline
+                    && let Some(Some(CachedDrawData::WaveDrawData(draw_data))) =
+                        self.draw_data.borrow().first()
+                    && let Some(samples) = draw_data.sparklines.get(&displayed_id)
+                {
+                    let rect = Rect::from_min_size(
+                        label.rect.right_top(),
+                        Vec2::new(SPARKLINE_WIDTH, label.rect.height()),
+                    );
+                    draw_sparkline(samples, rect, self.user.config.theme.foreground, ui);
+                }
                 drawing_infos.push(ItemDrawingInfo::Variable(VariableDrawingInfo {
                     displayed_field_ref,
                     field_ref: field.clone(),
@@ -1091,10 +1247,20 @@ impl SystemState {
             )
         });
 
-        let left_x = |level: u8| -> f32 { rect_with_margin.left() + f32::from(level) * 10.0 };
-        let Some(insert_level) = level_range.find_or_last(|&level| {
+        let modifiers = ui.input(|i| i.modifiers);
+        let drop_level = if modifiers.alt {
+            GroupDropLevel::Inside
+        } else if modifiers.shift {
+            GroupDropLevel::Sibling
+        } else {
+            self.user.config.behavior.group_drop_level()
+        };
+
+        let indent = self.user.config.layout.group_indent_px();
+        let left_x = |level: u8| -> f32 { rect_with_margin.left() + group_indent(level, indent) };
+        let Some(insert_level) = DisplayedItemTree::drop_level(level_range, drop_level, |&level| {
             let mut rect = expanded_rect.with_min_x(left_x(level));
-            rect.set_width(10.0);
+            rect.set_width(indent.max(2.0));
             if level == 0 {
                 rect.set_left(available_rect.left());
             }
@@ -1103,15 +1269,19 @@ impl SystemState {
             return;
         };
 
+        // The circle at the start of the line makes the level of the drop easy to see
+        let stroke = Stroke::new(
+            self.user.config.theme.linewidth,
+            self.user.config.theme.drag_hint_color,
+        );
+        ui.painter()
+            .circle_stroke(Pos2::new(left_x(insert_level), line_y), 3.0, stroke);
         ui.painter().line_segment(
             [
-                Pos2::new(left_x(insert_level), line_y),
+                Pos2::new(left_x(insert_level) + 3.0, line_y),
                 Pos2::new(rect_with_margin.right(), line_y),
             ],
-            Stroke::new(
-                self.user.config.theme.linewidth,
-                self.user.config.theme.drag_hint_color,
-            ),
+            stroke,
         );
         msgs.push(Message::VariableDragTargetChanged(
             crate::displayed_item_tree::TargetPosition {
@@ -1159,6 +1329,11 @@ impl SystemState {
             let style = ui.style_mut();
             style.visuals.selection.bg_fill = color_pair.background;
         }
+        let foreground = if self.item_is_hidden(vidx) {
+            color_pair.foreground.gamma_multiply(0.5)
+        } else {
+            color_pair.foreground
+        };
 
         let mut layout_job = LayoutJob::default();
         match displayed_item {
@@ -1188,13 +1363,13 @@ impl SystemState {
                             &true_name,
                             &mut layout_job,
                             monospace_font.clone(),
-                            color_pair.foreground,
+                            foreground,
                             monospace_width,
                             available_width,
                         );
                     } else {
                         displayed_item.add_to_layout_job(
-                            color_pair.foreground,
+                            foreground,
                             ui.style(),
                             &mut layout_job,
                             Some(field),
@@ -1203,7 +1378,7 @@ impl SystemState {
                     }
                 } else {
                     RichText::new(field.field.last().unwrap().clone())
-                        .color(color_pair.foreground)
+                        .color(foreground)
                         .line_height(Some(self.user.config.layout.waveforms_line_height))
                         .append_to(
                             &mut layout_job,
@@ -1214,13 +1389,27 @@ impl SystemState {
                 }
             }
             _ => displayed_item.add_to_layout_job(
-                color_pair.foreground,
+                foreground,
                 ui.style(),
                 &mut layout_job,
                 field,
                 &self.user.config,
             ),
         }
+        if let DisplayedItem::Variable(var) = displayed_item
+            && field.is_some_and(|field| field.field.is_empty())
+            && let Some(annotation) = var.captured_value_annotation()
+        {
+            RichText::new(format!(" {annotation}"))
+                .color(foreground.gamma_multiply(0.6))
+                .small()
+                .append_to(
+                    &mut layout_job,
+                    ui.style(),
+                    FontSelection::Default,
+                    Align::Center,
+                );
+        }
 
         let item_label = ui
             .selectable_label(
@@ -1396,6 +1585,15 @@ impl SystemState {
         }
     }
 
+    fn item_is_hidden(&self, vidx: VisibleItemIndex) -> bool {
+        self.user.waves.as_ref().is_some_and(|waves| {
+            waves
+                .items_tree
+                .get_visible(vidx)
+                .is_some_and(|node| node.hidden)
+        })
+    }
+
     fn item_is_selected(&self, id: DisplayedItemRef) -> bool {
         if let Some(waves) = &self.user.waves {
             waves
@@ -1438,6 +1636,11 @@ impl SystemState {
         let gap = ui.spacing().item_spacing.y * 0.5;
         let y_zero = to_screen.transform_pos(Pos2::ZERO).y;
         let ucursor = waves.cursor.as_ref().and_then(num::BigInt::to_biguint);
+        let cursor_outside_data = self.show_no_data_hatch()
+            && waves
+                .cursor
+                .as_ref()
+                .is_some_and(|cursor| is_outside_of_data(cursor, &waves.safe_num_timestamps()));
 
         // Add default margin as it was removed when creating the frame
         let rect_with_margin = Rect {
@@ -1468,16 +1671,22 @@ impl SystemState {
                 self.draw_background(drawing_info, y_zero, &ctx, gap, backgroundcolor);
                 match drawing_info {
                     ItemDrawingInfo::Variable(drawing_info) => {
-                        if ucursor.as_ref().is_none() {
+                        if (ucursor.as_ref().is_none() && !cursor_outside_data)
+                            || self.item_is_hidden(drawing_info.vidx)
+                        {
                             ui.label("");
                             continue;
                         }
 
-                        let v = self.get_variable_value(
-                            waves,
-                            &drawing_info.displayed_field_ref,
-                            ucursor.as_ref(),
-                        );
+                        let v = if cursor_outside_data {
+                            Some("—".to_string())
+                        } else {
+                            self.get_variable_value(
+                                waves,
+                                &drawing_info.displayed_field_ref,
+                                ucursor.as_ref(),
+                            )
+                        };
                         if let Some(v) = v {
                             ui.label(
                                 RichText::new(v)
@@ -1566,7 +1775,8 @@ impl SystemState {
             .as_waves()
             .unwrap()
             .variable_meta(variable)
-            .ok()?;
+            .ok()
+            .map(|meta| displayed_variable.translation_meta(meta))?;
         let translator = waves.variable_translator_with_meta(
             &displayed_field_ref.without_field(),
             &self.translators,
@@ -1624,6 +1834,76 @@ impl SystemState {
         }
     }
 
+    /// Time of the next or previous change of the translated value of the field, ignoring
+    /// changes of the raw value that translate to the same value. Moving back from within a
+    /// value, or from the time it starts, gives the start of the value or of the one before it.
+    #[must_use]
+    pub fn value_change_time(
+        &self,
+        waves: &WaveData,
+        displayed_field_ref: &DisplayedFieldRef,
+        cursor: &BigUint,
+        next: bool,
+    ) -> Option<BigUint> {
+        let DisplayedItem::Variable(displayed_variable) =
+            waves.displayed_items.get(&displayed_field_ref.item)?
+        else {
+            return None;
+        };
+        let variable = &displayed_variable.variable_ref;
+        let wave_container = waves.inner.as_waves()?;
+        let meta =
+            displayed_variable.translation_meta(wave_container.variable_meta(variable).ok()?);
+        let translator = waves.variable_translator_with_meta(
+            &displayed_field_ref.without_field(),
+            &self.translators,
+            &meta,
+        );
+        // translated value at `time`, the time its raw value started and the next raw change
+        let value_at = |time: &BigUint| {
+            let result = wave_container.query_variable(variable, time).ok()??;
+            let (start, value) = result.current?;
+            let value = self.translate_query_result(
+                displayed_field_ref,
+                displayed_variable,
+                translator,
+                meta.clone(),
+                value,
+            );
+            Some((value, start, result.next))
+        };
+
+        let (mut value, mut start, mut next_change) = value_at(cursor)?;
+        if next {
+            loop {
+                let time = next_change?;
+                let (new_value, _, new_next_change) = value_at(&time)?;
+                if new_value != value {
+                    return Some(time);
+                }
+                next_change = new_next_change;
+            }
+        }
+
+        if !cursor.is_zero() {
+            let (prev_value, prev_start, _) = value_at(&(cursor - BigUint::one()))?;
+            if prev_value != value {
+                // on the first time of a value, so look for the start of the one before
+                (value, start) = (prev_value, prev_start);
+            }
+        }
+        loop {
+            if start.is_zero() {
+                return Some(start);
+            }
+            let (prev_value, prev_start, _) = value_at(&(&start - BigUint::one()))?;
+            if prev_value != value {
+                return Some(start);
+            }
+            start = prev_start;
+        }
+    }
+
     fn translate_query_result(
         &self,
         displayed_field_ref: &DisplayedFieldRef,
@@ -1644,6 +1924,21 @@ impl SystemState {
             .find(|res| res.names == displayed_field_ref.field)?;
 
         match &subfield.value {
+            Some(value) if displayed_field_ref.field.is_empty() => {
+                let number_notation = translator.number_notation();
+                let value = displayed_variable
+                    .numeric_format
+                    .unwrap_or_else(|| self.numeric_format())
+                    .apply(
+                        displayed_variable.leading_zeros.apply(
+                            value.clone(),
+                            number_notation,
+                            meta.num_bits,
+                        ),
+                        number_notation,
+                    );
+                Some(displayed_variable.with_unit(value).value)
+            }
             Some(TranslatedValue { value, .. }) => Some(value.clone()),
             None => Some("-".to_string()),
         }
@@ -1700,15 +1995,31 @@ impl SystemState {
             .get(&waves.items_tree.get_visible(vidx).unwrap().item_ref)
             .and_then(super::displayed_item::DisplayedItem::background_color)
             .and_then(|color| self.user.config.theme.get_color(color))
-            .unwrap_or_else(|| self.get_default_alternating_background_color(item_count))
+            .unwrap_or_else(|| {
+                self.get_default_alternating_background_color(waves, vidx, item_count)
+            })
     }
 
-    fn get_default_alternating_background_color(&self, item_count: usize) -> Color32 {
+    fn get_default_alternating_background_color(
+        &self,
+        waves: &WaveData,
+        vidx: VisibleItemIndex,
+        item_count: usize,
+    ) -> Color32 {
+        let theme = &self.user.config.theme;
+        if theme.zebra_rows {
+            return if waves.zebra_shading.get(vidx.0).copied().unwrap_or(false) {
+                theme
+                    .canvas_colors
+                    .background
+                    .lerp_to_gamma(theme.canvas_colors.foreground, 0.05)
+            } else {
+                Color32::TRANSPARENT
+            };
+        }
         // Set background color
-        if self.user.config.theme.alt_frequency != 0
-            && (item_count / self.user.config.theme.alt_frequency) % 2 == 1
-        {
-            self.user.config.theme.canvas_colors.alt_background
+        if theme.alt_frequency != 0 && (item_count / theme.alt_frequency) % 2 == 1 {
+            theme.canvas_colors.alt_background
         } else {
             Color32::TRANSPARENT
         }
@@ -1731,6 +2042,58 @@ impl SystemState {
             emath::Align2::CENTER_TOP,
         );
     }
+
+    /// Context menu of the default timeline, acting on `time`, the time that was clicked
+    pub fn ruler_context_menu(
+        &self,
+        ui: &mut Ui,
+        waves: &WaveData,
+        time: &num::BigInt,
+        viewport_idx: usize,
+        msgs: &mut Vec<Message>,
+    ) {
+        if waves.can_add_marker() && ui.button("Add marker here").clicked() {
+            msgs.push(Message::AddMarker {
+                time: time.clone(),
+                name: None,
+                idx: None,
+                move_focus: true,
+            });
+        }
+        if ui.button("Set cursor here").clicked() {
+            msgs.push(Message::CursorSet(time.clone()));
+        }
+        for (label, delta) in [("Zoom in here", 0.5), ("Zoom out here", 2.0)] {
+            if ui.button(label).clicked() {
+                msgs.push(Message::CanvasZoom {
+                    mouse_ptr: Some(time.clone()),
+                    delta,
+                    viewport_idx,
+                });
+            }
+        }
+    }
+}
+
+/// Space in front of the name of an item `level` groups deep, indenting each level by
+/// `indent_px`
+#[must_use]
+pub fn group_indent(level: u8, indent_px: f32) -> f32 {
+    indent_px.max(0.) * f32::from(level)
+}
+
+/// The time at `pos`, in canvas coordinates, if it is on the default timeline of height
+/// `ruler_height` at the top of the canvas.
+#[must_use]
+pub fn ruler_time_at(
+    pos: Pos2,
+    ruler_height: f32,
+    viewport: &Viewport,
+    frame_width: f32,
+    num_timestamps: &num::BigInt,
+) -> Option<num::BigInt> {
+    ((0.0..ruler_height).contains(&pos.y) && (0.0..=frame_width).contains(&pos.x))
+        .then(|| viewport.as_time_bigint(pos.x, frame_width, num_timestamps))
 }
 
 pub fn draw_true_name(
@@ -1854,3 +2217,251 @@ pub fn draw_true_name(
         }
     }
 }
+
+/// Panels next to the waveform view
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ItemPanel {
+    FocusIds,
+    Names,
+    /// Always on the right side of the window
+    TransactionDetails,
+    Values,
+}
+
+/// Order in which the panels next to the waveform view are added. Each panel is placed
+/// between the window edge and the panels added before it, so the values always end up next to
+/// the waveforms, and the transaction details to the right of the names and values.
+fn item_panel_order(side: ItemColumnSide) -> [ItemPanel; 4] {
+    match side {
+        ItemColumnSide::Left => [
+            ItemPanel::FocusIds,
+            ItemPanel::Names,
+            ItemPanel::TransactionDetails,
+            ItemPanel::Values,
+        ],
+        ItemColumnSide::Right => [
+            ItemPanel::TransactionDetails,
+            ItemPanel::FocusIds,
+            ItemPanel::Names,
+            ItemPanel::Values,
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::panel::Side;
+    use egui_skia_renderer::{create_surface, draw_onto_surface};
+    use num::BigInt;
+
+    use super::*;
+    use crate::message::MessageTarget;
+    use crate::setup_custom_font;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+    use crate::wave_container::VariableRefExt;
+
+    #[test]
+    fn ruler_time_at_maps_x_to_time_on_the_ruler_only() {
+        let viewport = Viewport::new();
+        let num_timestamps = BigInt::from(800);
+        let time_at =
+            |x: f32, y: f32| ruler_time_at(Pos2::new(x, y), 16., &viewport, 400., &num_timestamps);
+
+        assert_eq!(time_at(0., 0.), Some(BigInt::from(0)));
+        assert_eq!(time_at(100., 8.), Some(BigInt::from(200)));
+        assert_eq!(time_at(400., 15.), Some(BigInt::from(800)));
+        // Below the ruler, or outside the canvas
+        assert_eq!(time_at(100., 16.), None);
+        assert_eq!(time_at(100., -1.), None);
+        assert_eq!(time_at(401., 8.), None);
+    }
+
+    #[test]
+    fn values_are_next_to_the_waveforms_on_both_sides() {
+        for side in enum_iterator::all::<ItemColumnSide>() {
+            let order = item_panel_order(side);
+            // The last added panel is the innermost one
+            assert_eq!(order.last(), Some(&ItemPanel::Values));
+            let position = |panel| order.iter().position(|p| *p == panel).unwrap();
+            assert_eq!(
+                position(ItemPanel::FocusIds) + 1,
+                position(ItemPanel::Names)
+            );
+        }
+        // The transaction details are on the right of the window, outside of the columns if
+        // they are on the right too
+        assert_eq!(
+            item_panel_order(ItemColumnSide::Right)[0],
+            ItemPanel::TransactionDetails
+        );
+        assert_eq!(ItemColumnSide::Left.panel_side(), Side::Left);
+        assert_eq!(ItemColumnSide::Right.panel_side(), Side::Right);
+    }
+
+    #[test]
+    fn nested_items_are_indented_per_level() {
+        assert_eq!(group_indent(0, 10.), 0.);
+        assert_eq!(group_indent(3, 10.), 30.);
+        assert_eq!(group_indent(3, 4.5), 13.5);
+        assert_eq!(group_indent(2, 0.), 0.);
+        // Negative widths would move nested items out of the column
+        assert_eq!(group_indent(2, -5.), 0.);
+
+        assert_eq!(GroupConnectorStyle::Arrow.icon_width(10.), 10.);
+        assert!(GroupConnectorStyle::Compact.icon_width(10.) < 10.);
+    }
+
+    #[test]
+    fn move_cursor_to_value_change_skips_unchanged_translations() {
+        let mut state = load_counter_vcd_with_variables(&["tb.dut.counter"]);
+        let counter = DisplayedFieldRef::from(DisplayedItemRef(1));
+        // The counter goes 1, 2, 3, 4, 5, 6, 7 at 110, 130, ..., 230, which have 1, 1, 2, 1, 2, 2, 3
+        // bits set
+        state.update(Message::VariableFormatChange(
+            MessageTarget::Explicit(counter.clone()),
+            String::from("Number of ones"),
+        ));
+        state.update(Message::CursorSet(BigInt::from(110)));
+
+        let move_to_change = |state: &mut SystemState, next: bool| {
+            state.update(Message::MoveCursorToValueChange {
+                variable: counter.clone(),
+                next,
+            });
+            state.user.waves.as_ref().unwrap().cursor.clone().unwrap()
+        };
+        assert_eq!(move_to_change(&mut state, true), BigInt::from(150));
+        assert_eq!(move_to_change(&mut state, true), BigInt::from(170));
+        assert_eq!(move_to_change(&mut state, true), BigInt::from(190));
+        assert_eq!(move_to_change(&mut state, true), BigInt::from(230));
+        // From within a value back to its start, and from its start to the start of the one before
+        state.update(Message::CursorSet(BigInt::from(220)));
+        assert_eq!(move_to_change(&mut state, false), BigInt::from(190));
+        state.update(Message::CursorSet(BigInt::from(150)));
+        assert_eq!(move_to_change(&mut state, false), BigInt::from(110));
+
+        // Transitions still stop at every change of the raw value
+        state.update(Message::FocusItem(VisibleItemIndex(0)));
+        state.update(Message::MoveCursorToTransition {
+            next: true,
+            variable: None,
+            skip_zero: false,
+        });
+        assert_eq!(
+            state.user.waves.as_ref().unwrap().cursor,
+            Some(BigInt::from(130))
+        );
+    }
+
+    #[test]
+    fn selected_values_table_lists_selected_variables() {
+        let mut state = load_counter_vcd_with_variables(&["tb.dut.counter", "tb.clk", "tb.reset"]);
+        state.update(Message::AddDivider(Some("divider".to_string()), None));
+        state.update(Message::ItemSelectionClear);
+        state.update(Message::SetItemSelected(VisibleItemIndex(0), true));
+        state.update(Message::SetItemSelected(VisibleItemIndex(2), true));
+        state.update(Message::SetItemSelected(VisibleItemIndex(3), true));
+
+        // No values without a cursor
+        assert_eq!(state.selected_values_table(true), None);
+
+        state.update(Message::CursorSet(BigInt::from(130)));
+        assert_eq!(
+            state.selected_values_table(true).as_deref(),
+            Some("Name\tValue\ncounter\t2\nreset\t0")
+        );
+        assert_eq!(
+            state.selected_values_table(false).as_deref(),
+            Some("counter\t2\nreset\t0")
+        );
+    }
+
+    #[test]
+    fn expand_all_to_depth_opens_limited_levels() {
+        let mut state = load_counter_vcd_with_variables(&["tb.dut.counter"]);
+
+        // None of the built-in translators produce nested fields, so give the variable some
+        match state
+            .user
+            .waves
+            .as_mut()
+            .unwrap()
+            .displayed_items
+            .get_mut(&DisplayedItemRef(1))
+        {
+            Some(DisplayedItem::Variable(variable)) => {
+                variable.info = VariableInfo::Compound {
+                    subfields: vec![
+                        ("a".to_string(), VariableInfo::Bits),
+                        (
+                            "b".to_string(),
+                            VariableInfo::Compound {
+                                subfields: vec![(
+                                    "c".to_string(),
+                                    VariableInfo::Compound {
+                                        subfields: vec![("d".to_string(), VariableInfo::Bool)],
+                                    },
+                                )],
+                            },
+                        ),
+                    ],
+                };
+            }
+            _ => panic!("Expected a variable"),
+        }
+
+        // The fields drawn in the item list after expanding everything `levels` deep
+        let drawn_fields = |state: &mut SystemState, levels| {
+            state.update(Message::ExpandAllToDepth(levels));
+            let mut surface = create_surface((1280, 720));
+            draw_onto_surface(
+                &mut surface,
+                |ctx| {
+                    setup_custom_font(ctx);
+                    state.draw(ctx, Some(Vec2::new(1280., 720.)));
+                },
+                None,
+            );
+            let mut fields = state
+                .user
+                .waves
+                .as_ref()
+                .unwrap()
+                .drawing_infos
+                .iter()
+                .filter_map(|info| match info {
+                    ItemDrawingInfo::Variable(info) => Some(info.field_ref.field.join(".")),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            fields.sort();
+            fields
+        };
+
+        assert_eq!(drawn_fields(&mut state, 0), vec![""]);
+        assert_eq!(drawn_fields(&mut state, 1), vec!["", "a", "b"]);
+        assert_eq!(drawn_fields(&mut state, 2), vec!["", "a", "b", "b.c"]);
+        let all = vec!["", "a", "b", "b.c", "b.c.d"];
+        assert_eq!(drawn_fields(&mut state, 3), all);
+        assert_eq!(drawn_fields(&mut state, 10), all);
+    }
+
+    #[test]
+    fn all_values_table_lists_displayed_variables_in_order() {
+        let mut state = load_counter_vcd_with_variables(&["tb.reset", "tb.dut.counter"]);
+        state.update(Message::AddDivider(Some("divider".to_string()), None));
+        state.update(Message::AddTimeLine(None));
+        state.update(Message::AddVariables(vec![
+            VariableRef::from_hierarchy_string("tb.clk"),
+        ]));
+
+        // No values without a cursor
+        assert_eq!(state.all_values_table(), None);
+
+        state.update(Message::CursorSet(BigInt::from(130)));
+        assert_eq!(
+            state.all_values_table().as_deref(),
+            Some("reset\t0\ncounter\t2\nclk\t1")
+        );
+    }
+}
@@ -4,6 +4,8 @@ use derive_more::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
 use num::{BigInt, BigRational, FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
 
+use crate::config::ReloadViewport;
+
 #[derive(
     Debug,
     Clone,
@@ -119,6 +121,10 @@ pub struct Viewport {
 
     #[serde(skip, default = "default_min_width")]
     min_width: Absolute,
+
+    /// Recenter on the cursor whenever it is outside of the viewport
+    #[serde(default)]
+    pub follow_cursor: bool,
 }
 
 impl Default for Viewport {
@@ -134,6 +140,7 @@ impl Default for Viewport {
             move_strategy: ViewportStrategy::Instant,
             edge_space: default_edge_space(),
             min_width: default_min_width(),
+            follow_cursor: false,
         }
     }
 }
@@ -152,6 +159,21 @@ impl Viewport {
         BigInt::from(self.curr_right.absolute(num_timestamps).0 as i64)
     }
 
+    /// Left and right edge times the viewport shows once its ongoing movement, if any, has
+    /// finished
+    #[must_use]
+    pub fn target_edge_times(&self, num_timestamps: &BigInt) -> (BigInt, BigInt) {
+        let (left, right) = if self.is_moving() {
+            (self.target_left, self.target_right)
+        } else {
+            (self.curr_left, self.curr_right)
+        };
+        (
+            BigInt::from(left.absolute(num_timestamps).0 as i64),
+            BigInt::from(right.absolute(num_timestamps).0 as i64),
+        )
+    }
+
     #[must_use]
     pub fn as_absolute_time(&self, x: f64, view_width: f32, num_timestamps: &BigInt) -> Absolute {
         let time_spacing = self.width_absolute(num_timestamps) / f64::from(view_width);
@@ -210,8 +232,22 @@ impl Viewport {
     /// too short, the viewport will be moved to the left as much as needed for the zoom level.
     #[must_use]
     pub fn clip_to(&self, old_num_timestamps: &BigInt, new_num_timestamps: &BigInt) -> Viewport {
-        let left_timestamp = self.curr_left.absolute(old_num_timestamps);
-        let right_timestamp = self.curr_right.absolute(old_num_timestamps);
+        self.clip_range_to(
+            self.curr_left.absolute(old_num_timestamps),
+            self.curr_right.absolute(old_num_timestamps),
+            new_num_timestamps,
+        )
+    }
+
+    /// Return new viewport showing the time range from `left_timestamp` to `right_timestamp`
+    /// in a file of length `new_num_timestamps`, clipped like [`Viewport::clip_to`]
+    #[must_use]
+    pub fn clip_range_to(
+        &self,
+        left_timestamp: Absolute,
+        right_timestamp: Absolute,
+        new_num_timestamps: &BigInt,
+    ) -> Viewport {
         let absolute_width = right_timestamp - left_timestamp;
 
         let new_absolute_width = new_num_timestamps
@@ -243,6 +279,60 @@ impl Viewport {
             }
         };
 
+        self.with_range(left, right)
+    }
+
+    /// Return new viewport showing the time range from `left` to `right` in a file of length
+    /// `num_timestamps`. The range is kept as-is if it fits in the file, including the empty
+    /// space at the edges, otherwise it is clipped like [`Viewport::clip_to`].
+    #[must_use]
+    pub fn restore_range(
+        &self,
+        left: Absolute,
+        right: Absolute,
+        num_timestamps: &BigInt,
+    ) -> Viewport {
+        let relative_left = left.relative(num_timestamps);
+        let relative_right = right.relative(num_timestamps);
+        if relative_left >= Relative(-self.edge_space)
+            && relative_right <= Relative(1.0 + self.edge_space)
+        {
+            self.with_range(relative_left, relative_right)
+        } else {
+            self.clip_range_to(left, right, num_timestamps)
+        }
+    }
+
+    /// Return new viewport after a reload changed the file length from `old_num_timestamps`
+    /// to `new_num_timestamps`, following the configured `behavior`.
+    #[must_use]
+    pub fn after_reload(
+        &self,
+        old_num_timestamps: &BigInt,
+        new_num_timestamps: &BigInt,
+        behavior: ReloadViewport,
+    ) -> Viewport {
+        match behavior {
+            ReloadViewport::Keep => self.clip_to(old_num_timestamps, new_num_timestamps),
+            ReloadViewport::ZoomToFit => self.with_range(Relative(0.0), Relative(1.0)),
+            ReloadViewport::KeepIfValid => {
+                let new_num_ts_f64 = new_num_timestamps
+                    .to_f64()
+                    .expect("Failed to convert timestamp to f64");
+                let left = Relative(self.curr_left.absolute(old_num_timestamps).0 / new_num_ts_f64);
+                let right =
+                    Relative(self.curr_right.absolute(old_num_timestamps).0 / new_num_ts_f64);
+                if left >= Relative(-self.edge_space) && right <= Relative(1.0 + self.edge_space) {
+                    self.with_range(left, right)
+                } else {
+                    self.with_range(Relative(0.0), Relative(1.0))
+                }
+            }
+        }
+    }
+
+    /// Return a copy of this viewport, settled at the given range
+    fn with_range(&self, left: Relative, right: Relative) -> Viewport {
         Viewport {
             curr_left: left,
             curr_right: right,
@@ -254,6 +344,7 @@ impl Viewport {
             move_strategy: self.move_strategy,
             edge_space: self.edge_space,
             min_width: self.min_width,
+            follow_cursor: self.follow_cursor,
         }
     }
 
@@ -418,6 +509,30 @@ impl Viewport {
         }
     }
 
+    /// Make the cursor visible after it was moved. With `center`, the viewport is centered on
+    /// the cursor even if it already is in view. Returns true if the viewport was moved.
+    pub fn follow_cursor(
+        &mut self,
+        cursor: &BigInt,
+        num_timestamps: &BigInt,
+        center: bool,
+    ) -> bool {
+        if center {
+            self.go_to_time_f64(cursor.into(), num_timestamps);
+            true
+        } else {
+            self.go_to_cursor_if_not_in_view(cursor, num_timestamps)
+        }
+    }
+
+    /// Recenter on `cursor` if this viewport follows the cursor and it is out of view. Does
+    /// nothing while the viewport is moving, to let a previous recentering finish.
+    pub fn keep_cursor_in_view(&mut self, cursor: &BigInt, num_timestamps: &BigInt) -> bool {
+        self.follow_cursor
+            && !self.is_moving()
+            && self.go_to_cursor_if_not_in_view(cursor, num_timestamps)
+    }
+
     pub fn go_to_time_f64(&mut self, center: Absolute, num_timestamps: &BigInt) {
         let half_width = (self.curr_right.absolute(num_timestamps)
             - self.curr_left.absolute(num_timestamps))
@@ -490,6 +605,15 @@ pub fn ease_in_out_size(r: RangeInclusive<f64>, t: f64) -> f64 {
     r.start() + ((r.end() - r.start()) * -((std::f64::consts::PI * t).cos() - 1.) / 2.)
 }
 
+/// Zoom factor of a scroll wheel or pinch gesture after applying the sensitivity and direction
+/// preferences. `delta` is the factor reported by the input, below 1 to zoom in. A
+/// `sensitivity` of 1 keeps it as is, larger values zoom faster and 0 disables zooming.
+#[must_use]
+pub fn scroll_zoom_delta(delta: f32, sensitivity: f32, invert: bool) -> f32 {
+    let delta = if invert { delta.recip() } else { delta };
+    delta.powf(sensitivity.max(0.))
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum ViewportStrategy {
     Instant,
@@ -516,6 +640,22 @@ mod tests {
         assert!((s1 - 10.0).abs() < 1e-12);
     }
 
+    #[test]
+    fn scroll_zoom_delta_applies_sensitivity_and_direction() {
+        // The defaults keep the delta
+        assert!((scroll_zoom_delta(0.8, 1., false) - 0.8).abs() < 1e-6);
+        assert!((scroll_zoom_delta(1., 3., true) - 1.).abs() < 1e-6);
+
+        assert!((scroll_zoom_delta(0.5, 2., false) - 0.25).abs() < 1e-6);
+        assert!((scroll_zoom_delta(0.25, 0.5, false) - 0.5).abs() < 1e-6);
+        assert!((scroll_zoom_delta(0.5, 1., true) - 2.).abs() < 1e-6);
+        assert!((scroll_zoom_delta(0.5, 2., true) - 4.).abs() < 1e-6);
+
+        // No zooming without sensitivity, negative values do not invert
+        assert!((scroll_zoom_delta(0.5, 0., false) - 1.).abs() < 1e-6);
+        assert!((scroll_zoom_delta(0.5, -1., false) - 1.).abs() < 1e-6);
+    }
+
     #[test]
     fn relative_absolute_roundtrip() {
         let n = bi(1000);
@@ -587,6 +727,56 @@ mod tests {
         assert!((vp.curr_right.0 - 0.3).abs() < 1e-6);
     }
 
+    fn viewport_at(left: f64, right: f64) -> Viewport {
+        let mut vp = Viewport::default();
+        vp.curr_left = Relative(left);
+        vp.curr_right = Relative(right);
+        vp.target_left = vp.curr_left;
+        vp.target_right = vp.curr_right;
+        vp
+    }
+
+    #[test]
+    fn after_reload_keep_preserves_absolute_range() {
+        // Viewing timestamps 200-400 in a 1000-timestamp file, file grows to 2000
+        let vp = viewport_at(0.2, 0.4);
+        let reloaded = vp.after_reload(&bi(1000), &bi(2000), ReloadViewport::Keep);
+        assert!((reloaded.curr_left.0 - 0.1).abs() < 1e-9);
+        assert!((reloaded.curr_right.0 - 0.2).abs() < 1e-9);
+
+        // File shrinks so that the range no longer fits, keep the width but move left
+        let reloaded = vp.after_reload(&bi(1000), &bi(500), ReloadViewport::Keep);
+        let width = reloaded.curr_right.0 - reloaded.curr_left.0;
+        assert!((width - 0.4).abs() < 1e-9);
+        assert!(reloaded.curr_right.0 <= 1.0 + vp.edge_space + 1e-9);
+    }
+
+    #[test]
+    fn after_reload_zoom_to_fit_shows_everything() {
+        let vp = viewport_at(0.2, 0.4);
+        for new in [500, 1000, 2000] {
+            let reloaded = vp.after_reload(&bi(1000), &bi(new), ReloadViewport::ZoomToFit);
+            assert!((reloaded.curr_left.0 - 0.0).abs() < 1e-12);
+            assert!((reloaded.curr_right.0 - 1.0).abs() < 1e-12);
+            assert!(!reloaded.is_moving());
+        }
+    }
+
+    #[test]
+    fn after_reload_keep_if_valid() {
+        let vp = viewport_at(0.2, 0.4);
+
+        // Timestamps 200-400 still exist in a 2000-timestamp file, keep them
+        let reloaded = vp.after_reload(&bi(1000), &bi(2000), ReloadViewport::KeepIfValid);
+        assert!((reloaded.curr_left.0 - 0.1).abs() < 1e-9);
+        assert!((reloaded.curr_right.0 - 0.2).abs() < 1e-9);
+
+        // Timestamps 200-400 do not fit in a 250-timestamp file, zoom to fit instead
+        let reloaded = vp.after_reload(&bi(1000), &bi(250), ReloadViewport::KeepIfValid);
+        assert!((reloaded.curr_left.0 - 0.0).abs() < 1e-12);
+        assert!((reloaded.curr_right.0 - 1.0).abs() < 1e-12);
+    }
+
     #[test]
     fn clip_to_does_not_invert_viewport() {
         // Regression test: when clipping viewport to a file with different num_timestamps,
@@ -686,4 +876,45 @@ mod tests {
             actual_width
         );
     }
+
+    #[test]
+    fn follow_cursor_centers_or_nudges() {
+        let n = bi(1000);
+
+        // Cursor in view: only centering moves the viewport
+        let mut vp = viewport_at(0.4, 0.6);
+        assert!(!vp.follow_cursor(&bi(450), &n, false));
+        assert!((vp.curr_left.0 - 0.4).abs() < 1e-9);
+        assert!((vp.curr_right.0 - 0.6).abs() < 1e-9);
+        assert!(vp.follow_cursor(&bi(450), &n, true));
+        assert!((vp.curr_left.0 - 0.35).abs() < 1e-9);
+        assert!((vp.curr_right.0 - 0.55).abs() < 1e-9);
+
+        // Cursor out of view: both center on it
+        for center in [false, true] {
+            let mut vp = viewport_at(0.4, 0.6);
+            assert!(vp.follow_cursor(&bi(700), &n, center));
+            assert!((vp.curr_left.0 - 0.6).abs() < 1e-9);
+            assert!((vp.curr_right.0 - 0.8).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn only_following_viewports_keep_cursor_in_view() {
+        let n = bi(1000);
+        let mut following = viewport_at(0.4, 0.6);
+        following.follow_cursor = true;
+        let mut other = viewport_at(0.4, 0.6);
+
+        // Cursor in view: nothing moves
+        assert!(!following.keep_cursor_in_view(&bi(500), &n));
+
+        // Cursor out of view: only the following viewport recenters
+        assert!(following.keep_cursor_in_view(&bi(700), &n));
+        assert!((following.curr_left.0 - 0.6).abs() < 1e-9);
+        assert!((following.curr_right.0 - 0.8).abs() < 1e-9);
+        assert!(!other.keep_cursor_in_view(&bi(700), &n));
+        assert!((other.curr_left.0 - 0.4).abs() < 1e-9);
+        assert!((other.curr_right.0 - 0.6).abs() < 1e-9);
+    }
 }
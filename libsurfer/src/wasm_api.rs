@@ -116,6 +116,29 @@ impl WebHandle {
             )
             .await
     }
+
+    /// Load a waveform from `url`, replacing the currently loaded one.
+    #[wasm_bindgen]
+    pub fn load_url(&self, url: String) {
+        enqueue_message(Message::load_url(url));
+    }
+
+    /// Add the variable with the full hierarchical `path`, e.g., `top.cpu.clk`.
+    #[wasm_bindgen]
+    pub fn add_variable(&self, path: String) {
+        enqueue_message(Message::add_variable(&path));
+    }
+
+    /// Run one or more commands, separated by `;` or newlines, as in a command file.
+    #[wasm_bindgen]
+    pub fn run_command(&self, command: String) {
+        enqueue_message(Message::run_commands(command));
+    }
+}
+
+fn enqueue_message(message: Message) {
+    block_on(MESSAGE_QUEUE.lock()).push(message);
+    try_repaint();
 }
 
 // NOTE: Remember to add WASM_bindgen'd functions to the exports in Trunk.toml
@@ -125,11 +148,7 @@ pub fn inject_message(message: &str) {
     let deser = serde_json::from_str(message);
 
     match deser {
-        Ok(message) => {
-            block_on(MESSAGE_QUEUE.lock()).push(message);
-
-            try_repaint()
-        }
+        Ok(message) => enqueue_message(message),
         Err(e) => {
             error!("When injecting message {message}:");
             error!(" Injection failed{e:#?}")
@@ -328,7 +347,9 @@ pub async fn handle_wcp_cs_message(message: String) -> Result<(), JsError> {
 
 impl SystemState {
     pub(crate) fn handle_wasm_external_messages(&mut self) {
-        while let Some(msg) = block_on(MESSAGE_QUEUE.lock()).pop() {
+        // Handle messages in the order they were sent, and without holding the lock
+        let messages = std::mem::take(&mut *block_on(MESSAGE_QUEUE.lock()));
+        for msg in messages {
             self.update(msg);
         }
 
@@ -351,6 +372,7 @@ impl StartupParams {
             waves: url.load_url.map(WaveSource::Url),
             wcp_initiate: None,
             startup_commands: url.startup_commands.map(|c| vec![c]).unwrap_or_default(),
+            view_range: None,
         }
     }
 }
@@ -368,3 +390,26 @@ fn vcd_from_url() -> UrlArgs {
             .and_then(|p| p.get("startup_commands")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wave_source::LoadOptions;
+
+    #[test]
+    fn web_handle_enqueues_messages_in_order() {
+        let handle = WebHandle::new();
+        handle.load_url("https://example.com/counter.vcd".to_string());
+        handle.run_command("variable_add top.clk; zoom_fit".to_string());
+
+        let queue = std::mem::take(&mut *block_on(MESSAGE_QUEUE.lock()));
+        assert!(matches!(
+            queue.as_slice(),
+            [
+                Message::LoadWaveformFileFromUrl(url, LoadOptions::Clear),
+                Message::LoadCommandFromData(command),
+            ] if url == "https://example.com/counter.vcd"
+                && command == b"variable_add top.clk; zoom_fit"
+        ));
+    }
+}
@@ -0,0 +1,140 @@
+//! Floating panel with pinned variable values that follow the cursor.
+use egui::{Context, Window};
+use egui_extras::{Column, TableBuilder};
+use num::BigUint;
+use surfer_translation_types::TranslatedValue;
+
+use crate::SystemState;
+use crate::message::Message;
+use crate::translation::TranslationResultExt;
+use crate::wave_container::{VariableRef, VariableRefExt};
+use crate::wave_data::{WaveData, variable_translator};
+
+impl SystemState {
+    /// Values of all watched variables at `cursor`, formatted with their preferred translator.
+    /// Variables without a value at that time, e.g. because they are not loaded yet, get `None`.
+    #[must_use]
+    pub fn watch_list_values(
+        &self,
+        waves: &WaveData,
+        cursor: &BigUint,
+    ) -> Vec<(VariableRef, Option<String>)> {
+        self.watch_list
+            .iter()
+            .map(|variable| {
+                (
+                    variable.clone(),
+                    self.watched_variable_value(waves, variable, cursor),
+                )
+            })
+            .collect()
+    }
+
+    fn watched_variable_value(
+        &self,
+        waves: &WaveData,
+        variable: &VariableRef,
+        cursor: &BigUint,
+    ) -> Option<String> {
+        let wave_container = waves.inner.as_waves()?;
+        let meta = wave_container.variable_meta(variable).ok()?;
+        let translator = variable_translator(None, &[], &self.translators, || Ok(meta.clone()));
+
+        let (_, value) = wave_container
+            .query_variable(variable, cursor)
+            .ok()
+            .flatten()?
+            .current?;
+        let translated = translator.translate(&meta, &value).ok()?;
+        let fields = translated.format_flat(&Some(translator.name()), &[], &self.translators);
+
+        match &fields.iter().find(|res| res.names.is_empty())?.value {
+            Some(TranslatedValue { value, .. }) => Some(value.clone()),
+            None => Some("-".to_string()),
+        }
+    }
+
+    pub fn draw_watch_window(&self, waves: &WaveData, ctx: &Context, msgs: &mut Vec<Message>) {
+        let values = waves
+            .cursor
+            .as_ref()
+            .and_then(|cursor| cursor.to_biguint())
+            .map(|cursor| self.watch_list_values(waves, &cursor))
+            .unwrap_or_else(|| {
+                self.watch_list
+                    .iter()
+                    .map(|variable| (variable.clone(), None))
+                    .collect()
+            });
+
+        Window::new("Watch")
+            .collapsible(true)
+            .resizable(true)
+            .show(ctx, |ui| {
+                let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                TableBuilder::new(ui)
+                    .striped(true)
+                    .columns(Column::auto().resizable(true), 2)
+                    .column(Column::auto())
+                    .auto_shrink(emath::Vec2b::new(false, true))
+                    .body(|mut body| {
+                        for (variable, value) in values {
+                            body.row(row_height, |mut row| {
+                                row.col(|ui| {
+                                    ui.label(variable.full_path_string());
+                                });
+                                row.col(|ui| {
+                                    ui.monospace(value.unwrap_or_else(|| "-".to_string()));
+                                });
+                                row.col(|ui| {
+                                    if ui.small_button("Remove").clicked() {
+                                        msgs.push(Message::RemoveFromWatch(variable));
+                                    }
+                                });
+                            });
+                        }
+                    });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::{load_counter_vcd, wait_for_waves_fully_loaded};
+
+    #[test]
+    fn watch_list_values_at_cursor() {
+        let mut state = load_counter_vcd();
+        let counter = VariableRef::from_hierarchy_string("tb.dut.counter");
+        let clk = VariableRef::from_hierarchy_string("tb.clk");
+        state.update(Message::AddToWatch(counter.clone()));
+        state.update(Message::AddToWatch(clk.clone()));
+        state.update(Message::AddToWatch(counter.clone()));
+        wait_for_waves_fully_loaded(&mut state, 10);
+
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(waves.items_tree.len(), 0);
+        assert_eq!(
+            state.watch_list_values(waves, &BigUint::from(0u32)),
+            vec![
+                (counter.clone(), Some("0".to_string())),
+                (clk.clone(), Some("0".to_string()))
+            ]
+        );
+        assert_eq!(
+            state.watch_list_values(waves, &BigUint::from(140u32)),
+            vec![
+                (counter.clone(), Some("2".to_string())),
+                (clk.clone(), Some("0".to_string()))
+            ]
+        );
+
+        state.update(Message::RemoveFromWatch(counter));
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(
+            state.watch_list_values(waves, &BigUint::from(150u32)),
+            vec![(clk, Some("1".to_string()))]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::sync::Mutex;
 
+use camino::Utf8PathBuf;
 use chrono::prelude::{DateTime, Utc};
 use eyre::{Result, bail};
 use num::BigUint;
@@ -7,6 +8,8 @@ use serde::{Deserialize, Serialize};
 use surfer_translation_types::VariableValue;
 
 use crate::cxxrtl_container::CxxrtlContainer;
+use crate::displayed_item::FixedPointRadix;
+use crate::merged_container::MergedContainer;
 use crate::time::{TimeScale, TimeUnit};
 use crate::wellen::{BodyResult, LoadSignalsCmd, LoadSignalsResult, WellenContainer};
 
@@ -15,8 +18,8 @@ pub type ScopeRef = surfer_translation_types::ScopeRef<ScopeId>;
 pub type VariableRef = surfer_translation_types::VariableRef<VarId, ScopeId>;
 pub type VariableMeta = surfer_translation_types::VariableMeta<VarId, ScopeId>;
 
-/// Cache key for analog signal data: (`signal_id`, `translator_name`)
-pub type AnalogCacheKey = (SignalId, String);
+/// Cache key for analog signal data: (`signal_id`, `translator_name`, `fixed_point_radix`)
+pub type AnalogCacheKey = (SignalId, String, Option<FixedPointRadix>);
 
 #[derive(Debug, Clone)]
 pub enum SimulationStatus {
@@ -54,6 +57,8 @@ pub enum SignalId {
     #[default]
     None,
     Wellen(wellen::SignalRef),
+    /// Signal of the added file with the index in a merged container
+    Merged(usize, wellen::SignalRef),
 }
 
 /// Backend-agnostic enum for accessing signal data.
@@ -297,6 +302,8 @@ pub enum WaveContainer {
     /// a placehodler when serializing and deserializing wave state.
     Empty,
     Cxxrtl(Box<Mutex<CxxrtlContainer>>),
+    /// Several waveform files, combined into one hierarchy
+    Merged(Box<MergedContainer>),
 }
 
 impl WaveContainer {
@@ -327,7 +334,7 @@ impl WaveContainer {
     // Perform tasks that are done on the main thread each frame
     pub fn tick(&self) {
         match self {
-            WaveContainer::Wellen(_) => {}
+            WaveContainer::Wellen(_) | WaveContainer::Merged(_) => {}
             WaveContainer::Empty => {}
             WaveContainer::Cxxrtl(c) => c.lock().unwrap().tick(),
         }
@@ -336,7 +343,7 @@ impl WaveContainer {
     #[must_use]
     pub fn wants_anti_aliasing(&self) -> bool {
         match self {
-            WaveContainer::Wellen(_) => true,
+            WaveContainer::Wellen(_) | WaveContainer::Merged(_) => true,
             WaveContainer::Empty => true,
             // FIXME: Once we do AA on the server side, we can set this to false
             WaveContainer::Cxxrtl(_) => true,
@@ -350,6 +357,7 @@ impl WaveContainer {
     pub fn is_fully_loaded(&self) -> bool {
         match self {
             WaveContainer::Wellen(f) => f.is_fully_loaded(),
+            WaveContainer::Merged(m) => m.is_fully_loaded(),
             WaveContainer::Empty => true,
             WaveContainer::Cxxrtl(_) => true,
         }
@@ -360,6 +368,7 @@ impl WaveContainer {
     pub fn variable_names(&self) -> Vec<String> {
         match self {
             WaveContainer::Wellen(f) => f.variable_names(),
+            WaveContainer::Merged(m) => m.variable_names(),
             WaveContainer::Empty => vec![],
             // I don't know if we can do
             WaveContainer::Cxxrtl(_) => vec![], // FIXME: List variable names
@@ -371,6 +380,7 @@ impl WaveContainer {
     pub fn variables(&self) -> Vec<VariableRef> {
         match self {
             WaveContainer::Wellen(f) => f.variables(),
+            WaveContainer::Merged(m) => m.variables(),
             WaveContainer::Empty => vec![],
             WaveContainer::Cxxrtl(_) => vec![],
         }
@@ -381,6 +391,7 @@ impl WaveContainer {
     pub fn variables_in_scope(&self, scope: &ScopeRef) -> Vec<VariableRef> {
         match self {
             WaveContainer::Wellen(f) => f.variables_in_scope(scope),
+            WaveContainer::Merged(m) => m.variables_in_scope(scope),
             WaveContainer::Empty => vec![],
             WaveContainer::Cxxrtl(c) => c.lock().unwrap().variables_in_module(scope),
         }
@@ -391,6 +402,7 @@ impl WaveContainer {
     pub fn parameters_in_scope(&self, scope: &ScopeRef) -> Vec<VariableRef> {
         match self {
             WaveContainer::Wellen(f) => f.parameters_in_scope(scope),
+            WaveContainer::Merged(m) => m.parameters_in_scope(scope),
             WaveContainer::Empty => vec![],
             // No parameters in Cxxrtl
             WaveContainer::Cxxrtl(_) => vec![],
@@ -402,6 +414,7 @@ impl WaveContainer {
     pub fn no_variables_in_scope(&self, scope: &ScopeRef) -> bool {
         match self {
             WaveContainer::Wellen(f) => f.no_variables_in_scope(scope),
+            WaveContainer::Merged(m) => m.no_variables_in_scope(scope),
             WaveContainer::Empty => true,
             WaveContainer::Cxxrtl(c) => c.lock().unwrap().no_variables_in_module(scope),
         }
@@ -414,6 +427,7 @@ impl WaveContainer {
     ) -> Result<Option<LoadSignalsCmd>> {
         match self {
             WaveContainer::Wellen(f) => f.load_variables(variables),
+            WaveContainer::Merged(m) => m.load_variables(variables),
             WaveContainer::Empty => bail!("Cannot load variables from empty container."),
             WaveContainer::Cxxrtl(c) => {
                 c.get_mut().unwrap().load_variables(variables);
@@ -421,10 +435,23 @@ impl WaveContainer {
             }
         }
     }
+    /// Fetches the variables again from a remote server, replacing the loaded data.
+    pub fn refresh_variables<S: AsRef<VariableRef>, T: Iterator<Item = S>>(
+        &mut self,
+        variables: T,
+    ) -> Result<Option<LoadSignalsCmd>> {
+        match self {
+            WaveContainer::Wellen(f) => f.refresh_variables(variables),
+            WaveContainer::Merged(m) => m.refresh_variables(variables),
+            WaveContainer::Empty => bail!("Cannot refresh variables of empty container."),
+            WaveContainer::Cxxrtl(_) => bail!("Refreshing variables is not supported for Cxxrtl."),
+        }
+    }
     /// Load all the parameters in the design so that the value can be displayed.
     pub fn load_parameters(&mut self) -> Result<Option<LoadSignalsCmd>> {
         match self {
             WaveContainer::Wellen(f) => f.load_all_params(),
+            WaveContainer::Merged(m) => m.load_all_params(),
             WaveContainer::Empty => bail!("Cannot load parameters from empty container."),
             WaveContainer::Cxxrtl(_) => {
                 // Cxxrtl does not deal with parameters
@@ -438,6 +465,7 @@ impl WaveContainer {
     pub fn on_signals_loaded(&mut self, res: LoadSignalsResult) -> Result<Option<LoadSignalsCmd>> {
         match self {
             WaveContainer::Wellen(f) => f.on_signals_loaded(res),
+            WaveContainer::Merged(m) => m.on_signals_loaded(res),
             WaveContainer::Empty => {
                 bail!("on_load_signals should only be called with the wellen backend.")
             }
@@ -450,6 +478,7 @@ impl WaveContainer {
     pub fn variable_meta<'a>(&'a self, variable: &'a VariableRef) -> Result<VariableMeta> {
         match self {
             WaveContainer::Wellen(f) => f.variable_to_meta(variable),
+            WaveContainer::Merged(m) => m.variable_meta(variable),
             WaveContainer::Empty => bail!("Getting meta from empty wave container"),
             WaveContainer::Cxxrtl(c) => c.lock().unwrap().variable_meta(variable),
         }
@@ -465,6 +494,7 @@ impl WaveContainer {
     ) -> Result<Option<QueryResult>> {
         match self {
             WaveContainer::Wellen(f) => f.query_variable(variable, time),
+            WaveContainer::Merged(m) => m.query_variable(variable, time),
             WaveContainer::Empty => bail!("Querying variable from empty wave container"),
             WaveContainer::Cxxrtl(c) => Ok(c.lock().unwrap().query_variable(variable, time)),
         }
@@ -475,6 +505,9 @@ impl WaveContainer {
             (WaveContainer::Wellen(f), SignalId::Wellen(signal_ref)) => {
                 Ok(SignalAccessor::Wellen(f.signal_accessor(signal_ref)?))
             }
+            (WaveContainer::Merged(m), signal_id) => {
+                Ok(SignalAccessor::Wellen(m.signal_accessor(signal_id)?))
+            }
             _ => bail!("Invalid signal accessor combination"),
         }
     }
@@ -482,6 +515,7 @@ impl WaveContainer {
     pub fn signal_id(&self, variable: &VariableRef) -> Result<SignalId> {
         match self {
             WaveContainer::Wellen(f) => Ok(SignalId::Wellen(f.signal_ref(variable)?)),
+            WaveContainer::Merged(m) => m.signal_id(variable),
             WaveContainer::Empty => bail!("No signal data"),
             WaveContainer::Cxxrtl(_) => bail!("Not supported for Cxxrtl yet"),
         }
@@ -494,6 +528,7 @@ impl WaveContainer {
             (WaveContainer::Wellen(f), SignalId::Wellen(signal_ref)) => {
                 f.is_signal_loaded(*signal_ref)
             }
+            (WaveContainer::Merged(m), signal_id) => m.is_signal_loaded(signal_id),
             _ => false,
         }
     }
@@ -503,6 +538,7 @@ impl WaveContainer {
     pub fn update_variable_ref(&self, variable: &VariableRef) -> Option<VariableRef> {
         match self {
             WaveContainer::Wellen(f) => f.update_variable_ref(variable),
+            WaveContainer::Merged(m) => m.update_variable_ref(variable),
             WaveContainer::Empty => None,
             WaveContainer::Cxxrtl(_) => None,
         }
@@ -513,6 +549,7 @@ impl WaveContainer {
     pub fn scope_names(&self) -> Vec<String> {
         match self {
             WaveContainer::Wellen(f) => f.scope_names(),
+            WaveContainer::Merged(m) => m.scope_names(),
             WaveContainer::Empty => vec![],
             WaveContainer::Cxxrtl(c) => c
                 .lock()
@@ -528,6 +565,7 @@ impl WaveContainer {
     pub fn metadata(&self) -> MetaData {
         match self {
             WaveContainer::Wellen(f) => f.metadata(),
+            WaveContainer::Merged(m) => m.metadata(),
             WaveContainer::Empty => MetaData {
                 date: None,
                 version: None,
@@ -550,10 +588,21 @@ impl WaveContainer {
         }
     }
 
+    #[must_use]
+    pub fn header_fields(&self) -> Vec<(&'static str, String)> {
+        match self {
+            WaveContainer::Wellen(f) => f.header_fields(),
+            WaveContainer::Merged(m) => m.header_fields(),
+            WaveContainer::Empty => vec![],
+            WaveContainer::Cxxrtl(_) => vec![],
+        }
+    }
+
     #[must_use]
     pub fn root_scopes(&self) -> Vec<ScopeRef> {
         match self {
             WaveContainer::Wellen(f) => f.root_scopes(),
+            WaveContainer::Merged(m) => m.root_scopes(),
             WaveContainer::Empty => vec![],
             WaveContainer::Cxxrtl(c) => c.lock().unwrap().root_modules(),
         }
@@ -562,6 +611,7 @@ impl WaveContainer {
     pub fn child_scopes(&self, scope: &ScopeRef) -> Result<Vec<ScopeRef>> {
         match self {
             WaveContainer::Wellen(f) => f.child_scopes(scope),
+            WaveContainer::Merged(m) => m.child_scopes(scope),
             WaveContainer::Empty => bail!("Getting child modules from empty wave container"),
             WaveContainer::Cxxrtl(c) => Ok(c.lock().unwrap().child_scopes(scope)),
         }
@@ -571,6 +621,7 @@ impl WaveContainer {
     pub fn max_timestamp(&self) -> Option<BigUint> {
         match self {
             WaveContainer::Wellen(f) => f.max_timestamp(),
+            WaveContainer::Merged(m) => m.max_timestamp(),
             WaveContainer::Empty => None,
             WaveContainer::Cxxrtl(c) => c
                 .lock()
@@ -584,6 +635,7 @@ impl WaveContainer {
     pub fn scope_exists(&self, scope: &ScopeRef) -> bool {
         match self {
             WaveContainer::Wellen(f) => f.scope_exists(scope),
+            WaveContainer::Merged(m) => m.scope_exists(scope),
             WaveContainer::Empty => false,
             WaveContainer::Cxxrtl(c) => c.lock().unwrap().module_exists(scope),
         }
@@ -594,6 +646,7 @@ impl WaveContainer {
     pub fn scope_is_variable(&self, scope: &ScopeRef) -> bool {
         match self {
             WaveContainer::Wellen(f) => f.scope_is_variable(scope),
+            WaveContainer::Merged(m) => m.scope_is_variable(scope),
             WaveContainer::Empty => false,
             WaveContainer::Cxxrtl(_) => false, // TODO: Check if scope is variable
         }
@@ -605,6 +658,7 @@ impl WaveContainer {
     pub fn get_scope_tooltip_data(&self, scope: &ScopeRef) -> String {
         match self {
             WaveContainer::Wellen(f) => f.get_scope_tooltip_data(scope),
+            WaveContainer::Merged(m) => m.get_scope_tooltip_data(scope),
             WaveContainer::Empty => String::new(),
             // FIXME: Tooltip
             WaveContainer::Cxxrtl(_) => String::new(),
@@ -617,6 +671,18 @@ impl WaveContainer {
     pub fn get_scope_type(&self, scope: &ScopeRef) -> Option<wellen::ScopeType> {
         match self {
             WaveContainer::Wellen(f) => f.get_scope_type(scope),
+            WaveContainer::Merged(m) => m.get_scope_type(scope),
+            WaveContainer::Empty | WaveContainer::Cxxrtl(_) => None,
+        }
+    }
+
+    /// Returns the name of the module or entity that the scope is an instance of.
+    /// Returns `None` if the scope has no such type or the backend doesn't provide it
+    #[must_use]
+    pub fn get_scope_component(&self, scope: &ScopeRef) -> Option<String> {
+        match self {
+            WaveContainer::Wellen(f) => f.get_scope_component(scope),
+            WaveContainer::Merged(m) => m.get_scope_component(scope),
             WaveContainer::Empty | WaveContainer::Cxxrtl(_) => None,
         }
     }
@@ -627,7 +693,7 @@ impl WaveContainer {
     #[must_use]
     pub fn simulation_status(&self) -> Option<SimulationStatus> {
         match self {
-            WaveContainer::Wellen(_) => None,
+            WaveContainer::Wellen(_) | WaveContainer::Merged(_) => None,
             WaveContainer::Empty => None,
             WaveContainer::Cxxrtl(c) => c.lock().unwrap().simulation_status(),
         }
@@ -637,7 +703,7 @@ impl WaveContainer {
     /// simulation otherwise does nothing
     pub fn unpause_simulation(&self) {
         match self {
-            WaveContainer::Wellen(_) => {}
+            WaveContainer::Wellen(_) | WaveContainer::Merged(_) => {}
             WaveContainer::Empty => {}
             WaveContainer::Cxxrtl(c) => c.lock().unwrap().unpause(),
         }
@@ -646,7 +712,7 @@ impl WaveContainer {
     /// See [`WaveContainer::unpause_simulation`]
     pub fn pause_simulation(&self) {
         match self {
-            WaveContainer::Wellen(_) => {}
+            WaveContainer::Wellen(_) | WaveContainer::Merged(_) => {}
             WaveContainer::Empty => {}
             WaveContainer::Cxxrtl(c) => c.lock().unwrap().pause(),
         }
@@ -656,6 +722,7 @@ impl WaveContainer {
     pub fn wellen_add_body(&mut self, body: BodyResult) -> Result<Option<LoadSignalsCmd>> {
         match self {
             WaveContainer::Wellen(inner) => inner.add_body(body),
+            WaveContainer::Merged(m) => m.add_body(body),
             _ => {
                 bail!("Should never call this function on a non wellen container!")
             }
@@ -666,6 +733,7 @@ impl WaveContainer {
     pub fn body_loaded(&self) -> bool {
         match self {
             WaveContainer::Wellen(inner) => inner.body_loaded(),
+            WaveContainer::Merged(m) => m.body_loaded(),
             WaveContainer::Empty => true,
             WaveContainer::Cxxrtl(_) => true,
         }
@@ -675,7 +743,49 @@ impl WaveContainer {
     /// Currently only the wellen backend (VCD/FST/GHW) supports analog rendering.
     #[must_use]
     pub fn supports_analog(&self) -> bool {
-        matches!(self, WaveContainer::Wellen(_))
+        matches!(self, WaveContainer::Wellen(_) | WaveContainer::Merged(_))
+    }
+
+    /// Add the waves read from `path` under a new root scope named after the file, or replace
+    /// the waves of a file added before from the same path. Returns the name of the root scope.
+    pub fn merge_file(&mut self, path: Utf8PathBuf, waves: WellenContainer) -> Result<String> {
+        if matches!(self, WaveContainer::Wellen(_)) {
+            let WaveContainer::Wellen(primary) = std::mem::replace(self, WaveContainer::Empty)
+            else {
+                unreachable!()
+            };
+            *self = WaveContainer::Merged(Box::new(MergedContainer::new(*primary)));
+        }
+        match self {
+            WaveContainer::Merged(m) => Ok(m.add_file(path, waves)),
+            WaveContainer::Wellen(_) | WaveContainer::Empty | WaveContainer::Cxxrtl(_) => {
+                bail!("Only waveform files can be merged")
+            }
+        }
+    }
+
+    /// Paths of the files merged with [`WaveContainer::merge_file`]
+    #[must_use]
+    pub fn merged_files(&self) -> Vec<Utf8PathBuf> {
+        match self {
+            WaveContainer::Merged(m) => m.merged_files(),
+            _ => vec![],
+        }
+    }
+
+    /// `new_waves`, loaded to replace this container, with the files that were merged into
+    /// this container added to it
+    #[must_use]
+    pub fn keep_merged_files(&mut self, new_waves: WaveContainer) -> WaveContainer {
+        match (std::mem::replace(self, WaveContainer::Empty), new_waves) {
+            (WaveContainer::Merged(m), WaveContainer::Wellen(primary)) => {
+                WaveContainer::Merged(Box::new(m.with_primary(*primary)))
+            }
+            (old, new_waves) => {
+                *self = old;
+                new_waves
+            }
+        }
     }
 }
 
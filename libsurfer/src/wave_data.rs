@@ -7,19 +7,21 @@ use serde::{Deserialize, Serialize};
 use surfer_translation_types::{TranslationPreference, Translator, VariableValue};
 use tracing::{error, info, warn};
 
+use crate::config::{OnDuplicateAdd, ReloadViewport};
 use crate::data_container::DataContainer;
 use crate::displayed_item::{
     DisplayedDivider, DisplayedFieldRef, DisplayedGroup, DisplayedItem, DisplayedItemRef,
-    DisplayedStream, DisplayedTimeLine, DisplayedVariable,
+    DisplayedStream, DisplayedTimeLine, DisplayedVariable, LeadingZeroMode,
 };
 use crate::displayed_item_tree::{DisplayedItemTree, ItemIndex, TargetPosition, VisibleItemIndex};
+use crate::events::WaveEvent;
 use crate::graphics::{Graphic, GraphicId};
 use crate::transaction_container::{StreamScopeRef, TransactionRef, TransactionStreamRef};
 use crate::transactions::calculate_rows_of_stream;
 use crate::translation::{DynTranslator, TranslatorList, VariableInfoExt};
 use crate::variable_name_type::VariableNameType;
 use crate::view::ItemDrawingInfo;
-use crate::viewport::Viewport;
+use crate::viewport::{Absolute, Viewport};
 use crate::wave_container::{
     AnalogCacheKey, ScopeRef, VariableMeta, VariableRef, VariableRefExt, WaveContainer,
 };
@@ -48,6 +50,60 @@ impl std::fmt::Display for ScopeType {
     }
 }
 
+/// Cursor, markers and viewport ranges in absolute time, kept across a reload
+#[derive(Clone, Debug, PartialEq)]
+pub struct PreservedView {
+    pub cursor: Option<BigInt>,
+    pub markers: HashMap<u8, BigInt>,
+    /// Left and right edge of each viewport
+    pub viewports: Vec<(Absolute, Absolute)>,
+}
+
+impl PreservedView {
+    #[must_use]
+    pub fn capture(
+        cursor: Option<&BigInt>,
+        markers: &HashMap<u8, BigInt>,
+        viewports: &[Viewport],
+        num_timestamps: &BigInt,
+    ) -> Self {
+        PreservedView {
+            cursor: cursor.cloned(),
+            markers: markers.clone(),
+            viewports: viewports
+                .iter()
+                .map(|viewport| {
+                    (
+                        viewport.curr_left.absolute(num_timestamps),
+                        viewport.curr_right.absolute(num_timestamps),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Move the cursor, the markers still present in `markers` and `viewports` back to the
+    /// captured times, clamped to a file of length `num_timestamps`
+    pub fn restore(
+        &self,
+        cursor: &mut Option<BigInt>,
+        markers: &mut HashMap<u8, BigInt>,
+        viewports: &mut [Viewport],
+        num_timestamps: &BigInt,
+    ) {
+        let clamp = |time: &BigInt| time.clone().clamp(BigInt::ZERO, num_timestamps.clone());
+        *cursor = self.cursor.as_ref().map(clamp);
+        for (idx, time) in markers.iter_mut() {
+            if let Some(captured) = self.markers.get(idx) {
+                *time = clamp(captured);
+            }
+        }
+        for (viewport, (left, right)) in viewports.iter_mut().zip(&self.viewports) {
+            *viewport = viewport.restore_range(*left, *right, num_timestamps);
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct WaveData {
     #[serde(skip, default = "DataContainer::__new_empty")]
@@ -63,8 +119,17 @@ pub struct WaveData {
     pub viewports: Vec<Viewport>,
     pub cursor: Option<BigInt>,
     pub markers: HashMap<u8, BigInt>,
+    /// Origin of the times shown relative to the trigger
+    #[serde(default)]
+    pub trigger_time: Option<BigInt>,
+    /// Named times of interest in variables
+    #[serde(default)]
+    pub events: Vec<WaveEvent>,
     pub focused_item: Option<VisibleItemIndex>,
     pub focused_transaction: (Option<TransactionRef>, Option<Transaction>),
+    /// Transactions selected with Ctrl+click, in the order they were selected
+    #[serde(default)]
+    pub selected_transactions: Vec<TransactionRef>,
     pub default_variable_name_type: VariableNameType,
     pub scroll_offset: f32,
     pub display_variable_indices: bool,
@@ -72,10 +137,16 @@ pub struct WaveData {
     /// These are just stored during operation, so no need to serialize
     #[serde(skip)]
     pub drawing_infos: Vec<ItemDrawingInfo>,
+    /// Alternate row shading of the visible items, updated together with `drawing_infos`
+    #[serde(skip)]
+    pub zebra_shading: Vec<bool>,
     #[serde(skip)]
     pub top_item_draw_offset: f32,
     #[serde(skip)]
     pub total_height: f32,
+    /// Only show the focused item, using all of `total_height`
+    #[serde(skip)]
+    pub focused_item_maximized: bool,
     #[serde(skip)]
     pub old_num_timestamps: Option<BigInt>,
     /// Generation counter for analog cache invalidation on waveform reload.
@@ -165,6 +236,10 @@ impl WaveData {
         translators: &TranslatorList,
         keep_unavailable: bool,
     ) -> (WaveData, Option<LoadSignalsCmd>) {
+        let new_waves = match self.inner.as_waves_mut() {
+            Some(old_waves) => Box::new(old_waves.keep_merged_files(*new_waves)),
+            None => new_waves,
+        };
         let active_scope = self.active_scope.take().filter(|m| {
             if let ScopeType::WaveScope(w) = m {
                 new_waves.scope_exists(w)
@@ -192,15 +267,20 @@ impl WaveData {
             viewports: self.viewports,
             cursor: self.cursor.clone(),
             markers: self.markers.clone(),
+            trigger_time: self.trigger_time.clone(),
+            events: self.events,
             focused_item: self.focused_item,
             focused_transaction: self.focused_transaction,
+            selected_transactions: self.selected_transactions,
             default_variable_name_type: self.default_variable_name_type,
             display_variable_indices: self.display_variable_indices,
             scroll_offset: self.scroll_offset,
             drawing_infos: vec![],
+            zebra_shading: vec![],
             top_item_draw_offset: 0.,
             graphics: HashMap::new(),
             total_height: 0.,
+            focused_item_maximized: self.focused_item_maximized,
             old_num_timestamps,
             cache_generation: self.cache_generation + 1, // Invalidate all existing caches
             inflight_caches: HashMap::new(),
@@ -237,6 +317,31 @@ impl WaveData {
         self.load_waves()
     }
 
+    /// Look up the displayed variables again after files have been merged into the wave
+    /// container, and load their signals
+    pub fn update_with_merged_waves(
+        &mut self,
+        translators: &TranslatorList,
+        keep_unavailable: bool,
+    ) -> Option<LoadSignalsCmd> {
+        let waves = self.inner.as_waves()?;
+        let mut items_tree = std::mem::take(&mut self.items_tree);
+        self.displayed_items = Self::update_displayed_items(
+            waves,
+            &self.displayed_items,
+            keep_unavailable,
+            translators,
+            &mut items_tree,
+        );
+        self.items_tree = items_tree;
+        // Signals of replaced files get new data
+        self.cache_generation += 1;
+        self.inflight_caches.clear();
+
+        self.update_metadata(translators);
+        self.load_waves()
+    }
+
     /// Go through all signals and update the metadata for all signals
     ///
     /// Used after loading new waves, signals or switching a bunch of translators
@@ -283,8 +388,8 @@ impl WaveData {
     }
 
     /// Needs to be called after `update_with`, once the new number of timestamps is available in
-    /// the inner `WaveContainer`.
-    pub fn update_viewports(&mut self) {
+    /// the inner `WaveContainer`. `reload_viewport` selects how the viewports are updated.
+    pub fn update_viewports(&mut self, reload_viewport: ReloadViewport) {
         if let Some(old_num_timestamps) = std::mem::take(&mut self.old_num_timestamps) {
             // FIXME: I'm not sure if Defaulting to 1 time step is the right thing to do if we
             // have none, but it does avoid some potentially nasty division by zero problems
@@ -294,14 +399,43 @@ impl WaveData {
                 .unwrap_or_else(BigUint::one)
                 .to_bigint()
                 .unwrap();
-            if new_num_timestamps != old_num_timestamps {
+            if new_num_timestamps != old_num_timestamps
+                || reload_viewport == ReloadViewport::ZoomToFit
+            {
                 for viewport in &mut self.viewports {
-                    *viewport = viewport.clip_to(&old_num_timestamps, &new_num_timestamps);
+                    *viewport = viewport.after_reload(
+                        &old_num_timestamps,
+                        &new_num_timestamps,
+                        reload_viewport,
+                    );
                 }
             }
         }
     }
 
+    /// Cursor, marker and viewport times, to be put back by [`WaveData::restore_view`]
+    #[must_use]
+    pub fn capture_view(&self) -> PreservedView {
+        PreservedView::capture(
+            self.cursor.as_ref(),
+            &self.markers,
+            &self.viewports,
+            &self.safe_num_timestamps(),
+        )
+    }
+
+    /// Move the cursor, markers and viewports back to the times in `view`, clamped to the
+    /// time range of the current waves
+    pub fn restore_view(&mut self, view: &PreservedView) {
+        let num_timestamps = self.safe_num_timestamps();
+        view.restore(
+            &mut self.cursor,
+            &mut self.markers,
+            &mut self.viewports,
+            &num_timestamps,
+        );
+    }
+
     fn update_displayed_items(
         waves: &WaveContainer,
         items: &HashMap<DisplayedItemRef, DisplayedItem>,
@@ -427,6 +561,9 @@ impl WaveData {
         )
     }
 
+    /// Add `variables` as displayed items, returning the references of the added items.
+    /// Variables that are already displayed are handled according to `on_duplicate`.
+    #[allow(clippy::too_many_arguments)]
     pub fn add_variables(
         &mut self,
         translators: &TranslatorList,
@@ -435,6 +572,7 @@ impl WaveData {
         update_display_names: bool,
         ignore_failures: bool,
         variable_name_type: Option<VariableNameType>,
+        on_duplicate: OnDuplicateAdd,
     ) -> (Option<LoadSignalsCmd>, Vec<DisplayedItemRef>) {
         let mut indices = vec![];
         // load variables from waveform
@@ -455,7 +593,26 @@ impl WaveData {
         let mut target_position = target_position
             .or_else(|| self.insert_position(self.focused_item))
             .unwrap_or(self.end_insert_position());
+        let mut focus_existing = None;
         for variable in variables {
+            if on_duplicate != OnDuplicateAdd::Allow
+                && let Some(existing) = self.displayed_variable_item_ref(&variable)
+            {
+                if on_duplicate == OnDuplicateAdd::Focus {
+                    info!(
+                        "{} is already displayed, focusing it",
+                        variable.full_path_string()
+                    );
+                    focus_existing = Some(existing);
+                } else {
+                    info!(
+                        "{} is already displayed, not adding it again",
+                        variable.full_path_string()
+                    );
+                }
+                continue;
+            }
+
             let Ok(meta) = self
                 .inner
                 .as_waves()
@@ -485,6 +642,14 @@ impl WaveData {
                 field_formats: vec![],
                 height_scaling_factor: None,
                 analog: None,
+                assertion: None,
+                unit: None,
+                value_text_align: None,
+                show_interval_labels: false,
+                leading_zeros: LeadingZeroMode::Default,
+                numeric_format: None,
+                fixed_point_radix: None,
+                captured_value: None,
             });
 
             indices.push(self.insert_item(new_variable, Some(target_position), true));
@@ -494,6 +659,13 @@ impl WaveData {
             }
         }
 
+        if let Some(item_ref) = focus_existing {
+            self.focused_item = self
+                .items_tree
+                .iter_visible_extra()
+                .find_map(|info| (info.node.item_ref == item_ref).then_some(info.vidx));
+        }
+
         if update_display_names {
             self.compute_variable_display_names();
         }
@@ -756,6 +928,18 @@ impl WaveData {
         }
     }
 
+    /// The first displayed item showing `variable`, if any
+    fn displayed_variable_item_ref(&self, variable: &VariableRef) -> Option<DisplayedItemRef> {
+        self.items_tree
+            .iter()
+            .find_map(|node| match self.displayed_items.get(&node.item_ref) {
+                Some(DisplayedItem::Variable(displayed)) if displayed.variable_ref == *variable => {
+                    Some(node.item_ref)
+                }
+                _ => None,
+            })
+    }
+
     /// Insert item after item vidx if Some(vidx).
     /// If None, insert in relation to focused item (see [`Self::focused_insert_position()`]).
     /// If nothing is selected, fall back to appending.
@@ -788,14 +972,31 @@ impl WaveData {
     }
 
     pub fn go_to_cursor_if_not_in_view(&mut self) -> bool {
+        self.follow_cursor(false)
+    }
+
+    /// See [`Viewport::follow_cursor`]
+    pub fn follow_cursor(&mut self, center: bool) -> bool {
         if let Some(cursor) = &self.cursor {
             let num_timestamps = self.safe_num_timestamps();
-            self.viewports[0].go_to_cursor_if_not_in_view(cursor, &num_timestamps)
+            self.viewports[0].follow_cursor(cursor, &num_timestamps, center)
         } else {
             false
         }
     }
 
+    /// Recenter the viewports which follow the cursor if it is out of view. Returns true if
+    /// any viewport was moved.
+    pub fn keep_cursor_in_following_viewports(&mut self) -> bool {
+        let Some(cursor) = &self.cursor else {
+            return false;
+        };
+        let num_timestamps = self.safe_num_timestamps();
+        self.viewports.iter_mut().fold(false, |moved, viewport| {
+            viewport.keep_cursor_in_view(cursor, &num_timestamps) || moved
+        })
+    }
+
     #[inline]
     pub fn numbered_marker_location(&self, idx: u8, viewport: &Viewport, view_width: f32) -> f32 {
         viewport.pixel_from_time(
@@ -853,6 +1054,16 @@ impl WaveData {
             .map_or(self.drawing_infos.len() - 1, |(idx, _)| idx)
     }
 
+    /// The focused item, if it is maximized to be the only item shown.
+    #[must_use]
+    pub fn maximized_item(&self) -> Option<VisibleItemIndex> {
+        if !self.focused_item_maximized {
+            return None;
+        }
+        self.focused_item
+            .filter(|vidx| self.items_tree.get_visible(*vidx).is_some())
+    }
+
     /// Find the item at a given y-location.
     #[must_use]
     pub fn get_item_at_y(&self, y: f32) -> Option<VisibleItemIndex> {
@@ -870,7 +1081,15 @@ impl WaveData {
             .enumerate()
             .rev()
             .find(|(_, di)| di.top() <= threshold)
-            .map(|(vidx, _)| VisibleItemIndex(vidx))
+            .map(|(vidx, di)| {
+                // With a maximized item, the drawn items no longer map one-to-one
+                // to the visible items
+                if self.maximized_item().is_some() {
+                    di.vidx()
+                } else {
+                    VisibleItemIndex(vidx)
+                }
+            })
     }
 
     pub fn scroll_to_item(&mut self, idx: usize) {
@@ -898,6 +1117,28 @@ impl WaveData {
         self.scroll_offset = target_scroll.clamp(0.0, max_scroll);
     }
 
+    /// Scroll so that the focused item is in the vertical middle of the waveform view
+    pub fn center_focused_item(&mut self) {
+        let Some(vidx) = self.focused_item else {
+            return;
+        };
+        // Compound variables have one drawing info per field, so the item spans all infos of
+        // its visible item index
+        let (Some((item_top, item_bottom)), Some((first_top, last_bottom))) = (
+            vertical_span(self.drawing_infos.iter().filter(|di| di.vidx() == vidx)),
+            vertical_span(self.drawing_infos.iter()),
+        ) else {
+            return;
+        };
+        self.scroll_offset = centered_scroll_offset(
+            item_top,
+            item_bottom,
+            first_top,
+            last_bottom,
+            self.total_height,
+        );
+    }
+
     /// Set cursor at next (or previous, if `next` is false) transition of `variable`. If `skip_zero` is true,
     /// use the next transition to a non-zero value.
     pub fn set_cursor_at_transition(
@@ -977,11 +1218,87 @@ impl WaveData {
         }
     }
 
+    /// Time of the first or last transition of `variable`. The initial value is not considered
+    /// a transition, so `None` is returned for variables that never change.
+    #[must_use]
+    pub fn signal_edge_time(&self, variable: &VariableRef, first: bool) -> Option<BigInt> {
+        let waves = self.inner.as_waves()?;
+        let first_transition = waves
+            .query_variable(variable, &BigUint::zero())
+            .ok()??
+            .next?;
+        let time = if first {
+            first_transition
+        } else {
+            let end = self.num_timestamps()?.to_biguint()?;
+            waves.query_variable(variable, &end).ok()??.current?.0
+        };
+        time.to_bigint()
+    }
+
+    /// Time at which `variable` first has a non-zero value. Values with bits other than `0` and
+    /// `1`, e.g., `x` or `z`, are skipped unless `unknown_is_nonzero` is set.
+    #[must_use]
+    pub fn first_nonzero_time(
+        &self,
+        variable: &VariableRef,
+        unknown_is_nonzero: bool,
+    ) -> Option<BigInt> {
+        let waves = self.inner.as_waves()?;
+        let mut time = BigUint::zero();
+        loop {
+            let result = waves.query_variable(variable, &time).ok()??;
+            if let Some((change_time, value)) = &result.current {
+                let nonzero = match value.parse_biguint() {
+                    Ok(v) => !v.is_zero(),
+                    Err(_) => unknown_is_nonzero,
+                };
+                if nonzero {
+                    return change_time.to_bigint();
+                }
+            }
+            time = result.next?;
+        }
+    }
+
     pub fn next_displayed_item_ref(&mut self) -> DisplayedItemRef {
         self.display_item_ref_counter += 1;
         self.display_item_ref_counter.into()
     }
 
+    /// Zoom all other viewports to the time range shown by the viewport `source`
+    pub fn sync_viewports(&mut self, source: usize) {
+        let Some(num_timestamps) = self.num_timestamps() else {
+            return;
+        };
+        let Some((left, right)) = self
+            .viewports
+            .get(source)
+            .map(|viewport| viewport.target_edge_times(&num_timestamps))
+        else {
+            return;
+        };
+        for (idx, viewport) in self.viewports.iter_mut().enumerate() {
+            if idx != source {
+                viewport.zoom_to_range(&left, &right, &num_timestamps);
+            }
+        }
+    }
+
+    /// Time range shown by the first viewport, clamped to the waveform
+    #[must_use]
+    pub fn visible_time_range(&self) -> Option<(BigUint, BigUint)> {
+        let num_timestamps = self.num_timestamps()?;
+        let last = num_timestamps.to_biguint()?;
+        let viewport = self.viewports.first()?;
+        let left = viewport.left_edge_time(&num_timestamps);
+        let right = viewport.right_edge_time(&num_timestamps);
+        Some((
+            left.to_biguint().unwrap_or_default().min(last.clone()),
+            right.to_biguint().unwrap_or_default().min(last),
+        ))
+    }
+
     /// Returns the number of timestamps in the current waves. For now, this adjusts the
     /// number of timestamps as returned by wave sources if they specify 0 timestamps. This is
     /// done to avoid having to consider what happens with the viewport.
@@ -1022,12 +1339,13 @@ impl WaveData {
     pub fn build_analog_cache_async(
         &self,
         entry: std::sync::Arc<crate::analog_signal_cache::AnalogCacheEntry>,
-        variable_ref: &VariableRef,
+        variable: &DisplayedVariable,
         translator: crate::translation::AnyTranslator,
         sender: &std::sync::mpsc::Sender<crate::message::Message>,
     ) -> Option<()> {
         let wave_container = self.inner.as_waves()?;
-        let meta = wave_container.variable_meta(variable_ref).ok()?.clone();
+        let meta =
+            variable.translation_meta(wave_container.variable_meta(&variable.variable_ref).ok()?);
 
         let num_timestamps = self.num_timestamps()?.to_u64()?;
 
@@ -1086,3 +1404,392 @@ impl WaveData {
         Some(())
     }
 }
+
+/// Height scaling factor to draw the item at `vidx` with, given that `maximized` is the item
+/// to show on its own in `available_height`, if any. Returns `None` if the item is hidden.
+#[must_use]
+pub fn item_height_scaling_factor(
+    vidx: VisibleItemIndex,
+    height_scaling_factor: f32,
+    maximized: Option<VisibleItemIndex>,
+    available_height: f32,
+    line_height: f32,
+) -> Option<f32> {
+    match maximized {
+        None => Some(height_scaling_factor),
+        // Leave room for the padding added below the last item to not trigger scrolling
+        Some(maximized) if maximized == vidx => {
+            Some((available_height / line_height - 1.0).max(height_scaling_factor))
+        }
+        Some(_) => None,
+    }
+}
+
+/// Top of the highest and bottom of the lowest of `infos`
+fn vertical_span<'a>(infos: impl Iterator<Item = &'a ItemDrawingInfo>) -> Option<(f32, f32)> {
+    infos
+        .map(|di| (di.top(), di.bottom()))
+        .reduce(|(top, bottom), (di_top, di_bottom)| (top.min(di_top), bottom.max(di_bottom)))
+}
+
+/// Scroll offset placing the item spanning `item_top` to `item_bottom` in the middle of a view
+/// of height `view_height`, where the items span `first_top` to `last_bottom`. Clamped so that
+/// the view does not scroll past either end of the list.
+#[must_use]
+pub fn centered_scroll_offset(
+    item_top: f32,
+    item_bottom: f32,
+    first_top: f32,
+    last_bottom: f32,
+    view_height: f32,
+) -> f32 {
+    let max_scroll = (last_bottom - first_top - view_height).max(0.0);
+    let item_center = (item_top + item_bottom) / 2.0 - first_top;
+    (item_center - view_height / 2.0).clamp(0.0, max_scroll)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SystemState;
+    use crate::command_parser::get_parser;
+    use crate::fzcmd::parse_command;
+    use crate::message::CursorStep;
+    use crate::message::Message;
+    use crate::tests::fixtures::load_counter_vcd;
+    use crate::tests::fixtures::load_counter_vcd_with_variables;
+    use crate::translation::all_translators;
+    use surfer_translation_types::VariableEncoding;
+
+    #[test]
+    fn reals_default_to_floating_point_translator() {
+        let meta = VariableMeta {
+            var: VariableRef::from_hierarchy_string("tb.dut.voltage"),
+            num_bits: Some(64),
+            variable_type: None,
+            variable_type_name: None,
+            index: None,
+            direction: None,
+            enum_map: Default::default(),
+            encoding: VariableEncoding::Real,
+        };
+        let translators = all_translators();
+        assert_eq!(
+            translators
+                .get_translator("Engineering notation")
+                .translates(&meta)
+                .unwrap(),
+            TranslationPreference::Yes
+        );
+        assert_eq!(
+            select_preferred_translator(&meta, &translators),
+            "FP: 64-bit IEEE 754"
+        );
+    }
+
+    #[test]
+    fn focused_item_is_centered_within_list_ends() {
+        // Ten items of height 20 starting at y = 100 in a view of height 60
+        let item = |idx: f32| (100.0 + 20.0 * idx, 120.0 + 20.0 * idx);
+
+        let (top, bottom) = item(5.0);
+        assert_eq!(
+            centered_scroll_offset(top, bottom, 100.0, 300.0, 60.0),
+            80.0
+        );
+        let (top, bottom) = item(0.0);
+        assert_eq!(centered_scroll_offset(top, bottom, 100.0, 300.0, 60.0), 0.0);
+        let (top, bottom) = item(9.0);
+        assert_eq!(
+            centered_scroll_offset(top, bottom, 100.0, 300.0, 60.0),
+            140.0
+        );
+        // Larger items are centered around their middle
+        assert_eq!(
+            centered_scroll_offset(180.0, 240.0, 100.0, 300.0, 60.0),
+            80.0
+        );
+        // Nothing to scroll when all items fit
+        assert_eq!(
+            centered_scroll_offset(top, bottom, 100.0, 300.0, 400.0),
+            0.0
+        );
+    }
+
+    #[test]
+    fn all_items_keep_their_height_when_nothing_is_maximized() {
+        assert_eq!(
+            item_height_scaling_factor(VisibleItemIndex(0), 1.0, None, 400.0, 16.0),
+            Some(1.0)
+        );
+        assert_eq!(
+            item_height_scaling_factor(VisibleItemIndex(3), 4.0, None, 400.0, 16.0),
+            Some(4.0)
+        );
+    }
+
+    #[test]
+    fn only_maximized_item_is_drawn_using_available_height() {
+        let maximized = Some(VisibleItemIndex(2));
+        assert_eq!(
+            item_height_scaling_factor(VisibleItemIndex(2), 1.0, maximized, 400.0, 16.0),
+            Some(24.0)
+        );
+        assert_eq!(
+            item_height_scaling_factor(VisibleItemIndex(0), 1.0, maximized, 400.0, 16.0),
+            None
+        );
+        assert_eq!(
+            item_height_scaling_factor(VisibleItemIndex(3), 1.0, maximized, 400.0, 16.0),
+            None
+        );
+    }
+
+    #[test]
+    fn maximized_item_is_never_shrunk() {
+        assert_eq!(
+            item_height_scaling_factor(
+                VisibleItemIndex(1),
+                8.0,
+                Some(VisibleItemIndex(1)),
+                64.0,
+                16.0
+            ),
+            Some(8.0)
+        );
+    }
+
+    #[test]
+    fn preserved_view_is_clamped_to_the_reloaded_range() {
+        let edges = |viewport: &Viewport, num_timestamps: &BigInt| {
+            (
+                viewport.curr_left.absolute(num_timestamps).0.round(),
+                viewport.curr_right.absolute(num_timestamps).0.round(),
+            )
+        };
+        let num_timestamps = BigInt::from(1000);
+        let mut viewport = Viewport::default();
+        viewport.zoom_to_range(&BigInt::from(900), &BigInt::from(980), &num_timestamps);
+        let view = PreservedView::capture(
+            Some(&BigInt::from(950)),
+            &HashMap::from([(0, BigInt::from(100)), (1, BigInt::from(990))]),
+            &[viewport, Viewport::default()],
+            &num_timestamps,
+        );
+
+        // The reloaded file is slightly longer, so everything is kept
+        let longer = BigInt::from(1010);
+        let mut cursor = None;
+        let mut markers = HashMap::from([(0, BigInt::ZERO), (1, BigInt::ZERO)]);
+        let mut viewports = [Viewport::default(), Viewport::default()];
+        view.restore(&mut cursor, &mut markers, &mut viewports, &longer);
+        assert_eq!(cursor, Some(BigInt::from(950)));
+        assert_eq!(markers[&0], BigInt::from(100));
+        assert_eq!(markers[&1], BigInt::from(990));
+        assert_eq!(edges(&viewports[0], &longer), (900., 980.));
+        assert_eq!(edges(&viewports[1], &longer), (0., 1000.));
+
+        // Times past the end of a shorter file are clamped, the zoom level is kept
+        let shorter = BigInt::from(500);
+        let mut markers = HashMap::from([(1, BigInt::ZERO), (2, BigInt::from(5))]);
+        view.restore(&mut cursor, &mut markers, &mut viewports, &shorter);
+        assert_eq!(cursor, Some(BigInt::from(500)));
+        assert_eq!(markers[&1], BigInt::from(500));
+        assert_eq!(markers[&2], BigInt::from(5));
+        assert_eq!(edges(&viewports[0], &shorter), (520., 600.));
+    }
+
+    #[test]
+    fn cursor_to_signal_edge() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk", "tb._tmp"]);
+
+        let mut cursor_after_edge = |first, item| {
+            state.update(Message::CursorToSignalEdge {
+                first,
+                variable: DisplayedFieldRef {
+                    item: DisplayedItemRef(item),
+                    field: vec![],
+                },
+            });
+            state.user.waves.as_ref().unwrap().cursor.clone()
+        };
+        assert_eq!(cursor_after_edge(true, 1), Some(BigInt::from(10)));
+        assert_eq!(cursor_after_edge(false, 1), Some(BigInt::from(800)));
+        // tb._tmp keeps its initial value, so the cursor does not move
+        assert_eq!(cursor_after_edge(true, 2), Some(BigInt::from(800)));
+    }
+
+    #[test]
+    fn go_to_first_nonzero() {
+        let mut state = load_counter_vcd_with_variables(&["tb.dut.counter", "tb._tmp"]);
+
+        state.update(Message::GoToFirstNonZero {
+            variable: DisplayedItemRef(1).into(),
+            unknown_is_nonzero: false,
+        });
+        assert_eq!(
+            state.user.waves.as_ref().unwrap().cursor,
+            Some(BigInt::from(110))
+        );
+
+        // tb._tmp is x all the time, so the cursor does not move
+        state.update(Message::GoToFirstNonZero {
+            variable: DisplayedItemRef(2).into(),
+            unknown_is_nonzero: false,
+        });
+        assert_eq!(
+            state.user.waves.as_ref().unwrap().cursor,
+            Some(BigInt::from(110))
+        );
+
+        // unless x counts as non-zero
+        state.update(Message::GoToFirstNonZero {
+            variable: DisplayedItemRef(2).into(),
+            unknown_is_nonzero: true,
+        });
+        assert_eq!(
+            state.user.waves.as_ref().unwrap().cursor,
+            Some(BigInt::from(0))
+        );
+    }
+
+    #[test]
+    fn adding_displayed_variable_follows_on_duplicate_add() {
+        for on_duplicate in [
+            OnDuplicateAdd::Allow,
+            OnDuplicateAdd::Focus,
+            OnDuplicateAdd::Skip,
+        ] {
+            let mut state = load_counter_vcd();
+            let clk = VariableRef::from_hierarchy_string("tb.clk");
+            let counter = VariableRef::from_hierarchy_string("tb.dut.counter");
+            state.update(Message::AddVariables(vec![clk.clone(), counter.clone()]));
+            state.update(Message::FocusItem(VisibleItemIndex(1)));
+
+            let waves = state.user.waves.as_mut().unwrap();
+            let (_, added) = waves.add_variables(
+                &state.translators,
+                vec![clk.clone()],
+                None,
+                true,
+                false,
+                None,
+                on_duplicate,
+            );
+            match on_duplicate {
+                OnDuplicateAdd::Allow => {
+                    assert_eq!(added.len(), 1);
+                    assert_eq!(waves.items_tree.len(), 3);
+                }
+                OnDuplicateAdd::Focus => {
+                    assert!(added.is_empty());
+                    assert_eq!(waves.items_tree.len(), 2);
+                    assert_eq!(waves.focused_item, Some(VisibleItemIndex(0)));
+                }
+                OnDuplicateAdd::Skip => {
+                    assert!(added.is_empty());
+                    assert_eq!(waves.items_tree.len(), 2);
+                    assert_eq!(waves.focused_item, Some(VisibleItemIndex(1)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn move_cursor_by_transition_and_time_steps() {
+        let mut state = load_counter_vcd_with_variables(&["tb.clk"]);
+        state.update(Message::FocusItem(VisibleItemIndex(0)));
+        state.update(Message::CursorSet(BigInt::from(0)));
+
+        let move_by = |state: &mut SystemState, step: CursorStep| {
+            state.update(Message::MoveCursorBy { step });
+            state.user.waves.as_ref().unwrap().cursor.clone().unwrap()
+        };
+        assert_eq!(
+            move_by(&mut state, CursorStep::Transition { next: true }),
+            BigInt::from(10)
+        );
+        assert_eq!(
+            move_by(&mut state, CursorStep::Time(BigInt::from(3))),
+            BigInt::from(13)
+        );
+        assert_eq!(
+            move_by(&mut state, CursorStep::Transition { next: false }),
+            BigInt::from(10)
+        );
+        // The cursor stays within the waveform
+        assert_eq!(
+            move_by(&mut state, CursorStep::Time(BigInt::from(-100))),
+            BigInt::from(0)
+        );
+
+        // The viewport follows the cursor
+        state.update(Message::ZoomToRange {
+            start: BigInt::from(0),
+            end: BigInt::from(50),
+            viewport_idx: 0,
+        });
+        assert_eq!(
+            move_by(&mut state, CursorStep::Time(BigInt::from(600))),
+            BigInt::from(600)
+        );
+        let waves = state.user.waves.as_ref().unwrap();
+        let num_timestamps = waves.num_timestamps().unwrap();
+        let viewport = waves.viewports[0];
+        assert!(viewport.left_edge_time(&num_timestamps) <= BigInt::from(600));
+        assert!(viewport.right_edge_time(&num_timestamps) >= BigInt::from(600));
+    }
+
+    #[test]
+    fn viewports_can_be_synced_and_linked() {
+        let mut state = load_counter_vcd();
+        state.update(Message::AddViewport);
+
+        let edges = |state: &SystemState, viewport_idx: usize| {
+            let waves = state.user.waves.as_ref().unwrap();
+            let num_timestamps = waves.num_timestamps().unwrap();
+            let viewport = waves.viewports[viewport_idx];
+            (
+                viewport.left_edge_time(&num_timestamps),
+                viewport.right_edge_time(&num_timestamps),
+            )
+        };
+        let full_range = edges(&state, 0);
+
+        state.update(Message::ZoomToRange {
+            start: BigInt::from(100),
+            end: BigInt::from(200),
+            viewport_idx: 1,
+        });
+        assert_eq!(edges(&state, 1), (BigInt::from(100), BigInt::from(200)));
+        assert_eq!(edges(&state, 0), full_range);
+
+        state.update(Message::SyncViewports { source: 1 });
+        assert_eq!(edges(&state, 0), (BigInt::from(100), BigInt::from(200)));
+
+        // Linking aligns all viewports to the first one, after which they move together
+        state.update(Message::ZoomToFit { viewport_idx: 0 });
+        assert_eq!(edges(&state, 1), (BigInt::from(100), BigInt::from(200)));
+        state.update(Message::SetViewportsLinked(true));
+        assert_eq!(edges(&state, 1), full_range);
+
+        state.update(Message::ZoomToRange {
+            start: BigInt::from(200),
+            end: BigInt::from(400),
+            viewport_idx: 1,
+        });
+        assert_eq!(edges(&state, 0), (BigInt::from(200), BigInt::from(400)));
+        state.update(Message::GoToStart { viewport_idx: 0 });
+        assert_eq!(edges(&state, 1), (BigInt::from(0), BigInt::from(200)));
+
+        state.update(Message::SetViewportsLinked(false));
+        state.update(Message::ZoomToFit { viewport_idx: 1 });
+        assert_eq!(edges(&state, 1), full_range);
+        assert_ne!(edges(&state, 0), full_range);
+
+        assert!(matches!(
+            parse_command("viewport_sync 1", get_parser(&state)),
+            Ok(Message::SyncViewports { source: 1 })
+        ));
+    }
+}
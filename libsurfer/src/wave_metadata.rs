@@ -0,0 +1,93 @@
+//! Window with information about the loaded waveform file.
+use egui::{Context, Grid, Window};
+
+use crate::SystemState;
+use crate::message::Message;
+use crate::wave_data::WaveData;
+
+impl WaveData {
+    /// Name and value of everything known about the loaded file, including the header fields
+    /// that the file format provides.
+    #[must_use]
+    pub fn metadata_fields(&self) -> Vec<(&'static str, String)> {
+        let metadata = self.inner.metadata();
+        let mut fields = vec![
+            ("File", self.source.to_string()),
+            ("Format", self.format.to_string()),
+        ];
+        if let Some(waves) = self.inner.as_waves() {
+            fields.extend(waves.header_fields());
+        }
+        fields.push((
+            "Timescale",
+            format!(
+                "{}{}",
+                metadata.timescale.multiplier.unwrap_or(1),
+                metadata.timescale.unit
+            ),
+        ));
+        fields.push((
+            "Top-level scopes",
+            self.inner.root_scopes().len().to_string(),
+        ));
+        fields
+    }
+}
+
+impl SystemState {
+    pub fn draw_wave_metadata_window(
+        &self,
+        waves: &WaveData,
+        ctx: &Context,
+        msgs: &mut Vec<Message>,
+    ) {
+        let mut open = true;
+        Window::new("Waveform information")
+            .collapsible(true)
+            .resizable(true)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                Grid::new("wave_metadata").striped(true).show(ui, |ui| {
+                    for (name, value) in waves.metadata_fields() {
+                        ui.label(name);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+                ui.add_space(15.);
+                ui.vertical_centered(|ui| {
+                    if ui.button("Close").clicked() {
+                        msgs.push(Message::SetWaveMetadataVisible(false));
+                    }
+                });
+            });
+        if !open {
+            msgs.push(Message::SetWaveMetadataVisible(false));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::fixtures::load_example;
+
+    #[test]
+    fn wave_metadata_fields() {
+        let state = load_example("examples/xx_1.vcd");
+
+        let fields = state.user.waves.as_ref().unwrap().metadata_fields();
+        assert_eq!(
+            fields.iter().skip(1).cloned().collect::<Vec<_>>(),
+            vec![
+                ("Format", "VCD".to_string()),
+                ("Date", "Sun 07 Jan 2024 07:36:45 PM CET".to_string()),
+                ("Version", "Generated by VerilatedVcd".to_string()),
+                ("Timescale", "1s".to_string()),
+                ("Top-level scopes", "1".to_string()),
+            ]
+        );
+        assert_eq!(fields[0].0, "File");
+        assert!(fields[0].1.ends_with("xx_1.vcd"));
+    }
+}
@@ -8,6 +8,7 @@ use std::sync::atomic::AtomicU64;
 use crate::async_util::{perform_async_work, perform_work};
 use crate::channels::checked_send;
 use crate::cxxrtl_container::CxxrtlContainer;
+use crate::displayed_item::DisplayedItem;
 use crate::file_dialog::OpenMode;
 use crate::remote::{get_hierarchy_from_server, get_server_status, server_reload};
 use crate::transactions::TRANSACTIONS_FILE_EXTENSION;
@@ -25,7 +26,7 @@ use web_time::Instant;
 use crate::transaction_container::TransactionContainer;
 use crate::wave_container::WaveContainer;
 use crate::wellen::{
-    BodyResult, HeaderResult, LoadSignalPayload, LoadSignalsCmd, LoadSignalsResult,
+    BodyResult, HeaderResult, LoadSignalPayload, LoadSignalsCmd, LoadSignalsResult, WellenContainer,
 };
 use crate::{SystemState, message::Message};
 use surver::{
@@ -57,6 +58,19 @@ pub enum WaveSource {
 
 pub const STATE_FILE_EXTENSION: &str = "surf.ron";
 
+/// Path of the state file for the waveform at `wave` according to `pattern`, see
+/// [`crate::config::SurferConfig::sibling_state_file_pattern`].
+#[must_use]
+pub fn sibling_state_file_path(wave: &Utf8Path, pattern: &str) -> Option<Utf8PathBuf> {
+    let name = wave.file_name()?;
+    let stem = wave.file_stem()?;
+    let state_file = pattern.replace("{name}", name).replace("{stem}", stem);
+    if state_file.is_empty() {
+        return None;
+    }
+    Some(wave.parent()?.join(state_file))
+}
+
 impl WaveSource {
     #[must_use]
     pub fn as_file(&self) -> Option<&Utf8Path> {
@@ -75,9 +89,43 @@ impl WaveSource {
         }
     }
 
+    /// Path or URL the waveform was loaded from, `None` for sources without one
+    #[must_use]
+    pub fn copyable_location(&self) -> Option<String> {
+        match self {
+            WaveSource::File(path) | WaveSource::DragAndDrop(Some(path)) => Some(path.to_string()),
+            WaveSource::Url(url) => Some(url.clone()),
+            WaveSource::Cxxrtl(CxxrtlKind::Tcp { .. }) => Some(self.to_string()),
+            WaveSource::Data
+            | WaveSource::DragAndDrop(None)
+            | WaveSource::Cxxrtl(CxxrtlKind::Mailbox) => None,
+        }
+    }
+
+    /// Description of the source of at most `max_chars` characters. Long descriptions keep
+    /// their end, the file name, and start with an ellipsis.
     #[must_use]
-    pub fn sibling_state_file(&self) -> Option<Utf8PathBuf> {
+    pub fn short_description(&self, max_chars: usize) -> String {
+        let description = self.to_string();
+        let len = description.chars().count();
+        if len <= max_chars {
+            return description;
+        }
+        let tail: String = description
+            .chars()
+            .skip(len + 1 - max_chars.max(1))
+            .collect();
+        format!("…{tail}")
+    }
+
+    /// State file next to the waveform, named according to `pattern` or any state file in
+    /// the same directory if `pattern` is empty.
+    #[must_use]
+    pub fn sibling_state_file(&self, pattern: &str) -> Option<Utf8PathBuf> {
         let path = self.path()?;
+        if !pattern.is_empty() {
+            return sibling_state_file_path(path, pattern).filter(|path| path.is_file());
+        }
         let directory = path.parent()?;
         let paths = fs::read_dir(directory).ok()?;
 
@@ -223,6 +271,7 @@ impl SystemState {
         filename: Utf8PathBuf,
         load_options: LoadOptions,
     ) -> Result<()> {
+        self.push_recent_file(&filename);
         match get_multi_extension(&filename) {
             Some(ext) => match ext.as_str() {
                 STATE_FILE_EXTENSION => {
@@ -288,11 +337,61 @@ impl SystemState {
         Ok(())
     }
 
+    /// Read the header and body of the waveform in `filename` in the background, to be merged
+    /// into the loaded waveform once done
+    pub fn load_additional_file(&mut self, filename: Utf8PathBuf, load_options: LoadOptions) {
+        info!("Loading a waveform file to merge: {filename}");
+        let sender = self.channels.msg_sender.clone();
+
+        perform_work(move || {
+            let waves = wellen::viewers::read_header_from_file(
+                filename.as_str(),
+                &WELLEN_SURFER_DEFAULT_OPTIONS,
+            )
+            .and_then(|header| {
+                let hierarchy = Arc::new(header.hierarchy);
+                let body = wellen::viewers::read_body(header.body, &hierarchy, None)?;
+                Ok((hierarchy, body))
+            })
+            .map_err(|e| anyhow!("{e:?}"))
+            .and_then(|(hierarchy, body)| {
+                let mut waves = WellenContainer::new(hierarchy, None);
+                waves.add_body(BodyResult::Local(body))?;
+                Ok(waves)
+            })
+            .with_context(|| format!("Failed to parse wave file: {filename}"));
+
+            let msg = match waves {
+                Ok(waves) => {
+                    Message::AdditionalWavesLoaded(filename, load_options, Box::new(waves))
+                }
+                Err(e) => Message::Error(e),
+            };
+            checked_send(&sender, msg);
+        });
+    }
+
     pub fn load_from_data(&mut self, data: Vec<u8>, load_options: LoadOptions) -> Result<()> {
         self.load_from_bytes(WaveSource::Data, data, load_options);
         Ok(())
     }
 
+    /// Load several files dropped at once. The file picked by [`primary_dropped_file`] is
+    /// loaded, and the other waveforms are merged into it once it has been loaded.
+    pub fn load_from_dropped_files(&mut self, mut files: Vec<egui::DroppedFile>) -> Result<()> {
+        let primary = primary_dropped_file(&files)
+            .ok_or_else(|| anyhow!("Got a drop event without files"))?;
+        let file = files.remove(primary);
+        self.load_from_dropped(file)?;
+        for path in mergeable_dropped_files(&files) {
+            self.add_batch_message(Message::LoadAdditionalFile(
+                path,
+                LoadOptions::KeepAvailable,
+            ));
+        }
+        Ok(())
+    }
+
     pub fn load_from_dropped(&mut self, file: egui::DroppedFile) -> Result<()> {
         info!("Got a dropped file");
 
@@ -346,6 +445,12 @@ impl SystemState {
         }
     }
 
+    /// Bearer token set for `url` with [`Message::SetUrlToken`], if any
+    #[must_use]
+    pub fn url_token(&self, url: &str) -> Option<String> {
+        self.url_tokens.get(url).cloned()
+    }
+
     pub fn load_wave_from_url(
         &mut self,
         url: String,
@@ -365,11 +470,10 @@ impl SystemState {
                 let sender = self.channels.msg_sender.clone();
                 let url_ = url.clone();
                 let file_index = self.user.selected_server_file_index;
+                let token = self.url_token(&url);
                 info!("Loading wave from url: {url}");
                 perform_async_work(async move {
-                    let maybe_response = reqwest::get(&url)
-                        .map(|e| e.with_context(|| format!("Failed fetch download {url}")))
-                        .await;
+                    let maybe_response = fetch_url(&url, token.as_deref()).await;
                     let response: reqwest::Response = match maybe_response {
                         Ok(r) => r,
                         Err(e) => {
@@ -655,6 +759,31 @@ impl SystemState {
         )));
     }
 
+    /// Command fetching the signals of the displayed variables from the server again. Signals
+    /// that are loaded but not displayed are left alone.
+    pub fn refresh_displayed_signals_cmd(&mut self) -> Option<LoadSignalsCmd> {
+        let waves = self.user.waves.as_mut()?;
+        let variables = waves
+            .items_tree
+            .iter()
+            .filter_map(|node| match waves.displayed_items.get(&node.item_ref) {
+                Some(DisplayedItem::Variable(variable)) => Some(variable.variable_ref.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        match waves
+            .inner
+            .as_waves_mut()?
+            .refresh_variables(variables.iter())
+        {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                warn!("{e:#}");
+                None
+            }
+        }
+    }
+
     pub fn load_variables(&mut self, cmd: LoadSignalsCmd) {
         let (signals, from_unique_id, payload) = cmd.destruct();
         if signals.is_empty() {
@@ -750,3 +879,316 @@ pub fn draw_progress_information(ui: &mut egui::Ui, progress_data: &LoadProgress
         }
     }
 }
+
+fn dropped_file_name(file: &egui::DroppedFile) -> Utf8PathBuf {
+    file.path
+        .clone()
+        .and_then(|path| Utf8PathBuf::try_from(path).ok())
+        .unwrap_or_else(|| Utf8PathBuf::from(&file.name))
+}
+
+/// Paths of the dropped `files` that can be merged into the loaded waveform. Only waveforms
+/// dropped with a path can be merged, the other files are skipped with a warning.
+fn mergeable_dropped_files(files: &[egui::DroppedFile]) -> Vec<Utf8PathBuf> {
+    files
+        .iter()
+        .filter_map(|file| {
+            let name = dropped_file_name(file);
+            if get_multi_extension(&name).as_deref() == Some(STATE_FILE_EXTENSION) {
+                warn!("Only one state file can be loaded, not loading dropped file {name}");
+                None
+            } else if file.path.is_none() {
+                warn!("Cannot merge dropped file {name}, it has no path");
+                None
+            } else {
+                Some(name)
+            }
+        })
+        .collect()
+}
+
+/// Index of the file to load when several files are dropped at once. A state file is preferred
+/// since it refers to its own waveform, otherwise the first dropped file is used.
+#[must_use]
+pub fn primary_dropped_file(files: &[egui::DroppedFile]) -> Option<usize> {
+    files
+        .iter()
+        .position(|file| {
+            get_multi_extension(&dropped_file_name(file)).as_deref() == Some(STATE_FILE_EXTENSION)
+        })
+        .or_else(|| (!files.is_empty()).then_some(0))
+}
+
+/// GET `url`, sending `token` as bearer token if given. An unauthorized response is
+/// returned as an error.
+pub(crate) async fn fetch_url(url: &str, token: Option<&str>) -> Result<reqwest::Response> {
+    let mut request = reqwest::Client::new().get(url);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("Failed fetch download {url}"))?;
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(if token.is_some() {
+            anyhow!("The bearer token for {url} was not accepted")
+        } else {
+            anyhow!("{url} requires authorization. Give a bearer token when opening the URL.")
+        });
+    }
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use project_root::get_project_root;
+
+    use super::*;
+    use crate::StartupParams;
+    use crate::config::AutoLoad;
+    use crate::tests::fixtures::wait_for_waves_fully_loaded;
+
+    fn dropped(name: &str, with_path: bool) -> egui::DroppedFile {
+        egui::DroppedFile {
+            path: with_path.then(|| format!("/waves/{name}").into()),
+            name: if with_path {
+                String::new()
+            } else {
+                name.to_string()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn first_dropped_waveform_is_primary() {
+        let files = [dropped("a.vcd", true), dropped("b.fst", true)];
+        assert_eq!(primary_dropped_file(&files), Some(0));
+    }
+
+    #[test]
+    fn dropped_state_file_is_primary() {
+        let files = [
+            dropped("a.vcd", true),
+            dropped("b.ghw", false),
+            dropped(&format!("view.{STATE_FILE_EXTENSION}"), false),
+        ];
+        assert_eq!(primary_dropped_file(&files), Some(2));
+    }
+
+    #[test]
+    fn sources_are_described_and_copied() {
+        let cases = [
+            (
+                WaveSource::File("/waves/counter.vcd".into()),
+                "/waves/counter.vcd",
+                Some("/waves/counter.vcd"),
+            ),
+            (WaveSource::Data, "File data", None),
+            (WaveSource::DragAndDrop(None), "Dropped file", None),
+            (
+                WaveSource::DragAndDrop(Some("/waves/a.fst".into())),
+                "Dropped file (/waves/a.fst)",
+                Some("/waves/a.fst"),
+            ),
+            (
+                WaveSource::Url("https://example.com/a.vcd".to_string()),
+                "https://example.com/a.vcd",
+                Some("https://example.com/a.vcd"),
+            ),
+            (
+                WaveSource::Cxxrtl(CxxrtlKind::Tcp {
+                    url: "localhost:6618".to_string(),
+                }),
+                "cxxrtl+tcp://localhost:6618",
+                Some("cxxrtl+tcp://localhost:6618"),
+            ),
+            (
+                WaveSource::Cxxrtl(CxxrtlKind::Mailbox),
+                "cxxrtl mailbox",
+                None,
+            ),
+        ];
+        for (source, description, location) in cases {
+            assert_eq!(source.short_description(60), description);
+            assert_eq!(source.copyable_location().as_deref(), location);
+        }
+    }
+
+    #[test]
+    fn long_source_descriptions_keep_their_end() {
+        let source = WaveSource::File("/home/user/project/build/waves/counter.vcd".into());
+        assert_eq!(source.short_description(16), "…ves/counter.vcd");
+        assert_eq!(source.short_description(16).chars().count(), 16);
+        assert_eq!(
+            source.copyable_location().as_deref(),
+            Some("/home/user/project/build/waves/counter.vcd")
+        );
+    }
+
+    #[test]
+    fn dropped_waveforms_with_paths_are_merged() {
+        let files = [
+            dropped("b.fst", true),
+            dropped("c.vcd", false),
+            dropped(&format!("view.{STATE_FILE_EXTENSION}"), true),
+            dropped("d.ghw", true),
+        ];
+        assert_eq!(
+            mergeable_dropped_files(&files),
+            vec![
+                Utf8PathBuf::from("/waves/b.fst"),
+                Utf8PathBuf::from("/waves/d.ghw")
+            ]
+        );
+    }
+
+    #[test]
+    fn no_dropped_files_has_no_primary() {
+        assert_eq!(primary_dropped_file(&[]), None);
+    }
+
+    #[test]
+    fn sibling_state_file_path_follows_pattern() {
+        let wave = Utf8Path::new("/waves/counter.vcd");
+        let sibling = |pattern| sibling_state_file_path(wave, pattern);
+        assert_eq!(
+            sibling("{name}.surf.ron"),
+            Some(Utf8PathBuf::from("/waves/counter.vcd.surf.ron"))
+        );
+        assert_eq!(
+            sibling("{stem}.surf.ron"),
+            Some(Utf8PathBuf::from("/waves/counter.surf.ron"))
+        );
+        assert_eq!(
+            sibling("state/{stem}_view.surf.ron"),
+            Some(Utf8PathBuf::from("/waves/state/counter_view.surf.ron"))
+        );
+        assert_eq!(
+            sibling("view.surf.ron"),
+            Some(Utf8PathBuf::from("/waves/view.surf.ron"))
+        );
+        assert_eq!(sibling(""), None);
+        assert_eq!(
+            sibling_state_file_path(Utf8Path::new("/waves/dump.fst.gz"), "{stem}.surf.ron"),
+            Some(Utf8PathBuf::from("/waves/dump.fst.surf.ron"))
+        );
+    }
+
+    /// Serve one request on a local port with `status`, returning the URL and the request
+    /// head as received by the mock server
+    fn mock_server(status: &'static str) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/counter.vcd", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut head = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line.trim().is_empty() {
+                    break;
+                }
+                head.push_str(&line);
+            }
+            write!(
+                stream,
+                "HTTP/1.1 {status}\r\nContent-Length: 4\r\nConnection: close\r\n\r\nwave"
+            )
+            .unwrap();
+            head
+        });
+        (url, server)
+    }
+
+    fn fetch(url: &str, token: Option<&str>) -> Result<String> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let response = fetch_url(url, token).await?;
+            Ok(response.text().await?)
+        })
+    }
+
+    #[test]
+    fn bearer_token_is_sent_when_set() {
+        let (url, server) = mock_server("200 OK");
+        assert_eq!(fetch(&url, Some("s3cr3t")).unwrap(), "wave");
+        let head = server.join().unwrap().to_lowercase();
+        assert!(head.contains("authorization: bearer s3cr3t"), "{head}");
+
+        let (url, server) = mock_server("200 OK");
+        assert_eq!(fetch(&url, None).unwrap(), "wave");
+        let head = server.join().unwrap().to_lowercase();
+        assert!(!head.contains("authorization"), "{head}");
+    }
+
+    #[test]
+    fn unauthorized_response_is_an_error() {
+        let (url, server) = mock_server("401 Unauthorized");
+        let error = fetch(&url, None).unwrap_err().to_string();
+        server.join().unwrap();
+        assert!(error.contains("requires authorization"), "{error}");
+
+        let (url, server) = mock_server("401 Unauthorized");
+        let error = fetch(&url, Some("expired")).unwrap_err().to_string();
+        server.join().unwrap();
+        assert!(error.contains("was not accepted"), "{error}");
+    }
+
+    #[test]
+    fn sibling_state_file_follows_pattern_and_always_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let wave = dir.path().join("counter.vcd");
+        std::fs::copy(
+            get_project_root().unwrap().join("examples/counter.vcd"),
+            &wave,
+        )
+        .unwrap();
+        let encoded = SystemState::new_default_config()
+            .unwrap()
+            .with_params(StartupParams::default())
+            .encode_state()
+            .unwrap();
+        let sibling = dir.path().join("counter.vcd.surf.ron");
+        std::fs::write(&sibling, &encoded).unwrap();
+        // Not matching the pattern, so not picked up
+        std::fs::write(dir.path().join("other.surf.ron"), &encoded).unwrap();
+
+        let mut state = SystemState::new_default_config().unwrap();
+        state.user.config.sibling_state_file_pattern = "{name}.surf.ron".to_string();
+        let mut state = state.with_params(StartupParams {
+            waves: Some(WaveSource::File(wave.try_into().unwrap())),
+            ..Default::default()
+        });
+        wait_for_waves_fully_loaded(&mut state, 10);
+
+        // The default configuration asks before loading
+        assert!(state.user.show_open_sibling_state_file_suggestion.is_some());
+        let waves = state.user.waves.as_ref().unwrap();
+        assert_eq!(
+            waves.source.sibling_state_file("{name}.surf.ron"),
+            Some(sibling.clone().try_into().unwrap())
+        );
+        assert_eq!(waves.source.sibling_state_file("{stem}.surf.ron"), None);
+
+        state.user.show_open_sibling_state_file_suggestion = None;
+        state.user.autoload_sibling_state_files = Some(AutoLoad::Always);
+        while state.channels.msg_receiver.try_recv().is_ok() {}
+        state.update(Message::SuggestOpenSiblingStateFile);
+        assert!(state.user.show_open_sibling_state_file_suggestion.is_none());
+        let msg = state.channels.msg_receiver.try_recv().unwrap();
+        assert!(matches!(msg, Message::LoadState(_, Some(path)) if path == sibling));
+
+        // Nothing is loaded if no file matches the pattern
+        state.user.config.sibling_state_file_pattern = "{stem}.surf.ron".to_string();
+        state.update(Message::SuggestOpenSiblingStateFile);
+        assert!(state.channels.msg_receiver.try_recv().is_err());
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
     SystemState, WcpClientCapabilities,
+    config::OnDuplicateAdd,
     displayed_item::{DisplayedItem, DisplayedItemRef},
     message::{Message, MessageTarget},
+    time::{parse_time, time_string},
     wave_container::{ScopeRefExt, VariableRef, VariableRefExt},
     wave_data::WaveData,
     wave_source::{LoadOptions, WaveSource, string_to_wavesource},
@@ -9,6 +11,7 @@ use crate::{
 
 use futures::executor::block_on;
 use itertools::Itertools;
+use num::BigInt;
 use std::sync::atomic::Ordering;
 use surfer_translation_types::ScopeRef;
 use tracing::{trace, warn};
@@ -135,6 +138,7 @@ impl SystemState {
                                 true,
                                 false,
                                 None,
+                                OnDuplicateAdd::Allow,
                             );
                             if let Some(cmd) = cmd {
                                 self.load_variables(cmd);
@@ -165,6 +169,7 @@ impl SystemState {
                                 true,
                                 false,
                                 None,
+                                OnDuplicateAdd::Allow,
                             );
                             if let Some(cmd) = cmd {
                                 self.load_variables(cmd);
@@ -199,6 +204,7 @@ impl SystemState {
                                 true,
                                 true,
                                 None,
+                                OnDuplicateAdd::Allow,
                             );
                             if let Some(cmd) = cmd {
                                 self.load_variables(cmd);
@@ -344,10 +350,51 @@ impl SystemState {
                         });
                         self.send_response(WcpResponse::ack);
                     }
-                    WcpCommand::set_cursor { timestamp } => {
-                        self.update(Message::CursorSet(timestamp.to_owned()));
+                    WcpCommand::set_cursor {
+                        timestamp,
+                        time,
+                        scroll,
+                    } => {
+                        let Some(waves) = &self.user.waves else {
+                            self.send_error("set_cursor", vec![], "No waveform loaded");
+                            return;
+                        };
+                        let Some(num_timestamps) = waves.num_timestamps() else {
+                            self.send_error("set_cursor", vec![], "No waveform loaded");
+                            return;
+                        };
+                        let time = time.clone().unwrap_or_else(|| timestamp.to_string());
+                        let Some(timestamp) = parse_time(&time, &waves.inner.metadata().timescale)
+                        else {
+                            self.send_error(
+                                "set_cursor",
+                                vec![time.clone()],
+                                &format!("Cannot parse time {time:?}"),
+                            );
+                            return;
+                        };
+                        let timestamp = timestamp.clamp(BigInt::ZERO, num_timestamps);
+                        self.update(Message::CursorSet(timestamp));
+                        if *scroll {
+                            self.update(Message::GoToCursorIfNotInView);
+                        }
                         self.send_response(WcpResponse::ack);
                     }
+                    WcpCommand::get_cursor => {
+                        let Some(waves) = &self.user.waves else {
+                            self.send_error("get_cursor", vec![], "No waveform loaded");
+                            return;
+                        };
+                        let time = waves.cursor.as_ref().map(|cursor| {
+                            time_string(
+                                cursor,
+                                &waves.inner.metadata().timescale,
+                                &self.user.wanted_timeunit,
+                                &self.get_time_format(),
+                            )
+                        });
+                        self.send_response(WcpResponse::get_cursor { time });
+                    }
                     WcpCommand::shutdown => {
                         warn!("WCP Shutdown message should not reach this place");
                     }
@@ -400,6 +447,7 @@ impl SystemState {
             "zoom_to_fit",
             "add_markers",
             "set_viewport_range_to",
+            "get_cursor",
         ]
         .into_iter()
         .map(str::to_string)
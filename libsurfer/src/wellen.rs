@@ -30,6 +30,9 @@ pub struct WellenContainer {
     hierarchy: std::sync::Arc<Hierarchy>,
     /// the url of a remote server, None if waveforms are loaded locally
     server: Option<String>,
+    /// true if signals are loaded from a remote server, also while `server` is taken by a
+    /// pending load
+    remote: bool,
     scopes: Vec<String>,
     vars: Vec<String>,
     varrefs: Vec<VariableRef>,
@@ -73,6 +76,17 @@ pub enum LoadSignalPayload {
 }
 
 impl LoadSignalsCmd {
+    #[must_use]
+    pub fn signals(&self) -> &[SignalRef] {
+        &self.signals
+    }
+
+    /// Id of the container the signals are loaded for
+    #[must_use]
+    pub fn from_unique_id(&self) -> u64 {
+        self.from_unique_id
+    }
+
     #[must_use]
     pub fn destruct(self) -> (Vec<SignalRef>, u64, LoadSignalPayload) {
         (self.signals, self.from_unique_id, self.payload)
@@ -111,6 +125,12 @@ impl LoadSignalsResult {
         }
     }
 
+    /// Id of the container the signals were loaded for
+    #[must_use]
+    pub fn from_unique_id(&self) -> u64 {
+        self.from_unique_id
+    }
+
     #[must_use]
     pub fn len(&self) -> usize {
         self.signals.len()
@@ -169,6 +189,7 @@ impl WellenContainer {
 
         Self {
             hierarchy,
+            remote: server.is_some(),
             server,
             scopes,
             vars,
@@ -187,6 +208,12 @@ impl WellenContainer {
         self.body_loaded
     }
 
+    /// Id telling the signals loaded for this container apart from those of other files
+    #[must_use]
+    pub fn unique_id(&self) -> u64 {
+        self.unique_id
+    }
+
     pub fn add_body(&mut self, body: BodyResult) -> Result<Option<LoadSignalsCmd>> {
         if self.body_loaded {
             bail!("Did we just parse the body twice? That should not happen!");
@@ -236,6 +263,22 @@ impl WellenContainer {
         }
     }
 
+    /// Free-form header fields of the file, such as `$date` and `$version` of a VCD. Fields
+    /// that the file does not set are left out.
+    #[must_use]
+    pub fn header_fields(&self) -> Vec<(&'static str, String)> {
+        [
+            ("Date", self.hierarchy.date()),
+            ("Version", self.hierarchy.version()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| (name, value.to_string()))
+        })
+        .collect()
+    }
+
     #[must_use]
     pub fn max_timestamp(&self) -> Option<BigUint> {
         self.time_table.last().map(|t| BigUint::from(*t))
@@ -271,6 +314,13 @@ impl WellenContainer {
             .map(|scope_ref| self.hierarchy[scope_ref].scope_type())
     }
 
+    #[must_use]
+    pub fn get_scope_component(&self, scope: &ScopeRef) -> Option<String> {
+        self.lookup_scope(scope)
+            .and_then(|scope_ref| self.hierarchy[scope_ref].component(&self.hierarchy))
+            .map(str::to_string)
+    }
+
     #[must_use]
     pub fn variables(&self) -> Vec<VariableRef> {
         self.varrefs.clone()
@@ -472,6 +522,26 @@ impl WellenContainer {
         Ok(self.load_signals(&signal_refs))
     }
 
+    /// Fetch the signals of `variables` from the server again, even if they are already
+    /// loaded. The loaded data is replaced once the new data arrives.
+    pub fn refresh_variables<S: AsRef<VariableRef>, T: Iterator<Item = S>>(
+        &mut self,
+        variables: T,
+    ) -> Result<Option<LoadSignalsCmd>> {
+        if !self.remote {
+            bail!("Only signals from a remote server can be refreshed");
+        }
+        let h = &self.hierarchy;
+        let signal_refs = variables
+            .flat_map(|s| {
+                let r = s.as_ref();
+                self.get_var_ref(r).map(|v| h[v].signal_ref())
+            })
+            .collect::<Vec<_>>();
+        self.signals_to_be_loaded.extend(signal_refs);
+        Ok(self.load_signals(&[]))
+    }
+
     pub fn load_all_params(&mut self) -> Result<Option<LoadSignalsCmd>> {
         let h = &self.hierarchy;
         let params = h
@@ -32,7 +32,7 @@ pub use crate::variable_ref::VariableRef;
 
 #[cfg_attr(feature = "wasm_plugins", derive(FromBytes, ToBytes))]
 #[cfg_attr(feature = "wasm_plugins", encoding(Json))]
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
 pub struct PluginConfig(pub HashMap<String, String>);
 
 /// Quiet NaN representing undefined (X) values in analog signals.
@@ -150,6 +150,15 @@ pub fn kind_for_binary_representation(s: &str) -> ValueKind {
     }
 }
 
+/// True if some, but not all, bits of the binary representation `s` are undefined or
+/// high-impedance. [`check_vector_variable`] and [`kind_for_binary_representation`] return a
+/// single kind for the whole value, so these values look the same as fully unknown ones.
+#[must_use]
+pub fn is_partially_unknown(s: &str) -> bool {
+    let is_unknown = |c: char| matches!(c, 'x' | 'z' | 'u' | 'w');
+    s.chars().any(is_unknown) && !s.chars().all(is_unknown)
+}
+
 /// VCD bit extension.
 /// This function extends the given string `val` to match `num_bits` by adding
 /// leading characters according to VCD rules:
@@ -253,6 +262,17 @@ pub enum TranslationPreference {
     No,
 }
 
+/// Notation of the values produced by a translator, which allows Surfer to reformat them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberNotation {
+    /// Unsigned integers written with the digits of base `radix`
+    Unsigned { radix: u32 },
+    /// Signed decimal integers
+    Signed,
+    /// Decimal numbers with a fraction, such as floating point values
+    Decimal,
+}
+
 /// Static information about the structure of a variable.
 #[cfg_attr(feature = "wasm_plugins", derive(FromBytes, ToBytes))]
 #[cfg_attr(feature = "wasm_plugins", encoding(Json))]
@@ -391,7 +411,9 @@ pub enum VariableEncoding {
 
 #[cfg(test)]
 mod tests {
-    use super::{ValueKind, check_vector_variable, extend_string, parse_numeric_string};
+    use super::{
+        ValueKind, check_vector_variable, extend_string, is_partially_unknown, parse_numeric_string,
+    };
 
     #[test]
     fn binary_only_returns_none() {
@@ -457,6 +479,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn partially_unknown_needs_known_and_unknown_bits() {
+        for s in ["10x01", "zz01", "1u", "w0"] {
+            assert!(is_partially_unknown(s), "{s}");
+        }
+        for s in ["0101", "xxxx", "zz", "xz", "-01--", "h10", ""] {
+            assert!(!is_partially_unknown(s), "{s}");
+        }
+    }
+
     #[test]
     fn precedence_is_respected() {
         // contains both x and z -> x handled first (UNDEF)
@@ -11,8 +11,8 @@ use std::borrow::Cow;
 
 use crate::result::TranslationResult;
 use crate::{
-    NAN_HIGHIMP, NAN_UNDEF, TranslationPreference, ValueKind, ValueRepr, VariableEncoding,
-    VariableInfo, VariableMeta, VariableValue, parse_numeric_string,
+    NAN_HIGHIMP, NAN_UNDEF, NumberNotation, PluginConfig, TranslationPreference, ValueKind,
+    ValueRepr, VariableEncoding, VariableInfo, VariableMeta, VariableValue, parse_numeric_string,
 };
 
 #[cfg_attr(feature = "wasm_plugins", derive(FromBytes, ToBytes))]
@@ -77,6 +77,11 @@ pub trait Translator<VarId, ScopeId, Message>: Send + Sync {
     /// Notify the translator that the wave source has changed to the specified source
     fn set_wave_source(&self, _wave_source: Option<WaveSource>) {}
 
+    /// Pass the user configuration of this translator, stored in the state file, to the
+    /// translator. Called when the translator is loaded, when a waveform or state file is
+    /// loaded and whenever the configuration changes.
+    fn set_config(&self, _config: &PluginConfig) {}
+
     /// Translate the specified variable value into a human-readable form
     fn translate(
         &self,
@@ -121,6 +126,11 @@ pub trait Translator<VarId, ScopeId, Message>: Send + Sync {
         parse_numeric_string(&value_str, &self.name())
     }
 
+    /// Notation of the translated values if they are plain numbers, see [`NumberNotation`].
+    fn number_notation(&self) -> Option<NumberNotation> {
+        None
+    }
+
     /// By default translators are stateless, but if they need to reload, they can
     /// do by defining this method.
     /// Long running translators should run the reloading in the background using `perform_work`
@@ -171,6 +181,11 @@ pub trait BasicTranslator<VarId, ScopeId>: Send + Sync {
         parse_numeric_string(&val, &self.name())
     }
 
+    /// Notation of the translated values if they are plain numbers, see [`NumberNotation`].
+    fn number_notation(&self) -> Option<NumberNotation> {
+        None
+    }
+
     /// Return [`TranslationPreference`] based on if the translator can handle this variable.
     ///
     /// If this is not implemented, it will default to accepting all bit-vector types.
@@ -35,6 +35,7 @@ pub enum WcpResponse {
     add_variables { ids: Vec<DisplayedItemRef> },
     add_scope { ids: Vec<DisplayedItemRef> },
     add_markers { ids: Vec<DisplayedItemRef> },
+    get_cursor { time: Option<String> },
     ack,
 }
 
@@ -181,12 +182,23 @@ pub enum WcpCommand {
     /// Zooms out fully to fit the whole waveform in the view
     /// Responds instantly with [`WcpResponse::ack`]
     zoom_to_fit { viewport_idx: usize },
-    /// Set the cursor to the given time.
-    /// Responds instantly with [`WcpResponse::ack`]
+    /// Set the cursor to `timestamp` ticks or, if given, to `time`, which is either a number of
+    /// ticks or a time with a unit such as `"2.5 ns"`. The time is clamped to the waveform. If
+    /// `scroll` is set, the viewport is moved to the cursor if it is not in view.
+    /// Responds with [`WcpResponse::ack`]
+    /// Responds with an error if no waveforms are loaded or if the time cannot be parsed
     set_cursor {
-        #[serde(deserialize_with = "deserialize_timestamp")]
+        #[serde(default, deserialize_with = "deserialize_timestamp")]
         timestamp: BigInt,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        time: Option<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        scroll: bool,
     },
+    /// Responds with [`WcpResponse::get_cursor`] which contains the time of the cursor,
+    /// formatted with the time unit and format shown in the UI, or `null` if it is not set.
+    /// Responds with an error if no waveforms are loaded
+    get_cursor,
     /// Shut down the WCP server.
     // FIXME: What does this mean? Does it kill the server, the current connection or surfer itself?
     shutdown,
@@ -64,6 +64,15 @@ mod main_impl {
         /// Port for WCP to connect to
         wcp_initiate: Option<u16>,
 
+        #[clap(long, num_args = 2, value_names = ["START", "END"], allow_negative_numbers = true)]
+        /// Time range to show once the waveform has loaded, in the time unit of the file
+        view_range: Option<Vec<i64>>,
+
+        #[cfg(feature = "python")]
+        #[clap(long)]
+        /// Python translator to load at startup, before any commands are run
+        python_translator: Option<Utf8PathBuf>,
+
         #[command(subcommand)]
         command: Option<Commands>,
     }
@@ -80,6 +89,36 @@ mod main_impl {
                 (None, None) => None,
             }
         }
+
+        pub fn view_range(&self) -> Option<(i64, i64)> {
+            match self.view_range.as_deref() {
+                Some(&[start, end]) if start < end => Some((start, end)),
+                Some(&[start, end]) => {
+                    error!("The start of --view-range ({start}) must be before its end ({end})");
+                    None
+                }
+                _ => None,
+            }
+        }
+    }
+
+    /// Queue loading the translator at `path`, given by --python-translator, if the file exists
+    #[cfg(feature = "python")]
+    fn enqueue_python_translator(path: Option<&Utf8PathBuf>, state: &SystemState) {
+        let Some(path) = path else {
+            return;
+        };
+        if !path.is_file() {
+            error!("Cannot load Python translator: {path} is not a file");
+            return;
+        }
+        if let Err(e) = state
+            .channels
+            .msg_sender
+            .send(Message::LoadPythonTranslator(path.clone()))
+        {
+            error!("Failed to send message: {e}");
+        }
     }
 
     #[allow(dead_code)] // NOTE: Only used in desktop version
@@ -88,10 +127,14 @@ mod main_impl {
             .command_file()
             .map(read_command_file)
             .unwrap_or_default();
+        let view_range = args
+            .view_range()
+            .map(|(start, end)| (start.into(), end.into()));
         StartupParams {
             waves: args.wave_file.map(|s| string_to_wavesource(&s)),
             wcp_initiate: args.wcp_initiate,
             startup_commands,
+            view_range,
         }
     }
 
@@ -147,6 +190,8 @@ mod main_impl {
         });
 
         let state_file = args.state_file.clone();
+        #[cfg(feature = "python")]
+        let python_translator = args.python_translator.clone();
         let startup_params = startup_params_from_args(args);
         let waves = startup_params.waves.clone();
 
@@ -181,6 +226,9 @@ mod main_impl {
                 }
             }
         }
+        // Sent before the startup commands run, so that they can use the translator
+        #[cfg(feature = "python")]
+        enqueue_python_translator(python_translator.as_ref(), &state);
         // install a file watcher that emits a `SuggestReloadWaveform` message
         // whenever the user-provided file changes.
         let _watcher = match waves {
@@ -310,6 +358,43 @@ mod main_impl {
             ]);
             assert!(args.command_file().is_none());
         }
+
+        #[test]
+        fn view_range_requires_start_before_end() {
+            let args = Args::parse_from(["surfer", "--view-range", "100", "250"]);
+            assert_eq!(args.view_range(), Some((100, 250)));
+
+            let args = Args::parse_from(["surfer", "--view-range", "250", "100"]);
+            assert_eq!(args.view_range(), None);
+
+            // The range may start before time zero
+            let args = Args::parse_from(["surfer", "--view-range", "-50", "100"]);
+            assert_eq!(args.view_range(), Some((-50, 100)));
+
+            assert!(Args::try_parse_from(["surfer", "--view-range", "100"]).is_err());
+            assert_eq!(Args::parse_from(["surfer"]).view_range(), None);
+        }
+
+        #[cfg(feature = "python")]
+        #[test]
+        fn python_translator_flag_loads_translator() {
+            let state = SystemState::new().unwrap();
+            let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../examples/hexadecimal.py");
+            let args = Args::parse_from(["surfer", "--python-translator", path]);
+            enqueue_python_translator(args.python_translator.as_ref(), &state);
+            assert!(matches!(
+                state.channels.msg_receiver.try_recv(),
+                Ok(Message::LoadPythonTranslator(file)) if file == path
+            ));
+
+            let args = Args::parse_from(["surfer", "--python-translator", "missing.py"]);
+            enqueue_python_translator(args.python_translator.as_ref(), &state);
+            enqueue_python_translator(
+                Args::parse_from(["surfer"]).python_translator.as_ref(),
+                &state,
+            );
+            assert!(state.channels.msg_receiver.try_recv().is_err());
+        }
     }
 }
 
@@ -20,7 +20,7 @@ pub const WELLEN_SURFER_DEFAULT_OPTIONS: wellen::LoadOptions = wellen::LoadOptio
     remove_scopes_with_empty_name: true,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct SurverConfig {
     /// IP address to bind the HTTP server to
     pub bind_address: String,
@@ -35,6 +35,7 @@
     - [new]: Called once on plugin load
     - [reload]: Called when Surfer reloads the waveform
     - [set_wave_source]: Called when the current waveform changes
+    - [set_config]: Receive the user configuration of the translator
     - [variable_name_info]: Translate signal names
 
     ## Accessing Files
@@ -174,6 +175,7 @@ pub fn translate(
 /// advanced things.
 pub mod optional {
     use extism_pdk::Json;
+    use surfer_translation_types::PluginConfig;
     use surfer_translation_types::translator::{TrueName, VariableNameInfo};
 
     use super::*;
@@ -201,6 +203,13 @@ pub mod optional {
         Ok(())
     }
 
+    /// Receives the configuration the user has set for this translator as key-value pairs.
+    /// The configuration is stored in the Surfer state file, so this is called on load, whenever
+    /// a waveform or state file is loaded and when the user changes a value.
+    pub fn set_config(_config: PluginConfig) -> FnResult<()> {
+        Ok(())
+    }
+
     /// Can be used to convert a variable name into a name that is more descriptive.
     /// See [VariableNameInfo] and [TrueName] for details on the possible output.
     ///